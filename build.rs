@@ -0,0 +1,41 @@
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/spotter.proto");
+
+    // Build metadata of `spotter --version --json`
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let git_sha = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|sha| sha.trim().to_string())
+        .unwrap_or_default();
+    // Reproducible builds pin the build date with SOURCE_DATE_EPOCH
+    let build_timestamp = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.parse()?,
+        Err(_) => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    println!("cargo:rustc-env=SPOTTER_GIT_SHA={}", git_sha);
+    println!(
+        "cargo:rustc-env=SPOTTER_BUILD_TIMESTAMP={}",
+        build_timestamp
+    );
+    println!(
+        "cargo:rustc-env=SPOTTER_TARGET={}",
+        std::env::var("TARGET")?
+    );
+
+    // The gRPC service is optional, and compiling it needs protoc
+    #[cfg(feature = "grpc")]
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/spotter.proto"], &["proto"])?;
+
+    Ok(())
+}
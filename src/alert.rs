@@ -0,0 +1,647 @@
+use crate::cli::{self, Os, Provider};
+use crate::display::{InstanceData, InstanceInfo};
+use crate::notify::NotifierConfig;
+use crate::recommend::INTERRUPTION_RATE_UPPER_BOUNDS;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Value of an instance type a rule condition compares
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    /// Spot price per hour of the OS
+    Price,
+    /// Upper bound (%) of the interruption rate bucket
+    Interruption,
+    /// Savings over on-demand (%)
+    Savings,
+    /// Spot price as a percentage of the on-demand price
+    OnDemand,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "price" => Some(Field::Price),
+            "interruption" => Some(Field::Interruption),
+            "savings" => Some(Field::Savings),
+            "on_demand" => Some(Field::OnDemand),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Field::Price => "price",
+            Field::Interruption => "interruption",
+            Field::Savings => "savings",
+            Field::OnDemand => "on_demand",
+        }
+    }
+
+    fn value(&self, info: &InstanceInfo, os: Os) -> Option<f64> {
+        match self {
+            Field::Price => info.spot_price_value(os.price_column()),
+            Field::Interruption => info
+                .interruption_bucket()
+                .map(|bucket| INTERRUPTION_RATE_UPPER_BOUNDS[bucket]),
+            Field::Savings => info.savings_value(),
+            Field::OnDemand => {
+                let on_demand_price = info.on_demand_price_value().filter(|p| *p > 0.0)?;
+                Some(info.spot_price_value(os.price_column())? / on_demand_price * 100.0)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Comparison {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+        }
+    }
+
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Gt => value > threshold,
+            Comparison::Ge => value >= threshold,
+            Comparison::Lt => value < threshold,
+            Comparison::Le => value <= threshold,
+        }
+    }
+}
+
+/// Comparison of a field with a threshold, like "price>0.10"
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.field.name(),
+            self.comparison.symbol(),
+            self.threshold
+        )
+    }
+}
+
+impl Condition {
+    fn parse(condition: &str) -> Option<Self> {
+        // Two-character comparisons are tried first so ">=" isn't read as ">"
+        let comparisons = [
+            Comparison::Ge,
+            Comparison::Le,
+            Comparison::Gt,
+            Comparison::Lt,
+        ];
+        let (field, comparison, threshold) = comparisons.iter().find_map(|comparison| {
+            let (field, threshold) = condition.split_once(comparison.symbol())?;
+            Some((field, *comparison, threshold))
+        })?;
+
+        Some(Self {
+            field: Field::parse(field.trim())?,
+            comparison,
+            threshold: threshold.trim().parse().ok()?,
+        })
+    }
+
+    /// Returns true if the instance info meets the condition, false when the value is missing
+    pub fn holds(&self, info: &InstanceInfo, os: Os) -> bool {
+        self.field
+            .value(info, os)
+            .is_some_and(|value| self.comparison.holds(value, self.threshold))
+    }
+}
+
+/// Alert rule on an instance type of a region, like
+/// "m5.xlarge@eu-west-1 price>0.10 or interruption>10", where "and" binds tighter than "or"
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub text: String,
+    pub instance_type: String,
+    pub region: String,
+    /// Conditions of the rule, tripping when every condition of any group holds
+    pub any_of: Vec<Vec<Condition>>,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Rule {
+    /// Returns true if any condition of the rule compares the field
+    pub fn uses(&self, field: Field) -> bool {
+        self.any_of.iter().flatten().any(|c| c.field == field)
+    }
+
+    /// Validates the region of the rule, which is only known up front for AWS. The regions of the
+    /// other clouds are only known from their spot data
+    pub fn validate_region(&self, provider: Provider) -> Result<()> {
+        if provider == Provider::Aws {
+            cli::validate_region(&self.region)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses conditions like "price>0.10 or interruption>10", where "and" binds tighter than "or"
+fn parse_conditions(
+    conditions: &str,
+    invalid: impl Fn(&str) -> anyhow::Error,
+) -> Result<Vec<Vec<Condition>>> {
+    let mut any_of = Vec::new();
+    for group in conditions.split(" or ") {
+        let all_of = group
+            .split(" and ")
+            .map(|condition| {
+                Condition::parse(condition).ok_or_else(|| {
+                    invalid(&format!(
+                        "invalid condition '{}', expected price, interruption, savings or on_demand compared with >, >=, < or <= to a number",
+                        condition.trim()
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        any_of.push(all_of);
+    }
+    Ok(any_of)
+}
+
+/// Parses an alert rule like "m5.xlarge@eu-west-1 price>0.10 or interruption>10"
+pub fn parse_rule(rule: &str) -> Result<Rule> {
+    let invalid = |reason: &str| {
+        anyhow!(
+            "Invalid rule '{}': {}. Please use INSTANCE_TYPE@REGION CONDITIONS (e.g., \"m5.xlarge@eu-west-1 price>0.10 or interruption>10\")",
+            rule,
+            reason
+        )
+    };
+
+    let rule = rule.trim();
+    let (target, conditions) = rule
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| invalid("no conditions"))?;
+    let (instance_type, region) = target.split_once('@').ok_or_else(|| invalid("no region"))?;
+    if instance_type.is_empty() {
+        return Err(invalid("no instance type"));
+    }
+
+    Ok(Rule {
+        text: rule.to_string(),
+        instance_type: instance_type.to_string(),
+        region: region.to_string(),
+        any_of: parse_conditions(conditions, invalid)?,
+    })
+}
+
+/// Rule of a rule file, tripping when the conditions of "when" hold or any threshold is crossed
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    /// Name shown in alerts, the conditions of the rule by default
+    name: Option<String>,
+    instance: String,
+    region: String,
+    /// Conditions like "price>0.10 or interruption>10"
+    when: Option<String>,
+    max_price: Option<f64>,
+    max_interruption: Option<f64>,
+    min_savings: Option<f64>,
+    /// Maximum spot price as a percentage of the on-demand price
+    max_on_demand: Option<f64>,
+}
+
+impl RuleSpec {
+    fn into_rule(self) -> Result<Rule> {
+        let invalid = |reason: &str| {
+            anyhow!(
+                "Invalid rule for '{}' in '{}': {}",
+                self.instance,
+                self.region,
+                reason
+            )
+        };
+        if self.instance.is_empty() {
+            return Err(invalid("no instance type"));
+        }
+
+        let mut any_of = match &self.when {
+            Some(when) => parse_conditions(when, invalid)?,
+            None => Vec::new(),
+        };
+        let thresholds = [
+            (Field::Price, Comparison::Gt, self.max_price),
+            (Field::Interruption, Comparison::Gt, self.max_interruption),
+            (Field::Savings, Comparison::Lt, self.min_savings),
+            (Field::OnDemand, Comparison::Gt, self.max_on_demand),
+        ];
+        for (field, comparison, threshold) in thresholds {
+            if let Some(threshold) = threshold {
+                any_of.push(vec![Condition {
+                    field,
+                    comparison,
+                    threshold,
+                }]);
+            }
+        }
+        if any_of.is_empty() {
+            return Err(invalid(
+                "no conditions, expected when, max_price, max_interruption, min_savings or max_on_demand",
+            ));
+        }
+
+        let text = self.name.clone().unwrap_or_else(|| {
+            let groups: Vec<String> = any_of
+                .iter()
+                .map(|all_of| {
+                    let conditions: Vec<String> = all_of.iter().map(Condition::to_string).collect();
+                    conditions.join(" and ")
+                })
+                .collect();
+            format!("{}@{} {}", self.instance, self.region, groups.join(" or "))
+        });
+
+        Ok(Rule {
+            text,
+            instance_type: self.instance,
+            region: self.region,
+            any_of,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFileSpec {
+    rules: Vec<RuleSpec>,
+    #[serde(default)]
+    notifiers: NotifierConfig,
+}
+
+/// Alert rules and the notifiers to dispatch them to, loaded from a YAML file like
+///
+/// ```yaml
+/// rules:
+///   - instance: m5.xlarge
+///     region: eu-west-1
+///     max_price: 0.10
+///     max_interruption: 10
+///   - name: c5 savings dropped
+///     instance: c5.large
+///     region: us-east-1
+///     when: savings<50 and on_demand>40
+/// notifiers:
+///   slack_webhook: https://hooks.slack.com/services/...
+/// ```
+#[derive(Debug)]
+pub struct RuleFile {
+    pub rules: Vec<Rule>,
+    pub notifiers: NotifierConfig,
+}
+
+impl RuleFile {
+    /// Parses the rule file of the rules on the spot data of a provider
+    pub fn parse(contents: &str, provider: Provider) -> Result<Self> {
+        let spec: RuleFileSpec = serde_yaml::from_str(contents)?;
+        let rules: Vec<Rule> = spec
+            .rules
+            .into_iter()
+            .map(RuleSpec::into_rule)
+            .collect::<Result<_>>()?;
+        for rule in &rules {
+            rule.validate_region(provider)?;
+        }
+        Ok(Self {
+            rules,
+            notifiers: spec.notifiers,
+        })
+    }
+
+    pub fn load(path: &Path, provider: Provider) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the rule file {}", path.display()))?;
+        Self::parse(&contents, provider)
+            .with_context(|| format!("Invalid rule file {}", path.display()))
+    }
+}
+
+/// Rule that tripped, with the values of its instance type at the time
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Trigger {
+    pub rule: String,
+    pub instance_type: String,
+    pub region: String,
+    pub price: Option<f64>,
+    pub interruption_rate: String,
+    pub savings: Option<f64>,
+}
+
+/// Returns the triggers of the rules that weren't tripping at the last evaluation, and remembers
+/// the tripping rules, so a daemon doesn't repeat the same alert every interval
+pub fn started_tripping(triggers: Vec<Trigger>, tripped: &mut HashSet<String>) -> Vec<Trigger> {
+    let started = triggers
+        .iter()
+        .filter(|trigger| !tripped.contains(&trigger.rule))
+        .cloned()
+        .collect();
+    *tripped = triggers.into_iter().map(|trigger| trigger.rule).collect();
+    started
+}
+
+/// Evaluates a rule, returning the trigger when it trips. Instance types missing from the region
+/// never trip.
+pub fn evaluate(rule: &Rule, instance_data: &InstanceData, os: Os) -> Option<Trigger> {
+    let info = instance_data
+        .get(&(rule.instance_type.clone(), os.advisor_keys()[0].to_string()))
+        .and_then(|region_map| region_map.get(&rule.region));
+    let Some(info) = info else {
+        log::warn!(
+            "Instance type '{}' not found in region '{}', rule '{}' can't trip",
+            rule.instance_type,
+            rule.region,
+            rule
+        );
+        return None;
+    };
+
+    let tripped = rule
+        .any_of
+        .iter()
+        .any(|all_of| all_of.iter().all(|condition| condition.holds(info, os)));
+
+    tripped.then(|| Trigger {
+        rule: rule.text.clone(),
+        instance_type: rule.instance_type.clone(),
+        region: rule.region.clone(),
+        price: info.spot_price_value(os.price_column()),
+        interruption_rate: info.interruption_rate.clone(),
+        savings: info.savings_value(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    fn trigger(rule: &str) -> Trigger {
+        Trigger {
+            rule: rule.to_string(),
+            instance_type: "m5.xlarge".to_string(),
+            region: "eu-west-1".to_string(),
+            price: Some(0.12),
+            interruption_rate: "< 5%".to_string(),
+            savings: Some(70.0),
+        }
+    }
+
+    /// Instance data of m5.xlarge on Linux in eu-west-1
+    fn m5_xlarge(interruption_rate: &str, price: &str) -> InstanceData {
+        instance_data([(
+            "m5.xlarge",
+            "Linux",
+            "eu-west-1",
+            info(interruption_rate, price),
+        )])
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = parse_rule("m5.xlarge@eu-west-1 price>0.10 or interruption>=10 and savings<50")
+            .unwrap();
+        assert_eq!(rule.instance_type, "m5.xlarge");
+        assert_eq!(rule.region, "eu-west-1");
+        assert_eq!(
+            rule.any_of,
+            vec![
+                vec![Condition {
+                    field: Field::Price,
+                    comparison: Comparison::Gt,
+                    threshold: 0.10,
+                }],
+                vec![
+                    Condition {
+                        field: Field::Interruption,
+                        comparison: Comparison::Ge,
+                        threshold: 10.0,
+                    },
+                    Condition {
+                        field: Field::Savings,
+                        comparison: Comparison::Lt,
+                        threshold: 50.0,
+                    },
+                ],
+            ]
+        );
+        assert_eq!(
+            rule.to_string(),
+            "m5.xlarge@eu-west-1 price>0.10 or interruption>=10 and savings<50"
+        );
+
+        // Spaces around comparisons are allowed
+        assert!(parse_rule("m5.xlarge@eu-west-1 price > 0.1").is_ok());
+    }
+
+    #[test]
+    fn test_parse_rule_invalid() {
+        assert!(parse_rule("").is_err());
+        assert!(parse_rule("m5.xlarge@eu-west-1").is_err());
+        assert!(parse_rule("m5.xlarge price>0.1").is_err());
+        assert!(parse_rule("@eu-west-1 price>0.1").is_err());
+        assert!(
+            parse_rule("m5.xlarge@mars-1 price>0.1")
+                .unwrap()
+                .validate_region(Provider::Aws)
+                .is_err()
+        );
+        assert!(parse_rule("m5.xlarge@eu-west-1 cost>0.1").is_err());
+        assert!(parse_rule("m5.xlarge@eu-west-1 price=0.1").is_err());
+        assert!(parse_rule("m5.xlarge@eu-west-1 price>cheap").is_err());
+        assert!(parse_rule("m5.xlarge@eu-west-1 price>0.1 or").is_err());
+    }
+
+    #[test]
+    fn test_parse_rule_of_other_clouds() {
+        // Regions of GCP and Azure are only checked against AWS regions for AWS rules
+        let rule = parse_rule("n2-standard-8@us-central1 price>0.1").unwrap();
+        assert_eq!(rule.region, "us-central1");
+        assert!(rule.validate_region(Provider::Gcp).is_ok());
+        assert!(rule.validate_region(Provider::Aws).is_err());
+
+        let file = RuleFile::parse(
+            "rules:\n  - instance: Standard_D4s_v5\n    region: eastus\n    max_price: 0.1",
+            Provider::Azure,
+        )
+        .unwrap();
+        assert_eq!(file.rules[0].region, "eastus");
+        assert!(
+            RuleFile::parse(
+                "rules:\n  - instance: Standard_D4s_v5\n    region: eastus\n    max_price: 0.1",
+                Provider::Aws,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_started_tripping() {
+        let mut tripped = HashSet::new();
+        let started = started_tripping(vec![trigger("a"), trigger("b")], &mut tripped);
+        assert_eq!(started.len(), 2);
+
+        // Rules still tripping aren't repeated
+        let started = started_tripping(vec![trigger("a"), trigger("c")], &mut tripped);
+        assert_eq!(started, vec![trigger("c")]);
+
+        // A rule that stopped tripping alerts again when it trips again
+        started_tripping(vec![], &mut tripped);
+        let started = started_tripping(vec![trigger("a")], &mut tripped);
+        assert_eq!(started, vec![trigger("a")]);
+    }
+
+    #[test]
+    fn test_rule_file() {
+        let file = RuleFile::parse(
+            r#"
+rules:
+  - instance: m5.xlarge
+    region: eu-west-1
+    max_price: 0.1
+    max_interruption: 10
+  - name: c5 savings dropped
+    instance: c5.large
+    region: us-east-1
+    when: savings<50 and on_demand>40
+notifiers:
+  webhook: https://example.com/hooks/spotter
+  desktop: true
+"#,
+            Provider::Aws,
+        )
+        .unwrap();
+
+        assert_eq!(file.rules.len(), 2);
+        assert_eq!(
+            file.rules[0].text,
+            "m5.xlarge@eu-west-1 price>0.1 or interruption>10"
+        );
+        assert_eq!(
+            file.rules[0],
+            parse_rule("m5.xlarge@eu-west-1 price>0.1 or interruption>10").unwrap()
+        );
+        assert_eq!(file.rules[1].text, "c5 savings dropped");
+        assert!(file.rules[1].uses(Field::OnDemand));
+        assert!(!file.rules[0].uses(Field::OnDemand));
+        assert_eq!(
+            file.notifiers.webhook.as_deref(),
+            Some("https://example.com/hooks/spotter")
+        );
+        assert!(file.notifiers.desktop);
+    }
+
+    #[test]
+    fn test_rule_file_invalid() {
+        // No conditions
+        assert!(
+            RuleFile::parse(
+                "rules:\n  - instance: m5.xlarge\n    region: eu-west-1",
+                Provider::Aws
+            )
+            .is_err()
+        );
+        // Unknown region
+        assert!(
+            RuleFile::parse(
+                "rules:\n  - instance: m5.xlarge\n    region: mars-1\n    max_price: 1",
+                Provider::Aws
+            )
+            .is_err()
+        );
+        // Unknown key
+        assert!(
+            RuleFile::parse(
+                "rules:\n  - instance: m5.xlarge\n    region: eu-west-1\n    max_cost: 1",
+                Provider::Aws
+            )
+            .is_err()
+        );
+        // Invalid condition
+        assert!(
+            RuleFile::parse(
+                "rules:\n  - instance: m5.xlarge\n    region: eu-west-1\n    when: cost>1",
+                Provider::Aws
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let rule = parse_rule("m5.xlarge@eu-west-1 price>0.10 or interruption>10").unwrap();
+
+        // Neither condition holds
+        assert_eq!(
+            evaluate(&rule, &m5_xlarge("5-10%", "0.08"), Os::Linux),
+            None
+        );
+
+        // The price condition holds
+        let trigger = evaluate(&rule, &m5_xlarge("< 5%", "0.12"), Os::Linux).unwrap();
+        assert_eq!(trigger.rule, rule.text);
+        assert_eq!(trigger.price, Some(0.12));
+        assert_eq!(trigger.savings, Some(70.0));
+
+        // The 10-15% bucket is over 10%
+        let trigger = evaluate(&rule, &m5_xlarge("10-15%", "0.08"), Os::Linux).unwrap();
+        assert_eq!(trigger.interruption_rate, "10-15%");
+
+        // Both conditions of a group must hold
+        let rule = parse_rule("m5.xlarge@eu-west-1 price>0.10 and interruption>10").unwrap();
+        assert_eq!(evaluate(&rule, &m5_xlarge("< 5%", "0.12"), Os::Linux), None);
+
+        // The spot price is 12% of the on-demand price
+        let rule = parse_rule("m5.xlarge@eu-west-1 on_demand>10").unwrap();
+        assert_eq!(
+            evaluate(&rule, &m5_xlarge("< 5%", "0.024"), Os::Linux),
+            None
+        );
+        let data = instance_data([(
+            "m5.xlarge",
+            "Linux",
+            "eu-west-1",
+            info("< 5%", "0.024").with_on_demand_price("0.2"),
+        )]);
+        assert!(evaluate(&rule, &data, Os::Linux).is_some());
+
+        // Missing prices and instance types never trip
+        let rule = parse_rule("m5.xlarge@eu-west-1 price<1").unwrap();
+        assert_eq!(evaluate(&rule, &m5_xlarge("< 5%", "N/A"), Os::Linux), None);
+        assert_eq!(
+            evaluate(&rule, &m5_xlarge("< 5%", "0.1"), Os::Windows),
+            None
+        );
+    }
+}
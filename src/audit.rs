@@ -0,0 +1,456 @@
+use crate::cli::{AwsArgs, Os};
+use crate::display::{InstanceData, InstanceInfo};
+use crate::live::AzPrice;
+use crate::recommend::INTERRUPTION_RATE_UPPER_BOUNDS;
+use anyhow::{Context, Result, anyhow};
+use aws_sdk_autoscaling::types::{LaunchTemplateSpecification, MixedInstancesPolicy};
+use aws_sdk_ec2::types::{Filter, Instance};
+
+/// Running spot instance of the account
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunningInstance {
+    pub instance_id: String,
+    pub instance_type: String,
+    pub availability_zone: String,
+    /// OS of the spot price, Linux or Windows
+    pub os: Os,
+    /// Unix time (seconds) the instance was launched
+    pub launched_at: Option<i64>,
+}
+
+/// Limits above which a running spot instance is flagged
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditThresholds {
+    /// Maximum interruption rate (%) before a pool is risky
+    pub max_interruption: f64,
+    /// Maximum premium (%) over the cheapest Availability Zone before a pool is overpriced
+    pub max_premium: f64,
+}
+
+/// Running spot instance annotated with its advisor and current price data
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditFinding {
+    pub instance: RunningInstance,
+    pub interruption_rate: String,
+    /// Current spot price in the Availability Zone of the instance, what it's paying
+    pub price: Option<f64>,
+    /// Cheapest Availability Zone of the instance type and its price
+    pub cheapest: Option<(String, f64)>,
+    /// Reasons the pool is risky or overpriced, empty when healthy
+    pub flags: Vec<String>,
+}
+
+impl AuditFinding {
+    pub fn flagged(&self) -> bool {
+        !self.flags.is_empty()
+    }
+}
+
+/// Advisor rating and price of an instance type configured in an Auto Scaling group
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolRating {
+    pub instance_type: String,
+    pub interruption_rate: String,
+    pub savings: String,
+    pub spot_price: Option<f64>,
+    /// Whether the interruption rate has risen above the maximum
+    pub high_interruption: bool,
+}
+
+/// Instance types configured in an Auto Scaling group
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AsgInstanceTypes {
+    pub instance_types: Vec<String>,
+    /// Whether some overrides select instance types by attributes, which can't be audited
+    pub attribute_based: bool,
+}
+
+/// Returns whether the interruption rate of a pool is above the maximum (%)
+fn above_max_interruption(info: Option<&InstanceInfo>, max_interruption: f64) -> bool {
+    info.and_then(|info| info.interruption_bucket())
+        .is_some_and(|bucket| INTERRUPTION_RATE_UPPER_BOUNDS[bucket] > max_interruption)
+}
+
+/// Returns the OS of the spot price of a platform from DescribeInstances, like "Linux/UNIX"
+fn platform_os(platform_details: Option<&str>) -> Os {
+    match platform_details {
+        Some(platform) if platform.starts_with("Windows") => Os::Windows,
+        _ => Os::Linux,
+    }
+}
+
+fn running_instance(instance: &Instance) -> Option<RunningInstance> {
+    Some(RunningInstance {
+        instance_id: instance.instance_id()?.to_string(),
+        instance_type: instance.instance_type()?.as_str().to_string(),
+        availability_zone: instance.placement()?.availability_zone()?.to_string(),
+        os: platform_os(instance.platform_details()),
+        launched_at: instance.launch_time().map(|time| time.secs()),
+    })
+}
+
+/// Fetches the running spot instances of the account in a region with DescribeInstances
+pub async fn fetch_running_instances(aws: &AwsArgs, region: &str) -> Result<Vec<RunningInstance>> {
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_ec2::Client::new(&config);
+
+    let reservations = client
+        .describe_instances()
+        .filters(
+            Filter::builder()
+                .name("instance-lifecycle")
+                .values("spot")
+                .build(),
+        )
+        .filters(
+            Filter::builder()
+                .name("instance-state-name")
+                .values("running")
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to describe the instances in {} (are AWS credentials configured?)",
+                region
+            )
+        })?;
+
+    let mut instances: Vec<RunningInstance> = reservations
+        .iter()
+        .flat_map(|reservation| reservation.instances())
+        .filter_map(running_instance)
+        .collect();
+    instances.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+    Ok(instances)
+}
+
+/// Annotates the running instances with their advisor interruption rates (from instance data of
+/// every OS) and current spot prices per Availability Zone, flagging risky or overpriced pools
+pub fn audit(
+    instances: &[RunningInstance],
+    instance_data: &InstanceData,
+    region: &str,
+    az_prices: &[(Os, AzPrice)],
+    thresholds: &AuditThresholds,
+) -> Vec<AuditFinding> {
+    instances
+        .iter()
+        .map(|instance| {
+            let info = instance_data
+                .get(&(
+                    instance.instance_type.clone(),
+                    instance.os.advisor_keys()[0].to_string(),
+                ))
+                .and_then(|region_map| region_map.get(region));
+            let pool_prices: Vec<&AzPrice> = az_prices
+                .iter()
+                .filter(|(os, price)| {
+                    *os == instance.os && price.instance_type == instance.instance_type
+                })
+                .map(|(_, price)| price)
+                .collect();
+            let price = pool_prices
+                .iter()
+                .find(|price| price.availability_zone == instance.availability_zone)
+                .map(|price| price.price);
+            let cheapest = pool_prices
+                .iter()
+                .min_by(|a, b| a.price.total_cmp(&b.price))
+                .map(|price| (price.availability_zone.clone(), price.price));
+
+            let mut flags = Vec::new();
+            if above_max_interruption(info, thresholds.max_interruption) {
+                flags.push(format!(
+                    "risky: interruption rate above {}%",
+                    thresholds.max_interruption
+                ));
+            }
+            if let (Some(price), Some((zone, lowest))) = (price, &cheapest)
+                && *lowest > 0.0
+            {
+                let premium = (price / lowest - 1.0) * 100.0;
+                if premium > thresholds.max_premium {
+                    flags.push(format!(
+                        "overpriced: {:.0}% above {} (${:.4}/h)",
+                        premium, zone, lowest
+                    ));
+                }
+            }
+
+            AuditFinding {
+                instance: instance.clone(),
+                interruption_rate: info
+                    .map_or_else(|| "N/A".to_string(), |info| info.interruption_rate.clone()),
+                price,
+                cheapest,
+                flags,
+            }
+        })
+        .collect()
+}
+
+/// Returns the instance types of the launch template overrides of a mixed instances policy
+fn override_instance_types(policy: &MixedInstancesPolicy) -> AsgInstanceTypes {
+    let overrides = policy
+        .launch_template()
+        .map(|template| template.overrides())
+        .unwrap_or_default();
+    let mut instance_types = Vec::new();
+    for instance_type in overrides.iter().filter_map(|o| o.instance_type()) {
+        if !instance_types.iter().any(|t| t == instance_type) {
+            instance_types.push(instance_type.to_string());
+        }
+    }
+    AsgInstanceTypes {
+        instance_types,
+        attribute_based: overrides
+            .iter()
+            .any(|o| o.instance_requirements().is_some()),
+    }
+}
+
+/// Returns the instance type of a launch template version with DescribeLaunchTemplateVersions
+async fn launch_template_instance_type(
+    client: &aws_sdk_ec2::Client,
+    template: &LaunchTemplateSpecification,
+) -> Result<Option<String>> {
+    let versions = client
+        .describe_launch_template_versions()
+        .set_launch_template_id(template.launch_template_id().map(str::to_string))
+        .set_launch_template_name(template.launch_template_name().map(str::to_string))
+        .versions(template.version().unwrap_or("$Default"))
+        .send()
+        .await
+        .context("Failed to describe the launch template versions")?;
+    Ok(versions
+        .launch_template_versions()
+        .first()
+        .and_then(|version| version.launch_template_data())
+        .and_then(|data| data.instance_type())
+        .map(|instance_type| instance_type.as_str().to_string()))
+}
+
+/// Fetches the instance types configured in an Auto Scaling group: the overrides of its mixed
+/// instances policy, or the instance type of its launch template
+pub async fn fetch_asg_instance_types(
+    aws: &AwsArgs,
+    region: &str,
+    name: &str,
+) -> Result<AsgInstanceTypes> {
+    let config = aws.sdk_config(region).await;
+    let groups = aws_sdk_autoscaling::Client::new(&config)
+        .describe_auto_scaling_groups()
+        .auto_scaling_group_names(name)
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to describe the Auto Scaling groups in {} (are AWS credentials configured?)",
+                region
+            )
+        })?;
+    let group = groups
+        .auto_scaling_groups()
+        .first()
+        .ok_or_else(|| anyhow!("Auto Scaling group '{}' not found in {}", name, region))?;
+
+    let mut asg_instance_types = group
+        .mixed_instances_policy()
+        .map(override_instance_types)
+        .unwrap_or_default();
+    if !asg_instance_types.instance_types.is_empty() || asg_instance_types.attribute_based {
+        return Ok(asg_instance_types);
+    }
+
+    // Without overrides the instances come from the launch template of the group or the policy
+    let template = group.launch_template().or_else(|| {
+        group
+            .mixed_instances_policy()
+            .and_then(|policy| policy.launch_template())
+            .and_then(|template| template.launch_template_specification())
+    });
+    let Some(template) = template else {
+        return Err(anyhow!(
+            "Auto Scaling group '{}' has no launch template (launch configurations aren't supported)",
+            name
+        ));
+    };
+    let ec2 = aws_sdk_ec2::Client::new(&config);
+    asg_instance_types.instance_types = launch_template_instance_type(&ec2, template)
+        .await?
+        .into_iter()
+        .collect();
+    Ok(asg_instance_types)
+}
+
+/// Rates the instance types of an Auto Scaling group by their advisor interruption rates and spot
+/// prices, marking the ones above the maximum interruption rate (%)
+pub fn rate_pools(
+    instance_types: &[String],
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    max_interruption: f64,
+) -> Vec<PoolRating> {
+    let os_name = os.advisor_keys()[0];
+    instance_types
+        .iter()
+        .map(|instance_type| {
+            let info = instance_data
+                .get(&(instance_type.clone(), os_name.to_string()))
+                .and_then(|region_map| region_map.get(region));
+            PoolRating {
+                instance_type: instance_type.clone(),
+                interruption_rate: info
+                    .map_or_else(|| "N/A".to_string(), |info| info.interruption_rate.clone()),
+                savings: info.map_or_else(|| "N/A".to_string(), |info| info.savings.clone()),
+                spot_price: info.and_then(|info| info.spot_price_value(os.price_column())),
+                high_interruption: above_max_interruption(info, max_interruption),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+    use aws_sdk_autoscaling::types::{LaunchTemplate, LaunchTemplateOverrides};
+
+    fn instance(instance_id: &str, instance_type: &str, az: &str) -> RunningInstance {
+        RunningInstance {
+            instance_id: instance_id.to_string(),
+            instance_type: instance_type.to_string(),
+            availability_zone: az.to_string(),
+            os: Os::Linux,
+            launched_at: None,
+        }
+    }
+
+    fn price(instance_type: &str, az: &str, price: f64) -> (Os, AzPrice) {
+        (
+            Os::Linux,
+            AzPrice {
+                instance_type: instance_type.to_string(),
+                availability_zone: az.to_string(),
+                price,
+                timestamp: 0,
+            },
+        )
+    }
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            ("m5.xlarge", "Linux", "us-east-1", info("< 5%", "0.05")),
+            ("c5.xlarge", "Linux", "us-east-1", info("15-20%", "0.05")),
+        ])
+    }
+
+    #[test]
+    fn test_platform_os() {
+        assert_eq!(platform_os(Some("Linux/UNIX")), Os::Linux);
+        assert_eq!(platform_os(Some("Windows BYOL")), Os::Windows);
+        assert_eq!(platform_os(None), Os::Linux);
+    }
+
+    #[test]
+    fn test_audit() {
+        let thresholds = AuditThresholds {
+            max_interruption: 10.0,
+            max_premium: 10.0,
+        };
+        let findings = audit(
+            &[
+                instance("i-1", "m5.xlarge", "us-east-1a"),
+                instance("i-2", "m5.xlarge", "us-east-1b"),
+                instance("i-3", "c5.xlarge", "us-east-1a"),
+                instance("i-4", "r5.xlarge", "us-east-1a"),
+            ],
+            &sample_data(),
+            "us-east-1",
+            &[
+                price("m5.xlarge", "us-east-1a", 0.05),
+                price("m5.xlarge", "us-east-1b", 0.08),
+                price("c5.xlarge", "us-east-1a", 0.04),
+            ],
+            &thresholds,
+        );
+
+        assert!(!findings[0].flagged());
+        assert_eq!(findings[0].price, Some(0.05));
+        assert_eq!(findings[0].cheapest, Some(("us-east-1a".to_string(), 0.05)));
+
+        assert_eq!(
+            findings[1].flags,
+            vec!["overpriced: 60% above us-east-1a ($0.0500/h)"]
+        );
+        assert_eq!(
+            findings[2].flags,
+            vec!["risky: interruption rate above 10%"]
+        );
+
+        // Pools missing from the advisor and price data aren't flagged
+        assert!(!findings[3].flagged());
+        assert_eq!(findings[3].interruption_rate, "N/A");
+        assert_eq!(findings[3].price, None);
+    }
+
+    #[test]
+    fn test_override_instance_types() {
+        let policy = MixedInstancesPolicy::builder()
+            .launch_template(
+                LaunchTemplate::builder()
+                    .overrides(
+                        LaunchTemplateOverrides::builder()
+                            .instance_type("m5.xlarge")
+                            .build(),
+                    )
+                    .overrides(
+                        LaunchTemplateOverrides::builder()
+                            .instance_type("c5.xlarge")
+                            .build(),
+                    )
+                    .overrides(
+                        LaunchTemplateOverrides::builder()
+                            .instance_type("m5.xlarge")
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+        assert_eq!(
+            override_instance_types(&policy),
+            AsgInstanceTypes {
+                instance_types: vec!["m5.xlarge".to_string(), "c5.xlarge".to_string()],
+                attribute_based: false,
+            }
+        );
+
+        let policy = MixedInstancesPolicy::builder().build();
+        assert_eq!(
+            override_instance_types(&policy),
+            AsgInstanceTypes::default()
+        );
+    }
+
+    #[test]
+    fn test_rate_pools() {
+        let types = vec![
+            "m5.xlarge".to_string(),
+            "c5.xlarge".to_string(),
+            "r5.xlarge".to_string(),
+        ];
+        let ratings = rate_pools(&types, &sample_data(), "us-east-1", Os::Linux, 10.0);
+        assert_eq!(ratings[0].interruption_rate, "< 5%");
+        assert_eq!(ratings[0].spot_price, Some(0.05));
+        assert!(!ratings[0].high_interruption);
+        assert!(ratings[1].high_interruption);
+        assert_eq!(ratings[2].interruption_rate, "N/A");
+        assert!(!ratings[2].high_interruption);
+    }
+}
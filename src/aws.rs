@@ -1,16 +1,120 @@
-use anyhow::Result;
-use reqwest::Client;
+use crate::cache::{self, Cache, Validators};
+use crate::telemetry;
+use anyhow::{Result, anyhow};
+use reqwest::header::{
+    ETAG, HeaderMap, HeaderName, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
 use serde_json::Value;
 
 pub const SPOT_ADVISOR_DATA_URL: &str =
     "https://spot-bid-advisor.s3.amazonaws.com/spot-advisor-data.json";
 pub const SPOT_PRICE_DATA_URL: &str = "http://spot-price.s3.amazonaws.com/spot.js";
+pub const ON_DEMAND_PRICE_DATA_URL: &str = "https://b0.p.awsstatic.com/pricing/2.0/meteredUnitMaps/ec2/USD/current/ec2-ondemand-without-sec-sel";
 
-pub async fn fetch_spot_advisor_data(client: &Client) -> Result<Value> {
+/// An upstream data source spotter reaches out to
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DataSource {
+    pub name: &'static str,
+    pub url: &'static str,
+    pub auth: &'static str,
+    /// Cache file of the fetched data, when it's cached
+    pub cache_file: Option<&'static str>,
+}
+
+/// Returns the public feeds of the AWS spot data
+pub fn data_sources() -> Vec<DataSource> {
+    vec![
+        DataSource {
+            name: "Spot Advisor Data",
+            url: SPOT_ADVISOR_DATA_URL,
+            auth: "None (public)",
+            cache_file: Some(cache::SPOT_ADVISOR_DATA_FILE),
+        },
+        DataSource {
+            name: "Spot Price Data",
+            url: SPOT_PRICE_DATA_URL,
+            auth: "None (public)",
+            cache_file: Some(cache::SPOT_PRICE_DATA_FILE),
+        },
+    ]
+}
+
+/// Returns the ETag and Last-Modified validators of a response
+fn response_validators(headers: &HeaderMap) -> Validators {
+    let header = |name: HeaderName| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    }
+}
+
+/// Fetches the body of a URL, reusing the cache entry while it's fresh and revalidating it with
+/// a conditional request once it's stale
+async fn fetch_text(
+    client: &Client,
+    url: &str,
+    cache: Option<&Cache>,
+    cache_name: &str,
+) -> Result<String> {
+    if let Some(text) = cache.and_then(|cache| cache.read(cache_name)) {
+        log::info!("Using cached {}", cache_name);
+        return Ok(text);
+    }
+
+    // A stale entry with validators can be revalidated instead of re-downloaded
+    let stale = cache.and_then(|cache| {
+        let validators = cache.validators(cache_name)?;
+        Some((cache, cache.read_stale(cache_name)?, validators))
+    });
+
+    let mut request = client.get(url);
+    if let Some((_, _, validators)) = &stale {
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request.send().await?;
+    if response.status() == StatusCode::NOT_MODIFIED
+        && let Some((cache, text, _)) = stale
+    {
+        log::info!("Cached {} is unchanged", cache_name);
+        if let Err(e) = cache.touch(cache_name) {
+            log::warn!("Failed to refresh cached {}: {}", cache_name, e);
+        }
+        return Ok(text);
+    }
+
+    let response = response.error_for_status()?;
+    let validators = response_validators(response.headers());
+    let text = response.text().await?;
+
+    if let Some(cache) = cache
+        && let Err(e) = cache
+            .write(cache_name, &text)
+            .and_then(|()| cache.write_validators(cache_name, &validators))
+    {
+        log::warn!("Failed to cache {}: {}", cache_name, e);
+    }
+
+    Ok(text)
+}
+
+pub async fn fetch_spot_advisor_data(client: &Client, cache: Option<&Cache>) -> Result<Value> {
     log::info!("Fetching spot advisor data...");
     let url = SPOT_ADVISOR_DATA_URL;
-    let response = client.get(url).send().await?;
-    let data = response.json::<Value>().await?;
+    let text = fetch_text(client, url, cache, cache::SPOT_ADVISOR_DATA_FILE).await?;
+    let data = serde_json::from_str::<Value>(&text)?;
 
     // Print a sample of the data structure
     if let Some(spot_advisor) = data.get("spot_advisor") {
@@ -73,11 +177,10 @@ pub async fn fetch_spot_advisor_data(client: &Client) -> Result<Value> {
     Ok(data)
 }
 
-pub async fn fetch_spot_price_data(client: &Client) -> Result<Value> {
+pub async fn fetch_spot_price_data(client: &Client, cache: Option<&Cache>) -> Result<Value> {
     log::info!("Fetching spot price data...");
     let url = SPOT_PRICE_DATA_URL;
-    let response = client.get(url).send().await?;
-    let text = response.text().await?;
+    let text = fetch_text(client, url, cache, cache::SPOT_PRICE_DATA_FILE).await?;
 
     // Extract JSON from callback function
     let callback_prefix = "callback(";
@@ -188,11 +291,117 @@ pub async fn fetch_spot_price_data(client: &Client) -> Result<Value> {
     Ok(data)
 }
 
+/// Returns the location name the AWS pricing data uses for a region (e.g., "EU (Ireland)")
+pub fn pricing_location(region: &str) -> Option<String> {
+    let name = crate::cli::region_name(region)?;
+    let location = match name.strip_prefix("Europe ") {
+        Some(rest) => format!("EU {}", rest),
+        None => name.to_string(),
+    };
+    Some(
+        location
+            .replace("São Paulo", "Sao Paulo")
+            .replace("Canada (Calgary)", "Canada West (Calgary)"),
+    )
+}
+
+/// Returns the URL of the on-demand price data for a region and OS (e.g., "Linux" or "Windows")
+pub fn on_demand_price_data_url(region: &str, os: &str) -> Result<String> {
+    let location = pricing_location(region)
+        .ok_or_else(|| anyhow!("No on-demand price data for region '{}'", region))?;
+    Ok(format!(
+        "{}/{}/{}/index.json",
+        ON_DEMAND_PRICE_DATA_URL,
+        location.replace(' ', "%20"),
+        os
+    ))
+}
+
+pub async fn fetch_on_demand_price_data(client: &Client, region: &str, os: &str) -> Result<Value> {
+    log::info!("Fetching on-demand price data...");
+    let url = on_demand_price_data_url(region, os)?;
+    log::debug!("On-demand price data URL: {}", url);
+    let response = client.get(&url).send().await?.error_for_status()?;
+    let data = response.json::<Value>().await?;
+
+    Ok(data)
+}
+
+/// Fetches both the spot advisor data and the spot price data, reusing fresh cached data
+pub async fn fetch_data(client: &Client, cache: Option<&Cache>) -> Result<(Value, Value)> {
+    telemetry::phase_async("fetch", async {
+        let advisor_data = fetch_spot_advisor_data(client, cache).await?;
+        let price_data = fetch_spot_price_data(client, cache).await?;
+        Ok((advisor_data, price_data))
+    })
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_response_validators() {
+        let mut headers = HeaderMap::new();
+        assert!(response_validators(&headers).is_empty());
+
+        headers.insert(ETAG, "\"abc123\"".parse().unwrap());
+        headers.insert(
+            LAST_MODIFIED,
+            "Wed, 21 Oct 2026 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(
+            response_validators(&headers),
+            Validators {
+                etag: Some("\"abc123\"".to_string()),
+                last_modified: Some("Wed, 21 Oct 2026 07:28:00 GMT".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_data_sources() {
+        let sources = data_sources();
+        assert_eq!(sources.len(), 2);
+        assert!(sources.iter().any(|s| s.url == SPOT_ADVISOR_DATA_URL));
+        assert!(sources.iter().any(|s| s.url == SPOT_PRICE_DATA_URL));
+    }
+
+    #[test]
+    fn test_pricing_location() {
+        assert_eq!(
+            pricing_location("us-east-1"),
+            Some("US East (N. Virginia)".to_string())
+        );
+        assert_eq!(
+            pricing_location("eu-west-1"),
+            Some("EU (Ireland)".to_string())
+        );
+        assert_eq!(
+            pricing_location("sa-east-1"),
+            Some("South America (Sao Paulo)".to_string())
+        );
+        assert_eq!(
+            pricing_location("ca-west-1"),
+            Some("Canada West (Calgary)".to_string())
+        );
+        assert_eq!(pricing_location("invalid-region"), None);
+    }
+
+    #[test]
+    fn test_on_demand_price_data_url() {
+        assert_eq!(
+            on_demand_price_data_url("eu-west-1", "Linux").unwrap(),
+            format!(
+                "{}/EU%20(Ireland)/Linux/index.json",
+                ON_DEMAND_PRICE_DATA_URL
+            )
+        );
+        assert!(on_demand_price_data_url("invalid-region", "Linux").is_err());
+    }
+
     // This test mocks the process of extracting JSON from the callback function
     #[test]
     fn test_extract_json_from_callback() {
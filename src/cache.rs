@@ -0,0 +1,419 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Default time fetched data is reused for before it's re-fetched
+pub const DEFAULT_CACHE_TTL: &str = "1h";
+
+/// File names of the cached data
+pub const SPOT_ADVISOR_DATA_FILE: &str = "spot-advisor-data.json";
+pub const SPOT_PRICE_DATA_FILE: &str = "spot.js";
+pub const GCP_SKUS_FILE: &str = "gcp-skus.json";
+pub const AZURE_SPOT_PRICES_FILE: &str = "azure-spot-prices.json";
+
+/// Prefix of the files of the Azure spot prices of a single region
+const AZURE_REGION_PREFIX: &str = "azure-spot-prices-";
+
+/// Every file the cache manages
+pub const CACHE_FILES: [&str; 4] = [
+    SPOT_ADVISOR_DATA_FILE,
+    SPOT_PRICE_DATA_FILE,
+    GCP_SKUS_FILE,
+    AZURE_SPOT_PRICES_FILE,
+];
+
+/// Returns the cache file of the Azure spot prices of a region, or of every region
+pub fn azure_spot_prices_file(region: Option<&str>) -> String {
+    match region {
+        Some(region) => format!("{}{}.json", AZURE_REGION_PREFIX, region),
+        None => AZURE_SPOT_PRICES_FILE.to_string(),
+    }
+}
+
+/// Suffix of the file storing the validators of a cache entry
+const VALIDATORS_SUFFIX: &str = ".validators.json";
+
+/// HTTP validators of a cache entry used to revalidate it with a conditional request
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// A cached file with its size and age
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheEntry {
+    pub name: String,
+    pub size: u64,
+    pub age: Duration,
+    pub fresh: bool,
+}
+
+/// Cache of fetched data, where each entry is a file that's fresh until it's older than the TTL
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the time an entry is reused for before it's re-fetched
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the files the cache manages: the fixed ones and the Azure spot prices of each
+    /// region cached so far
+    fn files(&self) -> Vec<String> {
+        let mut regions: Vec<String> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| {
+                name.starts_with(AZURE_REGION_PREFIX)
+                    && name.ends_with(".json")
+                    && !name.ends_with(VALIDATORS_SUFFIX)
+            })
+            .collect();
+        regions.sort();
+        CACHE_FILES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(regions)
+            .collect()
+    }
+
+    /// Returns the cache entries that exist
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        self.files()
+            .into_iter()
+            .filter_map(|name| {
+                let size = std::fs::metadata(self.path(&name)).ok()?.len();
+                let age = self.age(&name)?;
+                Some(CacheEntry {
+                    name,
+                    size,
+                    age,
+                    fresh: age < self.ttl,
+                })
+            })
+            .collect()
+    }
+
+    /// Removes every cache entry, returning the number of removed entries. Only the files the
+    /// cache manages are removed, so sharing the cache directory with other files is safe.
+    pub fn clear(&self) -> Result<usize> {
+        let remove = |path: PathBuf| match std::fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        };
+
+        let mut removed = 0;
+        for name in self.files() {
+            if remove(self.path(&name))? {
+                removed += 1;
+            }
+            remove(self.validators_path(&name))?;
+        }
+        Ok(removed)
+    }
+
+    /// Returns the path of a cache entry
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// Returns the age of a cache entry, or None when it doesn't exist
+    pub fn age(&self, name: &str) -> Option<Duration> {
+        let modified = std::fs::metadata(self.path(name)).ok()?.modified().ok()?;
+        // A modification time in the future counts as just written
+        Some(modified.elapsed().unwrap_or_default())
+    }
+
+    /// Returns the contents of a cache entry if it's younger than the TTL
+    pub fn read(&self, name: &str) -> Option<String> {
+        let age = self.age(name)?;
+        if age >= self.ttl {
+            log::debug!("Cached {} is stale ({}s old)", name, age.as_secs());
+            return None;
+        }
+
+        std::fs::read_to_string(self.path(name)).ok()
+    }
+
+    /// Returns the contents of a cache entry regardless of its age
+    pub fn read_stale(&self, name: &str) -> Option<String> {
+        std::fs::read_to_string(self.path(name)).ok()
+    }
+
+    /// Marks a cache entry as fresh again, after the server confirmed it hasn't changed
+    pub fn touch(&self, name: &str) -> Result<()> {
+        let file = std::fs::File::options()
+            .append(true)
+            .open(self.path(name))?;
+        file.set_modified(SystemTime::now())?;
+        Ok(())
+    }
+
+    fn validators_path(&self, name: &str) -> PathBuf {
+        self.path(&format!("{}{}", name, VALIDATORS_SUFFIX))
+    }
+
+    /// Returns the validators stored for a cache entry, if any
+    pub fn validators(&self, name: &str) -> Option<Validators> {
+        let contents = std::fs::read_to_string(self.validators_path(name)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Stores the validators of a cache entry, removing stale ones when there are none
+    pub fn write_validators(&self, name: &str, validators: &Validators) -> Result<()> {
+        if validators.is_empty() {
+            match std::fs::remove_file(self.validators_path(name)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => return Ok(()),
+            }
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(
+            self.validators_path(name),
+            serde_json::to_string(validators)?,
+        )?;
+        Ok(())
+    }
+
+    /// Writes a cache entry, creating the cache directory when needed
+    pub fn write(&self, name: &str, contents: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        // Write to a temporary file first so concurrent runs never read a partial entry
+        let tmp_path = self.path(&format!("{}.tmp", name));
+        std::fs::write(&tmp_path, contents)?;
+        std::fs::rename(&tmp_path, self.path(name))?;
+
+        Ok(())
+    }
+}
+
+/// Environment variable overriding the cache directory
+pub const CACHE_DIR_ENV: &str = "SPOTTER_CACHE_DIR";
+
+/// Resolves the cache directory: the --cache-dir flag, $SPOTTER_CACHE_DIR, and finally the
+/// platform default
+pub fn resolve_dir(cache_dir: Option<&Path>) -> Option<PathBuf> {
+    if let Some(dir) = cache_dir {
+        return Some(dir.to_path_buf());
+    }
+
+    cache_dir_from_env(|name| std::env::var_os(name), cfg!(windows))
+}
+
+/// Resolves the cache directory from environment variables: $SPOTTER_CACHE_DIR, then
+/// %LOCALAPPDATA%\spotter\cache on Windows, and $XDG_CACHE_HOME/spotter or ~/.cache/spotter
+/// elsewhere
+fn cache_dir_from_env(var: impl Fn(&str) -> Option<OsString>, windows: bool) -> Option<PathBuf> {
+    let var = |name: &str| {
+        var(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    if let Some(dir) = var(CACHE_DIR_ENV) {
+        return Some(dir);
+    }
+
+    if windows {
+        return var("LOCALAPPDATA").map(|dir| dir.join("spotter").join("cache"));
+    }
+
+    let cache_home =
+        var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))?;
+    Some(cache_home.join("spotter"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str, ttl: Duration) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("spotter-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Cache::new(dir, ttl)
+    }
+
+    #[test]
+    fn test_cache_read_write() {
+        let cache = temp_cache("read-write", Duration::from_secs(3600));
+        assert_eq!(cache.read(SPOT_PRICE_DATA_FILE), None);
+        assert_eq!(cache.age(SPOT_PRICE_DATA_FILE), None);
+
+        cache.write(SPOT_PRICE_DATA_FILE, "callback({})").unwrap();
+        assert_eq!(
+            cache.read(SPOT_PRICE_DATA_FILE),
+            Some("callback({})".to_string())
+        );
+        assert!(cache.age(SPOT_PRICE_DATA_FILE).is_some());
+        assert!(!cache.path("spot.js.tmp").exists());
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_entries_and_clear() {
+        let cache = temp_cache("entries", Duration::from_secs(3600));
+        assert!(cache.entries().is_empty());
+        assert_eq!(cache.clear().unwrap(), 0);
+
+        cache.write(SPOT_ADVISOR_DATA_FILE, "{}").unwrap();
+        std::fs::write(cache.path("other.txt"), "not a cache entry").unwrap();
+
+        let entries = cache.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, SPOT_ADVISOR_DATA_FILE);
+        assert_eq!(entries[0].size, 2);
+        assert!(entries[0].fresh);
+
+        // The Azure spot prices of each region are entries of their own
+        let eastus = azure_spot_prices_file(Some("eastus"));
+        assert_eq!(eastus, "azure-spot-prices-eastus.json");
+        cache.write(&eastus, "[]").unwrap();
+        assert_eq!(cache.entries()[1].name, eastus);
+
+        // Files the cache doesn't manage are kept
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert!(cache.entries().is_empty());
+        assert!(cache.path("other.txt").exists());
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_validators() {
+        let cache = temp_cache("validators", Duration::ZERO);
+        cache.write(SPOT_PRICE_DATA_FILE, "callback({})").unwrap();
+        assert_eq!(cache.validators(SPOT_PRICE_DATA_FILE), None);
+
+        let validators = Validators {
+            etag: Some("\"abc123\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2026 07:28:00 GMT".to_string()),
+        };
+        cache
+            .write_validators(SPOT_PRICE_DATA_FILE, &validators)
+            .unwrap();
+        assert_eq!(cache.validators(SPOT_PRICE_DATA_FILE), Some(validators));
+
+        // Stale entries can still be read for revalidation
+        assert_eq!(cache.read(SPOT_PRICE_DATA_FILE), None);
+        assert_eq!(
+            cache.read_stale(SPOT_PRICE_DATA_FILE),
+            Some("callback({})".to_string())
+        );
+        cache.touch(SPOT_PRICE_DATA_FILE).unwrap();
+
+        // Empty validators remove the stored ones
+        cache
+            .write_validators(SPOT_PRICE_DATA_FILE, &Validators::default())
+            .unwrap();
+        assert_eq!(cache.validators(SPOT_PRICE_DATA_FILE), None);
+
+        // Clearing removes the validators too
+        cache
+            .write_validators(
+                SPOT_PRICE_DATA_FILE,
+                &Validators {
+                    etag: Some("\"abc123\"".to_string()),
+                    last_modified: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.validators(SPOT_PRICE_DATA_FILE), None);
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_dir_with_flag() {
+        assert_eq!(
+            resolve_dir(Some(Path::new("/tmp/spotter-cache"))),
+            Some(PathBuf::from("/tmp/spotter-cache"))
+        );
+    }
+
+    #[test]
+    fn test_cache_dir_from_env() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+
+        // SPOTTER_CACHE_DIR takes precedence on every platform
+        let vars = env(&[
+            ("SPOTTER_CACHE_DIR", "/srv/cache"),
+            ("XDG_CACHE_HOME", "/xdg"),
+            ("LOCALAPPDATA", "C:\\Users\\me\\AppData\\Local"),
+        ]);
+        assert_eq!(
+            cache_dir_from_env(vars, false),
+            Some(PathBuf::from("/srv/cache"))
+        );
+        assert_eq!(
+            cache_dir_from_env(vars, true),
+            Some(PathBuf::from("/srv/cache"))
+        );
+
+        // XDG paths on Linux and macOS
+        let vars = env(&[("XDG_CACHE_HOME", "/xdg"), ("HOME", "/home/me")]);
+        assert_eq!(
+            cache_dir_from_env(vars, false),
+            Some(PathBuf::from("/xdg/spotter"))
+        );
+        let vars = env(&[("XDG_CACHE_HOME", ""), ("HOME", "/home/me")]);
+        assert_eq!(
+            cache_dir_from_env(vars, false),
+            Some(PathBuf::from("/home/me/.cache/spotter"))
+        );
+
+        // Local app data on Windows
+        let vars = env(&[("LOCALAPPDATA", "/appdata")]);
+        assert_eq!(
+            cache_dir_from_env(vars, true),
+            Some(PathBuf::from("/appdata").join("spotter").join("cache"))
+        );
+
+        assert_eq!(cache_dir_from_env(env(&[]), false), None);
+    }
+
+    #[test]
+    fn test_cache_stale() {
+        let cache = temp_cache("stale", Duration::ZERO);
+        cache.write(SPOT_ADVISOR_DATA_FILE, "{}").unwrap();
+
+        // Entries are stale once they're older than the TTL
+        assert_eq!(cache.read(SPOT_ADVISOR_DATA_FILE), None);
+        assert!(cache.path(SPOT_ADVISOR_DATA_FILE).exists());
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+use crate::display::{self, INTERRUPTION_RATES};
+use crate::history::HistoryRecord;
+use anyhow::{Result, anyhow};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+/// Size of the rendered chart in pixels
+const CHART_SIZE: (u32, u32) = (1024, 768);
+
+/// Image format of a chart, picked by the extension of the output file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChartFormat {
+    Svg,
+    Png,
+}
+
+impl ChartFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("svg") => Ok(ChartFormat::Svg),
+            Some("png") => Ok(ChartFormat::Png),
+            _ => Err(anyhow!(
+                "Unsupported chart file '{}', use a .svg or .png extension",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// Returns the recorded spot prices as (recorded at, price) points
+fn price_points(records: &[HistoryRecord]) -> Vec<(i64, f64)> {
+    records
+        .iter()
+        .filter_map(|r| Some((r.recorded_at, r.spot_price?)))
+        .collect()
+}
+
+/// Returns the recorded interruption buckets as the points of a step line, holding each bucket
+/// until the next record
+fn bucket_points(records: &[HistoryRecord]) -> Vec<(i64, i32)> {
+    let mut points: Vec<(i64, i32)> = Vec::new();
+    for record in records {
+        let Some(bucket) = INTERRUPTION_RATES
+            .iter()
+            .position(|rate| *rate == record.interruption_rate)
+        else {
+            continue;
+        };
+        if let Some(&(_, previous)) = points.last() {
+            points.push((record.recorded_at, previous));
+        }
+        points.push((record.recorded_at, bucket as i32));
+    }
+    points
+}
+
+/// Returns the range of the price axis, padded by 10% so the line doesn't touch the edges
+fn price_range(points: &[(i64, f64)]) -> (f64, f64) {
+    let min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    let padding = if max > min { (max - min) * 0.1 } else { 0.01 };
+    ((min - padding).max(0.0), max + padding)
+}
+
+/// Returns the time range of the records, widened by a minute when there is a single record
+fn time_range(records: &[HistoryRecord]) -> (i64, i64) {
+    let first = records.first().map_or(0, |r| r.recorded_at);
+    let last = records.last().map_or(0, |r| r.recorded_at);
+    if last > first {
+        (first, last)
+    } else {
+        (first - 60, last + 60)
+    }
+}
+
+/// Formats a time axis label like "10-16 12:00"
+fn time_label(secs: &i64) -> String {
+    display::format_rfc3339(*secs)[5..16].replace('T', " ")
+}
+
+/// Draws the price over time above the interruption bucket over time
+fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    title: &str,
+    records: &[HistoryRecord],
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 24))?;
+    let (upper, lower) = root.split_vertically(CHART_SIZE.1 * 3 / 5);
+    let (start, end) = time_range(records);
+
+    let prices = price_points(records);
+    let (min_price, max_price) = price_range(&prices);
+    let mut price_chart = ChartBuilder::on(&upper)
+        .caption("Spot price ($/h)", ("sans-serif", 18))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(start..end, min_price..max_price)?;
+    price_chart
+        .configure_mesh()
+        .x_labels(8)
+        .x_label_formatter(&time_label)
+        .y_label_formatter(&|price| format!("{:.4}", price))
+        .draw()?;
+    price_chart.draw_series(LineSeries::new(prices, &BLUE))?;
+
+    let mut bucket_chart = ChartBuilder::on(&lower)
+        .caption("Interruption rate", ("sans-serif", 18))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(start..end, 0..INTERRUPTION_RATES.len() as i32 - 1)?;
+    bucket_chart
+        .configure_mesh()
+        .x_labels(8)
+        .x_label_formatter(&time_label)
+        .y_labels(INTERRUPTION_RATES.len())
+        .y_label_formatter(&|bucket| {
+            INTERRUPTION_RATES
+                .get(*bucket as usize)
+                .map_or_else(String::new, |rate| rate.to_string())
+        })
+        .draw()?;
+    bucket_chart.draw_series(LineSeries::new(bucket_points(records), &RED))?;
+
+    root.present()
+}
+
+/// Renders the chart of the records of an instance type to an SVG or PNG file
+pub fn render(
+    path: &Path,
+    region: &str,
+    instance_type: &str,
+    records: &[HistoryRecord],
+) -> Result<()> {
+    if price_points(records).is_empty() {
+        return Err(anyhow!(
+            "No spot price of instance type '{}' recorded in region '{}'",
+            instance_type,
+            region
+        ));
+    }
+
+    let title = format!("{} in {}", instance_type, region);
+    let result = match ChartFormat::from_path(path)? {
+        ChartFormat::Svg => draw(
+            SVGBackend::new(path, CHART_SIZE).into_drawing_area(),
+            &title,
+            records,
+        )
+        .map_err(|e| e.to_string()),
+        ChartFormat::Png => draw(
+            BitMapBackend::new(path, CHART_SIZE).into_drawing_area(),
+            &title,
+            records,
+        )
+        .map_err(|e| e.to_string()),
+    };
+    result.map_err(|e| anyhow!("Failed to render chart '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(recorded_at: i64, interruption_rate: &str, spot_price: Option<f64>) -> HistoryRecord {
+        HistoryRecord {
+            recorded_at,
+            region: "us-east-1".to_string(),
+            instance_type: "m5.xlarge".to_string(),
+            os: "Linux".to_string(),
+            interruption_rate: interruption_rate.to_string(),
+            savings: Some(70.0),
+            spot_price,
+        }
+    }
+
+    #[test]
+    fn test_chart_format_from_path() {
+        assert_eq!(
+            ChartFormat::from_path(Path::new("chart.svg")).unwrap(),
+            ChartFormat::Svg
+        );
+        assert_eq!(
+            ChartFormat::from_path(Path::new("out/chart.PNG")).unwrap(),
+            ChartFormat::Png
+        );
+        assert!(ChartFormat::from_path(Path::new("chart.pdf")).is_err());
+        assert!(ChartFormat::from_path(Path::new("chart")).is_err());
+    }
+
+    #[test]
+    fn test_points() {
+        let records = vec![
+            record(100, "< 5%", Some(0.05)),
+            record(200, "5-10%", None),
+            record(300, "unknown", Some(0.06)),
+            record(400, "< 5%", Some(0.04)),
+        ];
+        assert_eq!(
+            price_points(&records),
+            vec![(100, 0.05), (300, 0.06), (400, 0.04)]
+        );
+        assert_eq!(
+            bucket_points(&records),
+            vec![(100, 0), (200, 0), (200, 1), (400, 1), (400, 0)]
+        );
+    }
+
+    #[test]
+    fn test_ranges() {
+        let (min, max) = price_range(&[(100, 0.1), (200, 0.2)]);
+        assert!((min - 0.09).abs() < 1e-9);
+        assert!((max - 0.21).abs() < 1e-9);
+        // A flat price still gets a non-empty axis
+        let (min, max) = price_range(&[(100, 0.005)]);
+        assert_eq!(min, 0.0);
+        assert!((max - 0.015).abs() < 1e-9);
+
+        assert_eq!(time_range(&[record(100, "< 5%", None)]), (40, 160));
+        assert_eq!(
+            time_range(&[record(100, "< 5%", None), record(500, "< 5%", None)]),
+            (100, 500)
+        );
+        assert_eq!(time_label(&1_760_616_000), "10-16 12:00");
+    }
+
+    #[test]
+    fn test_render_without_prices() {
+        let records = vec![record(100, "< 5%", None)];
+        assert!(render(Path::new("chart.svg"), "us-east-1", "m5.xlarge", &records).is_err());
+    }
+}
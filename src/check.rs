@@ -0,0 +1,243 @@
+use crate::cli::Os;
+use crate::display::{self, InstanceData};
+use crate::recommend::INTERRUPTION_RATE_UPPER_BOUNDS;
+use anyhow::{Result, anyhow};
+
+/// Exit code when thresholds are violated, distinct from the exit code 1 of errors
+pub const VIOLATION_EXIT_CODE: i32 = 2;
+
+/// Thresholds the checked instance types must stay within
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Thresholds {
+    /// Maximum interruption rate (%), compared with the upper bound of the bucket
+    pub max_interruption: Option<f64>,
+    /// Maximum spot price per hour
+    pub max_price: Option<f64>,
+    /// Minimum savings over on-demand (%)
+    pub min_savings: Option<f64>,
+}
+
+/// Result of checking an instance type against the thresholds
+#[derive(Clone, Debug, PartialEq)]
+pub struct Check {
+    pub instance_type: String,
+    pub interruption_rate: String,
+    pub price: Option<f64>,
+    pub savings: Option<f64>,
+    /// Descriptions of the violated thresholds, empty when the instance type passes
+    pub violations: Vec<String>,
+}
+
+impl Check {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Checks the instance types matching the filters (families like 'm5', sizes or full types)
+/// against the thresholds. Missing values violate their thresholds, so a gate never passes on
+/// data it couldn't check.
+pub fn check(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    filters: &[String],
+    thresholds: &Thresholds,
+) -> Result<Vec<Check>> {
+    if *thresholds == Thresholds::default() {
+        return Err(anyhow!(
+            "No thresholds to check. Please pass --max-interruption, --max-price or --min-savings"
+        ));
+    }
+
+    let os_name = os.advisor_keys()[0];
+    let price_column = os.price_column();
+    let mut checks = Vec::new();
+    for filter in filters {
+        let mut matching: Vec<Check> = instance_data
+            .iter()
+            .filter(|((name, instance_os), _)| {
+                instance_os == os_name && display::matches_instance_filter(name, filter)
+            })
+            .filter_map(|((name, _), region_map)| {
+                let info = region_map.get(region)?;
+                let price = info.spot_price_value(price_column);
+                let savings = info.savings_value();
+                let mut violations = Vec::new();
+
+                if let Some(max) = thresholds.max_interruption {
+                    match info.interruption_bucket() {
+                        Some(bucket) if INTERRUPTION_RATE_UPPER_BOUNDS[bucket] <= max => {}
+                        Some(_) => violations.push(format!(
+                            "interruption rate {} is above {}%",
+                            info.interruption_rate, max
+                        )),
+                        None => violations.push("interruption rate is unavailable".to_string()),
+                    }
+                }
+                if let Some(max) = thresholds.max_price {
+                    match price {
+                        Some(price) if price <= max => {}
+                        Some(price) => violations
+                            .push(format!("spot price ${:.4}/h is above ${}/h", price, max)),
+                        None => violations.push("spot price is unavailable".to_string()),
+                    }
+                }
+                if let Some(min) = thresholds.min_savings {
+                    match savings {
+                        Some(savings) if savings >= min => {}
+                        Some(savings) => {
+                            violations.push(format!("savings {}% are below {}%", savings, min))
+                        }
+                        None => violations.push("savings are unavailable".to_string()),
+                    }
+                }
+
+                Some(Check {
+                    instance_type: name.clone(),
+                    interruption_rate: info.interruption_rate.clone(),
+                    price,
+                    savings,
+                    violations,
+                })
+            })
+            .collect();
+
+        if matching.is_empty() {
+            return Err(anyhow!(
+                "No instance types matching '{}' found in region '{}'",
+                filter,
+                region
+            ));
+        }
+        matching.sort_by(|a, b| a.instance_type.cmp(&b.instance_type));
+        checks.extend(matching);
+    }
+
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            ("m6i.4xlarge", "Linux", "us-east-1", info("5-10%", "0.35")),
+            (
+                "m6i.8xlarge",
+                "Linux",
+                "us-east-1",
+                info("10-15%", "0.75").with_savings("60%"),
+            ),
+            (
+                "c6i.large",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "N/A").with_savings("80%"),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_check() {
+        let thresholds = Thresholds {
+            max_interruption: Some(10.0),
+            max_price: Some(0.40),
+            min_savings: None,
+        };
+        let checks = check(
+            &sample_data(),
+            "us-east-1",
+            Os::Linux,
+            &["m6i.4xlarge".to_string()],
+            &thresholds,
+        )
+        .unwrap();
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].passed());
+        assert_eq!(checks[0].price, Some(0.35));
+
+        // Families match every size, each violating both thresholds
+        let checks = check(
+            &sample_data(),
+            "us-east-1",
+            Os::Linux,
+            &["m6i".to_string()],
+            &thresholds,
+        )
+        .unwrap();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[1].instance_type, "m6i.8xlarge");
+        assert_eq!(
+            checks[1].violations,
+            vec![
+                "interruption rate 10-15% is above 10%",
+                "spot price $0.7500/h is above $0.4/h",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_missing_values() {
+        let thresholds = Thresholds {
+            max_price: Some(1.0),
+            min_savings: Some(90.0),
+            ..Default::default()
+        };
+        let checks = check(
+            &sample_data(),
+            "us-east-1",
+            Os::Linux,
+            &["c6i.large".to_string()],
+            &thresholds,
+        )
+        .unwrap();
+        assert_eq!(
+            checks[0].violations,
+            vec!["spot price is unavailable", "savings 80% are below 90%"]
+        );
+    }
+
+    #[test]
+    fn test_check_invalid() {
+        let thresholds = Thresholds {
+            max_price: Some(1.0),
+            ..Default::default()
+        };
+        // Unknown instance types and regions fail rather than pass
+        assert!(
+            check(
+                &sample_data(),
+                "us-east-1",
+                Os::Linux,
+                &["x1.large".to_string()],
+                &thresholds,
+            )
+            .is_err()
+        );
+        assert!(
+            check(
+                &sample_data(),
+                "eu-west-1",
+                Os::Linux,
+                &["m6i".to_string()],
+                &thresholds,
+            )
+            .is_err()
+        );
+
+        // At least one threshold is required
+        assert!(
+            check(
+                &sample_data(),
+                "us-east-1",
+                Os::Linux,
+                &["m6i".to_string()],
+                &Thresholds::default(),
+            )
+            .is_err()
+        );
+    }
+}
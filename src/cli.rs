@@ -1,109 +1,3135 @@
+use crate::alert;
+use crate::cache::DEFAULT_CACHE_TTL;
+use crate::notify;
 use anyhow::{Result, anyhow};
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_verbosity_flag::{InfoLevel, Verbosity};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, disable_version_flag = true)]
 pub struct Cli {
-    /// AWS region (default: us-east-1)
-    #[arg(short, long, default_value = "us-east-1")]
-    pub region: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// AWS region (default: detected from AWS_REGION, ~/.aws/config or EC2 metadata, falling back to us-east-1), or region of the --provider cloud
+    #[arg(short, long, global = true)]
+    pub region: Option<String>,
+
+    /// Cloud of the spot data. Exports, --live, --specs, record and the commands reading AWS
+    /// accounts stay AWS-only. The vCPUs and memory of Azure VM sizes are only known for the D, E,
+    /// F and L series, or those in --azure-vm-sizes, and the other sizes are left out by the vCPU
+    /// and memory filters
+    #[arg(long, value_enum, default_value_t = Provider::Aws, global = true)]
+    pub provider: Provider,
+
+    /// API key of the Cloud Billing API, required by --provider gcp
+    #[arg(long, global = true, env = "GOOGLE_API_KEY", hide_env_values = true)]
+    pub gcp_api_key: Option<String>,
+
+    /// Interruption rate (%) assumed for every machine type of --provider gcp, which doesn't
+    /// publish preemption rates (e.g., 15, the upper bound of the daily preemption rate GCP
+    /// documents). Without it, GCP offers have no interruption rate and are left out by the
+    /// interruption rate filters and the effective cost rankings
+    #[arg(long, global = true)]
+    pub gcp_interruption_rate: Option<f64>,
+
+    /// Eviction rates of --provider azure, the JSON output of an `az graph query` of the
+    /// microsoft.compute/skuspotevictionrate/location spot resources
+    #[arg(long, global = true)]
+    pub azure_eviction_rates: Option<PathBuf>,
+
+    /// VM sizes of --provider azure, the JSON output of `az vm list-skus --resource-type
+    /// virtualMachines` or `az vm list-sizes`, giving the vCPUs and memory of every series
+    #[arg(long, global = true)]
+    pub azure_vm_sizes: Option<PathBuf>,
 
     /// EC2 instance type to filter by (family like 'm5', size like 'large', or full type like 'm5.large')
     #[arg(short, long)]
     pub instance_type: Option<String>,
 
-    /// Show spot prices for Linux and Windows (for latest pricing information, check AWS Management Console)
-    #[arg(long)]
-    pub spot_price: bool,
+    /// Show spot prices for Linux and Windows, along with on-demand prices (for latest pricing information, check AWS Management Console)
+    #[arg(long)]
+    pub spot_price: bool,
+
+    /// Platforms of the spot price columns shown with --spot-price
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [PriceColumn::Linux, PriceColumn::Mswin]
+    )]
+    pub price_columns: Vec<PriceColumn>,
+
+    /// Operating system of the spot advisor data to show
+    #[arg(long, value_enum, default_value_t = Os::Linux, global = true)]
+    pub os: Os,
+
+    /// Sort instances by the given column (prices use the spot price of the selected OS)
+    #[arg(long, value_enum, default_value_t = SortBy::Name)]
+    pub sort_by: SortBy,
+
+    /// Only show instances whose spot price per vCPU-hour is at most this value
+    #[arg(long)]
+    pub max_price_per_vcpu: Option<f64>,
+
+    /// Only show instances whose spot price per GiB-hour is at most this value
+    #[arg(long)]
+    pub max_price_per_gib: Option<f64>,
+
+    /// Hours per month used for the monthly cost estimate shown with --spot-price
+    #[arg(long, default_value_t = DEFAULT_HOURS_PER_MONTH)]
+    pub hours_per_month: f64,
+
+    /// Risk penalties (%) added to the spot price for the effective cost, one per interruption
+    /// rate bucket (< 5%, 5-10%, 10-15%, 15-20%, > 20%)
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_values_t = DEFAULT_RISK_PENALTIES,
+        global = true
+    )]
+    pub risk_penalties: Vec<f64>,
+
+    /// Suggest newer generations of the same family that are cheaper or safer on spot
+    #[arg(long)]
+    pub suggest_upgrades: bool,
+
+    /// Collapse the instance types into one row per group
+    #[arg(long, value_enum)]
+    pub group_by: Option<GroupBy>,
+
+    /// Draw a histogram of how many instance types fall in each interruption rate bucket
+    #[arg(long)]
+    pub histogram: bool,
+
+    /// Show a suggested max price (bid ceiling) per instance type for launch templates and fleet requests
+    #[arg(long)]
+    pub suggest_max_price: bool,
+
+    /// Margin (%) over the current spot price of the suggested max price, capped at on-demand
+    #[arg(long, default_value_t = DEFAULT_MAX_PRICE_MARGIN)]
+    pub max_price_margin: f64,
+
+    /// Append a timestamped row per displayed instance type to a CSV price log
+    #[arg(long)]
+    pub append_csv: Option<PathBuf>,
+
+    /// Also show the current spot prices per Availability Zone from the EC2
+    /// DescribeSpotPriceHistory API (requires AWS credentials)
+    #[arg(long)]
+    pub live: bool,
+
+    /// Add the architecture, network performance, EBS bandwidth, GPUs and hypervisor of each
+    /// instance type from the EC2 DescribeInstanceTypes API (requires AWS credentials)
+    #[arg(long)]
+    pub specs: bool,
+
+    /// Show a sparkline of the spot price of each instance type over the last samples recorded
+    /// with `spotter record`
+    #[arg(long)]
+    pub trend: bool,
+
+    /// Number of recorded samples of the --trend sparklines
+    #[arg(long, default_value_t = 20)]
+    pub trend_samples: usize,
+
+    /// History database of the --trend sparklines and --volatility: a SQLite file, a DuckDB file
+    /// or a PostgreSQL URL
+    #[arg(long, default_value = "spot.db")]
+    pub trend_db: String,
+
+    /// Show the volatility of the spot price of each instance type, the coefficient of variation
+    /// of the prices recorded with `spotter record` over the volatility window
+    #[arg(long)]
+    pub volatility: bool,
+
+    /// Window of recorded prices of the volatility (e.g., '7d', '24h')
+    #[arg(long, value_parser = parse_duration, default_value = "7d")]
+    pub volatility_window: f64,
+
+    /// Only show instance types whose price volatility (%) is at most this, implying --volatility
+    #[arg(long)]
+    pub max_volatility: Option<f64>,
+
+    /// Don't suggest similar instance types when a queried instance type has an interruption rate above 20%
+    #[arg(long)]
+    pub no_suggestions: bool,
+
+    /// How long fetched data is reused before it's re-fetched (e.g., '1h', '30m')
+    #[arg(long, global = true, value_parser = parse_duration, default_value = DEFAULT_CACHE_TTL)]
+    pub cache_ttl: f64,
+
+    /// Directory of the cache (default: $SPOTTER_CACHE_DIR, or the platform cache directory)
+    #[arg(long, global = true)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Load the data from a snapshot saved with `spotter snapshot save` instead of fetching it
+    #[arg(long, global = true)]
+    pub from_snapshot: Option<PathBuf>,
+
+    /// Always fetch fresh data, bypassing the cache
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Config file providing defaults of the flags above (default:
+    /// ~/.config/spotter/config.toml when it exists)
+    #[arg(long, global = true, env = "SPOTTER_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Profile of the config file to use, bundling defaults for a workload
+    #[arg(long, global = true, env = "SPOTTER_PROFILE")]
+    pub profile: Option<String>,
+
+    /// OTLP/HTTP collector to export metrics and traces to in serve and record modes (requires
+    /// the 'otel' feature)
+    #[arg(long, global = true, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Print version
+    #[arg(short = 'V', long)]
+    pub version: bool,
+
+    /// Print the version with the git SHA, build date, target and data sources as JSON (with
+    /// --version)
+    #[arg(long, requires = "version")]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub aws: AwsArgs,
+
+    #[clap(flatten)]
+    pub verbose: Verbosity<InfoLevel>,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// List regions available in the spot advisor and price data
+    Regions,
+    /// List every upstream data source the configuration and the commands reach out to
+    Sources,
+    /// List instance families in a region with their sizes, cores and memory ranges
+    Families,
+    /// Show a detail card for a single instance type
+    Describe {
+        /// Instance type to describe (e.g., 'm6i.2xlarge')
+        instance_type: String,
+    },
+    /// Compare instance types side by side, highlighting the best value of each row
+    Compare {
+        /// Instance types to compare (e.g., 'm5.xlarge m6i.xlarge m7g.xlarge')
+        #[arg(required = true, num_args = 2..)]
+        instance_types: Vec<String>,
+    },
+    /// Show last month's on-demand EC2 spend per instance type from Cost Explorer with the
+    /// potential savings of moving it to spot (requires AWS credentials)
+    Spend,
+    /// Summarize the spot market of a region: price range, average savings and interruption rates
+    Stats {
+        /// Only summarize instance types matching this family, size or name (e.g., 'm5')
+        #[arg(short, long)]
+        instance_type: Option<String>,
+    },
+    /// Estimate the hourly and monthly spot cost of a mixed fleet and compare it to on-demand
+    Estimate {
+        /// Fleet spec of counts and instance types (e.g., '4x m6i.2xlarge, 2x r6g.xlarge')
+        #[arg(long)]
+        spec: String,
+
+        /// Hours per month the fleet runs
+        #[arg(long, default_value_t = DEFAULT_HOURS_PER_MONTH)]
+        hours: f64,
+    },
+    /// Simulate the expected wall-clock time and cost of a workload on spot, including the rework
+    /// after interruptions
+    Simulate {
+        /// Instance type to run the workload on (e.g., 'p4d.24xlarge')
+        #[arg(short, long)]
+        instance_type: String,
+
+        /// Uninterrupted runtime of the workload (e.g., '12h', '90m', '2d')
+        #[arg(long, value_parser = parse_duration)]
+        runtime: f64,
+
+        /// Work lost per interruption since the last checkpoint (e.g., '30m'), the whole
+        /// runtime when the workload doesn't checkpoint
+        #[arg(long, value_parser = parse_duration)]
+        checkpoint_loss: Option<f64>,
+    },
+    /// Advise whether lowest-price or price-capacity-optimized allocation suits the spot pools better
+    Strategy {
+        /// Comma-separated families, sizes or instance types of the pools (e.g., 'm5,m6i,c6i')
+        #[arg(long, required = true, value_delimiter = ',')]
+        types: Vec<String>,
+    },
+    /// Get AWS's spot placement scores of instance types in regions, alongside their advisor
+    /// interruption rates (requires AWS credentials)
+    Score {
+        /// Comma-separated instance types requested together (e.g., 'm5.xlarge,m6i.xlarge')
+        #[arg(long, required = true, value_delimiter = ',')]
+        types: Vec<String>,
+
+        /// Number of instances to score the capacity of
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i32).range(1..))]
+        target_capacity: i32,
+
+        /// Comma-separated regions to score (default: the region)
+        #[arg(long, value_delimiter = ',')]
+        regions: Vec<String>,
+
+        /// Score each Availability Zone instead of each region
+        #[arg(long)]
+        single_az: bool,
+    },
+    /// Audit the running spot instances of the account, flagging risky or overpriced pools by
+    /// their advisor interruption rates and current prices per AZ (requires AWS credentials)
+    Audit {
+        #[command(subcommand)]
+        target: Option<AuditTarget>,
+
+        /// Maximum interruption rate (%) before a pool is flagged as risky
+        #[arg(long, global = true, default_value_t = 10.0)]
+        max_interruption: f64,
+
+        /// Maximum premium (%) over the cheapest AZ of the instance type before a pool is flagged
+        /// as overpriced
+        #[arg(long, default_value_t = 10.0)]
+        max_premium: f64,
+    },
+    /// Export the recommended instance types as configuration of other tools
+    Export {
+        #[command(subcommand)]
+        target: ExportTarget,
+    },
+    /// Rate the spot instance types of the Kubernetes cluster of the kubeconfig (requires the
+    /// 'k8s' feature)
+    K8s {
+        #[command(subcommand)]
+        action: K8sAction,
+
+        /// Kubeconfig context of the cluster, the current context by default
+        #[arg(long, global = true)]
+        context: Option<String>,
+
+        /// Maximum interruption rate (%) before an instance type is flagged as risky
+        #[arg(long, global = true, default_value_t = 10.0)]
+        max_interruption: f64,
+    },
+    /// Manage the cache of fetched data
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Save point-in-time copies of the data for reproducible analyses
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+    /// Report the instance types whose price, savings or interruption rate changed between two snapshots
+    Diff {
+        /// Older snapshot saved with `spotter snapshot save`
+        old: PathBuf,
+        /// Newer snapshot saved with `spotter snapshot save`
+        new: PathBuf,
+    },
+    /// Periodically fetch the data and append it to a local SQLite database to build a price history
+    Record {
+        /// Time between two records (e.g., '1h', '30m')
+        #[arg(long, value_parser = parse_duration, default_value = "1h")]
+        interval: f64,
+
+        /// Database to append the records to: a SQLite file, a DuckDB file ('*.duckdb' or
+        /// 'duckdb://path') or a PostgreSQL URL ('postgres://...')
+        #[arg(long, default_value = "spot.db")]
+        db: String,
+    },
+    /// Summarize the recorded price history of an instance type in a region
+    History {
+        /// Instance type to summarize (e.g., 'm5.xlarge')
+        #[arg(short, long)]
+        instance_type: String,
+
+        /// How far back to look (e.g., '30d', '12h')
+        #[arg(long, value_parser = parse_duration, default_value = "30d")]
+        since: f64,
+
+        /// Database written by `spotter record`: a SQLite file, a DuckDB file or a PostgreSQL URL
+        #[arg(long, default_value = "spot.db")]
+        db: String,
+    },
+    /// Render the recorded spot price and interruption rate of an instance type to an SVG or
+    /// PNG chart
+    Chart {
+        /// Instance type to chart (e.g., 'm5.xlarge')
+        #[arg(short, long)]
+        instance_type: String,
+
+        /// How far back to look (e.g., '14d', '12h')
+        #[arg(long, value_parser = parse_duration, default_value = "14d")]
+        since: f64,
+
+        /// Database written by `spotter record`: a SQLite file, a DuckDB file or a PostgreSQL URL
+        #[arg(long, default_value = "spot.db")]
+        db: String,
+
+        /// Chart file to write, in the format of its extension (.svg or .png)
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Report the days of the week and hours (UTC) that historically had the lowest spot prices and
+    /// fewest price spikes of an instance type, to schedule flexible batch or training jobs
+    When {
+        /// Instance type to analyze (e.g., 'p4d.24xlarge')
+        #[arg(short, long)]
+        instance_type: String,
+
+        /// How far back to look (e.g., '30d'), at most 90 days without --db
+        #[arg(long, value_parser = parse_duration, default_value = "30d")]
+        since: f64,
+
+        /// Database written by `spotter record` to analyze, instead of the EC2 spot price history
+        /// of every Availability Zone (requires AWS credentials)
+        #[arg(long)]
+        db: Option<String>,
+    },
+    /// Find the Graviton (ARM) equivalents of an x86 instance type and the savings from switching
+    Graviton {
+        /// x86 instance type (e.g., 'm5.2xlarge')
+        instance_type: String,
+    },
+    /// List the accelerated instance types with their GPUs, sorted by spot price per GPU-hour
+    /// (requires AWS credentials to describe the instance types)
+    Gpu {
+        #[clap(flatten)]
+        filter: GpuFilterArgs,
+    },
+    /// Compare the Linux spot shapes closest to the vCPUs and memory on AWS, GCP and Azure, with
+    /// their price per vCPU-hour (--region is the AWS region)
+    CompareClouds {
+        /// Minimum number of vCPUs
+        #[arg(long)]
+        vcpus: u32,
+
+        /// Minimum memory in GB
+        #[arg(long)]
+        memory: f64,
+
+        /// Number of shapes per cloud
+        #[arg(long, default_value_t = 3)]
+        count: usize,
+
+        /// GCP region
+        #[arg(long, default_value = "us-central1")]
+        gcp_region: String,
+
+        /// Azure region
+        #[arg(long, default_value = "eastus")]
+        azure_region: String,
+    },
+    /// Recommend instance types meeting resource constraints, ranked by effective cost
+    Recommend {
+        /// Minimum number of vCPUs
+        #[arg(long)]
+        vcpus: Option<u32>,
+
+        /// Minimum memory in GB
+        #[arg(long)]
+        memory: Option<f64>,
+
+        /// Maximum interruption rate (%), e.g., 10 allows the '< 5%' and '5-10%' buckets
+        #[arg(long)]
+        max_interruption: Option<f64>,
+
+        /// Maximum number of instance types to recommend
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+
+        /// Suggest a spot instance type for each workload from the account's AWS Compute
+        /// Optimizer rightsizing recommendations instead (requires AWS credentials)
+        #[arg(long, conflicts_with_all = ["vcpus", "memory"])]
+        from_compute_optimizer: bool,
+    },
+    /// Show only Pareto-optimal instance types over spot price, interruption rate, vCPUs and memory
+    Pareto {
+        /// Minimum number of vCPUs
+        #[arg(long)]
+        min_vcpus: Option<u32>,
+
+        /// Minimum memory in GB
+        #[arg(long)]
+        min_memory: Option<f64>,
+
+        /// Maximum interruption rate (%)
+        #[arg(long)]
+        max_interruption: Option<f64>,
+    },
+    /// Pick instance types across different families for a diversified Spot Fleet or ASG
+    Diversify {
+        /// Minimum number of vCPUs
+        #[arg(long)]
+        vcpus: Option<u32>,
+
+        /// Minimum memory in GB
+        #[arg(long)]
+        memory: Option<f64>,
+
+        /// Maximum interruption rate (%)
+        #[arg(long, default_value_t = 10.0)]
+        max_interruption: f64,
+
+        /// Number of instance types to pick
+        #[arg(long, default_value_t = 6)]
+        count: usize,
+    },
+    /// Serve the spot data as an HTTP JSON API, refreshed every cache TTL
+    Serve {
+        /// Address to listen on, e.g., ':8080' for every interface or '127.0.0.1:8080'
+        #[arg(long, value_parser = parse_listen_addr, default_value = "127.0.0.1:8080")]
+        http: SocketAddr,
+
+        /// Also serve the gRPC SpotService on this address (requires the 'grpc' feature)
+        #[arg(long, value_parser = parse_listen_addr)]
+        grpc: Option<SocketAddr>,
+
+        /// Require this bearer token in the Authorization header of every request
+        #[arg(long, env = "SPOTTER_AUTH_TOKEN", hide_env_values = true)]
+        auth_token: Option<String>,
+
+        /// Maximum number of requests per minute of each client IP (0 for unlimited)
+        #[arg(long, default_value_t = DEFAULT_RATE_LIMIT)]
+        rate_limit: u32,
+    },
+    /// Notify when alert rules on the price, interruption rate or savings of instance types trip
+    Alert {
+        /// Rule like "m5.xlarge@eu-west-1 price>0.10 or interruption>10", comparing price,
+        /// interruption (upper bound of the bucket in %), savings (%) or on_demand (spot price as
+        /// % of the on-demand price) with >, >=, < or <=, joined by "and" and "or" (can be
+        /// repeated)
+        #[arg(long = "rule", value_parser = alert::parse_rule, required_unless_present = "rules_file")]
+        rules: Vec<alert::Rule>,
+
+        /// YAML file of rules and notifiers (e.g., alerts.yaml), see the README for its format
+        #[arg(long = "rules")]
+        rules_file: Option<PathBuf>,
+
+        /// Slack incoming webhook URL to post tripped rules to
+        #[arg(long)]
+        slack_webhook: Option<String>,
+
+        /// URL to POST a JSON payload of tripped rules to (e.g., a Teams, Mattermost or
+        /// PagerDuty proxy endpoint)
+        #[arg(long)]
+        webhook: Option<String>,
+
+        /// ARN of an SNS topic to publish tripped rules to (e.g.,
+        /// arn:aws:sns:us-east-1:123456789012:alerts), using the default AWS credentials
+        #[arg(long, value_parser = notify::parse_sns_topic)]
+        sns_topic: Option<String>,
+
+        #[clap(flatten)]
+        smtp: SmtpArgs,
+
+        /// Show a native desktop notification for tripped rules (requires the 'desktop' feature)
+        #[arg(long)]
+        desktop_notify: bool,
+
+        /// Keep running and re-evaluate the rules every interval (e.g., 5m), notifying only when
+        /// a rule starts tripping
+        #[arg(long, value_parser = parse_duration)]
+        interval: Option<f64>,
+    },
+    /// Diagnose fetch failures: connectivity to the data endpoints, proxy settings, cache
+    /// directory, clock skew and data freshness
+    Doctor,
+    /// Generate the config file interactively: default region, columns and cache settings
+    Init {
+        /// Replace the config file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Pick an instance type of the region in a fuzzy finder and print it to stdout (e.g.,
+    /// `aws ec2 run-instances --instance-type $(spotter pick)`)
+    Pick {
+        /// EC2 instance type to narrow the choices to (family like 'm5', size like 'large', or
+        /// full type)
+        #[arg(short, long)]
+        instance_type: Option<String>,
+    },
+    /// Redraw the instance table on each refresh to keep tracking pools, using the display
+    /// options given before the subcommand (e.g., `spotter --spot-price watch -i m5`)
+    Watch {
+        /// How often the data is re-fetched and the table redrawn (e.g., 60s, 5m)
+        #[arg(long, value_parser = parse_duration, default_value = "60s")]
+        interval: f64,
+
+        /// EC2 instance type to filter by (family like 'm5', size like 'large', or full type)
+        #[arg(short, long)]
+        instance_type: Option<String>,
+
+        /// Show a desktop notification when the spot price of a watched instance type rises above
+        /// this price (requires the 'desktop' feature)
+        #[arg(long)]
+        notify_above: Option<f64>,
+
+        /// Show a desktop notification when the spot price of a watched instance type falls below
+        /// this price (requires the 'desktop' feature)
+        #[arg(long)]
+        notify_below: Option<f64>,
+    },
+    /// Continuously rank the top spot pools of the region, reordering them as the data refreshes
+    Top {
+        /// How the pools are ranked
+        #[arg(long, value_enum, default_value_t = RankBy::Price)]
+        by: RankBy,
+
+        /// Number of pools shown
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// How often the data is re-fetched and the ranking redrawn (e.g., 60s, 5m)
+        #[arg(long, value_parser = parse_duration, default_value = "60s")]
+        interval: f64,
+
+        /// EC2 instance type to filter by (family like 'm5', size like 'large', or full type)
+        #[arg(short, long)]
+        instance_type: Option<String>,
+    },
+    /// Check instance types against thresholds, exiting with code 2 when any is violated (e.g.,
+    /// to block deploys onto degraded spot pools in CI)
+    Check {
+        /// Instance types to check (families like 'm5', sizes or full types)
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        instance_types: Vec<String>,
+
+        /// Maximum interruption rate (%), compared with the upper bound of the bucket
+        #[arg(long)]
+        max_interruption: Option<f64>,
+
+        /// Maximum spot price per hour
+        #[arg(long)]
+        max_price: Option<f64>,
+
+        /// Minimum savings over on-demand (%)
+        #[arg(long)]
+        min_savings: Option<f64>,
+
+        /// Append a Markdown report to $GITHUB_STEP_SUMMARY and emit ::warning:: annotations for
+        /// violated thresholds in GitHub Actions
+        #[arg(long)]
+        github_summary: bool,
+    },
+    /// Publish the current spot prices, interruption buckets and savings to a metrics backend
+    Publish {
+        #[command(subcommand)]
+        target: PublishTarget,
+    },
+}
+
+/// Default maximum number of requests per minute of each client IP in serve mode
+pub const DEFAULT_RATE_LIMIT: u32 = 120;
+
+/// Average number of hours in a month (24 * 365 / 12)
+pub const DEFAULT_HOURS_PER_MONTH: f64 = 730.0;
+
+/// Default margin (%) over the spot price of the suggested max price
+pub const DEFAULT_MAX_PRICE_MARGIN: f64 = 20.0;
+
+/// Default risk penalties (%) added to the spot price for each interruption rate bucket
+pub const DEFAULT_RISK_PENALTIES: [f64; 5] = [0.0, 5.0, 10.0, 20.0, 40.0];
+
+/// Operating system of the spot advisor data
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Os {
+    #[default]
+    Linux,
+    Windows,
+    All,
+}
+
+impl Os {
+    /// Returns the OS keys used in the spot advisor data
+    pub fn advisor_keys(&self) -> &'static [&'static str] {
+        match self {
+            Os::Linux => &["Linux"],
+            Os::Windows => &["Windows"],
+            Os::All => &["Linux", "Windows"],
+        }
+    }
+
+    /// Returns the price column of the OS, used for normalized prices and sorting
+    pub fn price_column(&self) -> PriceColumn {
+        match self {
+            Os::Windows => PriceColumn::Mswin,
+            Os::Linux | Os::All => PriceColumn::Linux,
+        }
+    }
+}
+
+/// Column to sort the instances by
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum SortBy {
+    #[default]
+    Name,
+    Price,
+    PricePerVcpu,
+    PricePerGib,
+    EffectiveCost,
+    Interruption,
+    Savings,
+}
+
+/// Order of the top view
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum RankBy {
+    /// Cheapest spot price first
+    #[default]
+    Price,
+    /// Lowest interruption rate first, cheapest first within a bucket
+    Interruption,
+    /// Highest savings first
+    Savings,
+}
+
+/// Action on the cache of fetched data
+#[derive(Subcommand, Clone, Copy, Debug, PartialEq)]
+pub enum CacheAction {
+    /// Show the cache directory and the age and size of each cached file
+    Info,
+    /// Remove the cached files
+    Clear,
+    /// Re-download the data into the cache
+    Refresh,
+}
+
+/// Configuration to export the recommended instance types as
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum ExportTarget {
+    /// Emit a Karpenter NodePool and EC2NodeClass YAML requiring the recommended spot instance types
+    Karpenter {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Name of the NodePool and EC2NodeClass
+        #[arg(long, default_value = "spot")]
+        name: String,
+
+        /// EKS cluster of the karpenter.sh/discovery tags of the subnets and security groups, and
+        /// of the node IAM role
+        #[arg(long, default_value = "my-cluster")]
+        cluster: String,
+    },
+    /// Emit an Auto Scaling MixedInstancesPolicy JSON overriding a launch template with the
+    /// recommended instance types and the advised spot allocation strategy
+    AsgPolicy {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Name of the launch template of the Auto Scaling group
+        #[arg(long, default_value = "my-launch-template")]
+        launch_template: String,
+
+        /// Minimum number of on-demand instances
+        #[arg(long, default_value_t = 0)]
+        on_demand_base: u32,
+
+        /// Percentage of on-demand instances above the base
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=100))]
+        on_demand_percentage: u32,
+    },
+    /// Emit a SpotFleetRequestConfig JSON with launch template overrides of the recommended
+    /// instance types in each Availability Zone
+    SpotFleet {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Number of instances of the fleet
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        target_capacity: u32,
+
+        /// IAM role granting Spot Fleet permission to launch and tag the instances
+        #[arg(
+            long,
+            default_value = "arn:aws:iam::123456789012:role/aws-ec2-spot-fleet-tagging-role"
+        )]
+        iam_fleet_role: String,
+
+        /// Name of the launch template of the fleet
+        #[arg(long, default_value = "my-launch-template")]
+        launch_template: String,
+
+        /// Comma-separated Availability Zones of the overrides (default: any AZ of the launch
+        /// template)
+        #[arg(long, value_delimiter = ',')]
+        availability_zones: Vec<String>,
+
+        /// Only request the target capacity once instead of maintaining it
+        #[arg(long)]
+        one_time: bool,
+    },
+    /// Emit a CreateFleet request body with launch template overrides of the recommended
+    /// instance types and the advised spot allocation strategy
+    Ec2Fleet {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Number of spot instances of the fleet
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        target_capacity: u32,
+
+        /// Name of the launch template of the fleet
+        #[arg(long, default_value = "my-launch-template")]
+        launch_template: String,
+
+        /// Comma-separated Availability Zones of the overrides (default: any AZ of the launch
+        /// template)
+        #[arg(long, value_delimiter = ',')]
+        availability_zones: Vec<String>,
+
+        /// Whether the fleet launches the capacity once synchronously or maintains it
+        #[arg(long = "type", value_enum, default_value_t = FleetType::Maintain)]
+        fleet_type: FleetType,
+    },
+    /// Emit the mixed_instances_policy block of a Terraform aws_autoscaling_group with launch
+    /// template overrides of the recommended instance types, as HCL
+    Terraform {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Terraform expression of the launch template ID
+        #[arg(long, default_value = "aws_launch_template.this.id")]
+        launch_template_id: String,
+
+        /// Minimum number of on-demand instances
+        #[arg(long, default_value_t = 0)]
+        on_demand_base: u32,
+
+        /// Percentage of on-demand instances above the base
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=100))]
+        on_demand_percentage: u32,
+
+        /// Only emit the launch template override blocks
+        #[arg(long)]
+        overrides_only: bool,
+    },
+    /// Emit an eksctl ClusterConfig with a spot managed node group of the recommended instance
+    /// types of a single architecture
+    Eksctl {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Name of the EKS cluster
+        #[arg(long)]
+        cluster: String,
+
+        /// Name of the managed node group
+        #[arg(long, default_value = "spot")]
+        name: String,
+
+        /// Minimum and desired number of nodes
+        #[arg(long, default_value_t = 1)]
+        min_size: u32,
+
+        /// Maximum number of nodes
+        #[arg(long, default_value_t = 10)]
+        max_size: u32,
+    },
+    /// Emit an AWS Batch managed SPOT compute environment of the recommended instance types with
+    /// a suggested bid percentage and the advised allocation strategy
+    Batch {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Name of the compute environment
+        #[arg(long, default_value = "spot")]
+        name: String,
+
+        /// Maximum number of vCPUs of the compute environment
+        #[arg(long, default_value_t = 256)]
+        max_vcpus: u32,
+
+        /// Comma-separated subnets of the instances
+        #[arg(long, value_delimiter = ',')]
+        subnets: Vec<String>,
+
+        /// Comma-separated security groups of the instances
+        #[arg(long, value_delimiter = ',')]
+        security_group_ids: Vec<String>,
+
+        /// ECS instance profile of the instances
+        #[arg(long, default_value = "ecsInstanceRole")]
+        instance_role: String,
+
+        /// Margin (%) over the spot prices of the suggested bid percentage
+        #[arg(long, default_value_t = DEFAULT_MAX_PRICE_MARGIN)]
+        max_price_margin: f64,
+    },
+    /// Emit an EMR instance fleet config of the recommended EMR-supported instance types, weighted
+    /// by their vCPUs
+    EmrFleet {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Name of the instance fleet
+        #[arg(long, default_value = "spot")]
+        name: String,
+
+        /// Node type of the instance fleet
+        #[arg(long, value_enum, default_value_t = EmrFleetType::Task)]
+        fleet_type: EmrFleetType,
+
+        /// Spot capacity of the instance fleet in vCPUs
+        #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u32).range(1..))]
+        target_spot_capacity: u32,
+
+        /// Minutes to wait for the spot capacity before switching to on-demand
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(5..=1440))]
+        timeout_minutes: u32,
+    },
+    /// Emit the node types of a Ray cluster YAML, a spot worker node type with its resources per
+    /// recommended instance type and an on-demand head node type
+    Ray {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// On-demand instance type of the head node
+        #[arg(long, default_value = "m5.large")]
+        head_instance_type: String,
+
+        /// Minimum workers of each worker node type
+        #[arg(long, default_value_t = 0)]
+        min_workers: u32,
+
+        /// Maximum workers of each worker node type
+        #[arg(long, default_value_t = 10)]
+        max_workers: u32,
+    },
+    /// Emit the resources of a SkyPilot task launching spot instances of the recommended instance
+    /// types, in order of preference
+    Skypilot {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// GPUs of each instance like 'a10g:1', recommending only instance types with exactly
+        /// these GPUs (requires AWS credentials to describe the instance types)
+        #[arg(long, value_parser = parse_accelerator)]
+        gpus: Option<Accelerator>,
+
+        #[clap(flatten)]
+        gpu_filter: GpuFilterArgs,
+    },
+    /// Emit a Nomad Autoscaler cluster scaling policy of an Auto Scaling group of spot Nomad
+    /// clients, with the recommended instance types of its mixed instances policy
+    Nomad {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Auto Scaling group of the Nomad clients
+        #[arg(long)]
+        asg_name: String,
+
+        /// Node class of the Nomad clients
+        #[arg(long, default_value = "spot")]
+        node_class: String,
+
+        /// Minimum number of Nomad clients
+        #[arg(long, default_value_t = 1)]
+        min: u32,
+
+        /// Maximum number of Nomad clients
+        #[arg(long, default_value_t = 10)]
+        max: u32,
+
+        /// Target allocated percentage of the clients' CPU and memory
+        #[arg(long, default_value_t = 70, value_parser = clap::value_parser!(u32).range(1..=100))]
+        target: u32,
+    },
+    /// Emit the slurm.conf node definitions of a cloud-bursting partition of spot nodes, weighted
+    /// by the recommended instance types' effective cost per vCPU
+    Slurm {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// Name of the partition
+        #[arg(long, default_value = "spot")]
+        partition: String,
+
+        /// Maximum dynamic nodes of each instance type
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        max_nodes: u32,
+    },
+    /// Emit a CloudFormation template of a spot Auto Scaling group of the recommended instance types
+    /// and its capacity provider, the default capacity provider strategy of an ECS cluster
+    Ecs {
+        #[clap(flatten)]
+        selection: SelectionArgs,
+
+        /// ECS cluster of the capacity provider
+        #[arg(long)]
+        cluster: String,
+
+        /// Name of the launch template of the container instances, with an ECS-optimized AMI
+        #[arg(long, default_value = "my-launch-template")]
+        launch_template: String,
+
+        /// Comma-separated subnets of the container instances
+        #[arg(long, value_delimiter = ',', required = true)]
+        subnets: Vec<String>,
+
+        /// Minimum number of container instances
+        #[arg(long, default_value_t = 0)]
+        min_size: u32,
+
+        /// Maximum number of container instances
+        #[arg(long, default_value_t = 10)]
+        max_size: u32,
+
+        /// Minimum number of on-demand instances
+        #[arg(long, default_value_t = 0)]
+        on_demand_base: u32,
+
+        /// Percentage of on-demand instances above the base
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=100))]
+        on_demand_percentage: u32,
+
+        /// Target utilization (%) of the container instances by managed scaling
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..=100))]
+        target_capacity: u32,
+    },
+    /// Print a ready-to-run AWS CLI command launching spot instances of an instance type at the
+    /// suggested max price
+    Awscli {
+        /// Instance type to launch
+        #[arg(short, long)]
+        instance_type: String,
+
+        /// Number of instances to launch
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+
+        /// Margin (%) over the spot price of the suggested max price
+        #[arg(long, default_value_t = DEFAULT_MAX_PRICE_MARGIN)]
+        max_price_margin: f64,
+
+        /// Launch template of the instances, the latest Amazon Linux 2023 or Windows Server AMI
+        /// without one
+        #[arg(long)]
+        launch_template: Option<String>,
+
+        /// Subnet of the instances
+        #[arg(long)]
+        subnet_id: Option<String>,
+
+        /// Comma-separated security groups of the instances
+        #[arg(long, value_delimiter = ',')]
+        security_group_ids: Vec<String>,
+
+        /// Key pair of the instances
+        #[arg(long)]
+        key_name: Option<String>,
+
+        /// Print an instant `aws ec2 create-fleet` from the launch template instead of
+        /// `aws ec2 run-instances`
+        #[arg(long, requires = "launch_template", conflicts_with_all = ["security_group_ids", "key_name"])]
+        create_fleet: bool,
+    },
+}
+
+/// Node type of an EMR instance fleet
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum EmrFleetType {
+    /// Core nodes, running tasks and storing HDFS data
+    Core,
+    /// Task nodes, only running tasks
+    #[default]
+    Task,
+}
+
+impl EmrFleetType {
+    /// Returns the name of the node type in instance fleet configs
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmrFleetType::Core => "CORE",
+            EmrFleetType::Task => "TASK",
+        }
+    }
+}
+
+/// Type of an EC2 Fleet
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum FleetType {
+    /// Launch the target capacity once, returning the instances or the errors
+    Instant,
+    /// Replace interrupted spot instances to maintain the target capacity
+    #[default]
+    Maintain,
+}
+
+impl FleetType {
+    /// Returns the name of the fleet type in CreateFleet requests
+    pub fn name(&self) -> &'static str {
+        match self {
+            FleetType::Instant => "instant",
+            FleetType::Maintain => "maintain",
+        }
+    }
+}
+
+impl ExportTarget {
+    /// Returns the constraints of the recommended instance types to export, None for targets of a
+    /// given instance type
+    pub fn selection(&self) -> Option<&SelectionArgs> {
+        match self {
+            ExportTarget::Karpenter { selection, .. }
+            | ExportTarget::AsgPolicy { selection, .. }
+            | ExportTarget::SpotFleet { selection, .. }
+            | ExportTarget::Ec2Fleet { selection, .. }
+            | ExportTarget::Terraform { selection, .. }
+            | ExportTarget::Eksctl { selection, .. }
+            | ExportTarget::Batch { selection, .. }
+            | ExportTarget::EmrFleet { selection, .. }
+            | ExportTarget::Ray { selection, .. }
+            | ExportTarget::Skypilot { selection, .. }
+            | ExportTarget::Nomad { selection, .. }
+            | ExportTarget::Slurm { selection, .. }
+            | ExportTarget::Ecs { selection, .. } => Some(selection),
+            ExportTarget::Awscli { .. } => None,
+        }
+    }
+}
+
+/// Constraints of the recommended instance types to export, spread across families
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct SelectionArgs {
+    /// vCPUs of the instance types, a range like '4-16' or a minimum like '8'
+    #[arg(long, value_parser = parse_vcpu_range)]
+    pub vcpus: Option<VcpuRange>,
+
+    /// Minimum memory in GB
+    #[arg(long)]
+    pub memory: Option<f64>,
+
+    /// Maximum interruption rate (%)
+    #[arg(long, default_value_t = 10.0)]
+    pub max_interruption: f64,
+
+    /// Number of instance types to export
+    #[arg(long, default_value_t = 10)]
+    pub count: usize,
+}
+
+/// Range of vCPUs, unbounded when a bound is missing
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VcpuRange {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+impl VcpuRange {
+    /// Returns true if the vCPUs are within the range
+    pub fn contains(&self, vcpus: f64) -> bool {
+        self.min.is_none_or(|min| vcpus >= f64::from(min))
+            && self.max.is_none_or(|max| vcpus <= f64::from(max))
+    }
+}
+
+/// Resource of the account to audit instead of the running spot instances
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum AuditTarget {
+    /// Rate every instance type configured in an Auto Scaling group's mixed instances policy or
+    /// launch template, highlighting the ones that have become high-interruption
+    Asg {
+        /// Name of the Auto Scaling group
+        name: String,
+    },
+}
+
+/// Kubernetes resource to rate the spot instance types of
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum K8sAction {
+    /// Rate every instance type allowed by the requirements of a Karpenter NodePool, highlighting
+    /// the high-interruption ones
+    #[command(name = "nodepool")]
+    NodePool {
+        /// Name of the NodePool
+        name: String,
+    },
+    /// Group the cluster's nodes by instance type and capacity type, rating each instance type
+    Nodes,
+    /// Recommend the spot instance types and node counts hosting the resource requests of a
+    /// namespace's pods most cheaply, by bin-packing
+    Fit {
+        /// Namespace of the pods
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+
+        /// Number of instance types to show
+        #[arg(long, default_value_t = 5)]
+        count: usize,
+    },
+}
+
+/// Action on snapshots of the data
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum SnapshotAction {
+    /// Save the spot advisor data and the spot price data to a file
+    Save {
+        /// File to save the snapshot to (e.g., 'prices-2025-01.json')
+        path: PathBuf,
+    },
+}
+
+/// Metrics backend to publish to
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum PublishTarget {
+    /// Publish custom CloudWatch metrics in the region, using the AWS credentials of the environment
+    Cloudwatch {
+        /// CloudWatch namespace of the metrics
+        #[arg(long, default_value = "Spotter")]
+        namespace: String,
+
+        /// Instance types to publish (families like 'm5', sizes or full types), all when not given
+        #[arg(short, long, value_delimiter = ',')]
+        instance_types: Vec<String>,
+    },
+    /// Send gauges tagged by instance_type, region and os to a DogStatsD agent, or to the
+    /// Datadog API when an API key is given
+    Datadog {
+        /// Datadog API key, submitting to the API instead of the agent
+        #[arg(long, env = "DD_API_KEY", hide_env_values = true)]
+        api_key: Option<String>,
+
+        /// Datadog site of the API
+        #[arg(long, env = "DD_SITE", default_value = "datadoghq.com")]
+        site: String,
+
+        /// DogStatsD address of the agent
+        #[arg(long, default_value = "127.0.0.1:8125")]
+        statsd: String,
+
+        /// Instance types to publish (families like 'm5', sizes or full types), all when not given
+        #[arg(short, long, value_delimiter = ',')]
+        instance_types: Vec<String>,
+    },
+}
+
+/// SMTP server and addresses to email tripped rules with
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct SmtpArgs {
+    /// SMTP server to email tripped rules through (e.g., smtp.example.com), using STARTTLS, or
+    /// TLS on port 465
+    #[arg(long, requires_all = ["smtp_from", "smtp_to"])]
+    pub smtp_host: Option<String>,
+
+    /// Port of the SMTP server
+    #[arg(long, default_value_t = 587)]
+    pub smtp_port: u16,
+
+    /// Username of the SMTP server, when it requires authentication
+    #[arg(long, env = "SPOTTER_SMTP_USERNAME", requires = "smtp_password")]
+    pub smtp_username: Option<String>,
+
+    /// Password of the SMTP server
+    #[arg(long, env = "SPOTTER_SMTP_PASSWORD", hide_env_values = true)]
+    pub smtp_password: Option<String>,
+
+    /// Sender address of the emails (e.g., "Spotter <spotter@example.com>")
+    #[arg(long)]
+    pub smtp_from: Option<String>,
+
+    /// Recipient addresses of the emails (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub smtp_to: Vec<String>,
+}
+
+impl SmtpArgs {
+    /// Returns the SMTP config when an SMTP server is given
+    pub fn config(&self) -> Result<Option<notify::SmtpConfig>> {
+        let Some(host) = &self.smtp_host else {
+            return Ok(None);
+        };
+        let credentials = self.smtp_username.clone().zip(self.smtp_password.clone());
+        let from = self.smtp_from.as_deref().unwrap_or_default();
+        notify::SmtpConfig::new(host, self.smtp_port, credentials, from, &self.smtp_to).map(Some)
+    }
+}
+
+/// AWS credentials of the SDK-backed features (--live, --specs, score, publish and SNS alerts),
+/// resolved through the standard credential chain
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct AwsArgs {
+    /// Named AWS profile of the credentials (default: $AWS_PROFILE, or the default profile)
+    #[arg(long, global = true)]
+    pub aws_profile: Option<String>,
+
+    /// Region of the AWS API calls not tied to a queried region, like GetSpotPlacementScores
+    /// (default: the region)
+    #[arg(long, global = true)]
+    pub aws_region: Option<String>,
+
+    /// IAM role to assume with STS AssumeRole for the AWS API calls, e.g. of a member account
+    #[arg(long, global = true, value_parser = parse_role_arn)]
+    pub role_arn: Option<String>,
+
+    /// External ID required by the trust policy of the assumed role
+    #[arg(long, global = true, requires = "role_arn")]
+    pub external_id: Option<String>,
+
+    /// Session name of the assumed role, shown in CloudTrail
+    #[arg(long, global = true, requires = "role_arn", default_value = DEFAULT_ROLE_SESSION_NAME)]
+    pub role_session_name: String,
+}
+
+/// Session name of the assumed role by default
+pub const DEFAULT_ROLE_SESSION_NAME: &str = "spotter";
+
+impl Default for AwsArgs {
+    fn default() -> Self {
+        Self {
+            aws_profile: None,
+            aws_region: None,
+            role_arn: None,
+            external_id: None,
+            role_session_name: DEFAULT_ROLE_SESSION_NAME.to_string(),
+        }
+    }
+}
+
+impl AwsArgs {
+    fn loader(&self, region: &str) -> aws_config::ConfigLoader {
+        let loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new(region.to_string()));
+        match &self.aws_profile {
+            Some(profile) => loader.profile_name(profile),
+            None => loader,
+        }
+    }
+
+    /// Loads the SDK config of the AWS API calls in a region, with the credentials of the profile,
+    /// or of the assumed role when one is given
+    pub async fn sdk_config(&self, region: &str) -> aws_config::SdkConfig {
+        let Some(role_arn) = &self.role_arn else {
+            return self.loader(region).load().await;
+        };
+
+        // The role is assumed with the credentials of the profile
+        let base = self.loader(region).load().await;
+        let mut provider = aws_config::sts::AssumeRoleProvider::builder(role_arn)
+            .session_name(&self.role_session_name)
+            .configure(&base);
+        if let Some(external_id) = &self.external_id {
+            provider = provider.external_id(external_id);
+        }
+        self.loader(region)
+            .credentials_provider(provider.build().await)
+            .load()
+            .await
+    }
+}
+
+/// Validates an IAM role ARN like "arn:aws:iam::123456789012:role/SpotterReadOnly"
+fn parse_role_arn(arn: &str) -> Result<String> {
+    let parts: Vec<&str> = arn.splitn(6, ':').collect();
+    match parts.as_slice() {
+        ["arn", _, "iam", "", account, resource]
+            if account.len() == 12
+                && account.chars().all(|c| c.is_ascii_digit())
+                && resource.starts_with("role/") =>
+        {
+            Ok(arn.to_string())
+        }
+        _ => Err(anyhow!(
+            "Invalid role ARN '{}'. Please use an IAM role ARN (e.g., arn:aws:iam::123456789012:role/SpotterReadOnly)",
+            arn
+        )),
+    }
+}
+
+/// How instance types are grouped into a single row
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum GroupBy {
+    Family,
+}
+
+/// Cloud of the spot data
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Provider {
+    /// AWS spot instances, from the spot advisor data and spot price data
+    Aws,
+    /// GCP Spot VMs, from the Compute Engine SKUs of the Cloud Billing Catalog API
+    Gcp,
+    /// Azure Spot VMs, from the Retail Prices API
+    Azure,
+}
+
+impl Provider {
+    /// Returns the name of the provider as given to --provider
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::Aws => "aws",
+            Provider::Gcp => "gcp",
+            Provider::Azure => "azure",
+        }
+    }
+
+    /// Returns the region used when none is given, or None for AWS, whose region is detected
+    /// from the environment
+    pub fn default_region(&self) -> Option<&'static str> {
+        match self {
+            Provider::Aws => None,
+            Provider::Gcp => Some("us-central1"),
+            Provider::Azure => Some("eastus"),
+        }
+    }
+}
+
+/// Platform of the spot price columns in the spot price data
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum PriceColumn {
+    Linux,
+    Mswin,
+    Rhel,
+    Suse,
+}
+
+impl PriceColumn {
+    /// Returns the table header of the price column
+    pub fn header(&self) -> &'static str {
+        match self {
+            PriceColumn::Linux => "Linux Spot Price",
+            PriceColumn::Mswin => "Windows Spot Price",
+            PriceColumn::Rhel => "RHEL Spot Price",
+            PriceColumn::Suse => "SUSE Spot Price",
+        }
+    }
+
+    /// Returns the operating system name used in the AWS on-demand price data
+    pub fn pricing_os(&self) -> &'static str {
+        match self {
+            PriceColumn::Linux => "Linux",
+            PriceColumn::Mswin => "Windows",
+            PriceColumn::Rhel => "RHEL",
+            PriceColumn::Suse => "SUSE",
+        }
+    }
+}
+
+/// List of valid AWS regions
+/// ref: https://docs.aws.amazon.com/global-infrastructure/latest/regions/aws-regions.html
+pub const VALID_AWS_REGIONS: &[(&str, &str)] = &[
+    ("us-east-1", "US East (N. Virginia)"),
+    ("us-east-2", "US East (Ohio)"),
+    ("us-west-1", "US West (N. California)"),
+    ("us-west-2", "US West (Oregon)"),
+    ("af-south-1", "Africa (Cape Town)"),
+    ("ap-east-1", "Asia Pacific (Hong Kong)"),
+    ("ap-south-1", "Asia Pacific (Mumbai)"),
+    ("ap-south-2", "Asia Pacific (Hyderabad)"),
+    ("ap-southeast-1", "Asia Pacific (Singapore)"),
+    ("ap-southeast-2", "Asia Pacific (Sydney)"),
+    ("ap-southeast-3", "Asia Pacific (Jakarta)"),
+    ("ap-southeast-4", "Asia Pacific (Melbourne)"),
+    ("ap-northeast-1", "Asia Pacific (Tokyo)"),
+    ("ap-northeast-2", "Asia Pacific (Seoul)"),
+    ("ap-northeast-3", "Asia Pacific (Osaka)"),
+    ("ca-central-1", "Canada (Central)"),
+    ("ca-west-1", "Canada (Calgary)"),
+    ("eu-central-1", "Europe (Frankfurt)"),
+    ("eu-central-2", "Europe (Zurich)"),
+    ("eu-west-1", "Europe (Ireland)"),
+    ("eu-west-2", "Europe (London)"),
+    ("eu-west-3", "Europe (Paris)"),
+    ("eu-south-1", "Europe (Milan)"),
+    ("eu-south-2", "Europe (Spain)"),
+    ("eu-north-1", "Europe (Stockholm)"),
+    ("il-central-1", "Israel (Tel Aviv)"),
+    ("me-south-1", "Middle East (Bahrain)"),
+    ("me-central-1", "Middle East (UAE)"),
+    ("sa-east-1", "South America (São Paulo)"),
+];
+
+/// Returns the friendly name of an AWS region (e.g., "US East (N. Virginia)")
+pub fn region_name(region: &str) -> Option<&'static str> {
+    VALID_AWS_REGIONS
+        .iter()
+        .find(|(code, _)| *code == region)
+        .map(|(_, name)| *name)
+}
+
+/// Validates if the provided region is a valid AWS region
+pub fn validate_region(region: &str) -> Result<()> {
+    if VALID_AWS_REGIONS.iter().any(|(code, _)| *code == region) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Invalid AWS region '{}'. Please use a valid AWS region code (e.g., us-east-1, eu-west-1, ap-northeast-1)",
+            region
+        ))
+    }
+}
+
+/// Parses a duration like "12h", "30m", "2d" or "1h30m" into hours
+pub fn parse_duration(duration: &str) -> Result<f64> {
+    let invalid = || {
+        anyhow!(
+            "Invalid duration '{}'. Please use a number followed by 'd', 'h', 'm' or 's' (e.g., 12h, 30m, 1h30m, 60s)",
+            duration
+        )
+    };
+
+    let mut hours = 0.0;
+    let mut number = String::new();
+    for c in duration.trim().chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+
+        let value: f64 = number.parse().map_err(|_| invalid())?;
+        hours += match c {
+            'd' => value * 24.0,
+            'h' => value,
+            'm' => value / 60.0,
+            's' => value / 3600.0,
+            _ => return Err(invalid()),
+        };
+        number.clear();
+    }
+
+    // Every number needs a unit
+    if !number.is_empty() || hours <= 0.0 {
+        return Err(invalid());
+    }
+
+    Ok(hours)
+}
+
+/// Hardware requirements of the GPUs of accelerated instance types
+#[derive(Args, Clone, Debug, Default, PartialEq)]
+pub struct GpuFilterArgs {
+    /// GPU model (e.g., 'a100', 'h100', 'l4', 't4')
+    #[arg(long)]
+    pub gpu_model: Option<String>,
+
+    /// Minimum memory of each GPU in GiB
+    #[arg(long)]
+    pub min_gpu_memory: Option<f64>,
+}
+
+impl GpuFilterArgs {
+    /// Returns true if no requirement is given
+    pub fn is_empty(&self) -> bool {
+        self.gpu_model.is_none() && self.min_gpu_memory.is_none()
+    }
+}
+
+/// GPUs of an instance of a model, like 1 A10G
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accelerator {
+    /// Model like "a10g"
+    pub model: String,
+    pub count: u32,
+}
+
+/// Formats the GPUs like SkyPilot accelerators, "A10G:1"
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.model.to_uppercase(), self.count)
+    }
+}
+
+/// Parses GPUs like "a10g:1", or "a10g" for a single one
+pub fn parse_accelerator(accelerator: &str) -> Result<Accelerator> {
+    let invalid = || {
+        anyhow!(
+            "Invalid GPUs '{}'. Please use MODEL:COUNT or MODEL (e.g., a10g:1, a100:8)",
+            accelerator
+        )
+    };
+    let (model, count) = match accelerator.split_once(':') {
+        Some((model, count)) => (model, count.parse::<u32>().map_err(|_| invalid())?),
+        None => (accelerator, 1),
+    };
+    if model.is_empty() || count == 0 {
+        return Err(invalid());
+    }
+    Ok(Accelerator {
+        model: model.to_string(),
+        count,
+    })
+}
+
+/// Parses a vCPU range like "4-16", or a minimum like "8"
+pub fn parse_vcpu_range(range: &str) -> Result<VcpuRange> {
+    let invalid = || {
+        anyhow!(
+            "Invalid vCPU range '{}'. Please use MIN-MAX or MIN (e.g., 4-16, 8)",
+            range
+        )
+    };
+    let bound = |value: &str| value.trim().parse::<u32>().map_err(|_| invalid());
+
+    let vcpus = match range.split_once('-') {
+        Some((min, max)) => VcpuRange {
+            min: Some(bound(min)?),
+            max: Some(bound(max)?),
+        },
+        None => VcpuRange {
+            min: Some(bound(range)?),
+            max: None,
+        },
+    };
+    if let VcpuRange {
+        min: Some(min),
+        max: Some(max),
+    } = vcpus
+        && min > max
+    {
+        return Err(invalid());
+    }
+    Ok(vcpus)
+}
+
+/// Parses a listen address like "127.0.0.1:8080", where a bare ":8080" listens on every interface
+pub fn parse_listen_addr(addr: &str) -> Result<SocketAddr> {
+    let addr = addr.trim();
+    let full = match addr.strip_prefix(':') {
+        Some(port) => format!("0.0.0.0:{}", port),
+        None => addr.to_string(),
+    };
+
+    full.parse().map_err(|_| {
+        anyhow!(
+            "Invalid listen address '{}'. Please use HOST:PORT or :PORT (e.g., 127.0.0.1:8080, :8080)",
+            addr
+        )
+    })
+}
+
+/// Validates that there's a non-negative risk penalty for each interruption rate bucket
+pub fn validate_risk_penalties(risk_penalties: &[f64]) -> Result<()> {
+    if risk_penalties.len() != DEFAULT_RISK_PENALTIES.len() {
+        return Err(anyhow!(
+            "Invalid risk penalties. Please specify {} comma-separated percentages, one per interruption rate bucket (e.g., 0,5,10,20,40)",
+            DEFAULT_RISK_PENALTIES.len()
+        ));
+    }
+    if risk_penalties.iter().any(|p| *p < 0.0) {
+        return Err(anyhow!(
+            "Invalid risk penalties. Percentages must not be negative"
+        ));
+    }
+    Ok(())
+}
+
+impl Cli {
+    /// Validates the CLI arguments
+    pub fn validate(&self) -> Result<()> {
+        // Regions of the other clouds are only known from their spot data
+        if let (Provider::Aws, Some(region)) = (self.provider, &self.region) {
+            validate_region(region)?;
+        }
+        if let Some(Command::Alert { rules, .. }) = &self.command {
+            for rule in rules {
+                rule.validate_region(self.provider)?;
+            }
+        }
+        if let Some(Command::Estimate { hours, .. }) = &self.command
+            && *hours <= 0.0
+        {
+            return Err(anyhow!(
+                "Invalid --hours {}. The hours must be positive",
+                hours
+            ));
+        }
+        if let Some(rate) = self.gcp_interruption_rate
+            && !(0.0..=100.0).contains(&rate)
+        {
+            return Err(anyhow!(
+                "Invalid --gcp-interruption-rate {}. Please use a percentage between 0 and 100",
+                rate
+            ));
+        }
+        if self.provider != Provider::Aws {
+            if matches!(self.command, Some(Command::CompareClouds { .. })) {
+                return Err(anyhow!(
+                    "compare-clouds always compares every cloud, with --region as the AWS region. Please drop --provider {}",
+                    self.provider.name()
+                ));
+            }
+            if let Some(usage) = self.aws_only_usage() {
+                return Err(anyhow!(
+                    "{} only works with AWS instance types and regions and can't be used with --provider {}",
+                    usage,
+                    self.provider.name()
+                ));
+            }
+        }
+        validate_risk_penalties(&self.risk_penalties)?;
+        if self.max_price_margin < 0.0 {
+            return Err(anyhow!(
+                "Invalid max price margin. The percentage must not be negative"
+            ));
+        }
+        if self.hours_per_month <= 0.0 {
+            return Err(anyhow!(
+                "Invalid --hours-per-month {}. The hours must be positive",
+                self.hours_per_month
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the command or flag reading AWS accounts or APIs, or emitting AWS configuration,
+    /// if any
+    fn aws_only_usage(&self) -> Option<&'static str> {
+        let command = match &self.command {
+            Some(Command::Spend) => Some("spend"),
+            Some(Command::Score { .. }) => Some("score"),
+            Some(Command::Audit { .. }) => Some("audit"),
+            Some(Command::Export { .. }) => Some("export"),
+            Some(Command::K8s { .. }) => Some("k8s"),
+            Some(Command::Record { .. }) => Some("record"),
+            Some(Command::When { .. }) => Some("when"),
+            Some(Command::Graviton { .. }) => Some("graviton"),
+            Some(Command::Gpu { .. }) => Some("gpu"),
+            Some(Command::Recommend {
+                from_compute_optimizer: true,
+                ..
+            }) => Some("recommend --from-compute-optimizer"),
+            Some(Command::Publish {
+                target: PublishTarget::Cloudwatch { .. },
+            }) => Some("publish cloudwatch"),
+            _ => None,
+        };
+        command
+            .or(self.live.then_some("--live"))
+            .or(self.specs.then_some("--specs"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_default_values() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.region, None);
+        assert_eq!(cli.instance_type, None);
+        assert_eq!(cli.spot_price, false);
+        assert_eq!(cli.os, Os::Linux);
+        assert_eq!(cli.command, None);
+    }
+
+    #[test]
+    fn test_cli_with_regions_command() {
+        let cli = Cli::parse_from(["spotter", "regions"]);
+        assert_eq!(cli.command, Some(Command::Regions));
+    }
+
+    #[test]
+    fn test_cli_with_sources_command() {
+        let cli = Cli::parse_from(["spotter", "sources"]);
+        assert_eq!(cli.command, Some(Command::Sources));
+    }
+
+    #[test]
+    fn test_cli_with_families_command() {
+        let cli = Cli::parse_from(["spotter", "families"]);
+        assert_eq!(cli.command, Some(Command::Families));
+        assert_eq!(cli.region, None);
+
+        // Region can be given after the subcommand
+        let cli = Cli::parse_from(["spotter", "families", "--region", "eu-west-1"]);
+        assert_eq!(cli.command, Some(Command::Families));
+        assert_eq!(cli.region, Some("eu-west-1".to_string()));
+    }
+
+    #[test]
+    fn test_cli_with_describe_command() {
+        let cli = Cli::parse_from(["spotter", "describe", "m6i.2xlarge", "-r", "eu-west-1"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Describe {
+                instance_type: "m6i.2xlarge".to_string()
+            })
+        );
+        assert_eq!(cli.region, Some("eu-west-1".to_string()));
+
+        // Instance type is required
+        assert!(Cli::try_parse_from(["spotter", "describe"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_compare_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "compare",
+            "m5.xlarge",
+            "m6i.xlarge",
+            "m7g.xlarge",
+            "-r",
+            "us-east-1",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Compare {
+                instance_types: vec![
+                    "m5.xlarge".to_string(),
+                    "m6i.xlarge".to_string(),
+                    "m7g.xlarge".to_string()
+                ]
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+
+        // At least two instance types are required
+        assert!(Cli::try_parse_from(["spotter", "compare", "m5.xlarge"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_recommend_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "recommend",
+            "--vcpus",
+            "8",
+            "--memory",
+            "32",
+            "--max-interruption",
+            "10",
+            "--region",
+            "eu-west-1",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Recommend {
+                vcpus: Some(8),
+                memory: Some(32.0),
+                max_interruption: Some(10.0),
+                limit: 10,
+                from_compute_optimizer: false,
+            })
+        );
+        assert_eq!(cli.region, Some("eu-west-1".to_string()));
+
+        let cli = Cli::parse_from(["spotter", "recommend", "--from-compute-optimizer"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Recommend {
+                from_compute_optimizer: true,
+                ..
+            })
+        ));
+        // The rightsizing recommendations already size the workloads
+        assert!(
+            Cli::try_parse_from([
+                "spotter",
+                "recommend",
+                "--from-compute-optimizer",
+                "--vcpus",
+                "8"
+            ])
+            .is_err()
+        );
+
+        // Risk penalties can be given after the subcommand
+        let cli = Cli::parse_from(["spotter", "recommend", "--risk-penalties", "0,1,2,3,4"]);
+        assert_eq!(cli.risk_penalties, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_cli_with_diversify_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "diversify",
+            "--vcpus",
+            "8",
+            "--memory",
+            "32",
+            "--count",
+            "4",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Diversify {
+                vcpus: Some(8),
+                memory: Some(32.0),
+                max_interruption: 10.0,
+                count: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_pareto_command() {
+        let cli = Cli::parse_from(["spotter", "pareto", "-r", "us-east-1", "--min-vcpus", "4"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Pareto {
+                min_vcpus: Some(4),
+                min_memory: None,
+                max_interruption: None,
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+    }
+
+    #[test]
+    fn test_cli_with_graviton_command() {
+        let cli = Cli::parse_from(["spotter", "graviton", "m5.2xlarge"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Graviton {
+                instance_type: "m5.2xlarge".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_stats_command() {
+        let cli = Cli::parse_from(["spotter", "stats", "-r", "us-east-1"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Stats {
+                instance_type: None
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+
+        let cli = Cli::parse_from(["spotter", "stats", "-r", "us-east-1", "-i", "m5"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Stats {
+                instance_type: Some("m5".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_estimate_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "estimate",
+            "--spec",
+            "4x m6i.2xlarge, 2x r6g.xlarge",
+            "-r",
+            "eu-central-1",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Estimate {
+                spec: "4x m6i.2xlarge, 2x r6g.xlarge".to_string(),
+                hours: DEFAULT_HOURS_PER_MONTH,
+            })
+        );
+
+        let cli = Cli::parse_from([
+            "spotter", "estimate", "--spec", "m5.large", "--hours", "200",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Estimate {
+                spec: "m5.large".to_string(),
+                hours: 200.0,
+            })
+        );
+
+        // The spec is required
+        assert!(Cli::try_parse_from(["spotter", "estimate"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_simulate_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "simulate",
+            "--runtime",
+            "12h",
+            "--checkpoint-loss",
+            "30m",
+            "-i",
+            "p4d.24xlarge",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Simulate {
+                instance_type: "p4d.24xlarge".to_string(),
+                runtime: 12.0,
+                checkpoint_loss: Some(0.5),
+            })
+        );
+
+        let cli = Cli::parse_from(["spotter", "simulate", "--runtime", "2d", "-i", "m5.large"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Simulate {
+                instance_type: "m5.large".to_string(),
+                runtime: 48.0,
+                checkpoint_loss: None,
+            })
+        );
+
+        // Invalid durations are rejected
+        assert!(
+            Cli::try_parse_from(["spotter", "simulate", "--runtime", "12", "-i", "m5.large"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_vcpu_range() {
+        assert_eq!(
+            parse_vcpu_range("4-16").unwrap(),
+            VcpuRange {
+                min: Some(4),
+                max: Some(16)
+            }
+        );
+        assert_eq!(
+            parse_vcpu_range("8").unwrap(),
+            VcpuRange {
+                min: Some(8),
+                max: None
+            }
+        );
+        assert!(parse_vcpu_range("16-4").is_err());
+        assert!(parse_vcpu_range("four").is_err());
+        assert!(parse_vcpu_range("4-").is_err());
+
+        let range = parse_vcpu_range("4-16").unwrap();
+        assert!(range.contains(4.0) && range.contains(16.0));
+        assert!(!range.contains(2.0) && !range.contains(32.0));
+    }
+
+    #[test]
+    fn test_parse_accelerator() {
+        let accelerator = parse_accelerator("a10g:4").unwrap();
+        assert_eq!(
+            accelerator,
+            Accelerator {
+                model: "a10g".to_string(),
+                count: 4
+            }
+        );
+        assert_eq!(accelerator.to_string(), "A10G:4");
+        assert_eq!(parse_accelerator("t4").unwrap().count, 1);
+        assert!(parse_accelerator("a10g:0").is_err());
+        assert!(parse_accelerator(":1").is_err());
+        assert!(parse_accelerator("a10g:x").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("12h").unwrap(), 12.0);
+        assert_eq!(parse_duration("30m").unwrap(), 0.5);
+        assert_eq!(parse_duration("2d").unwrap(), 48.0);
+        assert_eq!(parse_duration("1h30m").unwrap(), 1.5);
+        assert_eq!(parse_duration("1.5h").unwrap(), 1.5);
+        assert_eq!(parse_duration("90s").unwrap(), 0.025);
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("12").is_err());
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("12w").is_err());
+    }
+
+    #[test]
+    fn test_cli_with_strategy_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "strategy",
+            "-r",
+            "us-east-1",
+            "--types",
+            "m5,m6i,c6i",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Strategy {
+                types: vec!["m5".to_string(), "m6i".to_string(), "c6i".to_string()],
+            })
+        );
+
+        // The types are required
+        assert!(Cli::try_parse_from(["spotter", "strategy"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_score_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "score",
+            "--types",
+            "m5.xlarge,m6i.xlarge",
+            "--target-capacity",
+            "50",
+            "--regions",
+            "us-east-1,us-west-2",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Score {
+                types: vec!["m5.xlarge".to_string(), "m6i.xlarge".to_string()],
+                target_capacity: 50,
+                regions: vec!["us-east-1".to_string(), "us-west-2".to_string()],
+                single_az: false,
+            })
+        );
+
+        // The types are required and the capacity must be positive
+        assert!(Cli::try_parse_from(["spotter", "score"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "spotter",
+                "score",
+                "--types",
+                "m5.xlarge",
+                "--target-capacity",
+                "0"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_with_audit_command() {
+        let cli = Cli::parse_from(["spotter", "audit", "--aws-profile", "prod"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Audit {
+                target: None,
+                max_interruption: 10.0,
+                max_premium: 10.0,
+            })
+        );
+        assert_eq!(cli.aws.aws_profile, Some("prod".to_string()));
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "audit",
+            "--max-interruption",
+            "5",
+            "--max-premium",
+            "25",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Audit {
+                target: None,
+                max_interruption: 5.0,
+                max_premium: 25.0,
+            })
+        );
+
+        let cli = Cli::parse_from(["spotter", "audit", "asg", "web", "--max-interruption", "5"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Audit {
+                target: Some(AuditTarget::Asg {
+                    name: "web".to_string()
+                }),
+                max_interruption: 5.0,
+                max_premium: 10.0,
+            })
+        );
+        assert!(Cli::try_parse_from(["spotter", "audit", "asg"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_spend_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "spend",
+            "--aws-profile",
+            "billing",
+            "--os",
+            "windows",
+        ]);
+        assert_eq!(cli.command, Some(Command::Spend));
+        assert_eq!(cli.aws.aws_profile, Some("billing".to_string()));
+        assert_eq!(cli.os, Os::Windows);
+    }
+
+    #[test]
+    fn test_cli_with_export_karpenter_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "karpenter",
+            "--vcpus",
+            "4-16",
+            "--max-interruption",
+            "10",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Karpenter {
+                    selection: SelectionArgs {
+                        vcpus: Some(VcpuRange {
+                            min: Some(4),
+                            max: Some(16)
+                        }),
+                        memory: None,
+                        max_interruption: 10.0,
+                        count: 10,
+                    },
+                    name: "spot".to_string(),
+                    cluster: "my-cluster".to_string(),
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_export_asg_policy_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "asg-policy",
+            "--launch-template",
+            "web",
+            "--on-demand-percentage",
+            "20",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::AsgPolicy {
+                    selection: SelectionArgs {
+                        vcpus: None,
+                        memory: None,
+                        max_interruption: 10.0,
+                        count: 10,
+                    },
+                    launch_template: "web".to_string(),
+                    on_demand_base: 0,
+                    on_demand_percentage: 20,
+                }
+            })
+        );
+        assert!(
+            Cli::try_parse_from([
+                "spotter",
+                "export",
+                "asg-policy",
+                "--on-demand-percentage",
+                "101"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_with_export_spot_fleet_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "spot-fleet",
+            "--target-capacity",
+            "20",
+            "--availability-zones",
+            "us-east-1a,us-east-1b",
+            "--vcpus",
+            "8",
+        ]);
+        let Some(Command::Export { target }) = cli.command else {
+            panic!("expected the export command");
+        };
+        assert_eq!(
+            target.selection().and_then(|selection| selection.vcpus),
+            Some(VcpuRange {
+                min: Some(8),
+                max: None
+            })
+        );
+        assert!(matches!(
+            target,
+            ExportTarget::SpotFleet {
+                target_capacity: 20,
+                one_time: false,
+                ref availability_zones,
+                ..
+            } if availability_zones == &["us-east-1a", "us-east-1b"]
+        ));
+        assert!(
+            Cli::try_parse_from(["spotter", "export", "spot-fleet", "--target-capacity", "0"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_with_export_ec2_fleet_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "ec2-fleet",
+            "--type",
+            "instant",
+            "--target-capacity",
+            "10",
+        ]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Ec2Fleet {
+                    target_capacity: 10,
+                    fleet_type: FleetType::Instant,
+                    ..
+                }
+            })
+        ));
+
+        let cli = Cli::parse_from(["spotter", "export", "ec2-fleet"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Ec2Fleet {
+                    fleet_type: FleetType::Maintain,
+                    ..
+                }
+            })
+        ));
+        assert!(
+            Cli::try_parse_from(["spotter", "export", "ec2-fleet", "--type", "request"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_with_export_terraform_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "terraform",
+            "--launch-template-id",
+            "module.lt.id",
+            "--overrides-only",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Terraform {
+                    selection: SelectionArgs {
+                        vcpus: None,
+                        memory: None,
+                        max_interruption: 10.0,
+                        count: 10,
+                    },
+                    launch_template_id: "module.lt.id".to_string(),
+                    on_demand_base: 0,
+                    on_demand_percentage: 0,
+                    overrides_only: true,
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_export_eksctl_command() {
+        let cli = Cli::parse_from(["spotter", "export", "eksctl", "--cluster", "my-cluster"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Eksctl {
+                    ref cluster,
+                    min_size: 1,
+                    max_size: 10,
+                    ..
+                }
+            }) if cluster == "my-cluster"
+        ));
+
+        // The cluster is required
+        assert!(Cli::try_parse_from(["spotter", "export", "eksctl"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_export_batch_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "batch",
+            "--subnets",
+            "subnet-1,subnet-2",
+            "--max-price-margin",
+            "10",
+        ]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Batch {
+                    ref subnets,
+                    max_vcpus: 256,
+                    max_price_margin: 10.0,
+                    ..
+                }
+            }) if subnets == &["subnet-1", "subnet-2"]
+        ));
+    }
+
+    #[test]
+    fn test_cli_with_export_emr_fleet_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "emr-fleet",
+            "--fleet-type",
+            "core",
+            "--target-spot-capacity",
+            "128",
+        ]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::EmrFleet {
+                    fleet_type: EmrFleetType::Core,
+                    target_spot_capacity: 128,
+                    timeout_minutes: 10,
+                    ..
+                }
+            })
+        ));
+        assert!(
+            Cli::try_parse_from(["spotter", "export", "emr-fleet", "--timeout-minutes", "1"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_with_export_ray_command() {
+        let cli = Cli::parse_from(["spotter", "export", "ray", "--max-workers", "20"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Ray {
+                    min_workers: 0,
+                    max_workers: 20,
+                    ..
+                }
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_with_export_skypilot_command() {
+        let cli = Cli::parse_from(["spotter", "export", "skypilot", "--gpus", "a10g:1"]);
+        let Some(Command::Export {
+            target: ExportTarget::Skypilot { gpus, .. },
+        }) = cli.command
+        else {
+            panic!("expected the export skypilot command");
+        };
+        assert_eq!(
+            gpus,
+            Some(Accelerator {
+                model: "a10g".to_string(),
+                count: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_export_nomad_command() {
+        let cli = Cli::parse_from(["spotter", "export", "nomad", "--asg-name", "nomad-clients"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Nomad {
+                    ref asg_name,
+                    min: 1,
+                    max: 10,
+                    target: 70,
+                    ..
+                }
+            }) if asg_name == "nomad-clients"
+        ));
+        assert!(Cli::try_parse_from(["spotter", "export", "nomad"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_export_slurm_command() {
+        let cli = Cli::parse_from(["spotter", "export", "slurm", "--max-nodes", "50"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Slurm {
+                    ref partition,
+                    max_nodes: 50,
+                    ..
+                }
+            }) if partition == "spot"
+        ));
+    }
+
+    #[test]
+    fn test_cli_with_export_ecs_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "export",
+            "ecs",
+            "--cluster",
+            "batch",
+            "--subnets",
+            "subnet-1,subnet-2",
+        ]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export {
+                target: ExportTarget::Ecs {
+                    ref subnets,
+                    target_capacity: 100,
+                    ..
+                }
+            }) if subnets.len() == 2
+        ));
+        assert!(Cli::try_parse_from(["spotter", "export", "ecs", "--cluster", "batch"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_export_awscli_command() {
+        let cli = Cli::parse_from(["spotter", "export", "awscli", "-i", "m6i.xlarge"]);
+        let Some(Command::Export { target }) = cli.command else {
+            panic!("expected the export command");
+        };
+        assert_eq!(target.selection(), None);
+        assert!(matches!(
+            target,
+            ExportTarget::Awscli {
+                ref instance_type,
+                count: 1,
+                create_fleet: false,
+                ..
+            } if instance_type == "m6i.xlarge"
+        ));
+
+        // An EC2 Fleet needs a launch template
+        assert!(
+            Cli::try_parse_from([
+                "spotter",
+                "export",
+                "awscli",
+                "-i",
+                "m6i.xlarge",
+                "--create-fleet"
+            ])
+            .is_err()
+        );
+        assert!(
+            Cli::try_parse_from([
+                "spotter",
+                "export",
+                "awscli",
+                "-i",
+                "m6i.xlarge",
+                "--create-fleet",
+                "--launch-template",
+                "web"
+            ])
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_cli_with_k8s_nodepool_command() {
+        let cli = Cli::parse_from(["spotter", "k8s", "nodepool", "default", "--context", "prod"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::K8s {
+                action: K8sAction::NodePool {
+                    name: "default".to_string()
+                },
+                context: Some("prod".to_string()),
+                max_interruption: 10.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_k8s_nodes_command() {
+        let cli = Cli::parse_from(["spotter", "k8s", "nodes", "--max-interruption", "5"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::K8s {
+                action: K8sAction::Nodes,
+                context: None,
+                max_interruption: 5.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_k8s_fit_command() {
+        let cli = Cli::parse_from(["spotter", "k8s", "fit", "--namespace", "batch"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::K8s {
+                action: K8sAction::Fit {
+                    namespace: "batch".to_string(),
+                    count: 5,
+                },
+                context: None,
+                max_interruption: 10.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_gpu_command() {
+        let cli = Cli::parse_from(["spotter", "gpu", "-r", "us-east-1"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Gpu {
+                filter: GpuFilterArgs::default()
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "gpu",
+            "--gpu-model",
+            "a100",
+            "--min-gpu-memory",
+            "24",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Gpu {
+                filter: GpuFilterArgs {
+                    gpu_model: Some("a100".to_string()),
+                    min_gpu_memory: Some(24.0),
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_compare_clouds_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "compare-clouds",
+            "--vcpus",
+            "8",
+            "--memory",
+            "32",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::CompareClouds {
+                vcpus: 8,
+                memory: 32.0,
+                count: 3,
+                gcp_region: "us-central1".to_string(),
+                azure_region: "eastus".to_string(),
+            })
+        );
+
+        assert!(Cli::try_parse_from(["spotter", "compare-clouds", "--vcpus", "8"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_cache_command() {
+        for (action, expected) in [
+            ("info", CacheAction::Info),
+            ("clear", CacheAction::Clear),
+            ("refresh", CacheAction::Refresh),
+        ] {
+            let cli = Cli::parse_from(["spotter", "cache", action]);
+            assert_eq!(cli.command, Some(Command::Cache { action: expected }));
+        }
+
+        // The action is required
+        assert!(Cli::try_parse_from(["spotter", "cache"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_snapshot_command() {
+        let cli = Cli::parse_from(["spotter", "snapshot", "save", "prices-2025-01.json"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Snapshot {
+                action: SnapshotAction::Save {
+                    path: PathBuf::from("prices-2025-01.json")
+                }
+            })
+        );
+        assert_eq!(cli.from_snapshot, None);
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "--from-snapshot",
+            "prices-2025-01.json",
+            "-r",
+            "eu-west-1",
+        ]);
+        assert_eq!(cli.command, None);
+        assert_eq!(
+            cli.from_snapshot,
+            Some(PathBuf::from("prices-2025-01.json"))
+        );
+    }
+
+    #[test]
+    fn test_cli_with_diff_command() {
+        let cli = Cli::parse_from(["spotter", "diff", "old.json", "new.json", "-r", "us-east-1"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Diff {
+                old: PathBuf::from("old.json"),
+                new: PathBuf::from("new.json"),
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+
+        // Both snapshots are required
+        assert!(Cli::try_parse_from(["spotter", "diff", "old.json"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_record_command() {
+        let cli = Cli::parse_from(["spotter", "record"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Record {
+                interval: 1.0,
+                db: "spot.db".to_string(),
+            })
+        );
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "record",
+            "--interval",
+            "30m",
+            "--db",
+            "/var/lib/spot.db",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Record {
+                interval: 0.5,
+                db: "/var/lib/spot.db".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_history_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "history",
+            "-i",
+            "m5.xlarge",
+            "-r",
+            "us-east-1",
+            "--since",
+            "30d",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::History {
+                instance_type: "m5.xlarge".to_string(),
+                since: 720.0,
+                db: "spot.db".to_string(),
+            })
+        );
+
+        // The instance type is required
+        assert!(Cli::try_parse_from(["spotter", "history"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_when_command() {
+        let cli = Cli::parse_from(["spotter", "when", "-i", "p4d.24xlarge", "-r", "us-east-1"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::When {
+                instance_type: "p4d.24xlarge".to_string(),
+                since: 720.0,
+                db: None,
+            })
+        );
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "when",
+            "-i",
+            "p4d.24xlarge",
+            "--since",
+            "14d",
+            "--db",
+            "spot.db",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::When {
+                instance_type: "p4d.24xlarge".to_string(),
+                since: 336.0,
+                db: Some("spot.db".to_string()),
+            })
+        );
+
+        // The instance type is required
+        assert!(Cli::try_parse_from(["spotter", "when"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_chart_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "chart",
+            "-i",
+            "m5.xlarge",
+            "-r",
+            "us-east-1",
+            "--out",
+            "chart.svg",
+        ]);
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+        assert_eq!(
+            cli.command,
+            Some(Command::Chart {
+                instance_type: "m5.xlarge".to_string(),
+                since: 336.0,
+                db: "spot.db".to_string(),
+                out: PathBuf::from("chart.svg"),
+            })
+        );
+
+        // The output file is required
+        assert!(Cli::try_parse_from(["spotter", "chart", "-i", "m5.xlarge"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_serve_command() {
+        let cli = Cli::parse_from(["spotter", "serve"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Serve {
+                http: "127.0.0.1:8080".parse().unwrap(),
+                grpc: None,
+                auth_token: None,
+                rate_limit: DEFAULT_RATE_LIMIT,
+            })
+        );
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "serve",
+            "--http",
+            ":9090",
+            "--grpc",
+            ":50051",
+            "--auth-token",
+            "secret",
+            "--rate-limit",
+            "0",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Serve {
+                http: "0.0.0.0:9090".parse().unwrap(),
+                grpc: Some("0.0.0.0:50051".parse().unwrap()),
+                auth_token: Some("secret".to_string()),
+                rate_limit: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_with_alert_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "alert",
+            "--slack-webhook",
+            "https://hooks.slack.com/services/T/B/X",
+            "--rule",
+            "m5.xlarge@eu-west-1 price>0.10 or interruption>10",
+            "--rule",
+            "c5.large@us-east-1 savings<50",
+            "--webhook",
+            "https://example.com/hooks/spotter",
+            "--sns-topic",
+            "arn:aws:sns:us-east-1:123456789012:alerts",
+            "--smtp-host",
+            "smtp.example.com",
+            "--smtp-from",
+            "spotter@example.com",
+            "--smtp-to",
+            "ops@example.com,oncall@example.com",
+            "--desktop-notify",
+            "--interval",
+            "5m",
+        ]);
+        let Some(Command::Alert {
+            rules,
+            rules_file,
+            slack_webhook,
+            webhook,
+            sns_topic,
+            smtp,
+            desktop_notify,
+            interval,
+        }) = cli.command
+        else {
+            panic!("expected the alert command");
+        };
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].instance_type, "m5.xlarge");
+        assert_eq!(rules[1].region, "us-east-1");
+        assert_eq!(rules_file, None);
+        assert_eq!(
+            slack_webhook,
+            Some("https://hooks.slack.com/services/T/B/X".to_string())
+        );
+        assert_eq!(
+            webhook,
+            Some("https://example.com/hooks/spotter".to_string())
+        );
+        assert_eq!(
+            sns_topic,
+            Some("arn:aws:sns:us-east-1:123456789012:alerts".to_string())
+        );
+        let config = smtp.config().unwrap().unwrap();
+        assert_eq!(config.host, "smtp.example.com");
+        assert_eq!(config.port, 587);
+        assert_eq!(config.credentials, None);
+        assert_eq!(config.to.len(), 2);
+        assert!(desktop_notify);
+        assert_eq!(interval, Some(5.0 / 60.0));
+
+        // The sender and recipients are required with an SMTP server
+        assert!(
+            Cli::try_parse_from([
+                "spotter",
+                "alert",
+                "--rule",
+                "m5.xlarge@eu-west-1 price>1",
+                "--smtp-host",
+                "smtp.example.com",
+            ])
+            .is_err()
+        );
+
+        // A rule file replaces the rules
+        let cli = Cli::parse_from(["spotter", "alert", "--rules", "alerts.yaml"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Alert { rules, rules_file: Some(_), .. }) if rules.is_empty()
+        ));
 
-    #[clap(flatten)]
-    pub verbose: Verbosity<InfoLevel>,
-}
+        // At least one valid rule is required
+        assert!(Cli::try_parse_from(["spotter", "alert"]).is_err());
+        assert!(Cli::try_parse_from(["spotter", "alert", "--rule", "m5.xlarge price>1"]).is_err());
+    }
 
-/// List of valid AWS regions
-/// ref: https://docs.aws.amazon.com/global-infrastructure/latest/regions/aws-regions.html
-const VALID_AWS_REGIONS: &[&str] = &[
-    "us-east-1",      // US East (N. Virginia)
-    "us-east-2",      // US East (Ohio)
-    "us-west-1",      // US West (N. California)
-    "us-west-2",      // US West (Oregon)
-    "af-south-1",     // Africa (Cape Town)
-    "ap-east-1",      // Asia Pacific (Hong Kong)
-    "ap-south-1",     // Asia Pacific (Mumbai)
-    "ap-south-2",     // Asia Pacific (Hyderabad)
-    "ap-southeast-1", // Asia Pacific (Singapore)
-    "ap-southeast-2", // Asia Pacific (Sydney)
-    "ap-southeast-3", // Asia Pacific (Jakarta)
-    "ap-southeast-4", // Asia Pacific (Melbourne)
-    "ap-northeast-1", // Asia Pacific (Tokyo)
-    "ap-northeast-2", // Asia Pacific (Seoul)
-    "ap-northeast-3", // Asia Pacific (Osaka)
-    "ca-central-1",   // Canada (Central)
-    "ca-west-1",      // Canada (Calgary)
-    "eu-central-1",   // Europe (Frankfurt)
-    "eu-central-2",   // Europe (Zurich)
-    "eu-west-1",      // Europe (Ireland)
-    "eu-west-2",      // Europe (London)
-    "eu-west-3",      // Europe (Paris)
-    "eu-south-1",     // Europe (Milan)
-    "eu-south-2",     // Europe (Spain)
-    "eu-north-1",     // Europe (Stockholm)
-    "il-central-1",   // Israel (Tel Aviv)
-    "me-south-1",     // Middle East (Bahrain)
-    "me-central-1",   // Middle East (UAE)
-    "sa-east-1",      // South America (São Paulo)
-];
+    #[test]
+    fn test_cli_with_doctor_command() {
+        let cli = Cli::parse_from(["spotter", "doctor"]);
+        assert_eq!(cli.command, Some(Command::Doctor));
+    }
 
-/// Validates if the provided region is a valid AWS region
-pub fn validate_region(region: &str) -> Result<()> {
-    if VALID_AWS_REGIONS.contains(&region) {
-        Ok(())
-    } else {
-        Err(anyhow!(
-            "Invalid AWS region '{}'. Please use a valid AWS region code (e.g., us-east-1, eu-west-1, ap-northeast-1)",
-            region
-        ))
+    #[test]
+    fn test_cli_with_init_command() {
+        let cli = Cli::parse_from(["spotter", "init"]);
+        assert_eq!(cli.command, Some(Command::Init { force: false }));
+
+        let cli = Cli::parse_from(["spotter", "init", "--force", "--config", "spotter.toml"]);
+        assert_eq!(cli.command, Some(Command::Init { force: true }));
+        assert_eq!(cli.config, Some(PathBuf::from("spotter.toml")));
     }
-}
 
-impl Cli {
-    /// Validates the CLI arguments
-    pub fn validate(&self) -> Result<()> {
-        validate_region(&self.region)?;
-        Ok(())
+    #[test]
+    fn test_cli_with_pick_command() {
+        let cli = Cli::parse_from(["spotter", "pick", "-r", "us-east-1", "-i", "m5"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Pick {
+                instance_type: Some("m5".to_string()),
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::CommandFactory;
+    #[test]
+    fn test_cli_with_watch_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "--spot-price",
+            "watch",
+            "--interval",
+            "30s",
+            "-i",
+            "m5",
+            "-r",
+            "us-east-1",
+            "--notify-above",
+            "0.1",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Watch {
+                interval: 30.0 / 3600.0,
+                instance_type: Some("m5".to_string()),
+                notify_above: Some(0.1),
+                notify_below: None,
+            })
+        );
+        assert!(cli.spot_price);
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+
+        let cli = Cli::parse_from(["spotter", "watch"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Watch { interval, .. }) if interval == 60.0 / 3600.0
+        ));
+    }
 
     #[test]
-    fn test_cli_default_values() {
-        let cli = Cli::parse_from(["spotter"]);
-        assert_eq!(cli.region, "us-east-1");
-        assert_eq!(cli.instance_type, None);
-        assert_eq!(cli.spot_price, false);
+    fn test_cli_with_top_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "top",
+            "-r",
+            "us-east-1",
+            "--by",
+            "interruption",
+            "--limit",
+            "5",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Top {
+                by: RankBy::Interruption,
+                limit: 5,
+                interval: 60.0 / 3600.0,
+                instance_type: None,
+            })
+        );
+
+        let cli = Cli::parse_from(["spotter", "top"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Top {
+                by: RankBy::Price,
+                limit: 20,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_with_check_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "check",
+            "-i",
+            "m6i.4xlarge",
+            "-r",
+            "us-east-1",
+            "--max-interruption",
+            "10",
+            "--max-price",
+            "0.40",
+            "--github-summary",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Check {
+                instance_types: vec!["m6i.4xlarge".to_string()],
+                max_interruption: Some(10.0),
+                max_price: Some(0.40),
+                min_savings: None,
+                github_summary: true,
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+
+        // Instance types are required
+        assert!(Cli::try_parse_from(["spotter", "check", "--max-price", "0.4"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_publish_command() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "publish",
+            "cloudwatch",
+            "--region",
+            "us-east-1",
+            "-i",
+            "m5,m6i",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Publish {
+                target: PublishTarget::Cloudwatch {
+                    namespace: "Spotter".to_string(),
+                    instance_types: vec!["m5".to_string(), "m6i".to_string()],
+                }
+            })
+        );
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+
+        let cli = Cli::parse_from(["spotter", "publish", "datadog", "--statsd", "agent:8125"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::Publish {
+                target: PublishTarget::Datadog {
+                    api_key: None,
+                    site: "datadoghq.com".to_string(),
+                    statsd: "agent:8125".to_string(),
+                    instance_types: vec![],
+                }
+            })
+        );
+
+        // A target is required
+        assert!(Cli::try_parse_from(["spotter", "publish"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_listen_addr() {
+        assert_eq!(
+            parse_listen_addr(":8080").unwrap(),
+            "0.0.0.0:8080".parse().unwrap()
+        );
+        assert_eq!(
+            parse_listen_addr("127.0.0.1:3000").unwrap(),
+            "127.0.0.1:3000".parse().unwrap()
+        );
+        assert_eq!(
+            parse_listen_addr("[::1]:80").unwrap(),
+            "[::1]:80".parse().unwrap()
+        );
+
+        assert!(parse_listen_addr("").is_err());
+        assert!(parse_listen_addr("8080").is_err());
+        assert!(parse_listen_addr(":http").is_err());
+        assert!(parse_listen_addr("localhost").is_err());
     }
 
     #[test]
     fn test_cli_with_region() {
         let cli = Cli::parse_from(["spotter", "--region", "eu-west-1"]);
-        assert_eq!(cli.region, "eu-west-1");
+        assert_eq!(cli.region, Some("eu-west-1".to_string()));
         assert_eq!(cli.instance_type, None);
         assert_eq!(cli.spot_price, false);
 
         // Test short form
         let cli = Cli::parse_from(["spotter", "-r", "ap-northeast-1"]);
-        assert_eq!(cli.region, "ap-northeast-1");
+        assert_eq!(cli.region, Some("ap-northeast-1".to_string()));
     }
 
     #[test]
     fn test_cli_with_instance_type() {
         let cli = Cli::parse_from(["spotter", "--instance-type", "m5.large"]);
-        assert_eq!(cli.region, "us-east-1");
+        assert_eq!(cli.region, None);
         assert_eq!(cli.instance_type, Some("m5.large".to_string()));
         assert_eq!(cli.spot_price, false);
 
@@ -115,11 +3141,368 @@ mod tests {
     #[test]
     fn test_cli_with_spot_price() {
         let cli = Cli::parse_from(["spotter", "--spot-price"]);
-        assert_eq!(cli.region, "us-east-1");
+        assert_eq!(cli.region, None);
         assert_eq!(cli.instance_type, None);
         assert_eq!(cli.spot_price, true);
     }
 
+    #[test]
+    fn test_cli_with_os() {
+        let cli = Cli::parse_from(["spotter", "--os", "windows"]);
+        assert_eq!(cli.os, Os::Windows);
+
+        let cli = Cli::parse_from(["spotter", "--os", "all"]);
+        assert_eq!(cli.os, Os::All);
+
+        // Test invalid OS
+        assert!(Cli::try_parse_from(["spotter", "--os", "macos"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_provider() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.provider, Provider::Aws);
+
+        let cli = Cli::parse_from(["spotter", "recommend", "--provider", "gcp"]);
+        assert_eq!(cli.provider, Provider::Gcp);
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "--provider",
+            "azure",
+            "--azure-eviction-rates",
+            "eviction-rates.json",
+        ]);
+        assert_eq!(cli.provider, Provider::Azure);
+        assert_eq!(
+            cli.azure_eviction_rates,
+            Some(PathBuf::from("eviction-rates.json"))
+        );
+        assert_eq!(cli.azure_vm_sizes, None);
+        assert_eq!(Provider::Aws.default_region(), None);
+        assert_eq!(Provider::Azure.default_region(), Some("eastus"));
+
+        assert!(Cli::try_parse_from(["spotter", "--provider", "oci"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_price_columns() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(
+            cli.price_columns,
+            vec![PriceColumn::Linux, PriceColumn::Mswin]
+        );
+
+        let cli = Cli::parse_from(["spotter", "--spot-price", "--price-columns", "rhel,suse"]);
+        assert_eq!(
+            cli.price_columns,
+            vec![PriceColumn::Rhel, PriceColumn::Suse]
+        );
+    }
+
+    #[test]
+    fn test_cli_with_sort_and_normalized_price_filters() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.sort_by, SortBy::Name);
+        assert_eq!(cli.max_price_per_vcpu, None);
+        assert_eq!(cli.max_price_per_gib, None);
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "--sort-by",
+            "price-per-vcpu",
+            "--max-price-per-vcpu",
+            "0.02",
+            "--max-price-per-gib",
+            "0.005",
+        ]);
+        assert_eq!(cli.sort_by, SortBy::PricePerVcpu);
+        assert_eq!(cli.max_price_per_vcpu, Some(0.02));
+        assert_eq!(cli.max_price_per_gib, Some(0.005));
+    }
+
+    #[test]
+    fn test_cli_with_hours_per_month() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.hours_per_month, 730.0);
+
+        let cli = Cli::parse_from(["spotter", "--hours-per-month", "200"]);
+        assert_eq!(cli.hours_per_month, 200.0);
+    }
+
+    #[test]
+    fn test_price_column_pricing_os() {
+        assert_eq!(PriceColumn::Linux.pricing_os(), "Linux");
+        assert_eq!(PriceColumn::Mswin.pricing_os(), "Windows");
+    }
+
+    #[test]
+    fn test_cli_with_risk_penalties() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.risk_penalties, DEFAULT_RISK_PENALTIES.to_vec());
+        assert!(cli.validate().is_ok());
+
+        let cli = Cli::parse_from(["spotter", "--risk-penalties", "0,10,20,30,50"]);
+        assert_eq!(cli.risk_penalties, vec![0.0, 10.0, 20.0, 30.0, 50.0]);
+        assert!(cli.validate().is_ok());
+
+        let cli = Cli::parse_from(["spotter", "--sort-by", "effective-cost"]);
+        assert_eq!(cli.sort_by, SortBy::EffectiveCost);
+    }
+
+    #[test]
+    fn test_validate_risk_penalties_invalid() {
+        assert!(validate_risk_penalties(&[0.0, 10.0]).is_err());
+        assert!(validate_risk_penalties(&[0.0, 5.0, -10.0, 20.0, 40.0]).is_err());
+
+        let cli = Cli::parse_from(["spotter", "--risk-penalties", "1,2,3"]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_cli_with_suggest_upgrades() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.suggest_upgrades);
+
+        let cli = Cli::parse_from(["spotter", "--suggest-upgrades"]);
+        assert!(cli.suggest_upgrades);
+    }
+
+    #[test]
+    fn test_cli_with_group_by() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.group_by, None);
+
+        let cli = Cli::parse_from(["spotter", "--group-by", "family"]);
+        assert_eq!(cli.group_by, Some(GroupBy::Family));
+    }
+
+    #[test]
+    fn test_cli_with_histogram() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.histogram);
+
+        let cli = Cli::parse_from(["spotter", "--histogram", "-r", "eu-west-1"]);
+        assert!(cli.histogram);
+    }
+
+    #[test]
+    fn test_cli_with_suggest_max_price() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.suggest_max_price);
+        assert_eq!(cli.max_price_margin, DEFAULT_MAX_PRICE_MARGIN);
+
+        let cli = Cli::parse_from(["spotter", "--suggest-max-price", "--max-price-margin", "10"]);
+        assert!(cli.suggest_max_price);
+        assert_eq!(cli.max_price_margin, 10.0);
+        assert!(cli.validate().is_ok());
+
+        let cli = Cli::parse_from(["spotter", "--suggest-max-price", "--max-price-margin=-5"]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_cli_with_cache_options() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.cache_ttl, 1.0);
+        assert!(!cli.no_cache);
+
+        let cli = Cli::parse_from(["spotter", "families", "--cache-ttl", "30m", "--no-cache"]);
+        assert_eq!(cli.cache_ttl, 0.5);
+        assert!(cli.no_cache);
+
+        assert!(Cli::try_parse_from(["spotter", "--cache-ttl", "1"]).is_err());
+
+        let cli = Cli::parse_from(["spotter", "cache", "info", "--cache-dir", "/tmp/spotter"]);
+        assert_eq!(cli.cache_dir, Some(PathBuf::from("/tmp/spotter")));
+    }
+
+    #[test]
+    fn test_cli_with_otlp_endpoint() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "record",
+            "--otlp-endpoint",
+            "http://localhost:4318",
+        ]);
+        assert_eq!(cli.otlp_endpoint, Some("http://localhost:4318".to_string()));
+    }
+
+    #[test]
+    fn test_cli_with_append_csv() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.append_csv, None);
+
+        let cli = Cli::parse_from(["spotter", "--append-csv", "prices.csv"]);
+        assert_eq!(cli.append_csv, Some(PathBuf::from("prices.csv")));
+    }
+
+    #[test]
+    fn test_cli_with_no_suggestions() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.no_suggestions);
+
+        let cli = Cli::parse_from(["spotter", "-i", "p3.2xlarge", "--no-suggestions"]);
+        assert!(cli.no_suggestions);
+    }
+
+    #[test]
+    fn test_cli_with_live() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.live);
+
+        let cli = Cli::parse_from(["spotter", "-i", "m5", "--live"]);
+        assert!(cli.live);
+    }
+
+    #[test]
+    fn test_cli_with_specs() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.specs);
+
+        let cli = Cli::parse_from(["spotter", "-i", "g5", "--specs"]);
+        assert!(cli.specs);
+    }
+
+    #[test]
+    fn test_cli_with_trend() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.trend);
+        assert_eq!(cli.trend_samples, 20);
+        assert_eq!(cli.trend_db, "spot.db");
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "--trend",
+            "--trend-samples",
+            "48",
+            "--trend-db",
+            "history.duckdb",
+        ]);
+        assert!(cli.trend);
+        assert_eq!(cli.trend_samples, 48);
+        assert_eq!(cli.trend_db, "history.duckdb");
+    }
+
+    #[test]
+    fn test_cli_with_volatility() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.volatility);
+        assert_eq!(cli.volatility_window, 168.0);
+        assert_eq!(cli.max_volatility, None);
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "-i",
+            "m5",
+            "--max-volatility",
+            "5",
+            "--volatility-window",
+            "24h",
+        ]);
+        assert_eq!(cli.max_volatility, Some(5.0));
+        assert_eq!(cli.volatility_window, 24.0);
+    }
+
+    #[test]
+    fn test_cli_with_version() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert!(!cli.version);
+        assert!(!cli.json);
+
+        let cli = Cli::parse_from(["spotter", "-V"]);
+        assert!(cli.version);
+
+        let cli = Cli::parse_from(["spotter", "--version", "--json"]);
+        assert!(cli.version);
+        assert!(cli.json);
+
+        // --json only applies to the version
+        assert!(Cli::try_parse_from(["spotter", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_with_aws_args() {
+        let cli = Cli::parse_from(["spotter"]);
+        assert_eq!(cli.aws, AwsArgs::default());
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "score",
+            "--types",
+            "m5.xlarge",
+            "--aws-profile",
+            "prod",
+            "--aws-region",
+            "eu-west-1",
+        ]);
+        assert_eq!(
+            cli.aws,
+            AwsArgs {
+                aws_profile: Some("prod".to_string()),
+                aws_region: Some("eu-west-1".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_cli_with_role_arn() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "-i",
+            "m5",
+            "--live",
+            "--role-arn",
+            "arn:aws:iam::123456789012:role/SpotterReadOnly",
+            "--external-id",
+            "platform",
+        ]);
+        assert_eq!(
+            cli.aws.role_arn,
+            Some("arn:aws:iam::123456789012:role/SpotterReadOnly".to_string())
+        );
+        assert_eq!(cli.aws.external_id, Some("platform".to_string()));
+        assert_eq!(cli.aws.role_session_name, "spotter");
+
+        // The external ID and session name only apply to an assumed role
+        assert!(Cli::try_parse_from(["spotter", "--external-id", "platform"]).is_err());
+        assert!(Cli::try_parse_from(["spotter", "--role-arn", "SpotterReadOnly"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_role_arn() {
+        assert!(parse_role_arn("arn:aws:iam::123456789012:role/SpotterReadOnly").is_ok());
+        assert!(parse_role_arn("arn:aws-cn:iam::123456789012:role/path/Spotter").is_ok());
+        assert!(parse_role_arn("arn:aws:iam::123456789012:user/spotter").is_err());
+        assert!(parse_role_arn("arn:aws:iam::1234:role/SpotterReadOnly").is_err());
+        assert!(parse_role_arn("SpotterReadOnly").is_err());
+    }
+
+    #[test]
+    fn test_cli_with_config() {
+        let cli = Cli::parse_from(["spotter", "regions", "--config", "spotter.toml"]);
+        assert_eq!(cli.config, Some(PathBuf::from("spotter.toml")));
+        assert_eq!(cli.profile, None);
+
+        let cli = Cli::parse_from(["spotter", "--profile", "ml-training"]);
+        assert_eq!(cli.profile, Some("ml-training".to_string()));
+    }
+
+    #[test]
+    fn test_os_price_column() {
+        assert_eq!(Os::Linux.price_column(), PriceColumn::Linux);
+        assert_eq!(Os::Windows.price_column(), PriceColumn::Mswin);
+        assert_eq!(Os::All.price_column(), PriceColumn::Linux);
+    }
+
+    #[test]
+    fn test_os_advisor_keys() {
+        assert_eq!(Os::Linux.advisor_keys(), &["Linux"]);
+        assert_eq!(Os::Windows.advisor_keys(), &["Windows"]);
+        assert_eq!(Os::All.advisor_keys(), &["Linux", "Windows"]);
+    }
+
     #[test]
     fn test_cli_with_multiple_args() {
         let cli = Cli::parse_from([
@@ -130,7 +3513,7 @@ mod tests {
             "c5.xlarge",
             "--spot-price",
         ]);
-        assert_eq!(cli.region, "us-west-2");
+        assert_eq!(cli.region, Some("us-west-2".to_string()));
         assert_eq!(cli.instance_type, Some("c5.xlarge".to_string()));
         assert_eq!(cli.spot_price, true);
     }
@@ -154,6 +3537,13 @@ mod tests {
         assert!(validate_region("sa-east-1").is_ok());
     }
 
+    #[test]
+    fn test_region_name() {
+        assert_eq!(region_name("us-east-1"), Some("US East (N. Virginia)"));
+        assert_eq!(region_name("ap-northeast-1"), Some("Asia Pacific (Tokyo)"));
+        assert_eq!(region_name("invalid-region"), None);
+    }
+
     #[test]
     fn test_validate_region_invalid() {
         // Test invalid regions
@@ -186,10 +3576,102 @@ mod tests {
         assert!(error_message.contains("Invalid AWS region 'invalid-region'"));
     }
 
+    #[test]
+    fn test_cli_validate_gcp_region() {
+        let cli = Cli::parse_from(["spotter", "--provider", "gcp", "--region", "europe-west4"]);
+        assert!(cli.validate().is_ok());
+
+        // The region is only validated as an AWS region with --provider aws
+        let cli = Cli::parse_from(["spotter", "--region", "europe-west4"]);
+        assert!(cli.validate().is_err());
+
+        // So are the regions of alert rules
+        let rule = ["alert", "--rule", "n2-standard-8@europe-west4 price>0.1"];
+        let cli = Cli::parse_from([&["spotter", "--provider", "gcp"][..], &rule].concat());
+        assert!(cli.validate().is_ok());
+        let cli = Cli::parse_from([&["spotter"][..], &rule].concat());
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_cli_validate_gcp_interruption_rate() {
+        let cli = Cli::parse_from([
+            "spotter",
+            "--provider",
+            "gcp",
+            "--gcp-interruption-rate",
+            "15",
+        ]);
+        assert_eq!(cli.gcp_interruption_rate, Some(15.0));
+        assert!(cli.validate().is_ok());
+
+        let cli = Cli::parse_from(["spotter", "--gcp-interruption-rate", "150"]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_cli_validate_hours_per_month() {
+        let cli = Cli::parse_from(["spotter", "--hours-per-month", "200"]);
+        assert!(cli.validate().is_ok());
+
+        for arg in ["--hours-per-month=0", "--hours-per-month=-730"] {
+            let cli = Cli::parse_from(["spotter", arg]);
+            assert!(cli.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_cli_validate_estimate_hours() {
+        let cli = Cli::parse_from([
+            "spotter", "estimate", "--spec", "m5.large", "--hours", "200",
+        ]);
+        assert!(cli.validate().is_ok());
+
+        for arg in ["--hours=0", "--hours=-200"] {
+            let cli = Cli::parse_from(["spotter", "estimate", "--spec", "m5.large", arg]);
+            assert!(cli.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_cli_validate_aws_only_usages() {
+        for args in [
+            &["spend"][..],
+            &["score", "--types", "m5.large"],
+            &["audit"],
+            &["export", "karpenter"],
+            &["k8s", "nodes"],
+            &["record"],
+            &["when", "-i", "p4d.24xlarge"],
+            &["graviton", "m5.large"],
+            &["gpu"],
+            &["recommend", "--from-compute-optimizer"],
+            &["publish", "cloudwatch"],
+            &["compare-clouds", "--vcpus", "8", "--memory", "32"],
+            &["--live"],
+            &["--specs"],
+        ] {
+            let cli = Cli::parse_from(["spotter"].iter().chain(args));
+            assert!(cli.validate().is_ok(), "{:?}", args);
+
+            for provider in ["gcp", "azure"] {
+                let cli = Cli::parse_from(["spotter", "--provider", provider].iter().chain(args));
+                let error = cli.validate().unwrap_err().to_string();
+                assert!(error.contains(provider), "{:?}: {}", args, error);
+            }
+        }
+
+        // The spot data of every cloud is displayed and recommended from
+        for args in [&["recommend"][..], &["stats"], &["--spot-price"]] {
+            let cli = Cli::parse_from(["spotter", "--provider", "gcp"].iter().chain(args));
+            assert!(cli.validate().is_ok(), "{:?}", args);
+        }
+    }
+
     #[test]
     fn test_cli_validate_default_region() {
         let cli = Cli::parse_from(["spotter"]);
         assert!(cli.validate().is_ok());
-        assert_eq!(cli.region, "us-east-1");
+        assert_eq!(cli.region, None);
     }
 }
@@ -0,0 +1,152 @@
+use crate::display::INTERRUPTION_RATES;
+use crate::provider::{MachineShape, SpotMarket};
+use std::collections::HashMap;
+
+/// Linux spot offer of a cloud whose shape is close to the requested vCPUs and memory
+#[derive(Clone, Debug, PartialEq)]
+pub struct CloudMatch {
+    pub cloud: &'static str,
+    pub region: String,
+    pub instance_type: String,
+    pub vcpus: u32,
+    pub memory_gb: f64,
+    pub interruption_rate: String,
+    pub spot_price: f64,
+    /// Spot price per vCPU-hour, comparable across clouds
+    pub price_per_vcpu: f64,
+}
+
+/// Returns how far a shape is from the requested vCPUs and memory, as the sum of the relative
+/// excess of each, or None when the shape has fewer vCPUs or less memory than requested
+fn distance(shape: &MachineShape, vcpus: u32, memory_gb: f64) -> Option<f64> {
+    if shape.vcpus < vcpus || shape.memory_gb < memory_gb {
+        return None;
+    }
+    let excess = |value: f64, requested: f64| {
+        if requested > 0.0 {
+            value / requested - 1.0
+        } else {
+            0.0
+        }
+    };
+    Some(excess(f64::from(shape.vcpus), f64::from(vcpus)) + excess(shape.memory_gb, memory_gb))
+}
+
+/// Returns the Linux spot offers of a region whose shapes are the closest to the requested vCPUs
+/// and memory, the cheapest first among equally close shapes
+pub fn closest(
+    cloud: &'static str,
+    market: &SpotMarket,
+    region: &str,
+    vcpus: u32,
+    memory_gb: f64,
+    count: usize,
+) -> Vec<CloudMatch> {
+    let shapes: HashMap<&str, &MachineShape> = market
+        .shapes
+        .iter()
+        .map(|shape| (shape.instance_type.as_str(), shape))
+        .collect();
+
+    let mut matches: Vec<(f64, CloudMatch)> = market
+        .offers
+        .iter()
+        .filter(|offer| offer.region == region && offer.os == "Linux")
+        .filter_map(|offer| {
+            let shape = shapes.get(offer.instance_type.as_str())?;
+            let distance = distance(shape, vcpus, memory_gb)?;
+            let spot_price = offer.spot_price?;
+            Some((
+                distance,
+                CloudMatch {
+                    cloud,
+                    region: offer.region.clone(),
+                    instance_type: offer.instance_type.clone(),
+                    vcpus: shape.vcpus,
+                    memory_gb: shape.memory_gb,
+                    interruption_rate: offer
+                        .interruption_bucket
+                        .map_or("N/A", |bucket| INTERRUPTION_RATES[bucket])
+                        .to_string(),
+                    spot_price,
+                    price_per_vcpu: spot_price / f64::from(shape.vcpus),
+                },
+            ))
+        })
+        .collect();
+
+    matches.sort_by(|(a_distance, a), (b_distance, b)| {
+        a_distance
+            .total_cmp(b_distance)
+            .then(a.spot_price.total_cmp(&b.spot_price))
+    });
+    matches
+        .into_iter()
+        .take(count)
+        .map(|(_, cloud_match)| cloud_match)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::SpotOffer;
+
+    fn market() -> SpotMarket {
+        let shape = |instance_type: &str, vcpus: u32, memory_gb: f64| MachineShape {
+            instance_type: instance_type.to_string(),
+            vcpus,
+            memory_gb,
+        };
+        let offer = |instance_type: &str, region: &str, spot_price: Option<f64>| SpotOffer {
+            region: region.to_string(),
+            instance_type: instance_type.to_string(),
+            os: "Linux",
+            interruption_bucket: Some(0),
+            savings: Some(70),
+            spot_price,
+        };
+        SpotMarket {
+            shapes: vec![
+                shape("m6i.2xlarge", 8, 32.0),
+                shape("m7g.2xlarge", 8, 32.0),
+                shape("r6i.2xlarge", 8, 64.0),
+                shape("m6i.4xlarge", 16, 64.0),
+                shape("c6i.2xlarge", 8, 16.0),
+            ],
+            offers: vec![
+                offer("m6i.2xlarge", "us-east-1", Some(0.16)),
+                offer("m7g.2xlarge", "us-east-1", Some(0.12)),
+                offer("r6i.2xlarge", "us-east-1", Some(0.2)),
+                offer("m6i.4xlarge", "us-east-1", Some(0.3)),
+                offer("c6i.2xlarge", "us-east-1", Some(0.1)),
+                offer("m6i.2xlarge", "us-west-2", Some(0.08)),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_distance() {
+        let shape = MachineShape {
+            instance_type: "r6i.2xlarge".to_string(),
+            vcpus: 8,
+            memory_gb: 64.0,
+        };
+        assert_eq!(distance(&shape, 8, 32.0), Some(1.0));
+        assert_eq!(distance(&shape, 8, 64.0), Some(0.0));
+        assert_eq!(distance(&shape, 16, 32.0), None);
+    }
+
+    #[test]
+    fn test_closest() {
+        let matches = closest("aws", &market(), "us-east-1", 8, 32.0, 3);
+        let types: Vec<&str> = matches.iter().map(|m| m.instance_type.as_str()).collect();
+        // The exact shapes, the cheapest first, then the shape with twice the memory
+        assert_eq!(types, vec!["m7g.2xlarge", "m6i.2xlarge", "r6i.2xlarge"]);
+        assert_eq!(matches[0].cloud, "aws");
+        assert_eq!(matches[0].interruption_rate, "< 5%");
+        assert!((matches[0].price_per_vcpu - 0.015).abs() < 1e-9);
+
+        assert!(closest("aws", &market(), "eu-west-1", 8, 32.0, 3).is_empty());
+    }
+}
@@ -0,0 +1,571 @@
+use crate::cli::{self, Cli, GroupBy, Os, PriceColumn, SortBy};
+use anyhow::{Context, Result, anyhow};
+use clap::ArgMatches;
+use clap::ValueEnum;
+use clap::parser::ValueSource;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Defaults of the command line flags read from the config file. Keys are the flag names with
+/// underscores, and enum values are spelled like on the command line. Named profiles in
+/// `[profile.<name>]` sections bundle more defaults for a workload, selected with --profile, and
+/// `[alias]` entries name flags of recurring queries, run with `spotter @<name>`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub region: Option<String>,
+    pub instance_type: Option<String>,
+    pub spot_price: Option<bool>,
+    pub price_columns: Option<Vec<String>>,
+    pub os: Option<String>,
+    pub sort_by: Option<String>,
+    pub max_price_per_vcpu: Option<f64>,
+    pub max_price_per_gib: Option<f64>,
+    pub hours_per_month: Option<f64>,
+    pub risk_penalties: Option<Vec<f64>>,
+    pub suggest_upgrades: Option<bool>,
+    pub group_by: Option<String>,
+    pub suggest_max_price: Option<bool>,
+    pub max_price_margin: Option<f64>,
+    pub no_suggestions: Option<bool>,
+    pub cache_ttl: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+    pub profile: BTreeMap<String, Config>,
+    pub alias: BTreeMap<String, String>,
+}
+
+/// Prefix of an alias on the command line
+const ALIAS_PREFIX: char = '@';
+
+/// Parses the value of a config key like the value of its flag
+fn value_enum<T: ValueEnum>(key: &str, value: &str) -> Result<T> {
+    T::from_str(value, true).map_err(|_| {
+        let valid: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| Some(v.to_possible_value()?.get_name().to_string()))
+            .collect();
+        anyhow!(
+            "Invalid value '{}' of '{}' in the config file. Please use one of: {}",
+            value,
+            key,
+            valid.join(", ")
+        )
+    })
+}
+
+impl Config {
+    /// Parses a config file
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config: Self = toml::from_str(contents).context("Failed to parse the config file")?;
+        if let Some((name, _)) = config
+            .profile
+            .iter()
+            .find(|(_, profile)| !profile.profile.is_empty() || !profile.alias.is_empty())
+        {
+            return Err(anyhow!(
+                "Profile '{}' must not define profiles or aliases",
+                name
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Replaces the alias in the first argument with its flags, keeping the other arguments
+    pub fn expand_alias(&self, args: Vec<OsString>) -> Result<Vec<OsString>> {
+        let Some(name) = alias_name(&args) else {
+            return Ok(args);
+        };
+        let flags = self.alias.get(name).ok_or_else(|| {
+            anyhow!(
+                "Alias '{}' not found. Define it in the [alias] section of the config file",
+                name
+            )
+        })?;
+
+        let mut expanded = vec![args[0].clone()];
+        expanded.extend(flags.split_whitespace().map(OsString::from));
+        expanded.extend(args[2..].iter().cloned());
+        Ok(expanded)
+    }
+
+    /// Returns the named profile
+    pub fn profile(&self, name: &str) -> Result<&Config> {
+        self.profile.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self.profile.keys().map(String::as_str).collect();
+            if names.is_empty() {
+                anyhow!(
+                    "Profile '{}' not found, the config file defines no profiles",
+                    name
+                )
+            } else {
+                anyhow!(
+                    "Profile '{}' not found. Available profiles: {}",
+                    name,
+                    names.join(", ")
+                )
+            }
+        })
+    }
+
+    /// Loads the config file, returning the default config when the file doesn't exist and
+    /// wasn't asked for explicitly
+    pub fn load(path: &Path, required: bool) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents)
+                .with_context(|| format!("Invalid config file '{}'", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => Ok(Self::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read config file '{}'", path.display()))
+            }
+        }
+    }
+
+    /// Sets the flags that weren't given on the command line (nor by their environment
+    /// variable) to the values of the config, then to the values of the selected profile
+    pub fn apply_with_profile(
+        &self,
+        profile: Option<&str>,
+        cli: &mut Cli,
+        matches: &ArgMatches,
+    ) -> Result<()> {
+        self.apply(cli, matches)?;
+        if let Some(name) = profile {
+            self.profile(name)?.apply(cli, matches)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the flags that weren't given on the command line (nor by their environment
+    /// variable) to the values of the config
+    fn apply(&self, cli: &mut Cli, matches: &ArgMatches) -> Result<()> {
+        let unset = |id: &str| {
+            matches
+                .value_source(id)
+                .is_none_or(|source| source == ValueSource::DefaultValue)
+        };
+
+        if let Some(region) = &self.region
+            && unset("region")
+        {
+            cli.region = Some(region.clone());
+        }
+        if let Some(instance_type) = &self.instance_type
+            && unset("instance_type")
+        {
+            cli.instance_type = Some(instance_type.clone());
+        }
+        if let Some(spot_price) = self.spot_price
+            && unset("spot_price")
+        {
+            cli.spot_price = spot_price;
+        }
+        if let Some(price_columns) = &self.price_columns
+            && unset("price_columns")
+        {
+            cli.price_columns = price_columns
+                .iter()
+                .map(|column| value_enum::<PriceColumn>("price_columns", column))
+                .collect::<Result<_>>()?;
+        }
+        if let Some(os) = &self.os
+            && unset("os")
+        {
+            cli.os = value_enum::<Os>("os", os)?;
+        }
+        if let Some(sort_by) = &self.sort_by
+            && unset("sort_by")
+        {
+            cli.sort_by = value_enum::<SortBy>("sort_by", sort_by)?;
+        }
+        if let Some(max_price_per_vcpu) = self.max_price_per_vcpu
+            && unset("max_price_per_vcpu")
+        {
+            cli.max_price_per_vcpu = Some(max_price_per_vcpu);
+        }
+        if let Some(max_price_per_gib) = self.max_price_per_gib
+            && unset("max_price_per_gib")
+        {
+            cli.max_price_per_gib = Some(max_price_per_gib);
+        }
+        if let Some(hours_per_month) = self.hours_per_month
+            && unset("hours_per_month")
+        {
+            cli.hours_per_month = hours_per_month;
+        }
+        if let Some(risk_penalties) = &self.risk_penalties
+            && unset("risk_penalties")
+        {
+            cli.risk_penalties = risk_penalties.clone();
+        }
+        if let Some(suggest_upgrades) = self.suggest_upgrades
+            && unset("suggest_upgrades")
+        {
+            cli.suggest_upgrades = suggest_upgrades;
+        }
+        if let Some(group_by) = &self.group_by
+            && unset("group_by")
+        {
+            cli.group_by = Some(value_enum::<GroupBy>("group_by", group_by)?);
+        }
+        if let Some(suggest_max_price) = self.suggest_max_price
+            && unset("suggest_max_price")
+        {
+            cli.suggest_max_price = suggest_max_price;
+        }
+        if let Some(max_price_margin) = self.max_price_margin
+            && unset("max_price_margin")
+        {
+            cli.max_price_margin = max_price_margin;
+        }
+        if let Some(no_suggestions) = self.no_suggestions
+            && unset("no_suggestions")
+        {
+            cli.no_suggestions = no_suggestions;
+        }
+        if let Some(cache_ttl) = &self.cache_ttl
+            && unset("cache_ttl")
+        {
+            cli.cache_ttl = cli::parse_duration(cache_ttl)
+                .context("Invalid value of 'cache_ttl' in the config file")?;
+        }
+        if let Some(cache_dir) = &self.cache_dir
+            && unset("cache_dir")
+        {
+            cli.cache_dir = Some(cache_dir.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Returns the alias given as the first argument, like "gpu-cheap" of `spotter @gpu-cheap`
+fn alias_name(args: &[OsString]) -> Option<&str> {
+    args.get(1)?.to_str()?.strip_prefix(ALIAS_PREFIX)
+}
+
+/// Returns the --config value of raw arguments, before they're parsed
+fn config_arg(args: &[OsString]) -> Option<PathBuf> {
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        let arg = arg.to_str()?;
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Expands an alias given as the first argument with the aliases of the config file. The config
+/// file is resolved like after parsing, since the expanded arguments aren't parsed yet.
+pub fn expand_alias(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    if alias_name(&args).is_none() {
+        return Ok(args);
+    }
+    let config = config_arg(&args).or_else(|| {
+        std::env::var_os("SPOTTER_CONFIG")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    });
+    let (path, _) = resolve_path(config.as_deref())
+        .ok_or_else(|| anyhow!("No config file to read aliases from. Please set --config"))?;
+    Config::load(&path, true)?.expand_alias(args)
+}
+
+/// Resolves the config file: the --config flag (or $SPOTTER_CONFIG), and finally the platform
+/// default. The flag is true when the file was given explicitly.
+pub fn resolve_path(config: Option<&Path>) -> Option<(PathBuf, bool)> {
+    if let Some(path) = config {
+        return Some((path.to_path_buf(), true));
+    }
+
+    config_path_from_env(|name| std::env::var_os(name), cfg!(windows)).map(|path| (path, false))
+}
+
+/// Resolves the default config file from environment variables: %APPDATA%\spotter\config.toml
+/// on Windows, and $XDG_CONFIG_HOME/spotter/config.toml or ~/.config/spotter/config.toml
+/// elsewhere
+fn config_path_from_env(var: impl Fn(&str) -> Option<OsString>, windows: bool) -> Option<PathBuf> {
+    let var = |name: &str| {
+        var(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let config_home = if windows {
+        var("APPDATA")?
+    } else {
+        var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))?
+    };
+    Some(config_home.join("spotter").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn apply(config: &Config, args: &[&str]) -> Result<Cli> {
+        let matches = Cli::command().try_get_matches_from(args)?;
+        let mut cli = Cli::from_arg_matches(&matches)?;
+        config.apply_with_profile(None, &mut cli, &matches)?;
+        Ok(cli)
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r#"
+region = "eu-west-1"
+spot_price = true
+price_columns = ["linux", "rhel"]
+sort_by = "price"
+risk_penalties = [0, 5, 10, 20, 40]
+cache_ttl = "30m"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.region, Some("eu-west-1".to_string()));
+        assert_eq!(config.spot_price, Some(true));
+        assert_eq!(
+            config.price_columns,
+            Some(vec!["linux".to_string(), "rhel".to_string()])
+        );
+        assert_eq!(
+            config.risk_penalties,
+            Some(vec![0.0, 5.0, 10.0, 20.0, 40.0])
+        );
+        assert_eq!(config.os, None);
+
+        // Typos are reported instead of ignored
+        assert!(Config::parse("regoin = \"eu-west-1\"").is_err());
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_apply() {
+        let config = Config::parse(
+            r#"
+region = "eu-west-1"
+spot_price = true
+price_columns = ["linux", "rhel"]
+os = "windows"
+sort_by = "price"
+cache_ttl = "30m"
+"#,
+        )
+        .unwrap();
+
+        let cli = apply(&config, &["spotter"]).unwrap();
+        assert_eq!(cli.region, Some("eu-west-1".to_string()));
+        assert!(cli.spot_price);
+        assert_eq!(
+            cli.price_columns,
+            vec![PriceColumn::Linux, PriceColumn::Rhel]
+        );
+        assert_eq!(cli.os, Os::Windows);
+        assert_eq!(cli.sort_by, SortBy::Price);
+        assert_eq!(cli.cache_ttl, 0.5);
+
+        // Flags on the command line take precedence, including after a subcommand
+        let cli = apply(
+            &config,
+            &["spotter", "--os", "linux", "families", "-r", "us-west-2"],
+        )
+        .unwrap();
+        assert_eq!(cli.region, Some("us-west-2".to_string()));
+        assert_eq!(cli.os, Os::Linux);
+        assert_eq!(cli.sort_by, SortBy::Price);
+    }
+
+    #[test]
+    fn test_apply_with_profile() {
+        let config = Config::parse(
+            r#"
+region = "eu-west-1"
+sort_by = "price"
+
+[profile.ml-training]
+region = "us-east-1"
+instance_type = "p4d"
+spot_price = true
+
+[profile.web]
+instance_type = "t3"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.profile.len(), 2);
+
+        let matches = Cli::command().try_get_matches_from(["spotter"]).unwrap();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        config
+            .apply_with_profile(Some("ml-training"), &mut cli, &matches)
+            .unwrap();
+        // The profile overrides the top-level defaults
+        assert_eq!(cli.region, Some("us-east-1".to_string()));
+        assert_eq!(cli.instance_type, Some("p4d".to_string()));
+        assert!(cli.spot_price);
+        assert_eq!(cli.sort_by, SortBy::Price);
+
+        // Flags on the command line override the profile
+        let matches = Cli::command()
+            .try_get_matches_from(["spotter", "-i", "c5"])
+            .unwrap();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        config
+            .apply_with_profile(Some("web"), &mut cli, &matches)
+            .unwrap();
+        assert_eq!(cli.region, Some("eu-west-1".to_string()));
+        assert_eq!(cli.instance_type, Some("c5".to_string()));
+
+        let error = config
+            .apply_with_profile(Some("batch"), &mut cli, &matches)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "Profile 'batch' not found. Available profiles: ml-training, web"
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_profiles() {
+        assert!(Config::parse("[profile.web.profile.api]\nregion = \"us-east-1\"").is_err());
+        assert!(Config::default().profile("web").is_err());
+    }
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        let config = Config::parse(
+            r#"
+[alias]
+cheap = "--sort-by price --spot-price"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config
+                .expand_alias(args(&["spotter", "@cheap", "-r", "us-east-1"]))
+                .unwrap(),
+            args(&[
+                "spotter",
+                "--sort-by",
+                "price",
+                "--spot-price",
+                "-r",
+                "us-east-1"
+            ])
+        );
+        // Arguments without an alias are kept as they are
+        assert_eq!(
+            config
+                .expand_alias(args(&["spotter", "-r", "us-east-1"]))
+                .unwrap(),
+            args(&["spotter", "-r", "us-east-1"])
+        );
+        assert!(config.expand_alias(args(&["spotter", "@pricey"])).is_err());
+
+        // Aliases belong to the whole config file
+        assert!(Config::parse("[profile.web.alias]\ncheap = \"--sort-by price\"").is_err());
+    }
+
+    #[test]
+    fn test_config_arg() {
+        assert_eq!(
+            config_arg(&args(&["spotter", "@cheap", "--config", "spotter.toml"])),
+            Some(PathBuf::from("spotter.toml"))
+        );
+        assert_eq!(
+            config_arg(&args(&["spotter", "@cheap", "--config=spotter.toml"])),
+            Some(PathBuf::from("spotter.toml"))
+        );
+        assert_eq!(config_arg(&args(&["spotter", "@cheap"])), None);
+    }
+
+    #[test]
+    fn test_apply_invalid_value() {
+        let config = Config {
+            sort_by: Some("colour".to_string()),
+            ..Default::default()
+        };
+        let error = apply(&config, &["spotter"]).unwrap_err().to_string();
+        assert!(error.contains("'colour' of 'sort_by'"));
+
+        let config = Config {
+            cache_ttl: Some("soon".to_string()),
+            ..Default::default()
+        };
+        assert!(apply(&config, &["spotter"]).is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let path =
+            std::env::temp_dir().join(format!("spotter-test-config-{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(Config::load(&path, false).unwrap(), Config::default());
+        assert!(Config::load(&path, true).is_err());
+
+        std::fs::write(&path, "region = \"ap-northeast-1\"\n").unwrap();
+        assert_eq!(
+            Config::load(&path, true).unwrap().region,
+            Some("ap-northeast-1".to_string())
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(
+            resolve_path(Some(Path::new("spotter.toml"))),
+            Some((PathBuf::from("spotter.toml"), true))
+        );
+    }
+
+    #[test]
+    fn test_config_path_from_env() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+
+        let vars = env(&[("XDG_CONFIG_HOME", "/xdg"), ("HOME", "/home/me")]);
+        assert_eq!(
+            config_path_from_env(&vars, false),
+            Some(PathBuf::from("/xdg/spotter/config.toml"))
+        );
+
+        let vars = env(&[("XDG_CONFIG_HOME", ""), ("HOME", "/home/me")]);
+        assert_eq!(
+            config_path_from_env(&vars, false),
+            Some(PathBuf::from("/home/me/.config/spotter/config.toml"))
+        );
+
+        let vars = env(&[("APPDATA", "C:\\Users\\me\\AppData\\Roaming")]);
+        assert_eq!(
+            config_path_from_env(&vars, true),
+            Some(
+                PathBuf::from("C:\\Users\\me\\AppData\\Roaming")
+                    .join("spotter")
+                    .join("config.toml")
+            )
+        );
+
+        assert_eq!(config_path_from_env(env(&[]), false), None);
+    }
+}
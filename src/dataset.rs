@@ -0,0 +1,178 @@
+use crate::aws;
+use crate::cache::Cache;
+use crate::cli::{self, Cli, Os, Provider};
+use crate::display::{self, InstanceData};
+use crate::provider::{Backend, ProviderConfig, SpotProvider};
+use crate::region;
+use crate::snapshot::Snapshot;
+use anyhow::Result;
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Spot advisor data and spot price data of the cloud of --provider, along with the region the
+/// command looks at
+pub struct Dataset {
+    pub region: String,
+    pub advisor_data: Value,
+    pub price_data: Value,
+}
+
+impl Dataset {
+    /// Resolves the region and loads the data
+    pub async fn load(cli: &Cli, client: &Client, cache: Option<&Cache>) -> Result<Self> {
+        let region = resolve_region(cli, client).await;
+        Self::load_in(region, cli, client, cache).await
+    }
+
+    /// Loads the data, looking at a region resolved beforehand
+    pub async fn load_in(
+        region: String,
+        cli: &Cli,
+        client: &Client,
+        cache: Option<&Cache>,
+    ) -> Result<Self> {
+        let (advisor_data, price_data) = load_data_of(Some(&region), cli, client, cache).await?;
+        Ok(Self {
+            region,
+            advisor_data,
+            price_data,
+        })
+    }
+
+    /// Returns the instance data of the OS
+    pub fn instance_data(&self, os: Os) -> InstanceData {
+        display::collect_instance_data(&self.region, &self.advisor_data, &self.price_data, os)
+    }
+
+    /// Fetches the on-demand prices of the region, see `on_demand_prices`
+    pub async fn on_demand_prices(
+        &self,
+        cli: &Cli,
+        client: &Client,
+    ) -> Option<HashMap<String, String>> {
+        on_demand_prices(cli, client, &self.region).await
+    }
+}
+
+/// Loads the spot data of every region, see `load_data_of`
+pub async fn load_data(
+    cli: &Cli,
+    client: &Client,
+    cache: Option<&Cache>,
+) -> Result<(Value, Value)> {
+    load_data_of(None, cli, client, cache).await
+}
+
+/// Loads the spot advisor data and the spot price data from the snapshot given with
+/// --from-snapshot, or fetches them from the cloud of --provider, only for the region when one
+/// is given
+async fn load_data_of(
+    region: Option<&str>,
+    cli: &Cli,
+    client: &Client,
+    cache: Option<&Cache>,
+) -> Result<(Value, Value)> {
+    match &cli.from_snapshot {
+        Some(path) => {
+            let snapshot = Snapshot::load(path)?;
+            log::info!(
+                "Using snapshot {} taken at Unix time {}",
+                path.display(),
+                snapshot.created_at
+            );
+            Ok((snapshot.advisor_data, snapshot.price_data))
+        }
+        None => {
+            let config = ProviderConfig {
+                region: region.map(str::to_string),
+                ..ProviderConfig::new(cli.provider, cli)
+            };
+            let provider = Backend::new(&config, client, cache);
+            log::debug!("Fetching the {} spot data", provider.name());
+            provider.fetch_data().await
+        }
+    }
+}
+
+/// Resolves the region to use and validates it
+pub async fn resolve_region(cli: &Cli, client: &Client) -> String {
+    // Only AWS regions are detected from the environment and validated
+    if let Some(default_region) = cli.provider.default_region() {
+        return cli
+            .region
+            .clone()
+            .unwrap_or_else(|| default_region.to_string());
+    }
+    let region = region::resolve_region(cli.region.as_deref(), client).await;
+    if let Err(e) = cli::validate_region(&region) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    region
+}
+
+/// Fetches the on-demand prices of the region, or None when they can't be fetched. Only AWS
+/// on-demand prices are known, so the other clouds have none
+pub async fn on_demand_prices(
+    cli: &Cli,
+    client: &Client,
+    region: &str,
+) -> Option<HashMap<String, String>> {
+    if cli.provider != Provider::Aws {
+        log::debug!("No on-demand prices of {}", cli.provider.name());
+        return None;
+    }
+    let os = cli.os.price_column().pricing_os();
+    match aws::fetch_on_demand_price_data(client, region, os).await {
+        Ok(data) => Some(display::collect_on_demand_prices(&data)),
+        Err(e) => {
+            log::warn!("Failed to fetch on-demand price data of {}: {}", region, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_dataset_from_snapshot() {
+        let path =
+            std::env::temp_dir().join(format!("spotter-test-dataset-{}.json", std::process::id()));
+        let advisor_data = json!({
+            "instance_types": { "n2-standard-8": { "cores": 8, "ram_gb": 32.0 } },
+            "spot_advisor": {
+                "europe-west4": { "Linux": { "n2-standard-8": { "s": 70 } } }
+            },
+        });
+        let price_data = json!({ "config": { "regions": [] } });
+        Snapshot::new(advisor_data, price_data).save(&path).unwrap();
+
+        let cli = Cli::parse_from([
+            "spotter",
+            "--provider",
+            "gcp",
+            "--from-snapshot",
+            path.to_str().unwrap(),
+        ]);
+        let client = Client::new();
+        let dataset = Dataset::load_in("europe-west4".to_string(), &cli, &client, None)
+            .await
+            .unwrap();
+        let instance_data = dataset.instance_data(Os::Linux);
+        let info =
+            &instance_data[&("n2-standard-8".to_string(), "Linux".to_string())]["europe-west4"];
+        // GCP publishes no preemption rates
+        assert_eq!(info.interruption_bucket(), None);
+        assert_eq!(info.savings_value(), Some(70.0));
+
+        // Only AWS on-demand prices are known
+        assert_eq!(dataset.on_demand_prices(&cli, &client).await, None);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
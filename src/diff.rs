@@ -0,0 +1,106 @@
+use crate::cli::PriceColumn;
+use crate::display::InstanceInfo;
+use std::collections::{BTreeSet, HashMap};
+
+/// Change of an instance type between two points in time, where a missing side means the
+/// instance type was added or removed
+#[derive(Clone, Debug)]
+pub struct InstanceChange {
+    pub instance_type: String,
+    pub old: Option<InstanceInfo>,
+    pub new: Option<InstanceInfo>,
+}
+
+impl InstanceChange {
+    /// Returns the change of the spot price
+    pub fn price_delta(&self, column: PriceColumn) -> Option<f64> {
+        let old = self.old.as_ref()?.spot_price_value(column)?;
+        let new = self.new.as_ref()?.spot_price_value(column)?;
+        Some(new - old)
+    }
+
+    /// Returns the change of the savings in percentage points
+    pub fn savings_delta(&self) -> Option<f64> {
+        Some(self.new.as_ref()?.savings_value()? - self.old.as_ref()?.savings_value()?)
+    }
+
+    /// Returns the change of the interruption rate in buckets, positive when it got riskier
+    pub fn interruption_delta(&self) -> Option<i64> {
+        let old = self.old.as_ref()?.interruption_bucket()? as i64;
+        let new = self.new.as_ref()?.interruption_bucket()? as i64;
+        Some(new - old)
+    }
+}
+
+/// Returns the instance types whose spot price, savings or interruption rate changed, or that
+/// were added or removed, ordered by instance type
+pub fn diff_instances(
+    old: &HashMap<&str, &InstanceInfo>,
+    new: &HashMap<&str, &InstanceInfo>,
+    price_column: PriceColumn,
+) -> Vec<InstanceChange> {
+    let instance_types: BTreeSet<&str> = old.keys().chain(new.keys()).copied().collect();
+
+    instance_types
+        .into_iter()
+        .filter_map(|instance_type| {
+            let old_info = old.get(instance_type);
+            let new_info = new.get(instance_type);
+
+            if let (Some(old_info), Some(new_info)) = (old_info, new_info)
+                && old_info.spot_price(price_column) == new_info.spot_price(price_column)
+                && old_info.savings == new_info.savings
+                && old_info.interruption_rate == new_info.interruption_rate
+            {
+                return None;
+            }
+
+            Some(InstanceChange {
+                instance_type: instance_type.to_string(),
+                old: old_info.map(|info| (*info).clone()),
+                new: new_info.map(|info| (*info).clone()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::info;
+
+    #[test]
+    fn test_diff_instances() {
+        let unchanged = info("< 5%", "0.1");
+        let old_m5 = info("< 5%", "0.1");
+        let new_m5 = info("10-15%", "0.12").with_savings("65%");
+        let removed = info("5-10%", "0.2").with_savings("60%");
+        let added = info("< 5%", "0.05").with_savings("80%");
+
+        let old = HashMap::from([
+            ("c5.large", &unchanged),
+            ("m5.large", &old_m5),
+            ("r5.large", &removed),
+        ]);
+        let new = HashMap::from([
+            ("c5.large", &unchanged),
+            ("m5.large", &new_m5),
+            ("t3.large", &added),
+        ]);
+
+        let changes = diff_instances(&old, &new, PriceColumn::Linux);
+        let instance_types: Vec<&str> = changes.iter().map(|c| c.instance_type.as_str()).collect();
+        assert_eq!(instance_types, vec!["m5.large", "r5.large", "t3.large"]);
+
+        let m5 = &changes[0];
+        assert!((m5.price_delta(PriceColumn::Linux).unwrap() - 0.02).abs() < 1e-9);
+        assert_eq!(m5.savings_delta(), Some(-5.0));
+        assert_eq!(m5.interruption_delta(), Some(2));
+
+        // Added and removed instance types have no deltas
+        assert!(changes[1].new.is_none());
+        assert_eq!(changes[1].price_delta(PriceColumn::Linux), None);
+        assert!(changes[2].old.is_none());
+        assert_eq!(changes[2].interruption_delta(), None);
+    }
+}
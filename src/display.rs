@@ -1,7 +1,41 @@
-use anyhow::Result;
+use crate::alert::Trigger;
+use crate::audit::{AuditFinding, PoolRating};
+use crate::aws::DataSource;
+use crate::cache::Cache;
+use crate::check::Check;
+use crate::cli::{
+    DEFAULT_HOURS_PER_MONTH, DEFAULT_RISK_PENALTIES, GroupBy, Os, PriceColumn, SortBy,
+};
+use crate::clouds::CloudMatch;
+use crate::diff;
+use crate::doctor::Diagnosis;
+use crate::fleet::FleetEntry;
+use crate::gpu::GpuOffer;
+use crate::history::{self, HistoryRecord, HistorySummary};
+use crate::instance;
+use crate::k8s::{Fit, NodeGroup, PodRequests};
+use crate::live::AzPriceTable;
+use crate::optimizer::SpotMove;
+use crate::recommend::{self, Recommendation};
+use crate::score::PlacementScore;
+use crate::simulate;
+use crate::snapshot::Snapshot;
+use crate::specs::InstanceSpecs;
+use crate::spend::SpendOverlay;
+use crate::telemetry;
+use crate::top::Ranked;
+use crate::when::{self, WhenReport};
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
 use prettytable::{Cell, Row, Table};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Interruption rates of the spot advisor data, indexed by the rate bucket ("r" value)
+pub const INTERRUPTION_RATES: [&str; 5] = ["< 5%", "5-10%", "10-15%", "15-20%", "> 20%"];
 
 #[derive(Clone, Debug)]
 pub struct InstanceInfo {
@@ -9,37 +43,723 @@ pub struct InstanceInfo {
     pub savings: String,
     pub linux_spot_price: String,
     pub windows_spot_price: String,
+    pub rhel_spot_price: String,
+    pub suse_spot_price: String,
+    pub on_demand_price: String,
     pub memory_gb: String,
     pub cores: String,
 }
 
+impl InstanceInfo {
+    /// Returns the spot price for the given price column
+    pub fn spot_price(&self, column: PriceColumn) -> &str {
+        match column {
+            PriceColumn::Linux => &self.linux_spot_price,
+            PriceColumn::Mswin => &self.windows_spot_price,
+            PriceColumn::Rhel => &self.rhel_spot_price,
+            PriceColumn::Suse => &self.suse_spot_price,
+        }
+    }
+
+    /// Returns the spot price for the given price column as a number
+    pub fn spot_price_value(&self, column: PriceColumn) -> Option<f64> {
+        self.spot_price(column).parse().ok()
+    }
+
+    /// Returns the on-demand price as a number
+    pub fn on_demand_price_value(&self) -> Option<f64> {
+        self.on_demand_price.parse().ok()
+    }
+
+    /// Returns the interruption rate bucket (0: "< 5%" to 4: "> 20%")
+    pub fn interruption_bucket(&self) -> Option<usize> {
+        INTERRUPTION_RATES
+            .iter()
+            .position(|rate| *rate == self.interruption_rate)
+    }
+
+    /// Returns the savings over on-demand as a percentage number
+    pub fn savings_value(&self) -> Option<f64> {
+        self.savings.trim_end_matches('%').parse().ok()
+    }
+
+    /// Returns the memory in GB as a number
+    pub fn memory_gb_value(&self) -> Option<f64> {
+        self.memory_gb.parse().ok()
+    }
+
+    /// Returns the number of cores as a number
+    pub fn cores_value(&self) -> Option<f64> {
+        self.cores.parse().ok()
+    }
+
+    /// Returns the spot price per vCPU-hour for the given price column
+    pub fn price_per_vcpu(&self, column: PriceColumn) -> Option<f64> {
+        let cores = self.cores_value().filter(|c| *c > 0.0)?;
+        Some(self.spot_price_value(column)? / cores)
+    }
+
+    /// Returns the estimated monthly spot cost for the given price column
+    pub fn monthly_cost(&self, column: PriceColumn, hours_per_month: f64) -> Option<f64> {
+        Some(self.spot_price_value(column)? * hours_per_month)
+    }
+
+    /// Returns the spot price inflated by the risk penalty (%) of its interruption rate bucket
+    pub fn effective_cost(&self, column: PriceColumn, risk_penalties: &[f64]) -> Option<f64> {
+        let penalty = risk_penalties.get(self.interruption_bucket()?)?;
+        Some(self.spot_price_value(column)? * (1.0 + penalty / 100.0))
+    }
+
+    /// Returns the suggested max price (bid ceiling) for the given price column: the spot price
+    /// plus a margin (%), capped at the on-demand price when it's known
+    pub fn suggested_max_price(&self, column: PriceColumn, margin: f64) -> Option<f64> {
+        let max_price = self.spot_price_value(column)? * (1.0 + margin / 100.0);
+        Some(match self.on_demand_price_value() {
+            Some(on_demand_price) => max_price.min(on_demand_price),
+            None => max_price,
+        })
+    }
+
+    /// Returns the spot price per GiB-hour for the given price column
+    pub fn price_per_gib(&self, column: PriceColumn) -> Option<f64> {
+        let memory_gb = self.memory_gb_value().filter(|m| *m > 0.0)?;
+        Some(self.spot_price_value(column)? / memory_gb)
+    }
+}
+
+/// Options controlling which instances and columns are displayed
+#[derive(Clone, Debug)]
+pub struct DisplayOptions<'a> {
+    /// Instance type filter (family, size, or full instance type)
+    pub instance_type: Option<&'a str>,
+    /// Spot price columns to show, none hides the prices
+    pub price_columns: &'a [PriceColumn],
+    pub os: Os,
+    pub sort_by: SortBy,
+    pub max_price_per_vcpu: Option<f64>,
+    pub max_price_per_gib: Option<f64>,
+    /// Hours per month used for the monthly cost estimate
+    pub hours_per_month: f64,
+    /// On-demand prices of the selected OS by instance type, none hides the column
+    pub on_demand_prices: Option<&'a HashMap<String, String>>,
+    /// Risk penalties (%) for each interruption rate bucket used for the effective cost
+    pub risk_penalties: &'a [f64],
+    /// Suggest newer generations that are cheaper or safer on spot
+    pub suggest_upgrades: bool,
+    /// Suggest similar instance types for instance types with the highest interruption rate
+    pub suggest_similar: bool,
+    /// Collapse the instance types into one row per group
+    pub group_by: Option<GroupBy>,
+    /// Draw a histogram of the interruption rates after the table
+    pub histogram: bool,
+    /// Margin (%) over the spot price of the suggested max price, none hides the column
+    pub max_price_margin: Option<f64>,
+    /// CSV price log to append a timestamped row per displayed instance type to
+    pub append_csv: Option<&'a Path>,
+    /// Sparklines of the recorded spot prices by instance type, none hides the column
+    pub trends: Option<&'a HashMap<String, String>>,
+    /// Specs by instance type from DescribeInstanceTypes, none hides the spec columns
+    pub specs: Option<&'a HashMap<String, InstanceSpecs>>,
+    /// Spot price volatility (%) by instance type, none hides the column
+    pub volatilities: Option<&'a HashMap<String, f64>>,
+    /// Maximum spot price volatility (%), filtering with the volatilities
+    pub max_volatility: Option<f64>,
+}
+
+impl Default for DisplayOptions<'_> {
+    fn default() -> Self {
+        Self {
+            instance_type: None,
+            price_columns: &[],
+            os: Os::default(),
+            sort_by: SortBy::default(),
+            max_price_per_vcpu: None,
+            max_price_per_gib: None,
+            hours_per_month: DEFAULT_HOURS_PER_MONTH,
+            on_demand_prices: None,
+            risk_penalties: &DEFAULT_RISK_PENALTIES,
+            suggest_upgrades: false,
+            suggest_similar: false,
+            group_by: None,
+            histogram: false,
+            max_price_margin: None,
+            append_csv: None,
+            trends: None,
+            specs: None,
+            volatilities: None,
+            max_volatility: None,
+        }
+    }
+}
+
 pub fn display_spot_data(
     region: &str,
-    instance_type: Option<&str>,
     advisor_data: &Value,
     price_data: &Value,
-    show_spot_price: bool,
+    options: &DisplayOptions,
 ) -> Result<()> {
+    let instance_type = options.instance_type;
+    let price_columns = options.price_columns;
+    let os = options.os;
+
     // Create a table to display the data
     let mut table = Table::new();
 
     // Add table headers
-    let mut headers = vec![
-        Cell::new("Instance Type"),
-        Cell::new("Region"),
-        Cell::new("Interruption Rate"),
-        Cell::new("Memory (GB)"),
-        Cell::new("Cores"),
-    ];
+    let mut headers = vec![Cell::new("Instance Type"), Cell::new("Region")];
+
+    // Render the advisor data of each OS side by side when more than one OS is selected
+    let os_keys = os.advisor_keys();
+    let os_header = |os_name: &str, header: &str| {
+        if os_keys.len() > 1 {
+            format!("{} {}", os_name, header)
+        } else {
+            header.to_string()
+        }
+    };
+
+    for os_name in os_keys {
+        headers.push(Cell::new(&os_header(os_name, "Interruption Rate")));
+    }
+
+    headers.extend([Cell::new("Memory (GB)"), Cell::new("Cores")]);
+
+    for column in price_columns {
+        headers.push(Cell::new(column.header()));
+    }
+
+    if options.on_demand_prices.is_some() {
+        headers.push(Cell::new("On-Demand Price"));
+    }
+
+    // Normalized prices are based on the spot price of the selected OS
+    let price_column = os.price_column();
+    let show_normalized_price = !price_columns.is_empty();
+    if show_normalized_price {
+        headers.push(Cell::new("$/vCPU-hr"));
+        headers.push(Cell::new("$/GiB-hr"));
+        headers.push(Cell::new("Monthly Cost"));
+        headers.push(Cell::new("Effective Cost"));
+    }
+
+    if options.max_price_margin.is_some() {
+        headers.push(Cell::new("Suggested Max Price"));
+    }
+
+    for os_name in os_keys {
+        headers.push(Cell::new(&os_header(os_name, "Savings")));
+    }
+
+    if options.trends.is_some() {
+        headers.push(Cell::new("Trend"));
+    }
+
+    if options.volatilities.is_some() {
+        headers.push(Cell::new("Volatility"));
+    }
 
-    if show_spot_price {
-        headers.push(Cell::new("Linux Spot Price"));
-        headers.push(Cell::new("Windows Spot Price"));
+    if options.specs.is_some() {
+        headers.extend(
+            InstanceSpecs::HEADERS
+                .iter()
+                .map(|header| Cell::new(header)),
+        );
     }
 
-    headers.push(Cell::new("Savings"));
+    if options.suggest_upgrades {
+        headers.push(Cell::new("Upgrade Suggestion"));
+    }
     table.add_row(Row::new(headers));
 
+    let instance_data = collect_instance_data(region, advisor_data, price_data, os);
+    let all_region_infos = region_infos(&instance_data, region, os_keys[0]);
+
+    // Filter data based on region and instance type, grouping the OS entries of each instance type
+    let mut filtered_data: BTreeMap<String, HashMap<String, InstanceInfo>> = BTreeMap::new();
+
+    for ((instance_name, os_name), region_map) in &instance_data {
+        if let Some(filter_instance) = instance_type
+            && !matches_instance_filter(instance_name, filter_instance)
+        {
+            continue;
+        }
+
+        if let Some(info) = region_map.get(region) {
+            filtered_data
+                .entry(instance_name.clone())
+                .or_default()
+                .insert(os_name.clone(), info.clone());
+        }
+    }
+
+    // Specs and prices are shared by every OS of an instance type
+    let mut rows: Vec<(String, InstanceInfo, HashMap<String, InstanceInfo>)> = Vec::new();
+    for (instance_name, os_infos) in filtered_data {
+        let Some(info) = os_keys.iter().find_map(|os_name| os_infos.get(*os_name)) else {
+            continue;
+        };
+        let mut info = info.clone();
+
+        if let Some(on_demand_price) = options
+            .on_demand_prices
+            .and_then(|prices| prices.get(&instance_name))
+        {
+            info.on_demand_price = on_demand_price.clone();
+        }
+
+        // Filter by normalized prices
+        let exceeds = |max: Option<f64>, value: Option<f64>| match (max, value) {
+            (Some(max), Some(value)) => value > max,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if exceeds(
+            options.max_price_per_vcpu,
+            info.price_per_vcpu(price_column),
+        ) || exceeds(options.max_price_per_gib, info.price_per_gib(price_column))
+        {
+            continue;
+        }
+
+        // Instance types without enough recorded prices have an unknown volatility
+        if let Some(volatilities) = options.volatilities
+            && exceeds(
+                options.max_volatility,
+                volatilities.get(&instance_name).copied(),
+            )
+        {
+            continue;
+        }
+
+        rows.push((instance_name, info, os_infos));
+    }
+
+    // Rows are sorted by instance name, and the sort is stable so ties keep that order
+    if options.sort_by != SortBy::Name {
+        rows.sort_by(|a, b| {
+            compare_values(
+                sort_value(&a.1, options, price_column),
+                sort_value(&b.1, options, price_column),
+            )
+        });
+    }
+
+    if let Some(path) = options.append_csv {
+        let recorded_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let records: Vec<HistoryRecord> = rows
+            .iter()
+            .flat_map(|(instance_name, _, os_infos)| {
+                os_keys.iter().filter_map(move |os_name| {
+                    let info = os_infos.get(*os_name)?;
+                    Some(HistoryRecord::new(
+                        recorded_at,
+                        region,
+                        instance_name,
+                        os_name,
+                        info,
+                    ))
+                })
+            })
+            .collect();
+        history::append_csv(path, &records)?;
+        log::info!("Appended {} rows to {}", records.len(), path.display());
+    }
+
+    let histogram = options.histogram.then(|| {
+        let mut counts = [0; INTERRUPTION_RATES.len()];
+        for bucket in rows
+            .iter()
+            .filter_map(|(_, info, _)| info.interruption_bucket())
+        {
+            counts[bucket] += 1;
+        }
+        histogram_lines(&counts)
+    });
+
+    if let Some(group_by) = options.group_by {
+        let infos: Vec<(&str, &InstanceInfo)> = rows
+            .iter()
+            .map(|(instance_name, info, _)| (instance_name.as_str(), info))
+            .collect();
+        display_groups(region, &infos, group_by, price_column)?;
+        print_histogram(histogram.as_deref());
+        return Ok(());
+    }
+
+    // Instance types with the highest interruption rate get similar instance types suggested
+    let risky_instances: Vec<(String, InstanceInfo)> = if options.suggest_similar {
+        rows.iter()
+            .filter(|(_, info, _)| info.interruption_bucket() == Some(INTERRUPTION_RATES.len() - 1))
+            .map(|(instance_name, info, _)| (instance_name.clone(), info.clone()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Add rows to the table
+    for (instance_name, info, os_infos) in rows {
+        let mut row_cells = vec![Cell::new(&instance_name), Cell::new(region)];
+
+        for os_name in os_keys {
+            let interruption_rate = os_infos
+                .get(*os_name)
+                .map_or("N/A", |i| i.interruption_rate.as_str());
+            row_cells.push(Cell::new(interruption_rate));
+        }
+
+        row_cells.extend([Cell::new(&info.memory_gb), Cell::new(&info.cores)]);
+
+        for column in price_columns {
+            row_cells.push(Cell::new(info.spot_price(*column)));
+        }
+
+        if options.on_demand_prices.is_some() {
+            row_cells.push(Cell::new(&info.on_demand_price));
+        }
+
+        if show_normalized_price {
+            row_cells.push(Cell::new(&format_price(info.price_per_vcpu(price_column))));
+            row_cells.push(Cell::new(&format_price(info.price_per_gib(price_column))));
+            row_cells.push(Cell::new(&format_cost(
+                info.monthly_cost(price_column, options.hours_per_month),
+            )));
+            row_cells.push(Cell::new(&format_price(
+                info.effective_cost(price_column, options.risk_penalties),
+            )));
+        }
+
+        if let Some(margin) = options.max_price_margin {
+            row_cells.push(Cell::new(&format_price(
+                info.suggested_max_price(price_column, margin),
+            )));
+        }
+
+        for os_name in os_keys {
+            let savings = os_infos.get(*os_name).map_or("N/A", |i| i.savings.as_str());
+            row_cells.push(Cell::new(savings));
+        }
+
+        if let Some(trends) = options.trends {
+            row_cells.push(Cell::new(
+                trends.get(&instance_name).map_or("-", String::as_str),
+            ));
+        }
+
+        if let Some(volatilities) = options.volatilities {
+            row_cells.push(Cell::new(&volatilities.get(&instance_name).map_or_else(
+                || "-".to_string(),
+                |volatility| format!("{:.1}%", volatility),
+            )));
+        }
+
+        if let Some(specs) = options.specs {
+            let cells = specs
+                .get(&instance_name)
+                .map_or_else(|| ["-"; 5].map(String::from), InstanceSpecs::cells);
+            row_cells.extend(cells.iter().map(|cell| Cell::new(cell)));
+        }
+
+        if options.suggest_upgrades {
+            row_cells.push(Cell::new(&upgrade_suggestion(
+                &instance_name,
+                &info,
+                &all_region_infos,
+                price_column,
+            )));
+        }
+
+        table.add_row(Row::new(row_cells));
+    }
+
+    // Print the number of instances found
+    log::info!(
+        "Found {} spot instances for region: {}, filtering by instance type: {}, OS: {:?}",
+        table.len() - 1,
+        region,
+        instance_type.unwrap_or("all"),
+        os
+    );
+
+    // Print the table
+    table.printstd();
+    print_histogram(histogram.as_deref());
+
+    for (instance_name, info) in &risky_instances {
+        let similar = recommend::similar_instances(
+            &instance_data,
+            region,
+            os,
+            instance_name,
+            info,
+            options.risk_penalties,
+        );
+        if similar.is_empty() {
+            continue;
+        }
+
+        println!(
+            "\n{} has an interruption rate of {}. Similar instance types with lower interruption rates:",
+            instance_name, info.interruption_rate
+        );
+        display_recommendations(region, &similar, price_column)?;
+    }
+
+    Ok(())
+}
+
+/// Maximum width of a histogram bar in characters
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Returns the lines of an ASCII bar chart of the number of instance types in each
+/// interruption rate bucket, scaled so the largest bucket is `HISTOGRAM_WIDTH` wide
+fn histogram_lines(counts: &[usize]) -> Vec<String> {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let label_width = INTERRUPTION_RATES
+        .iter()
+        .map(|r| r.len())
+        .max()
+        .unwrap_or(0);
+
+    INTERRUPTION_RATES
+        .iter()
+        .zip(counts)
+        .map(|(rate, count)| {
+            let width = if max == 0 {
+                0
+            } else {
+                // Round up so non-empty buckets always get a bar
+                (count * HISTOGRAM_WIDTH).div_ceil(max)
+            };
+            format!("{:<label_width$} | {} {}", rate, "#".repeat(width), count)
+        })
+        .collect()
+}
+
+/// Prints the histogram lines, if any
+fn print_histogram(lines: Option<&[String]>) {
+    let Some(lines) = lines else {
+        return;
+    };
+
+    println!("\nInterruption Rate Histogram");
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+/// Returns the value rows are sorted by, where lower values come first
+fn sort_value(
+    info: &InstanceInfo,
+    options: &DisplayOptions,
+    price_column: PriceColumn,
+) -> Option<f64> {
+    match options.sort_by {
+        SortBy::Name => None,
+        SortBy::Price => info.spot_price_value(price_column),
+        SortBy::PricePerVcpu => info.price_per_vcpu(price_column),
+        SortBy::PricePerGib => info.price_per_gib(price_column),
+        SortBy::EffectiveCost => info.effective_cost(price_column, options.risk_penalties),
+        SortBy::Interruption => info.interruption_bucket().map(|b| b as f64),
+        // Higher savings come first
+        SortBy::Savings => info.savings_value().map(|s| -s),
+    }
+}
+
+/// Compares optional values, ordering missing values last
+fn compare_values(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Formats a cost estimate, or "N/A" when it can't be computed
+fn format_cost(cost: Option<f64>) -> String {
+    cost.map(|c| format!("{:.2}", c))
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Displays one row per group of instance types with their size count, price range, best
+/// interruption rate and average savings
+fn display_groups(
+    region: &str,
+    infos: &[(&str, &InstanceInfo)],
+    group_by: GroupBy,
+    price_column: PriceColumn,
+) -> Result<()> {
+    let mut groups: BTreeMap<&str, Vec<&InstanceInfo>> = BTreeMap::new();
+    for (instance_name, info) in infos {
+        let key = match group_by {
+            GroupBy::Family => instance::family(instance_name),
+        };
+        groups.entry(key).or_default().push(info);
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Family"),
+        Cell::new("Sizes"),
+        Cell::new(&format!("{} Range", price_column.header())),
+        Cell::new("Best Interruption Rate"),
+        Cell::new("Average Savings"),
+    ]));
+
+    for (group, infos) in &groups {
+        let prices: Vec<f64> = infos
+            .iter()
+            .filter_map(|i| i.spot_price_value(price_column))
+            .collect();
+        let best_interruption = infos
+            .iter()
+            .filter_map(|i| i.interruption_bucket())
+            .min()
+            .map_or("N/A", |bucket| INTERRUPTION_RATES[bucket]);
+        let savings: Vec<f64> = infos.iter().filter_map(|i| i.savings_value()).collect();
+        let average_savings = if savings.is_empty() {
+            "N/A".to_string()
+        } else {
+            format!("{:.1}%", savings.iter().sum::<f64>() / savings.len() as f64)
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(group),
+            Cell::new(&infos.len().to_string()),
+            Cell::new(&format_range(&prices)),
+            Cell::new(best_interruption),
+            Cell::new(&average_savings),
+        ]));
+    }
+
+    log::info!("Found {} groups for region: {}", groups.len(), region);
+
+    table.printstd();
+
+    Ok(())
+}
+
+/// Returns whether the instance type matches the filter by family, size, or part of its name
+pub fn matches_instance_filter(instance_name: &str, filter_instance: &str) -> bool {
+    // Instance name format: "family.size" (e.g., "m5.large")
+    let parts: Vec<&str> = instance_name.split('.').collect();
+    let family = parts.first().unwrap_or(&"");
+    let size = parts.get(1).unwrap_or(&"");
+
+    *family == filter_instance
+        || *size == filter_instance
+        || instance_name.contains(filter_instance)
+}
+
+/// Formats a computed price, or "N/A" when it can't be computed
+fn format_price(price: Option<f64>) -> String {
+    price
+        .map(|p| format!("{:.4}", p))
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Returns the info of each instance type of an OS available in a region
+fn region_infos<'a>(
+    instance_data: &'a InstanceData,
+    region: &str,
+    os_name: &str,
+) -> HashMap<&'a str, &'a InstanceInfo> {
+    instance_data
+        .iter()
+        .filter(|((_, instance_os), _)| instance_os == os_name)
+        .filter_map(|((name, _), region_map)| Some((name.as_str(), region_map.get(region)?)))
+        .collect()
+}
+
+/// Suggests the newer generation of an instance type that is cheaper or safer on spot,
+/// without being worse on the other
+fn upgrade_suggestion(
+    instance_type: &str,
+    info: &InstanceInfo,
+    region_infos: &HashMap<&str, &InstanceInfo>,
+    price_column: PriceColumn,
+) -> String {
+    let (Some(price), Some(bucket)) = (
+        info.spot_price_value(price_column),
+        info.interruption_bucket(),
+    ) else {
+        return "-".to_string();
+    };
+
+    instance::newer_generations(instance_type, region_infos.keys().copied())
+        .into_iter()
+        .filter_map(|candidate| {
+            let candidate_info = region_infos.get(candidate)?;
+            let candidate_price = candidate_info.spot_price_value(price_column)?;
+            let candidate_bucket = candidate_info.interruption_bucket()?;
+            let better = candidate_price <= price
+                && candidate_bucket <= bucket
+                && (candidate_price < price || candidate_bucket < bucket);
+            better.then_some((candidate, candidate_price, candidate_info))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, candidate_price, candidate_info)| {
+            format!(
+                "{} ({:+.0}%, {})",
+                candidate,
+                (candidate_price - price) / price * 100.0,
+                candidate_info.interruption_rate
+            )
+        })
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Extracts the on-demand price of each instance type from the on-demand price data of a region
+pub fn collect_on_demand_prices(on_demand_data: &Value) -> HashMap<String, String> {
+    let mut prices = HashMap::new();
+
+    let Some(regions) = on_demand_data.get("regions").and_then(Value::as_object) else {
+        log::debug!("No regions found in on-demand price data");
+        return prices;
+    };
+
+    for products in regions.values().filter_map(Value::as_object) {
+        for product in products.values() {
+            let instance_type = product.get("Instance Type").and_then(Value::as_str);
+            let price = product
+                .get("price")
+                .and_then(Value::as_str)
+                .and_then(|p| p.parse::<f64>().ok());
+
+            if let (Some(instance_type), Some(price)) = (instance_type, price) {
+                prices
+                    .entry(instance_type.to_string())
+                    .or_insert_with(|| price.to_string());
+            }
+        }
+    }
+
+    log::debug!("Found on-demand prices for {} instance types", prices.len());
+
+    prices
+}
+
+/// Mapping of (instance type, OS) to the instance info of each region
+pub type InstanceData = HashMap<(String, String), HashMap<String, InstanceInfo>>;
+
+/// Combines the spot advisor data and the spot price data of the given OS into instance info
+pub fn collect_instance_data(
+    region: &str,
+    advisor_data: &Value,
+    price_data: &Value,
+    os: Os,
+) -> InstanceData {
+    telemetry::phase("parse", || {
+        parse_instance_data(region, advisor_data, price_data, os)
+    })
+}
+
+fn parse_instance_data(
+    region: &str,
+    advisor_data: &Value,
+    price_data: &Value,
+    os: Os,
+) -> InstanceData {
     log::info!("Processing spot instance data...");
 
     // Extract instance specifications from the instance_types data
@@ -105,8 +825,8 @@ pub fn display_spot_data(
         );
     }
 
-    // Create a mapping of instance types to their data
-    let mut instance_data: HashMap<String, HashMap<String, InstanceInfo>> = HashMap::new();
+    // Create a mapping of (instance type, OS) to their data
+    let mut instance_data: InstanceData = HashMap::new();
 
     // Debug counter for instance types
     let mut instance_count = 0;
@@ -124,56 +844,63 @@ pub fn display_spot_data(
             );
         }
 
-        // Check if Linux exists in the region data
-        if let Some(linux_data) = region_map.get("Linux") {
-            if let Some(linux_instances) = linux_data.as_object() {
-                log::debug!(
-                    "Found Linux instances for region {}: {}",
-                    region_name,
-                    linux_instances.len()
-                );
+        // Check if each selected OS exists in the region data
+        for os_name in os.advisor_keys() {
+            let Some(os_instances) = region_map.get(*os_name).and_then(Value::as_object) else {
+                continue;
+            };
 
-                // Process Linux instances
-                for (instance_name, instance_info) in linux_instances {
-                    instance_count += 1;
-
-                    if let Some(info) = instance_info.as_object() {
-                        let rate_info = info.get("r").and_then(Value::as_u64).unwrap_or(0);
-                        let savings = info.get("s").and_then(Value::as_u64).unwrap_or(0);
-
-                        // Map rate_info to a descriptive string
-                        let interruption_rate = match rate_info {
-                            0 => "< 5%",
-                            1 => "5-10%",
-                            2 => "10-15%",
-                            3 => "15-20%",
-                            _ => "> 20%",
-                        };
-
-                        // Create or get the region map for this instance type
-                        let region_map = instance_data
-                            .entry(instance_name.clone())
-                            .or_insert_with(HashMap::new);
-
-                        // Get memory and cores information from instance_specs
-                        let (memory_gb, cores) = instance_specs
-                            .get(instance_name)
-                            .map(|(ram, cores)| (ram.clone(), cores.clone()))
-                            .unwrap_or_else(|| ("N/A".to_string(), "N/A".to_string()));
-
-                        // Insert or update the instance info for this region
-                        region_map.insert(
-                            region_name.clone(),
-                            InstanceInfo {
-                                interruption_rate: interruption_rate.to_string(),
-                                savings: format!("{}%", savings),
-                                linux_spot_price: "N/A".to_string(),
-                                windows_spot_price: "N/A".to_string(),
-                                memory_gb: memory_gb.clone(),
-                                cores: cores.clone(),
-                            },
-                        );
-                    }
+            log::debug!(
+                "Found {} instances for region {}: {}",
+                os_name,
+                region_name,
+                os_instances.len()
+            );
+
+            // Process instances of this OS
+            for (instance_name, instance_info) in os_instances {
+                instance_count += 1;
+
+                if let Some(info) = instance_info.as_object() {
+                    // Providers without interruption signals or savings leave them out
+                    let interruption_rate =
+                        info.get("r")
+                            .and_then(Value::as_u64)
+                            .map_or("N/A", |rate_info| {
+                                INTERRUPTION_RATES
+                                    [(rate_info as usize).min(INTERRUPTION_RATES.len() - 1)]
+                            });
+                    let savings = info
+                        .get("s")
+                        .and_then(Value::as_u64)
+                        .map_or_else(|| "N/A".to_string(), |savings| format!("{}%", savings));
+
+                    // Create or get the region map for this instance type and OS
+                    let region_map = instance_data
+                        .entry((instance_name.clone(), os_name.to_string()))
+                        .or_insert_with(HashMap::new);
+
+                    // Get memory and cores information from instance_specs
+                    let (memory_gb, cores) = instance_specs
+                        .get(instance_name)
+                        .map(|(ram, cores)| (ram.clone(), cores.clone()))
+                        .unwrap_or_else(|| ("N/A".to_string(), "N/A".to_string()));
+
+                    // Insert or update the instance info for this region
+                    region_map.insert(
+                        region_name.clone(),
+                        InstanceInfo {
+                            interruption_rate: interruption_rate.to_string(),
+                            savings,
+                            linux_spot_price: "N/A".to_string(),
+                            windows_spot_price: "N/A".to_string(),
+                            rhel_spot_price: "N/A".to_string(),
+                            suse_spot_price: "N/A".to_string(),
+                            on_demand_price: "N/A".to_string(),
+                            memory_gb: memory_gb.clone(),
+                            cores: cores.clone(),
+                        },
+                    );
                 }
             }
         }
@@ -216,8 +943,13 @@ pub fn display_spot_data(
                 let size_name = size["size"].as_str().unwrap();
                 let full_name = format!("{}.{}", instance_type_name, size_name);
 
-                // Extract simple instance name (e.g., "m5.large" from "generalCurrentGen.m5.large")
-                let simple_name = if let Some(last_dot_index) = full_name.rfind('.') {
+                // Extract simple instance name (e.g., "m5.large" from "generalCurrentGen.m5.large"),
+                // unless named explicitly like the dotless instance types of other providers
+                let simple_name = if let Some(name) =
+                    size.get("instanceType").and_then(Value::as_str)
+                {
+                    name.to_string()
+                } else if let Some(last_dot_index) = full_name.rfind('.') {
                     if let Some(second_last_dot_index) = full_name[..last_dot_index].rfind('.') {
                         full_name[second_last_dot_index + 1..].to_string()
                     } else {
@@ -240,62 +972,66 @@ pub fn display_spot_data(
                 let values = size["valueColumns"].as_array().unwrap();
                 let mut linux_spot_price = "N/A".to_string();
                 let mut windows_spot_price = "N/A".to_string();
+                let mut rhel_spot_price = "N/A".to_string();
+                let mut suse_spot_price = "N/A".to_string();
 
                 for value in values {
                     let name = value["name"].as_str().unwrap();
 
-                    // Get Linux spot price
-                    if name == "linux" {
-                        // Try to get spot price if available
-                        if let Some(spot_values) = value.get("prices") {
-                            if let Some(spot_prices) = spot_values.get("USD") {
-                                linux_spot_price =
-                                    spot_prices.as_str().unwrap_or("N/A").to_string();
-                            }
-                        }
-                    }
+                    // Try to get spot price if available
+                    let Some(spot_prices) = value.get("prices").and_then(|p| p.get("USD")) else {
+                        continue;
+                    };
+                    let spot_price = spot_prices.as_str().unwrap_or("N/A").to_string();
 
-                    // Get Windows spot price
-                    if name == "mswin" {
-                        // Try to get spot price if available
-                        if let Some(spot_values) = value.get("prices") {
-                            if let Some(spot_prices) = spot_values.get("USD") {
-                                windows_spot_price =
-                                    spot_prices.as_str().unwrap_or("N/A").to_string();
-                            }
-                        }
+                    // Get the spot price of each platform
+                    match name {
+                        "linux" => linux_spot_price = spot_price,
+                        "mswin" => windows_spot_price = spot_price,
+                        "rhel" => rhel_spot_price = spot_price,
+                        "suse" => suse_spot_price = spot_price,
+                        _ => {}
                     }
                 }
 
-                // Update the instance info with price data using simple name
-                if let Some(region_map) = instance_data.get_mut(&simple_name) {
-                    if let Some(info) = region_map.get_mut(region_name) {
-                        info.linux_spot_price = linux_spot_price.clone();
-                        info.windows_spot_price = windows_spot_price.clone();
-
-                        if region_name == region && price_instance_count <= 5 {
-                            log::debug!(
-                                "      Updated existing entry: {} with Linux price: {}",
-                                simple_name,
-                                linux_spot_price
-                            );
+                // Update the instance info of each selected OS with price data using simple name
+                for os_name in os.advisor_keys() {
+                    let key = (simple_name.clone(), os_name.to_string());
+                    if let Some(region_map) = instance_data.get_mut(&key) {
+                        if let Some(info) = region_map.get_mut(region_name) {
+                            info.linux_spot_price = linux_spot_price.clone();
+                            info.windows_spot_price = windows_spot_price.clone();
+                            info.rhel_spot_price = rhel_spot_price.clone();
+                            info.suse_spot_price = suse_spot_price.clone();
+
+                            if region_name == region && price_instance_count <= 5 {
+                                log::debug!(
+                                    "      Updated existing entry: {} ({}) with Linux price: {}",
+                                    simple_name,
+                                    os_name,
+                                    linux_spot_price
+                                );
+                            }
                         }
+                    } else {
+                        // Instance not found in advisor data, create a new entry
+                        let mut region_map = HashMap::new();
+                        region_map.insert(
+                            region_name.to_string(),
+                            InstanceInfo {
+                                interruption_rate: "N/A".to_string(),
+                                savings: "N/A".to_string(),
+                                linux_spot_price: linux_spot_price.clone(),
+                                windows_spot_price: windows_spot_price.clone(),
+                                rhel_spot_price: rhel_spot_price.clone(),
+                                suse_spot_price: suse_spot_price.clone(),
+                                on_demand_price: "N/A".to_string(),
+                                memory_gb: "N/A".to_string(),
+                                cores: "N/A".to_string(),
+                            },
+                        );
+                        instance_data.insert(key, region_map);
                     }
-                } else {
-                    // Instance not found in advisor data, create a new entry
-                    let mut region_map = HashMap::new();
-                    region_map.insert(
-                        region_name.to_string(),
-                        InstanceInfo {
-                            interruption_rate: "N/A".to_string(),
-                            savings: "N/A".to_string(),
-                            linux_spot_price,
-                            windows_spot_price,
-                            memory_gb: "N/A".to_string(),
-                            cores: "N/A".to_string(),
-                        },
-                    );
-                    instance_data.insert(simple_name, region_map);
                 }
             }
         }
@@ -306,249 +1042,3163 @@ pub fn display_spot_data(
         price_instance_count
     );
 
-    // Filter data based on region and instance type
-    let mut filtered_data: Vec<(String, InstanceInfo)> = Vec::new();
-
-    for (instance_name, region_map) in &instance_data {
-        if let Some(filter_instance) = instance_type {
-            // Check if the filter matches family or size
-            // Instance name format: "family.size" (e.g., "m5.large")
-            let parts: Vec<&str> = instance_name.split('.').collect();
-            let family = parts.get(0).unwrap_or(&"");
-            let size = parts.get(1).unwrap_or(&"");
-
-            // Check if filter matches the family, size, or the whole instance name
-            let matches = family == &filter_instance
-                || size == &filter_instance
-                || instance_name.contains(filter_instance);
+    instance_data
+}
 
-            if !matches {
-                continue;
-            }
-        }
+pub fn display_families(region: &str, advisor_data: &Value, price_data: &Value) -> Result<()> {
+    let instance_data = collect_instance_data(region, advisor_data, price_data, Os::Linux);
 
+    // Group the instance types available in the region by family
+    let mut families: BTreeMap<&str, Vec<&InstanceInfo>> = BTreeMap::new();
+    for ((instance_name, _), region_map) in &instance_data {
         if let Some(info) = region_map.get(region) {
-            filtered_data.push((instance_name.clone(), info.clone()));
+            families
+                .entry(instance::family(instance_name))
+                .or_default()
+                .push(info);
         }
     }
 
-    // Sort by instance name
-    filtered_data.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Family"),
+        Cell::new("Generation"),
+        Cell::new("Instance Types"),
+        Cell::new("Cores"),
+        Cell::new("Memory (GB)"),
+    ]));
 
-    // Add rows to the table
-    for (instance_name, info) in filtered_data {
-        let mut row_cells = vec![
-            Cell::new(&instance_name),
-            Cell::new(region),
-            Cell::new(&info.interruption_rate),
+    for (family, infos) in &families {
+        let cores: Vec<f64> = infos.iter().filter_map(|i| i.cores_value()).collect();
+        let memory: Vec<f64> = infos.iter().filter_map(|i| i.memory_gb_value()).collect();
+        let generation = instance::generation(family)
+            .map(|g| g.to_string())
+            .unwrap_or_else(|| "N/A".to_string());
+
+        table.add_row(Row::new(vec![
+            Cell::new(family),
+            Cell::new(&generation),
+            Cell::new(&infos.len().to_string()),
+            Cell::new(&format_range(&cores)),
+            Cell::new(&format_range(&memory)),
+        ]));
+    }
+
+    log::info!(
+        "Found {} instance families for region: {}",
+        table.len() - 1,
+        region
+    );
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn display_stats(
+    region: &str,
+    instance_type: Option<&str>,
+    advisor_data: &Value,
+    price_data: &Value,
+    os: Os,
+) -> Result<()> {
+    let instance_data = collect_instance_data(region, advisor_data, price_data, os);
+    let price_column = os.price_column();
+
+    let infos: Vec<&InstanceInfo> = region_infos(&instance_data, region, os.advisor_keys()[0])
+        .into_iter()
+        .filter(|(name, _)| {
+            instance_type.is_none_or(|filter| matches_instance_filter(name, filter))
+        })
+        .map(|(_, info)| info)
+        .collect();
+
+    if infos.is_empty() {
+        return Err(anyhow!(
+            "No instance types matching '{}' found in region '{}'",
+            instance_type.unwrap_or("all"),
+            region
+        ));
+    }
+
+    let mut prices: Vec<f64> = infos
+        .iter()
+        .filter_map(|i| i.spot_price_value(price_column))
+        .collect();
+    prices.sort_by(f64::total_cmp);
+    let savings: Vec<f64> = infos.iter().filter_map(|i| i.savings_value()).collect();
+    let average_savings =
+        (!savings.is_empty()).then(|| savings.iter().sum::<f64>() / savings.len() as f64);
+
+    // Summary aggregates of the matching instance types
+    let mut summary = Table::new();
+    summary.add_row(Row::new(vec![Cell::new("Region"), Cell::new(region)]));
+    summary.add_row(Row::new(vec![
+        Cell::new("Instance Types"),
+        Cell::new(&infos.len().to_string()),
+    ]));
+    summary.add_row(Row::new(vec![
+        Cell::new(&format!("Min {}", price_column.header())),
+        Cell::new(&format_price(prices.first().copied())),
+    ]));
+    summary.add_row(Row::new(vec![
+        Cell::new(&format!("Median {}", price_column.header())),
+        Cell::new(&format_price(median(&prices))),
+    ]));
+    summary.add_row(Row::new(vec![
+        Cell::new(&format!("Max {}", price_column.header())),
+        Cell::new(&format_price(prices.last().copied())),
+    ]));
+    summary.add_row(Row::new(vec![
+        Cell::new("Average Savings"),
+        Cell::new(
+            &average_savings
+                .map(|s| format!("{:.1}%", s))
+                .unwrap_or_else(|| "N/A".to_string()),
+        ),
+    ]));
+
+    summary.printstd();
+
+    // Distribution of the interruption rates
+    let mut distribution = Table::new();
+    distribution.add_row(Row::new(vec![
+        Cell::new("Interruption Rate"),
+        Cell::new("Instance Types"),
+        Cell::new("Share"),
+    ]));
+
+    for (bucket, rate) in INTERRUPTION_RATES.iter().enumerate() {
+        let count = infos
+            .iter()
+            .filter(|i| i.interruption_bucket() == Some(bucket))
+            .count();
+        distribution.add_row(Row::new(vec![
+            Cell::new(rate),
+            Cell::new(&count.to_string()),
+            Cell::new(&format!(
+                "{:.1}%",
+                count as f64 / infos.len() as f64 * 100.0
+            )),
+        ]));
+    }
+
+    log::info!(
+        "Summarized {} instance types for region: {}",
+        infos.len(),
+        region
+    );
+
+    distribution.printstd();
+
+    Ok(())
+}
+
+/// Returns the median of sorted values
+pub fn median(sorted: &[f64]) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+pub fn display_cache_info(cache: &Cache) -> Result<()> {
+    println!("Cache directory: {}", cache.dir().display());
+    println!("TTL: {}", format_duration(cache.ttl()));
+
+    let entries = cache.entries();
+    if entries.is_empty() {
+        println!("The cache is empty");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("File"),
+        Cell::new("Size"),
+        Cell::new("Age"),
+        Cell::new("Status"),
+    ]));
+
+    for entry in &entries {
+        table.add_row(Row::new(vec![
+            Cell::new(&entry.name),
+            Cell::new(&format_size(entry.size)),
+            Cell::new(&format_duration(entry.age)),
+            Cell::new(if entry.fresh { "Fresh" } else { "Stale" }),
+        ]));
+    }
+
+    table.printstd();
+
+    Ok(())
+}
+
+/// Formats a size in bytes with a binary unit (e.g., "12.3 MiB")
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Formats a duration with its two largest units, like "2d 3h" or "45m 10s"
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes, seconds) = (
+        secs / 86400,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+pub fn display_history(
+    region: &str,
+    instance_type: &str,
+    summary: &HistorySummary,
+    price_column: PriceColumn,
+) -> Result<()> {
+    let mut card = Table::new();
+    let mut add_row = |name: &str, value: &str| {
+        card.add_row(Row::new(vec![Cell::new(name), Cell::new(value)]));
+    };
+    add_row("Instance Type", instance_type);
+    add_row("Region", region);
+    add_row(
+        "Period",
+        &format!(
+            "{} - {}",
+            format_timestamp(summary.first_recorded_at),
+            format_timestamp(summary.last_recorded_at)
+        ),
+    );
+    add_row("Records", &summary.records.to_string());
+    add_row(
+        &format!("Min {}", price_column.header()),
+        &format_price(summary.min_price),
+    );
+    add_row(
+        &format!("Max {}", price_column.header()),
+        &format_price(summary.max_price),
+    );
+    add_row(
+        &format!("Avg {}", price_column.header()),
+        &format_price(summary.avg_price),
+    );
+    add_row(
+        "Trend",
+        &match summary.price_change() {
+            Some(change) => format!(
+                "{} ({} -> {}, {:+.1}%)",
+                summary.trend(),
+                format_price(summary.first_price),
+                format_price(summary.last_price),
+                change
+            ),
+            None => summary.trend().to_string(),
+        },
+    );
+    add_row(
+        "Interruption Rate Changes",
+        &summary.interruption_changes.len().to_string(),
+    );
+
+    card.printstd();
+
+    if !summary.interruption_changes.is_empty() {
+        let mut changes = Table::new();
+        changes.add_row(Row::new(vec![
+            Cell::new("Recorded At (UTC)"),
+            Cell::new("Interruption Rate"),
+        ]));
+        for (recorded_at, old, new) in &summary.interruption_changes {
+            changes.add_row(Row::new(vec![
+                Cell::new(&format_timestamp(*recorded_at)),
+                Cell::new(&format!("{} -> {}", old, new)),
+            ]));
+        }
+        changes.printstd();
+    }
+
+    Ok(())
+}
+
+/// Displays the spot prices of an instance type by day of the week and hour of the day (UTC),
+/// naming the cheapest and calmest ones and the best hours of the week
+pub fn display_when(
+    region: &str,
+    instance_type: &str,
+    source: &str,
+    report: &WhenReport,
+    price_column: PriceColumn,
+) -> Result<()> {
+    let name = |names: &[String], index: Option<usize>| match index {
+        Some(index) => names[index].clone(),
+        None => "N/A".to_string(),
+    };
+    let weekday_names: Vec<String> = when::WEEKDAYS.iter().map(|day| day.to_string()).collect();
+    let hour_names: Vec<String> = (0..24).map(|hour| format!("{:02}:00", hour)).collect();
+
+    let mut card = Table::new();
+    let mut add_row = |name: &str, value: &str| {
+        card.add_row(Row::new(vec![Cell::new(name), Cell::new(value)]));
+    };
+    add_row("Instance Type", instance_type);
+    add_row("Region", region);
+    add_row("Source", source);
+    add_row(
+        "Period",
+        &format!(
+            "{} - {}",
+            format_timestamp(report.first_at),
+            format_timestamp(report.last_at)
+        ),
+    );
+    add_row("Samples", &report.samples.to_string());
+    add_row(
+        &format!("Median {}", price_column.header()),
+        &format_price(Some(report.median_price)),
+    );
+    add_row(
+        "Spike",
+        &format!(
+            "Above {} (+{}% over the median)",
+            format_price(Some(report.spike_price())),
+            when::SPIKE_THRESHOLD
+        ),
+    );
+    add_row(
+        "Cheapest Day",
+        &name(&weekday_names, report.cheapest_weekday()),
+    );
+    add_row(
+        "Fewest Spikes Day",
+        &name(&weekday_names, report.calmest_weekday()),
+    );
+    add_row(
+        "Cheapest Hour (UTC)",
+        &name(&hour_names, report.cheapest_hour()),
+    );
+    add_row(
+        "Fewest Spikes Hour (UTC)",
+        &name(&hour_names, report.calmest_hour()),
+    );
+    card.printstd();
+
+    let slot_table = |header: &str, names: &[String], slots: &[Option<when::SlotStats>]| {
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![
+            Cell::new(header),
+            Cell::new("Samples"),
+            Cell::new(&format!("Avg {}", price_column.header())),
+            Cell::new(&format!("Min {}", price_column.header())),
+            Cell::new("Spikes"),
+        ]));
+        for (name, stats) in names.iter().zip(slots) {
+            let Some(stats) = stats else {
+                continue;
+            };
+            table.add_row(Row::new(vec![
+                Cell::new(name),
+                Cell::new(&stats.samples.to_string()),
+                Cell::new(&format_price(Some(stats.avg_price))),
+                Cell::new(&format_price(Some(stats.min_price))),
+                Cell::new(&format!("{:.1}%", stats.spike_rate())),
+            ]));
+        }
+        table
+    };
+    slot_table("Day", &weekday_names, &report.weekdays).printstd();
+    slot_table("Hour (UTC)", &hour_names, &report.hours).printstd();
+
+    println!("\nBest Hours of the Week (UTC)");
+    let mut best = Table::new();
+    best.add_row(Row::new(vec![
+        Cell::new("Day"),
+        Cell::new("Hour"),
+        Cell::new(&format!("Avg {}", price_column.header())),
+        Cell::new("Spikes"),
+    ]));
+    for (weekday, hour, stats) in &report.best_slots {
+        best.add_row(Row::new(vec![
+            Cell::new(&weekday_names[*weekday]),
+            Cell::new(&hour_names[*hour]),
+            Cell::new(&format_price(Some(stats.avg_price))),
+            Cell::new(&format!("{:.1}%", stats.spike_rate())),
+        ]));
+    }
+    best.printstd();
+
+    Ok(())
+}
+
+/// Formats a Unix time (seconds) as a UTC date and time (e.g., "2025-01-31 12:00")
+pub fn format_timestamp(secs: i64) -> String {
+    let (year, month, day, hour, minute, _) = civil_datetime(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year, month, day, hour, minute
+    )
+}
+
+/// Formats a Unix time (seconds) as an RFC 3339 UTC timestamp (e.g., "2025-01-31T12:00:00Z")
+pub fn format_rfc3339(secs: i64) -> String {
+    let (year, month, day, hour, minute, second) = civil_datetime(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// Splits a Unix time (seconds) into the UTC year, month, day, hour, minute and second
+pub fn civil_datetime(secs: i64) -> (i64, i64, i64, i64, i64, i64) {
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+    )
+}
+
+/// Formats the range of values as "min-max", or a single value when they're all the same
+fn format_range(values: &[f64]) -> String {
+    if values.is_empty() {
+        return "N/A".to_string();
+    }
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if min == max {
+        min.to_string()
+    } else {
+        format!("{}-{}", min, max)
+    }
+}
+
+pub fn display_instance_detail(
+    region: &str,
+    instance_type: &str,
+    advisor_data: &Value,
+    price_data: &Value,
+) -> Result<()> {
+    let instance_data = collect_instance_data(region, advisor_data, price_data, Os::All);
+    let os_keys = Os::All.advisor_keys();
+
+    // Collect the instance info of each OS, keyed by region
+    let mut regions: BTreeMap<&str, HashMap<&str, &InstanceInfo>> = BTreeMap::new();
+    for os_name in os_keys {
+        let key = (instance_type.to_string(), os_name.to_string());
+        if let Some(region_map) = instance_data.get(&key) {
+            for (region_name, info) in region_map {
+                regions
+                    .entry(region_name.as_str())
+                    .or_default()
+                    .insert(*os_name, info);
+            }
+        }
+    }
+
+    let not_found = || {
+        anyhow!(
+            "Instance type '{}' not found in region '{}'",
+            instance_type,
+            region
+        )
+    };
+    let os_infos = regions.get(region).ok_or_else(not_found)?;
+    let info = os_keys
+        .iter()
+        .find_map(|os_name| os_infos.get(os_name))
+        .ok_or_else(not_found)?;
+
+    // Detail card of the instance type in the region
+    let mut card = Table::new();
+    card.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new(instance_type),
+    ]));
+    card.add_row(Row::new(vec![Cell::new("Region"), Cell::new(region)]));
+    card.add_row(Row::new(vec![
+        Cell::new("Memory (GB)"),
+        Cell::new(&info.memory_gb),
+    ]));
+    card.add_row(Row::new(vec![Cell::new("Cores"), Cell::new(&info.cores)]));
+
+    for os_name in os_keys {
+        let os_info = os_infos.get(os_name);
+        card.add_row(Row::new(vec![
+            Cell::new(&format!("{} Interruption Rate", os_name)),
+            Cell::new(os_info.map_or("N/A", |i| i.interruption_rate.as_str())),
+        ]));
+        card.add_row(Row::new(vec![
+            Cell::new(&format!("{} Savings", os_name)),
+            Cell::new(os_info.map_or("N/A", |i| i.savings.as_str())),
+        ]));
+    }
+
+    for column in PriceColumn::value_variants() {
+        card.add_row(Row::new(vec![
+            Cell::new(column.header()),
+            Cell::new(info.spot_price(*column)),
+        ]));
+    }
+
+    card.printstd();
+
+    // Summary of the instance type across every region it's available in
+    let mut summary = Table::new();
+    summary.add_row(Row::new(vec![
+        Cell::new("Region"),
+        Cell::new("Linux Interruption Rate"),
+        Cell::new("Linux Savings"),
+        Cell::new("Linux Spot Price"),
+        Cell::new("Windows Spot Price"),
+    ]));
+
+    for (region_name, os_infos) in &regions {
+        let linux_info = os_infos.get("Linux");
+        let Some(info) = os_keys.iter().find_map(|os_name| os_infos.get(os_name)) else {
+            continue;
+        };
+        summary.add_row(Row::new(vec![
+            Cell::new(region_name),
+            Cell::new(linux_info.map_or("N/A", |i| i.interruption_rate.as_str())),
+            Cell::new(linux_info.map_or("N/A", |i| i.savings.as_str())),
+            Cell::new(&info.linux_spot_price),
+            Cell::new(&info.windows_spot_price),
+        ]));
+    }
+
+    log::info!(
+        "Found instance type {} in {} regions",
+        instance_type,
+        summary.len() - 1
+    );
+
+    summary.printstd();
+
+    Ok(())
+}
+
+/// Which value of a comparison row is the best one
+#[derive(Clone, Copy)]
+enum Best {
+    Lowest,
+    Highest,
+}
+
+pub fn display_comparison(
+    region: &str,
+    instance_types: &[String],
+    advisor_data: &Value,
+    price_data: &Value,
+) -> Result<()> {
+    let instance_data = collect_instance_data(region, advisor_data, price_data, Os::Linux);
+
+    let mut infos: Vec<&InstanceInfo> = Vec::new();
+    for instance_type in instance_types {
+        let info = instance_data
+            .get(&(instance_type.clone(), "Linux".to_string()))
+            .and_then(|region_map| region_map.get(region))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Instance type '{}' not found in region '{}'",
+                    instance_type,
+                    region
+                )
+            })?;
+        infos.push(info);
+    }
+
+    let mut table = Table::new();
+    let mut headers = vec![Cell::new("Instance Type")];
+    headers.extend(instance_types.iter().map(|t| Cell::new(t)));
+    table.add_row(Row::new(headers));
+
+    table.add_row(comparison_row(
+        "Cores",
+        &infos,
+        |i| i.cores.as_str(),
+        |i| i.cores_value(),
+        Best::Highest,
+    ));
+    table.add_row(comparison_row(
+        "Memory (GB)",
+        &infos,
+        |i| i.memory_gb.as_str(),
+        |i| i.memory_gb_value(),
+        Best::Highest,
+    ));
+    table.add_row(comparison_row(
+        "Interruption Rate",
+        &infos,
+        |i| i.interruption_rate.as_str(),
+        |i| i.interruption_bucket().map(|b| b as f64),
+        Best::Lowest,
+    ));
+    for column in [PriceColumn::Linux, PriceColumn::Mswin] {
+        table.add_row(comparison_row(
+            column.header(),
+            &infos,
+            |i| i.spot_price(column),
+            |i| i.spot_price_value(column),
+            Best::Lowest,
+        ));
+    }
+    table.add_row(comparison_row(
+        "Savings",
+        &infos,
+        |i| i.savings.as_str(),
+        |i| i.savings_value(),
+        Best::Highest,
+    ));
+
+    log::info!(
+        "Comparing {} instance types in region: {}",
+        instance_types.len(),
+        region
+    );
+
+    table.printstd();
+
+    Ok(())
+}
+
+/// Builds a comparison row, highlighting the cells holding the best value
+fn comparison_row<'a>(
+    label: &str,
+    infos: &[&'a InstanceInfo],
+    value: impl Fn(&'a InstanceInfo) -> &'a str,
+    score: impl Fn(&'a InstanceInfo) -> Option<f64>,
+    best: Best,
+) -> Row {
+    let scores: Vec<Option<f64>> = infos.iter().map(|i| score(i)).collect();
+    let best_score = scores.iter().flatten().copied().reduce(|a, b| match best {
+        Best::Lowest => a.min(b),
+        Best::Highest => a.max(b),
+    });
+
+    let mut cells = vec![Cell::new(label)];
+    for (info, score) in infos.iter().zip(&scores) {
+        let cell = Cell::new(value(info));
+        if score.is_some() && *score == best_score {
+            cells.push(cell.style_spec("bFg"));
+        } else {
+            cells.push(cell);
+        }
+    }
+
+    Row::new(cells)
+}
+
+pub fn display_recommendations(
+    region: &str,
+    recommendations: &[Recommendation],
+    price_column: PriceColumn,
+) -> Result<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Rank"),
+        Cell::new("Instance Type"),
+        Cell::new("Region"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Memory (GB)"),
+        Cell::new("Cores"),
+        Cell::new(price_column.header()),
+        Cell::new("Effective Cost"),
+        Cell::new("Savings"),
+    ]));
+
+    for (rank, recommendation) in recommendations.iter().enumerate() {
+        let info = &recommendation.info;
+        table.add_row(Row::new(vec![
+            Cell::new(&(rank + 1).to_string()),
+            Cell::new(&recommendation.instance_type),
+            Cell::new(region),
+            Cell::new(&info.interruption_rate),
             Cell::new(&info.memory_gb),
             Cell::new(&info.cores),
-        ];
+            Cell::new(info.spot_price(price_column)),
+            Cell::new(&format_price(Some(recommendation.effective_cost))),
+            Cell::new(&info.savings),
+        ]));
+    }
+
+    log::info!(
+        "Found {} recommended instance types for region: {}",
+        recommendations.len(),
+        region
+    );
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn display_spot_moves(
+    region: &str,
+    moves: &[SpotMove],
+    price_column: PriceColumn,
+) -> Result<()> {
+    if moves.is_empty() {
+        println!("No Compute Optimizer recommendations in {}", region);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Workload"),
+        Cell::new("Current Type"),
+        Cell::new("Finding"),
+        Cell::new("Spot Type"),
+        Cell::new("Interruption Rate"),
+        Cell::new(price_column.header()),
+        Cell::new("Effective Cost"),
+        Cell::new("Savings"),
+    ]));
 
-        if show_spot_price {
-            row_cells.push(Cell::new(&info.linux_spot_price));
-            row_cells.push(Cell::new(&info.windows_spot_price));
+    for spot_move in moves {
+        let rightsizing = &spot_move.rightsizing;
+        let mut row = vec![
+            Cell::new(&rightsizing.workload),
+            Cell::new(&rightsizing.current_instance_type),
+            Cell::new(&rightsizing.finding),
+        ];
+        match &spot_move.suggestion {
+            Some(suggestion) => row.extend([
+                Cell::new(&suggestion.instance_type),
+                Cell::new(&suggestion.info.interruption_rate),
+                Cell::new(suggestion.info.spot_price(price_column)),
+                Cell::new(&format_price(Some(suggestion.effective_cost))),
+                Cell::new(&suggestion.info.savings),
+            ]),
+            None => {
+                row.push(Cell::new("-"));
+                row.extend((0..4).map(|_| Cell::new("N/A")));
+            }
         }
+        table.add_row(Row::new(row));
+    }
+
+    table.printstd();
+
+    let movable = moves.iter().filter(|m| m.suggestion.is_some()).count();
+    println!(
+        "{} of {} workloads in {} can move to a recommended spot instance type",
+        movable,
+        moves.len(),
+        region
+    );
+
+    Ok(())
+}
+
+pub fn display_spend(overlays: &[SpendOverlay]) -> Result<()> {
+    if overlays.is_empty() {
+        println!("No on-demand EC2 spend last month");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Region"),
+        Cell::new("On-Demand Spend"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Savings"),
+        Cell::new("Potential Savings"),
+    ]));
+
+    for overlay in overlays {
+        table.add_row(Row::new(vec![
+            Cell::new(&overlay.spend.instance_type),
+            Cell::new(&overlay.spend.region),
+            Cell::new(&format!("${:.2}", overlay.spend.amount)),
+            Cell::new(&overlay.interruption_rate),
+            Cell::new(
+                &overlay
+                    .savings
+                    .map_or_else(|| "N/A".to_string(), |savings| format!("{}%", savings)),
+            ),
+            Cell::new(
+                &overlay
+                    .potential_savings
+                    .map_or_else(|| "N/A".to_string(), |savings| format!("${:.2}", savings)),
+            ),
+        ]));
+    }
+
+    table.printstd();
+
+    let total: f64 = overlays.iter().map(|overlay| overlay.spend.amount).sum();
+    let potential: f64 = overlays
+        .iter()
+        .filter_map(|overlay| overlay.potential_savings)
+        .sum();
+    println!(
+        "Last month's on-demand EC2 spend: ${:.2}, potential spot savings: ${:.2}",
+        total, potential
+    );
+
+    Ok(())
+}
+
+pub fn display_estimate(
+    region: &str,
+    fleet: &[FleetEntry],
+    advisor_data: &Value,
+    price_data: &Value,
+    os: Os,
+    on_demand_prices: Option<&HashMap<String, String>>,
+    hours: f64,
+) -> Result<()> {
+    let instance_data = collect_instance_data(region, advisor_data, price_data, os);
+    let all_region_infos = region_infos(&instance_data, region, os.advisor_keys()[0]);
+    let price_column = os.price_column();
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Count"),
+        Cell::new("Interruption Rate"),
+        Cell::new(price_column.header()),
+        Cell::new("Hourly Spot Cost"),
+        Cell::new("Monthly Spot Cost"),
+        Cell::new("On-Demand Price"),
+        Cell::new("Monthly On-Demand Cost"),
+    ]));
+
+    let mut total_spot = 0.0;
+    // On-demand totals are only comparable when every instance type has an on-demand price
+    let mut total_on_demand = Some(0.0);
+
+    for entry in fleet {
+        let info = all_region_infos
+            .get(entry.instance_type.as_str())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Instance type '{}' not found in region '{}'",
+                    entry.instance_type,
+                    region
+                )
+            })?;
+        let spot_price = info.spot_price_value(price_column).ok_or_else(|| {
+            anyhow!(
+                "No spot price for instance type '{}' in region '{}'",
+                entry.instance_type,
+                region
+            )
+        })?;
+        let on_demand_price = on_demand_prices
+            .and_then(|prices| prices.get(&entry.instance_type))
+            .and_then(|price| price.parse::<f64>().ok());
+
+        let count = f64::from(entry.count);
+        let hourly_spot = spot_price * count;
+        total_spot += hourly_spot;
+        total_on_demand = total_on_demand
+            .zip(on_demand_price)
+            .map(|(t, p)| t + p * count);
+
+        table.add_row(Row::new(vec![
+            Cell::new(&entry.instance_type),
+            Cell::new(&entry.count.to_string()),
+            Cell::new(&info.interruption_rate),
+            Cell::new(&format_price(Some(spot_price))),
+            Cell::new(&format_price(Some(hourly_spot))),
+            Cell::new(&format_cost(Some(hourly_spot * hours))),
+            Cell::new(&format_price(on_demand_price)),
+            Cell::new(&format_cost(on_demand_price.map(|p| p * count * hours))),
+        ]));
+    }
+
+    let instance_count: u32 = fleet.iter().map(|e| e.count).sum();
+    table.add_row(Row::new(vec![
+        Cell::new("Total").style_spec("b"),
+        Cell::new(&instance_count.to_string()).style_spec("b"),
+        Cell::new(""),
+        Cell::new(""),
+        Cell::new(&format_price(Some(total_spot))).style_spec("b"),
+        Cell::new(&format_cost(Some(total_spot * hours))).style_spec("b"),
+        Cell::new(""),
+        Cell::new(&format_cost(total_on_demand.map(|t| t * hours))).style_spec("b"),
+    ]));
+
+    log::info!(
+        "Estimated the cost of {} instances for region: {}",
+        instance_count,
+        region
+    );
+
+    table.printstd();
+
+    if let Some(total_on_demand) = total_on_demand.filter(|t| *t > 0.0) {
+        println!(
+            "Spot saves {} per month ({:.1}%) over on-demand for {} hours",
+            format_cost(Some((total_on_demand - total_spot) * hours)),
+            (1.0 - total_spot / total_on_demand) * 100.0,
+            hours
+        );
+    }
+
+    Ok(())
+}
+
+pub fn display_simulation(
+    region: &str,
+    instance_type: &str,
+    advisor_data: &Value,
+    price_data: &Value,
+    os: Os,
+    on_demand_prices: Option<&HashMap<String, String>>,
+    workload: &simulate::Workload,
+) -> Result<()> {
+    let instance_data = collect_instance_data(region, advisor_data, price_data, os);
+    let all_region_infos = region_infos(&instance_data, region, os.advisor_keys()[0]);
+    let price_column = os.price_column();
+
+    let info = all_region_infos.get(instance_type).ok_or_else(|| {
+        anyhow!(
+            "Instance type '{}' not found in region '{}'",
+            instance_type,
+            region
+        )
+    })?;
+    let bucket = info.interruption_bucket().ok_or_else(|| {
+        anyhow!(
+            "No interruption rate for instance type '{}' in region '{}'",
+            instance_type,
+            region
+        )
+    })?;
+    let spot_price = info.spot_price_value(price_column).ok_or_else(|| {
+        anyhow!(
+            "No spot price for instance type '{}' in region '{}'",
+            instance_type,
+            region
+        )
+    })?;
+    let on_demand_price = on_demand_prices
+        .and_then(|prices| prices.get(instance_type))
+        .and_then(|price| price.parse::<f64>().ok());
+
+    let simulation = simulate::simulate(
+        workload,
+        bucket,
+        spot_price,
+        on_demand_price,
+    )
+    .ok_or_else(|| {
+        anyhow!(
+            "The workload is expected never to complete on spot: the work lost per interruption outpaces the progress"
+        )
+    })?;
+
+    let mut card = Table::new();
+    let mut add_row = |name: &str, value: &str| {
+        card.add_row(Row::new(vec![Cell::new(name), Cell::new(value)]));
+    };
+    add_row("Instance Type", instance_type);
+    add_row("Region", region);
+    add_row("Interruption Rate", &info.interruption_rate);
+    add_row(
+        "Assumed Monthly Interruption Probability",
+        &format!(
+            "{:.1}%",
+            simulation.monthly_interruption_probability * 100.0
+        ),
+    );
+    add_row("Runtime (hours)", &format!("{:.2}", workload.runtime_hours));
+    add_row(
+        "Work Lost per Interruption (hours)",
+        &format!(
+            "{:.2}",
+            workload.checkpoint_loss_hours.min(workload.runtime_hours)
+        ),
+    );
+    add_row(
+        "Expected Interruptions",
+        &format!("{:.2}", simulation.expected_interruptions),
+    );
+    add_row(
+        "Expected Wall-Clock Time (hours)",
+        &format!("{:.2}", simulation.wall_clock_hours),
+    );
+    add_row(price_column.header(), &format_price(Some(spot_price)));
+    add_row(
+        "Expected Spot Cost",
+        &format_cost(Some(simulation.spot_cost)),
+    );
+    add_row("On-Demand Price", &format_price(on_demand_price));
+    add_row("On-Demand Cost", &format_cost(simulation.on_demand_cost));
+
+    card.printstd();
+
+    if let Some(on_demand_cost) = simulation.on_demand_cost.filter(|c| *c > 0.0) {
+        let savings = (1.0 - simulation.spot_cost / on_demand_cost) * 100.0;
+        if savings > 0.0 {
+            println!(
+                "Spot is expected to cost {:.1}% less than on-demand, taking {:.1}% longer",
+                savings,
+                (simulation.wall_clock_hours / workload.runtime_hours - 1.0) * 100.0
+            );
+        } else {
+            println!(
+                "On-demand is expected to cost {:.1}% less than spot after the rework",
+                -savings
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn display_strategy(
+    region: &str,
+    types: &[String],
+    advisor_data: &Value,
+    price_data: &Value,
+    os: Os,
+    risk_penalties: &[f64],
+) -> Result<()> {
+    let instance_data = collect_instance_data(region, advisor_data, price_data, os);
+    let price_column = os.price_column();
+
+    // Every instance type matching one of the types is a spot pool of the fleet
+    let mut pools: Vec<Recommendation> = recommend::recommend(
+        &instance_data,
+        region,
+        os,
+        &recommend::Constraints::default(),
+        risk_penalties,
+    )
+    .into_iter()
+    .filter(|r| {
+        types
+            .iter()
+            .any(|filter| matches_instance_filter(&r.instance_type, filter))
+    })
+    .collect();
+    pools.sort_by(|a, b| {
+        compare_values(
+            a.info.spot_price_value(price_column),
+            b.info.spot_price_value(price_column),
+        )
+    });
+
+    let advice = recommend::advise_strategy(&pools, price_column).ok_or_else(|| {
+        anyhow!(
+            "No spot pools matching '{}' found in region '{}'",
+            types.join(","),
+            region
+        )
+    })?;
+
+    display_recommendations(region, &pools, price_column)?;
+
+    println!(
+        "\nRecommended allocation strategy: {}",
+        advice.strategy.name()
+    );
+    for reason in &advice.reasons {
+        println!("  - {}", reason);
+    }
+
+    Ok(())
+}
+
+pub fn display_diff(region: &str, old: &Snapshot, new: &Snapshot, os: Os) -> Result<()> {
+    let os_name = os.advisor_keys()[0];
+    let price_column = os.price_column();
+
+    let old_data = collect_instance_data(region, &old.advisor_data, &old.price_data, os);
+    let new_data = collect_instance_data(region, &new.advisor_data, &new.price_data, os);
+    let changes = diff::diff_instances(
+        &region_infos(&old_data, region, os_name),
+        &region_infos(&new_data, region, os_name),
+        price_column,
+    );
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Change"),
+        Cell::new(price_column.header()),
+        Cell::new("Savings"),
+        Cell::new("Interruption Rate"),
+    ]));
+
+    for change in &changes {
+        let (old_info, new_info) = (change.old.as_ref(), change.new.as_ref());
+        let status = match (old_info, new_info) {
+            (None, _) => "Added",
+            (_, None) => "Removed",
+            _ => "Changed",
+        };
+
+        let price = format_change(
+            old_info.map_or("N/A", |i| i.spot_price(price_column)),
+            new_info.map_or("N/A", |i| i.spot_price(price_column)),
+            change.price_delta(price_column).map(|delta| {
+                let old_price = old_info.and_then(|i| i.spot_price_value(price_column));
+                match old_price.filter(|p| *p > 0.0) {
+                    Some(old_price) => {
+                        format!("{:+.4}, {:+.1}%", delta, delta / old_price * 100.0)
+                    }
+                    None => format!("{:+.4}", delta),
+                }
+            }),
+        );
+        let savings = format_change(
+            old_info.map_or("N/A", |i| i.savings.as_str()),
+            new_info.map_or("N/A", |i| i.savings.as_str()),
+            change
+                .savings_delta()
+                .map(|delta| format!("{:+} pp", delta)),
+        );
+        let interruption = format_change(
+            old_info.map_or("N/A", |i| i.interruption_rate.as_str()),
+            new_info.map_or("N/A", |i| i.interruption_rate.as_str()),
+            change
+                .interruption_delta()
+                .map(|delta| format!("{:+} buckets", delta)),
+        );
+
+        table.add_row(Row::new(vec![
+            Cell::new(&change.instance_type),
+            Cell::new(status),
+            Cell::new(&price),
+            Cell::new(&savings),
+            Cell::new(&interruption),
+        ]));
+    }
+
+    log::info!(
+        "Found {} changed instance types for region: {}",
+        changes.len(),
+        region
+    );
+
+    table.printstd();
+
+    Ok(())
+}
+
+/// Formats a change as "old -> new (delta)", or just the value when it didn't change
+fn format_change(old: &str, new: &str, delta: Option<String>) -> String {
+    if old == new {
+        return new.to_string();
+    }
+
+    match delta {
+        Some(delta) => format!("{} -> {} ({})", old, new, delta),
+        None => format!("{} -> {}", old, new),
+    }
+}
+
+pub fn display_graviton_equivalents(
+    region: &str,
+    instance_type: &str,
+    advisor_data: &Value,
+    price_data: &Value,
+    os: Os,
+) -> Result<()> {
+    if instance::is_graviton(instance::family(instance_type)) {
+        return Err(anyhow!(
+            "Instance type '{}' already runs on Graviton",
+            instance_type
+        ));
+    }
+
+    let instance_data = collect_instance_data(region, advisor_data, price_data, os);
+    let os_name = os.advisor_keys()[0];
+    let price_column = os.price_column();
+
+    let region_infos = region_infos(&instance_data, region, os_name);
+
+    let source = region_infos.get(instance_type).ok_or_else(|| {
+        anyhow!(
+            "Instance type '{}' not found in region '{}'",
+            instance_type,
+            region
+        )
+    })?;
+    let source_price = source.spot_price_value(price_column);
+
+    let equivalents = instance::graviton_equivalents(instance_type, region_infos.keys().copied());
+    if equivalents.is_empty() {
+        log::warn!(
+            "No Graviton equivalents of {} found in region: {}",
+            instance_type,
+            region
+        );
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Region"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Memory (GB)"),
+        Cell::new("Cores"),
+        Cell::new(price_column.header()),
+        Cell::new("Savings from Switching"),
+    ]));
+
+    for name in std::iter::once(instance_type).chain(equivalents) {
+        let Some(info) = region_infos.get(name) else {
+            continue;
+        };
+
+        // Percentage saved on the spot price compared to the x86 instance type
+        let switching_savings = match (source_price, info.spot_price_value(price_column)) {
+            (_, _) if name == instance_type => "-".to_string(),
+            (Some(source), Some(price)) if source > 0.0 => {
+                format!("{:.1}%", (source - price) / source * 100.0)
+            }
+            _ => "N/A".to_string(),
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(name),
+            Cell::new(region),
+            Cell::new(&info.interruption_rate),
+            Cell::new(&info.memory_gb),
+            Cell::new(&info.cores),
+            Cell::new(info.spot_price(price_column)),
+            Cell::new(&switching_savings),
+        ]));
+    }
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn display_regions(advisor_data: &Value, price_data: &Value) -> Result<()> {
+    let advisor_regions: BTreeSet<&str> = advisor_data["spot_advisor"]
+        .as_object()
+        .map(|regions| regions.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+
+    let price_regions: BTreeSet<&str> = price_data["config"]["regions"]
+        .as_array()
+        .map(|regions| {
+            regions
+                .iter()
+                .filter_map(|r| r.get("region").and_then(Value::as_str))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    log::debug!(
+        "Number of regions in advisor data: {}, price data: {}",
+        advisor_regions.len(),
+        price_regions.len()
+    );
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Region"),
+        Cell::new("Name"),
+        Cell::new("Advisor Data"),
+        Cell::new("Price Data"),
+    ]));
+
+    let yes_no = |covered: bool| if covered { "Yes" } else { "No" };
+    for region in advisor_regions.union(&price_regions) {
+        table.add_row(Row::new(vec![
+            Cell::new(region),
+            Cell::new(crate::cli::region_name(region).unwrap_or("N/A")),
+            Cell::new(yes_no(advisor_regions.contains(region))),
+            Cell::new(yes_no(price_regions.contains(region))),
+        ]));
+    }
+
+    log::info!("Found {} regions", table.len() - 1);
+
+    table.printstd();
+
+    Ok(())
+}
+
+/// Returns the cache status of the data of a source: the cache file, whether it's fresh or
+/// stale, and the TTL
+fn cache_status(cache: Option<&Cache>, cache_file: Option<&str>) -> String {
+    let Some(name) = cache_file else {
+        return "Not cached (fetched on every run)".to_string();
+    };
+    let Some(cache) = cache else {
+        return "Not cached (--no-cache or no cache directory)".to_string();
+    };
+    let ttl = format_duration(cache.ttl());
+    let status = match cache.age(name) {
+        Some(age) if age < cache.ttl() => {
+            format!("Fresh, {} old (TTL {})", format_duration(age), ttl)
+        }
+        Some(age) => format!("Stale, {} old (TTL {})", format_duration(age), ttl),
+        None => format!("Not fetched yet (TTL {})", ttl),
+    };
+    format!("{}\n{}", cache.path(name).display(), status)
+}
+
+pub fn display_sources(sources: &[DataSource], cache: Option<&Cache>) -> Result<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Source"),
+        Cell::new("URL"),
+        Cell::new("Auth"),
+        Cell::new("Cache"),
+    ]));
+
+    for source in sources {
+        table.add_row(Row::new(vec![
+            Cell::new(source.name),
+            Cell::new(source.url),
+            Cell::new(source.auth),
+            Cell::new(&cache_status(cache, source.cache_file)),
+        ]));
+    }
+
+    table.printstd();
+
+    Ok(())
+}
+
+/// Displays the live spot prices per Availability Zone, highlighting the cheapest AZ of each
+/// instance type
+pub fn display_az_prices(region: &str, table: &AzPriceTable) -> Result<()> {
+    if table.rows.is_empty() {
+        println!("\nNo live spot prices found in {}", region);
+        return Ok(());
+    }
+
+    println!("\nLive Spot Prices per Availability Zone in {}", region);
+    let mut az_table = Table::new();
+    let mut header = vec![Cell::new("Instance Type")];
+    header.extend(table.availability_zones.iter().map(|az| Cell::new(az)));
+    az_table.add_row(Row::new(header));
+
+    for (instance_type, prices) in &table.rows {
+        let cheapest = prices.iter().flatten().copied().reduce(f64::min);
+        let mut cells = vec![Cell::new(instance_type)];
+        for price in prices {
+            let cell = Cell::new(&price.map_or_else(|| "-".to_string(), |p| format_price(Some(p))));
+            if price.is_some() && *price == cheapest {
+                cells.push(cell.style_spec("bFg"));
+            } else {
+                cells.push(cell);
+            }
+        }
+        az_table.add_row(Row::new(cells));
+    }
+
+    az_table.printstd();
+
+    Ok(())
+}
+
+pub fn display_placement_scores(scores: &[PlacementScore]) -> Result<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Region"),
+        Cell::new("Availability Zone"),
+        Cell::new("Placement Score"),
+        Cell::new("Interruption Rates"),
+    ]));
+
+    for score in scores {
+        table.add_row(Row::new(vec![
+            Cell::new(&score.region),
+            Cell::new(score.availability_zone_id.as_deref().unwrap_or("-")),
+            Cell::new(&format!("{}/10", score.score)),
+            Cell::new(&score.interruption_rates),
+        ]));
+    }
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn display_audit(region: &str, findings: &[AuditFinding]) -> Result<()> {
+    if findings.is_empty() {
+        println!("No running spot instances in {}", region);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance"),
+        Cell::new("Instance Type"),
+        Cell::new("Availability Zone"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Paying"),
+        Cell::new("Cheapest AZ"),
+        Cell::new("Flags"),
+    ]));
+
+    for finding in findings {
+        table.add_row(Row::new(vec![
+            Cell::new(&finding.instance.instance_id),
+            Cell::new(&finding.instance.instance_type),
+            Cell::new(&finding.instance.availability_zone),
+            Cell::new(&finding.interruption_rate),
+            Cell::new(
+                &finding
+                    .price
+                    .map_or_else(|| "N/A".to_string(), |price| format!("${:.4}/h", price)),
+            ),
+            Cell::new(&finding.cheapest.as_ref().map_or_else(
+                || "N/A".to_string(),
+                |(zone, price)| format!("{} (${:.4}/h)", zone, price),
+            )),
+            Cell::new(&if finding.flagged() {
+                finding.flags.join("; ")
+            } else {
+                "-".to_string()
+            }),
+        ]));
+    }
+
+    table.printstd();
+
+    let flagged = findings.iter().filter(|finding| finding.flagged()).count();
+    println!(
+        "{} of {} running spot instances in {} flagged",
+        flagged,
+        findings.len(),
+        region
+    );
+
+    Ok(())
+}
+
+pub fn display_pool_ratings(
+    source: &str,
+    region: &str,
+    os: Os,
+    ratings: &[PoolRating],
+) -> Result<()> {
+    if ratings.is_empty() {
+        println!("No instance types configured in {}", source);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Savings"),
+        Cell::new(os.price_column().header()),
+    ]));
+
+    for rating in ratings {
+        let mut interruption_rate = Cell::new(&rating.interruption_rate);
+        if rating.high_interruption {
+            interruption_rate = interruption_rate.style_spec("Fr");
+        }
+        table.add_row(Row::new(vec![
+            Cell::new(&rating.instance_type),
+            interruption_rate,
+            Cell::new(&rating.savings),
+            Cell::new(
+                &rating
+                    .spot_price
+                    .map_or_else(|| "N/A".to_string(), |price| format!("${:.4}/h", price)),
+            ),
+        ]));
+    }
+
+    table.printstd();
+
+    let high = ratings
+        .iter()
+        .filter(|rating| rating.high_interruption)
+        .count();
+    println!(
+        "{} of {} instance types of {} in {} have become high-interruption",
+        high,
+        ratings.len(),
+        source,
+        region
+    );
+
+    Ok(())
+}
+
+pub fn display_node_groups(
+    region: &str,
+    os: Os,
+    groups: &[NodeGroup],
+    ratings: &[PoolRating],
+) -> Result<()> {
+    if groups.is_empty() {
+        println!("No nodes with an instance type label in the cluster");
+        return Ok(());
+    }
+
+    let ratings: HashMap<&str, &PoolRating> = ratings
+        .iter()
+        .map(|rating| (rating.instance_type.as_str(), rating))
+        .collect();
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Capacity Type"),
+        Cell::new("Nodes"),
+        Cell::new("Interruption Rate"),
+        Cell::new(os.price_column().header()),
+    ]));
+
+    for group in groups {
+        let rating = ratings.get(group.instance_type.as_str());
+        let mut interruption_rate =
+            Cell::new(rating.map_or("N/A", |rating| rating.interruption_rate.as_str()));
+        // Only spot nodes risk interruptions
+        if group.capacity_type == "spot" && rating.is_some_and(|rating| rating.high_interruption) {
+            interruption_rate = interruption_rate.style_spec("Fr");
+        }
+        table.add_row(Row::new(vec![
+            Cell::new(&group.instance_type),
+            Cell::new(&group.capacity_type),
+            Cell::new(&group.nodes.to_string()),
+            interruption_rate,
+            Cell::new(
+                &rating
+                    .and_then(|rating| rating.spot_price)
+                    .map_or_else(|| "N/A".to_string(), |price| format!("${:.4}/h", price)),
+            ),
+        ]));
+    }
+
+    table.printstd();
+
+    let nodes: usize = groups.iter().map(|group| group.nodes).sum();
+    let spot_nodes: usize = groups
+        .iter()
+        .filter(|group| group.capacity_type == "spot")
+        .map(|group| group.nodes)
+        .sum();
+    println!(
+        "{} of {} nodes run on spot, rated by the advisor data of {}",
+        spot_nodes, nodes, region
+    );
+
+    Ok(())
+}
+
+pub fn display_fits(
+    namespace: &str,
+    region: &str,
+    pods: &[PodRequests],
+    fits: &[Fit],
+) -> Result<()> {
+    let vcpus: f64 = pods.iter().map(|pod| pod.vcpus).sum();
+    let memory_gib: f64 = pods.iter().map(|pod| pod.memory_gib).sum();
+    println!(
+        "{} pods of namespace '{}' request {:.2} vCPUs and {:.2} GiB",
+        pods.len(),
+        namespace,
+        vcpus,
+        memory_gib
+    );
+
+    if fits.is_empty() {
+        println!(
+            "No spot instance type in {} within the interruption rate hosts every pod",
+            region
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Nodes"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Hourly Cost"),
+        Cell::new("Effective Cost"),
+        Cell::new("vCPU Utilization"),
+        Cell::new("Memory Utilization"),
+    ]));
+
+    for fit in fits {
+        table.add_row(Row::new(vec![
+            Cell::new(&fit.instance_type),
+            Cell::new(&fit.nodes.to_string()),
+            Cell::new(&fit.interruption_rate),
+            Cell::new(&format!("${:.4}/h", fit.hourly_cost)),
+            Cell::new(&format!("${:.4}/h", fit.effective_cost)),
+            Cell::new(&format!("{:.0}%", fit.vcpu_utilization)),
+            Cell::new(&format!("{:.0}%", fit.memory_utilization)),
+        ]));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+pub fn display_gpu_offers(region: &str, os: Os, offers: &[GpuOffer]) -> Result<()> {
+    if offers.is_empty() {
+        println!("No accelerated instance types in {}", region);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("GPU Model"),
+        Cell::new("GPUs"),
+        Cell::new("GPU Memory"),
+        Cell::new("Interruption Rate"),
+        Cell::new(os.price_column().header()),
+        Cell::new("Price per GPU"),
+    ]));
+
+    let price =
+        |price: Option<f64>| price.map_or_else(|| "N/A".to_string(), |p| format!("${:.4}/h", p));
+    for offer in offers {
+        table.add_row(Row::new(vec![
+            Cell::new(&offer.instance_type),
+            Cell::new(&offer.gpu_model),
+            Cell::new(&offer.gpu_count.to_string()),
+            Cell::new(
+                &offer
+                    .gpu_memory_gib
+                    .map_or_else(|| "N/A".to_string(), |memory| format!("{} GiB", memory)),
+            ),
+            Cell::new(&offer.interruption_rate),
+            Cell::new(&price(offer.spot_price)),
+            Cell::new(&price(offer.price_per_gpu)),
+        ]));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+pub fn display_cloud_matches(vcpus: u32, memory_gb: f64, matches: &[CloudMatch]) -> Result<()> {
+    if matches.is_empty() {
+        println!(
+            "No spot shapes with at least {} vCPUs and {} GB of memory",
+            vcpus, memory_gb
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Cloud"),
+        Cell::new("Region"),
+        Cell::new("Instance Type"),
+        Cell::new("vCPUs"),
+        Cell::new("Memory GB"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Linux Spot Price"),
+        Cell::new("Price per vCPU"),
+    ]));
+
+    for cloud_match in matches {
+        table.add_row(Row::new(vec![
+            Cell::new(cloud_match.cloud),
+            Cell::new(&cloud_match.region),
+            Cell::new(&cloud_match.instance_type),
+            Cell::new(&cloud_match.vcpus.to_string()),
+            Cell::new(&cloud_match.memory_gb.to_string()),
+            Cell::new(&cloud_match.interruption_rate),
+            Cell::new(&format!("${:.4}/h", cloud_match.spot_price)),
+            Cell::new(&format!("${:.5}/h", cloud_match.price_per_vcpu)),
+        ]));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+pub fn display_diagnoses(diagnoses: &[Diagnosis]) -> Result<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Check"),
+        Cell::new("Status"),
+        Cell::new("Detail"),
+        Cell::new("Fix"),
+    ]));
+
+    for diagnosis in diagnoses {
+        table.add_row(Row::new(vec![
+            Cell::new(&diagnosis.check),
+            Cell::new(diagnosis.status.label()),
+            Cell::new(&diagnosis.detail),
+            Cell::new(diagnosis.fix.as_deref().unwrap_or("-")),
+        ]));
+    }
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn display_top(
+    region: &str,
+    ranked: &[Ranked],
+    movements: &[String],
+    price_column: PriceColumn,
+) -> Result<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Rank"),
+        Cell::new("Move"),
+        Cell::new("Instance Type"),
+        Cell::new("Interruption Rate"),
+        Cell::new(price_column.header()),
+        Cell::new("Savings"),
+        Cell::new("Cores"),
+        Cell::new("Memory (GB)"),
+    ]));
+
+    for (rank, (ranked, movement)) in ranked.iter().zip(movements).enumerate() {
+        let info = &ranked.info;
+        table.add_row(Row::new(vec![
+            Cell::new(&(rank + 1).to_string()),
+            Cell::new(movement),
+            Cell::new(&ranked.instance_type),
+            Cell::new(&info.interruption_rate),
+            Cell::new(info.spot_price(price_column)),
+            Cell::new(&info.savings),
+            Cell::new(&info.cores),
+            Cell::new(&info.memory_gb),
+        ]));
+    }
+
+    log::info!("Top {} spot pools in region: {}", ranked.len(), region);
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn display_checks(region: &str, checks: &[Check]) -> Result<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Instance Type"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Spot Price"),
+        Cell::new("Savings"),
+        Cell::new("Result"),
+    ]));
+
+    for check in checks {
+        let result = if check.passed() {
+            "OK".to_string()
+        } else {
+            check.violations.join("; ")
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&check.instance_type),
+            Cell::new(&check.interruption_rate),
+            Cell::new(&format_price(check.price)),
+            Cell::new(
+                &check
+                    .savings
+                    .map(|s| format!("{}%", s))
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ),
+            Cell::new(&result),
+        ]));
+    }
+
+    log::info!(
+        "Checked {} instance types in {}, {} failed",
+        checks.len(),
+        region,
+        checks.iter().filter(|check| !check.passed()).count()
+    );
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn display_triggers(triggers: &[Trigger]) -> Result<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Rule"),
+        Cell::new("Instance Type"),
+        Cell::new("Region"),
+        Cell::new("Spot Price"),
+        Cell::new("Interruption Rate"),
+        Cell::new("Savings"),
+    ]));
+
+    for trigger in triggers {
+        table.add_row(Row::new(vec![
+            Cell::new(&trigger.rule),
+            Cell::new(&trigger.instance_type),
+            Cell::new(&trigger.region),
+            Cell::new(&format_price(trigger.price)),
+            Cell::new(&trigger.interruption_rate),
+            Cell::new(
+                &trigger
+                    .savings
+                    .map(|s| format!("{}%", s))
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ),
+        ]));
+    }
+
+    log::info!("{} alert rules tripped", triggers.len());
+
+    table.printstd();
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io;
+
+    /// Linux spot offer at 70% savings with 4 cores and 16 GB of memory
+    pub(crate) fn info(interruption_rate: &str, price: &str) -> InstanceInfo {
+        InstanceInfo {
+            interruption_rate: interruption_rate.to_string(),
+            savings: "70%".to_string(),
+            linux_spot_price: price.to_string(),
+            windows_spot_price: "N/A".to_string(),
+            rhel_spot_price: "N/A".to_string(),
+            suse_spot_price: "N/A".to_string(),
+            on_demand_price: "N/A".to_string(),
+            memory_gb: "16".to_string(),
+            cores: "4".to_string(),
+        }
+    }
+
+    impl InstanceInfo {
+        pub(crate) fn with_savings(self, savings: &str) -> Self {
+            Self {
+                savings: savings.to_string(),
+                ..self
+            }
+        }
+
+        pub(crate) fn with_windows_spot_price(self, price: &str) -> Self {
+            Self {
+                windows_spot_price: price.to_string(),
+                ..self
+            }
+        }
+
+        pub(crate) fn with_on_demand_price(self, price: &str) -> Self {
+            Self {
+                on_demand_price: price.to_string(),
+                ..self
+            }
+        }
+
+        pub(crate) fn with_size(self, cores: &str, memory_gb: &str) -> Self {
+            Self {
+                cores: cores.to_string(),
+                memory_gb: memory_gb.to_string(),
+                ..self
+            }
+        }
+    }
+
+    /// Instance data of (instance type, OS, region, info) entries
+    pub(crate) fn instance_data<'a>(
+        entries: impl IntoIterator<Item = (&'a str, &'a str, &'a str, InstanceInfo)>,
+    ) -> InstanceData {
+        let mut instance_data: InstanceData = HashMap::new();
+        for (instance_type, os, region, info) in entries {
+            instance_data
+                .entry((instance_type.to_string(), os.to_string()))
+                .or_default()
+                .insert(region.to_string(), info);
+        }
+        instance_data
+    }
+
+    #[test]
+    fn test_instance_info_creation() {
+        let info = InstanceInfo {
+            interruption_rate: "< 5%".to_string(),
+            savings: "80%".to_string(),
+            linux_spot_price: "0.123".to_string(),
+            windows_spot_price: "0.456".to_string(),
+            rhel_spot_price: "0.189".to_string(),
+            suse_spot_price: "N/A".to_string(),
+            on_demand_price: "0.615".to_string(),
+            memory_gb: "16".to_string(),
+            cores: "4".to_string(),
+        };
+
+        assert_eq!(info.interruption_rate, "< 5%");
+        assert_eq!(info.savings, "80%");
+        assert_eq!(info.linux_spot_price, "0.123");
+        assert_eq!(info.windows_spot_price, "0.456");
+        assert_eq!(info.rhel_spot_price, "0.189");
+        assert_eq!(info.suse_spot_price, "N/A");
+        assert_eq!(info.on_demand_price, "0.615");
+        assert_eq!(info.on_demand_price_value(), Some(0.615));
+        assert_eq!(info.spot_price(PriceColumn::Linux), "0.123");
+        assert_eq!(info.spot_price(PriceColumn::Mswin), "0.456");
+        assert_eq!(info.spot_price(PriceColumn::Rhel), "0.189");
+        assert_eq!(info.memory_gb, "16");
+        assert_eq!(info.cores, "4");
+    }
+
+    // Helper function to create mock advisor data
+    fn create_mock_advisor_data() -> Value {
+        json!({
+            "spot_advisor": {
+                "us-east-1": {
+                    "Linux": {
+                        "m5.large": {
+                            "r": 0,
+                            "s": 80
+                        },
+                        "t3.medium": {
+                            "r": 1,
+                            "s": 70
+                        }
+                    },
+                    "Windows": {
+                        "m5.large": {
+                            "r": 3,
+                            "s": 55
+                        }
+                    }
+                },
+                "us-west-2": {
+                    "Linux": {
+                        "m5.large": {
+                            "r": 2,
+                            "s": 75
+                        }
+                    }
+                }
+            },
+            "instance_types": {
+                "m5.large": {
+                    "cores": 2,
+                    "ram_gb": 8.0
+                },
+                "t3.medium": {
+                    "cores": 2,
+                    "ram_gb": 4.0
+                }
+            }
+        })
+    }
+
+    // Helper function to create mock price data
+    fn create_mock_price_data() -> Value {
+        json!({
+            "config": {
+                "regions": [
+                    {
+                        "region": "us-east-1",
+                        "instanceTypes": [
+                            {
+                                "type": "m5",
+                                "sizes": [
+                                    {
+                                        "size": "large",
+                                        "valueColumns": [
+                                            {
+                                                "name": "linux",
+                                                "prices": {
+                                                    "USD": "0.123"
+                                                }
+                                            },
+                                            {
+                                                "name": "mswin",
+                                                "prices": {
+                                                    "USD": "0.456"
+                                                }
+                                            },
+                                            {
+                                                "name": "rhel",
+                                                "prices": {
+                                                    "USD": "0.189"
+                                                }
+                                            },
+                                            {
+                                                "name": "suse",
+                                                "prices": {
+                                                    "USD": "0.156"
+                                                }
+                                            }
+                                        ]
+                                    }
+                                ]
+                            },
+                            {
+                                "type": "t3",
+                                "sizes": [
+                                    {
+                                        "size": "medium",
+                                        "valueColumns": [
+                                            {
+                                                "name": "linux",
+                                                "prices": {
+                                                    "USD": "0.042"
+                                                }
+                                            },
+                                            {
+                                                "name": "mswin",
+                                                "prices": {
+                                                    "USD": "0.073"
+                                                }
+                                            }
+                                        ]
+                                    }
+                                ]
+                            }
+                        ]
+                    },
+                    {
+                        "region": "us-west-2",
+                        "instanceTypes": [
+                            {
+                                "type": "m5",
+                                "sizes": [
+                                    {
+                                        "size": "large",
+                                        "valueColumns": [
+                                            {
+                                                "name": "linux",
+                                                "prices": {
+                                                    "USD": "0.119"
+                                                }
+                                            },
+                                            {
+                                                "name": "mswin",
+                                                "prices": {
+                                                    "USD": "0.442"
+                                                }
+                                            }
+                                        ]
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        })
+    }
+
+    // Test display_spot_data with no instance type filter
+    #[test]
+    fn test_display_spot_data_no_filter() -> Result<()> {
+        // Redirect stdout to capture the table output
+        let stdout = io::stdout();
+        let mut _handle = stdout.lock();
+
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with no instance type filter
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions::default(),
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        // We can't easily test the actual table output in a unit test,
+        // but we can verify the function completed successfully
+        Ok(())
+    }
+
+    // Test display_spot_data with instance type filter
+    #[test]
+    fn test_display_spot_data_with_filter() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with an instance type filter
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                instance_type: Some("m5"),
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_spot_data with spot price display
+    #[test]
+    fn test_display_spot_data_with_spot_price() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with spot price display enabled
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                price_columns: &[PriceColumn::Linux, PriceColumn::Mswin],
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_regions with regions from both data sources
+    #[test]
+    fn test_display_regions() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function
+        let result = display_regions(&advisor_data, &price_data);
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_sources with the default data sources
+    #[test]
+    fn test_display_sources() -> Result<()> {
+        let result = display_sources(&crate::aws::data_sources(), None);
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_status() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("spotter-test-sources-{}", std::process::id()));
+        let cache = Cache::new(dir.clone(), std::time::Duration::from_secs(3600));
+        let name = crate::cache::SPOT_PRICE_DATA_FILE;
+        let path = cache.path(name).display().to_string();
+
+        assert_eq!(
+            cache_status(Some(&cache), None),
+            "Not cached (fetched on every run)"
+        );
+        assert_eq!(
+            cache_status(None, Some(name)),
+            "Not cached (--no-cache or no cache directory)"
+        );
+        assert_eq!(
+            cache_status(Some(&cache), Some(name)),
+            format!("{}\nNot fetched yet (TTL 1h 0m)", path)
+        );
+
+        cache.write(name, "callback({})")?;
+        assert!(cache_status(Some(&cache), Some(name)).starts_with(&format!("{}\nFresh, ", path)));
+        let stale = Cache::new(dir.clone(), std::time::Duration::ZERO);
+        assert!(cache_status(Some(&stale), Some(name)).starts_with(&format!("{}\nStale, ", path)));
+        assert!(display_sources(&crate::aws::data_sources(), Some(&cache)).is_ok());
+
+        std::fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_triggers() -> Result<()> {
+        let triggers = vec![Trigger {
+            rule: "m5.xlarge@eu-west-1 price>0.10".to_string(),
+            instance_type: "m5.xlarge".to_string(),
+            region: "eu-west-1".to_string(),
+            price: Some(0.12),
+            interruption_rate: "5-10%".to_string(),
+            savings: Some(70.0),
+        }];
+
+        assert!(display_triggers(&triggers).is_ok());
+        assert!(display_triggers(&[]).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_top() -> Result<()> {
+        let ranked = vec![Ranked {
+            instance_type: "m5.large".to_string(),
+            info: InstanceInfo {
+                interruption_rate: "< 5%".to_string(),
+                savings: "70%".to_string(),
+                linux_spot_price: "0.04".to_string(),
+                windows_spot_price: "N/A".to_string(),
+                rhel_spot_price: "N/A".to_string(),
+                suse_spot_price: "N/A".to_string(),
+                on_demand_price: "N/A".to_string(),
+                memory_gb: "8".to_string(),
+                cores: "2".to_string(),
+            },
+        }];
+
+        assert!(
+            display_top(
+                "us-east-1",
+                &ranked,
+                &["▲1".to_string()],
+                PriceColumn::Linux
+            )
+            .is_ok()
+        );
+        assert!(display_top("us-east-1", &[], &[], PriceColumn::Linux).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_checks() -> Result<()> {
+        let checks = vec![Check {
+            instance_type: "m6i.4xlarge".to_string(),
+            interruption_rate: "10-15%".to_string(),
+            price: Some(0.35),
+            savings: None,
+            violations: vec!["interruption rate 10-15% is above 10%".to_string()],
+        }];
+
+        assert!(display_checks("us-east-1", &checks).is_ok());
+        assert!(display_checks("us-east-1", &[]).is_ok());
+
+        Ok(())
+    }
+
+    // Test display_spot_data with Windows advisor data
+    #[test]
+    fn test_display_spot_data_windows() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with Windows advisor data
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                price_columns: &[PriceColumn::Linux, PriceColumn::Mswin],
+                os: Os::Windows,
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_spot_data with both Linux and Windows advisor data
+    #[test]
+    fn test_display_spot_data_all_os() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with all OS advisor data
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                os: Os::All,
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_spot_data with RHEL and SUSE price columns
+    #[test]
+    fn test_display_spot_data_with_rhel_and_suse_prices() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with RHEL and SUSE price columns
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                price_columns: &[PriceColumn::Rhel, PriceColumn::Suse],
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_families groups instance types by family
+    #[test]
+    fn test_display_families() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function
+        let result = display_families("us-east-1", &advisor_data, &price_data);
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_spot_data_grouped_by_family() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function grouped by family
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                group_by: Some(GroupBy::Family),
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggested_max_price() {
+        let mut info = InstanceInfo {
+            interruption_rate: "< 5%".to_string(),
+            savings: "70%".to_string(),
+            linux_spot_price: "0.1".to_string(),
+            windows_spot_price: "N/A".to_string(),
+            rhel_spot_price: "N/A".to_string(),
+            suse_spot_price: "N/A".to_string(),
+            on_demand_price: "N/A".to_string(),
+            memory_gb: "8".to_string(),
+            cores: "2".to_string(),
+        };
+        let max_price = info.suggested_max_price(PriceColumn::Linux, 20.0).unwrap();
+        assert!((max_price - 0.12).abs() < 1e-9);
+        assert_eq!(info.suggested_max_price(PriceColumn::Mswin, 20.0), None);
+
+        // Capped at the on-demand price
+        info.on_demand_price = "0.11".to_string();
+        assert_eq!(
+            info.suggested_max_price(PriceColumn::Linux, 20.0),
+            Some(0.11)
+        );
+    }
+
+    #[test]
+    fn test_display_spot_data_with_suggested_max_price() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let on_demand_prices = collect_on_demand_prices(&create_mock_on_demand_data());
+
+        // Call the function with the suggested max price
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                price_columns: &[PriceColumn::Linux],
+                on_demand_prices: Some(&on_demand_prices),
+                max_price_margin: Some(20.0),
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_spot_data_with_append_csv() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let path =
+            std::env::temp_dir().join(format!("spotter-test-display-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Call the function appending to the CSV price log
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                instance_type: Some("m5"),
+                append_csv: Some(&path),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
+
+        // A header and a row for m5.large
+        let contents = std::fs::read_to_string(&path)?;
+        assert_eq!(contents.lines().count(), 2);
+        assert!(contents.contains(",us-east-1,m5.large,Linux,< 5%,"));
+
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_spot_data_with_histogram() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with the histogram
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                histogram: true,
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_lines() {
+        let lines = histogram_lines(&[4, 2, 0, 1, 0]);
+        assert_eq!(lines.len(), INTERRUPTION_RATES.len());
+        assert_eq!(lines[0], format!("< 5%   | {} 4", "#".repeat(40)));
+        assert_eq!(lines[1], format!("5-10%  | {} 2", "#".repeat(20)));
+        assert_eq!(lines[2], "10-15% |  0");
+        assert_eq!(lines[3], format!("15-20% | {} 1", "#".repeat(10)));
+
+        // No instance types draws no bars
+        let lines = histogram_lines(&[0; 5]);
+        assert_eq!(lines[4], "> 20%  |  0");
+    }
+
+    #[test]
+    fn test_display_when() -> Result<()> {
+        // Monday 2024-01-01 00:00 UTC, then every hour of two days
+        let samples: Vec<when::PriceSample> = (0..48)
+            .map(|i| when::PriceSample {
+                at: 1_704_067_200 + i * 3600,
+                price: if i % 24 == 14 { 15.0 } else { 10.0 },
+            })
+            .collect();
+        let report = when::analyze(&samples, when::BEST_SLOTS).unwrap();
+
+        // Call the function
+        let result = display_when(
+            "us-east-1",
+            "p4d.24xlarge",
+            "EC2 spot price history",
+            &report,
+            PriceColumn::Linux,
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_cache_info() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("spotter-test-display-{}", std::process::id()));
+        let cache = Cache::new(dir.clone(), std::time::Duration::from_secs(3600));
+
+        // Empty cache
+        assert!(display_cache_info(&cache).is_ok());
+
+        cache.write(crate::cache::SPOT_PRICE_DATA_FILE, "callback({})")?;
+        assert!(display_cache_info(&cache).is_ok());
+
+        std::fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_history() -> Result<()> {
+        let summary = HistorySummary {
+            records: 3,
+            first_recorded_at: 1_700_000_000,
+            last_recorded_at: 1_700_007_200,
+            min_price: Some(0.1),
+            max_price: Some(0.12),
+            avg_price: Some(0.11),
+            first_price: Some(0.1),
+            last_price: Some(0.12),
+            interruption_changes: vec![(1_700_003_600, "< 5%".to_string(), "5-10%".to_string())],
+        };
+
+        // Call the function
+        let result = display_history("us-east-1", "m5.xlarge", &summary, PriceColumn::Linux);
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_timestamp(-86400), "1969-12-31 00:00");
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.0 KiB");
+        assert_eq!(format_size(25 * 1024 * 1024 + 300 * 1024), "25.3 MiB");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        use std::time::Duration;
+
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 5m");
+        assert_eq!(format_duration(Duration::from_secs(90000)), "1d 1h");
+    }
+
+    #[test]
+    fn test_display_stats() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function for all instance types and a family
+        let result = display_stats("us-east-1", None, &advisor_data, &price_data, Os::Linux);
+        assert!(result.is_ok());
+        let result = display_stats(
+            "us-east-1",
+            Some("m5"),
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+        );
+        assert!(result.is_ok());
+
+        // No matching instance types is an error
+        let result = display_stats(
+            "us-east-1",
+            Some("x9"),
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+        );
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_instance_filter() {
+        assert!(matches_instance_filter("m5.large", "m5"));
+        assert!(matches_instance_filter("m5.large", "large"));
+        assert!(matches_instance_filter("m5.large", "m5.large"));
+        assert!(matches_instance_filter("m5a.large", "m5"));
+        assert!(!matches_instance_filter("t3.medium", "m5"));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[0.1]), Some(0.1));
+        assert_eq!(median(&[0.1, 0.2, 0.6]), Some(0.2));
+        assert_eq!(median(&[1.0, 2.0, 4.0, 8.0]), Some(3.0));
+    }
+
+    #[test]
+    fn test_format_range() {
+        assert_eq!(format_range(&[]), "N/A");
+        assert_eq!(format_range(&[4.0]), "4");
+        assert_eq!(format_range(&[2.0, 96.0, 8.0]), "2-96");
+        assert_eq!(format_range(&[0.5, 16.0]), "0.5-16");
+    }
+
+    // Test display_instance_detail with an existing instance type
+    #[test]
+    fn test_display_instance_detail() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function
+        let result = display_instance_detail("us-east-1", "m5.large", &advisor_data, &price_data);
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_instance_detail with an unknown instance type
+    #[test]
+    fn test_display_instance_detail_not_found() {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with an instance type missing from the data
+        let result = display_instance_detail("us-east-1", "x9.huge", &advisor_data, &price_data);
+        assert!(result.is_err());
+
+        // Call the function with a region the instance type isn't available in
+        let result = display_instance_detail("us-west-2", "t3.medium", &advisor_data, &price_data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_instance_info_values() {
+        let info = InstanceInfo {
+            interruption_rate: "10-15%".to_string(),
+            savings: "72%".to_string(),
+            linux_spot_price: "0.0431".to_string(),
+            windows_spot_price: "N/A".to_string(),
+            rhel_spot_price: "N/A".to_string(),
+            suse_spot_price: "N/A".to_string(),
+            on_demand_price: "N/A".to_string(),
+            memory_gb: "4".to_string(),
+            cores: "2".to_string(),
+        };
+
+        assert_eq!(info.interruption_bucket(), Some(2));
+        assert_eq!(info.savings_value(), Some(72.0));
+        assert_eq!(info.spot_price_value(PriceColumn::Linux), Some(0.0431));
+        assert_eq!(info.spot_price_value(PriceColumn::Mswin), None);
+        assert_eq!(info.memory_gb_value(), Some(4.0));
+        assert_eq!(info.cores_value(), Some(2.0));
+    }
+
+    // Test display_comparison with existing instance types
+    #[test]
+    fn test_display_comparison() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function
+        let instance_types = vec!["m5.large".to_string(), "t3.medium".to_string()];
+        let result = display_comparison("us-east-1", &instance_types, &advisor_data, &price_data);
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_comparison with an unknown instance type
+    #[test]
+    fn test_display_comparison_not_found() {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function with an instance type missing from the data
+        let instance_types = vec!["m5.large".to_string(), "x9.huge".to_string()];
+        let result = display_comparison("us-east-1", &instance_types, &advisor_data, &price_data);
+        assert!(result.is_err());
+    }
+
+    // Test display_spot_data sorted and filtered by normalized prices
+    #[test]
+    fn test_display_spot_data_with_normalized_prices() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+
+        // Call the function sorted by price per vCPU and filtered by price per GiB
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                price_columns: &[PriceColumn::Linux],
+                sort_by: SortBy::PricePerVcpu,
+                max_price_per_gib: Some(0.02),
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_instance_info_normalized_prices() {
+        let info = InstanceInfo {
+            interruption_rate: "< 5%".to_string(),
+            savings: "80%".to_string(),
+            linux_spot_price: "0.1".to_string(),
+            windows_spot_price: "N/A".to_string(),
+            rhel_spot_price: "N/A".to_string(),
+            suse_spot_price: "N/A".to_string(),
+            on_demand_price: "N/A".to_string(),
+            memory_gb: "8".to_string(),
+            cores: "2".to_string(),
+        };
+
+        assert_eq!(info.price_per_vcpu(PriceColumn::Linux), Some(0.05));
+        assert_eq!(info.price_per_gib(PriceColumn::Linux), Some(0.0125));
+        assert_eq!(info.price_per_vcpu(PriceColumn::Mswin), None);
+        assert_eq!(info.monthly_cost(PriceColumn::Linux, 730.0), Some(73.0));
+        assert_eq!(info.monthly_cost(PriceColumn::Mswin, 730.0), None);
+    }
+
+    #[test]
+    fn test_format_cost() {
+        assert_eq!(format_cost(Some(31.463)), "31.46");
+        assert_eq!(format_cost(None), "N/A");
+    }
+
+    #[test]
+    fn test_compare_values() {
+        assert_eq!(compare_values(Some(1.0), Some(2.0)), Ordering::Less);
+        assert_eq!(compare_values(Some(1.0), None), Ordering::Less);
+        assert_eq!(compare_values(None, Some(1.0)), Ordering::Greater);
+        assert_eq!(compare_values(None, None), Ordering::Equal);
+    }
+
+    // Helper function to create mock on-demand price data
+    fn create_mock_on_demand_data() -> Value {
+        json!({
+            "regions": {
+                "US East (N. Virginia)": {
+                    "m5.large Linux": {
+                        "Instance Type": "m5.large",
+                        "price": "0.0960000000",
+                        "vCPU": "2"
+                    },
+                    "t3.medium Linux": {
+                        "Instance Type": "t3.medium",
+                        "price": "0.0416000000",
+                        "vCPU": "2"
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_collect_on_demand_prices() {
+        let prices = collect_on_demand_prices(&create_mock_on_demand_data());
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices.get("m5.large"), Some(&"0.096".to_string()));
+        assert_eq!(prices.get("t3.medium"), Some(&"0.0416".to_string()));
+
+        // Data without regions has no prices
+        assert!(collect_on_demand_prices(&json!({})).is_empty());
+    }
+
+    // Test display_spot_data with on-demand prices
+    #[test]
+    fn test_display_spot_data_with_on_demand_prices() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let on_demand_prices = collect_on_demand_prices(&create_mock_on_demand_data());
+
+        // Call the function with on-demand prices
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                price_columns: &[PriceColumn::Linux],
+                on_demand_prices: Some(&on_demand_prices),
+                ..Default::default()
+            },
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_spot_data with price trends
+    #[test]
+    fn test_display_spot_data_with_trends() -> Result<()> {
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let trends = HashMap::from([("m5.large".to_string(), "▁▃█".to_string())]);
+
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                trends: Some(&trends),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_spot_data with price volatilities and their filter
+    #[test]
+    fn test_display_spot_data_with_volatilities() -> Result<()> {
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let volatilities = HashMap::from([("m5.large".to_string(), 3.2)]);
+
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                volatilities: Some(&volatilities),
+                max_volatility: Some(5.0),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_spot_data with instance specs
+    #[test]
+    fn test_display_spot_data_with_specs() -> Result<()> {
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let specs = HashMap::from([(
+            "m5.large".to_string(),
+            InstanceSpecs {
+                architectures: vec!["x86_64".to_string()],
+                network_performance: Some("Up to 10 Gigabit".to_string()),
+                ebs_baseline_mbps: Some(650),
+                gpus: vec![],
+                hypervisor: Some("nitro".to_string()),
+            },
+        )]);
 
-        row_cells.push(Cell::new(&info.savings));
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                specs: Some(&specs),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
 
-        table.add_row(Row::new(row_cells));
+        Ok(())
     }
 
-    // Print the number of instances found
-    log::info!(
-        "Found {} spot instances for region: {}, filtering by instance type: {}",
-        table.len() - 1,
-        region,
-        instance_type.unwrap_or("all")
-    );
-
-    // Print the table
-    table.printstd();
+    #[test]
+    fn test_instance_info_effective_cost() {
+        let mut info = InstanceInfo {
+            interruption_rate: "> 20%".to_string(),
+            savings: "90%".to_string(),
+            linux_spot_price: "0.5".to_string(),
+            windows_spot_price: "N/A".to_string(),
+            rhel_spot_price: "N/A".to_string(),
+            suse_spot_price: "N/A".to_string(),
+            on_demand_price: "N/A".to_string(),
+            memory_gb: "8".to_string(),
+            cores: "2".to_string(),
+        };
 
-    Ok(())
-}
+        assert_eq!(
+            info.effective_cost(PriceColumn::Linux, &[0.0, 5.0, 10.0, 20.0, 50.0]),
+            Some(0.75)
+        );
+        assert_eq!(
+            info.effective_cost(PriceColumn::Mswin, &DEFAULT_RISK_PENALTIES),
+            None
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
-    use std::io;
+        // Unknown interruption rates have no effective cost
+        info.interruption_rate = "N/A".to_string();
+        assert_eq!(
+            info.effective_cost(PriceColumn::Linux, &DEFAULT_RISK_PENALTIES),
+            None
+        );
+    }
 
+    // Test display_recommendations with recommendations from the mock data
     #[test]
-    fn test_instance_info_creation() {
-        let info = InstanceInfo {
-            interruption_rate: "< 5%".to_string(),
-            savings: "80%".to_string(),
-            linux_spot_price: "0.123".to_string(),
-            windows_spot_price: "0.456".to_string(),
-            memory_gb: "16".to_string(),
-            cores: "4".to_string(),
-        };
+    fn test_display_recommendations() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let instance_data =
+            collect_instance_data("us-east-1", &advisor_data, &price_data, Os::Linux);
+        let recommendations = crate::recommend::recommend(
+            &instance_data,
+            "us-east-1",
+            Os::Linux,
+            &crate::recommend::Constraints::default(),
+            &DEFAULT_RISK_PENALTIES,
+        );
+        assert_eq!(recommendations.len(), 2);
 
-        assert_eq!(info.interruption_rate, "< 5%");
-        assert_eq!(info.savings, "80%");
-        assert_eq!(info.linux_spot_price, "0.123");
-        assert_eq!(info.windows_spot_price, "0.456");
-        assert_eq!(info.memory_gb, "16");
-        assert_eq!(info.cores, "4");
+        // Call the function
+        let result = display_recommendations("us-east-1", &recommendations, PriceColumn::Linux);
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
     }
 
-    // Helper function to create mock advisor data
-    fn create_mock_advisor_data() -> Value {
-        json!({
+    // Helper function to create mock data with Graviton instance types
+    fn create_mock_graviton_data() -> (Value, Value) {
+        let advisor_data = json!({
             "spot_advisor": {
                 "us-east-1": {
                     "Linux": {
-                        "m5.large": {
-                            "r": 0,
-                            "s": 80
-                        },
-                        "t3.medium": {
-                            "r": 1,
-                            "s": 70
-                        }
-                    }
-                },
-                "us-west-2": {
-                    "Linux": {
-                        "m5.large": {
-                            "r": 2,
-                            "s": 75
-                        }
+                        "m5.2xlarge": { "r": 1, "s": 70 },
+                        "m6g.2xlarge": { "r": 0, "s": 75 },
+                        "m7g.2xlarge": { "r": 0, "s": 72 }
                     }
                 }
             },
             "instance_types": {
-                "m5.large": {
-                    "cores": 2,
-                    "ram_gb": 8.0
-                },
-                "t3.medium": {
-                    "cores": 2,
-                    "ram_gb": 4.0
-                }
+                "m5.2xlarge": { "cores": 8, "ram_gb": 32.0 },
+                "m6g.2xlarge": { "cores": 8, "ram_gb": 32.0 },
+                "m7g.2xlarge": { "cores": 8, "ram_gb": 32.0 }
             }
-        })
-    }
-
-    // Helper function to create mock price data
-    fn create_mock_price_data() -> Value {
-        json!({
+        });
+        let size = |size: &str, price: &str| {
+            json!({
+                "size": size,
+                "valueColumns": [{ "name": "linux", "prices": { "USD": price } }]
+            })
+        };
+        let price_data = json!({
             "config": {
-                "regions": [
-                    {
-                        "region": "us-east-1",
-                        "instanceTypes": [
-                            {
-                                "type": "m5",
-                                "sizes": [
-                                    {
-                                        "size": "large",
-                                        "valueColumns": [
-                                            {
-                                                "name": "linux",
-                                                "prices": {
-                                                    "USD": "0.123"
-                                                }
-                                            },
-                                            {
-                                                "name": "mswin",
-                                                "prices": {
-                                                    "USD": "0.456"
-                                                }
-                                            }
-                                        ]
-                                    }
-                                ]
-                            },
-                            {
-                                "type": "t3",
-                                "sizes": [
-                                    {
-                                        "size": "medium",
-                                        "valueColumns": [
-                                            {
-                                                "name": "linux",
-                                                "prices": {
-                                                    "USD": "0.042"
-                                                }
-                                            },
-                                            {
-                                                "name": "mswin",
-                                                "prices": {
-                                                    "USD": "0.073"
-                                                }
-                                            }
-                                        ]
-                                    }
-                                ]
-                            }
-                        ]
-                    },
-                    {
-                        "region": "us-west-2",
-                        "instanceTypes": [
-                            {
-                                "type": "m5",
-                                "sizes": [
-                                    {
-                                        "size": "large",
-                                        "valueColumns": [
-                                            {
-                                                "name": "linux",
-                                                "prices": {
-                                                    "USD": "0.119"
-                                                }
-                                            },
-                                            {
-                                                "name": "mswin",
-                                                "prices": {
-                                                    "USD": "0.442"
-                                                }
-                                            }
-                                        ]
-                                    }
-                                ]
-                            }
-                        ]
-                    }
-                ]
+                "regions": [{
+                    "region": "us-east-1",
+                    "instanceTypes": [
+                        { "type": "m5", "sizes": [size("2xlarge", "0.16")] },
+                        { "type": "m6g", "sizes": [size("2xlarge", "0.12")] },
+                        { "type": "m7g", "sizes": [size("2xlarge", "0.14")] }
+                    ]
+                }]
             }
-        })
+        });
+        (advisor_data, price_data)
     }
 
-    // Test display_spot_data with no instance type filter
+    // Test display_graviton_equivalents with an x86 instance type
     #[test]
-    fn test_display_spot_data_no_filter() -> Result<()> {
-        // Redirect stdout to capture the table output
-        let stdout = io::stdout();
-        let mut _handle = stdout.lock();
+    fn test_display_graviton_equivalents() -> Result<()> {
+        let (advisor_data, price_data) = create_mock_graviton_data();
+
+        let result = display_graviton_equivalents(
+            "us-east-1",
+            "m5.2xlarge",
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+        );
+
+        // Check that the function executed without errors
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    // Test display_graviton_equivalents with a Graviton or unknown instance type
+    #[test]
+    fn test_display_graviton_equivalents_invalid() {
+        let (advisor_data, price_data) = create_mock_graviton_data();
+
+        let result = display_graviton_equivalents(
+            "us-east-1",
+            "m6g.2xlarge",
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+        );
+        assert!(result.is_err());
+
+        let result = display_graviton_equivalents(
+            "us-east-1",
+            "c5.2xlarge",
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_upgrade_suggestion() {
+        let (advisor_data, price_data) = create_mock_graviton_data();
+        let instance_data =
+            collect_instance_data("us-east-1", &advisor_data, &price_data, Os::Linux);
+        let mut region_infos = region_infos(&instance_data, "us-east-1", "Linux");
 
+        // m6g.2xlarge is not a newer generation of m5.2xlarge
+        let m5 = region_infos["m5.2xlarge"].clone();
+        assert_eq!(
+            upgrade_suggestion("m5.2xlarge", &m5, &region_infos, PriceColumn::Linux),
+            "-"
+        );
+
+        // m6i.2xlarge is cheaper and safer
+        let m6i = InstanceInfo {
+            linux_spot_price: "0.12".to_string(),
+            interruption_rate: "< 5%".to_string(),
+            ..m5.clone()
+        };
+        region_infos.insert("m6i.2xlarge", &m6i);
+        assert_eq!(
+            upgrade_suggestion("m5.2xlarge", &m5, &region_infos, PriceColumn::Linux),
+            "m6i.2xlarge (-25%, < 5%)"
+        );
+    }
+
+    // Test display_spot_data with upgrade suggestions
+    #[test]
+    fn test_display_spot_data_with_upgrade_suggestions() -> Result<()> {
         // Create mock data
         let advisor_data = create_mock_advisor_data();
         let price_data = create_mock_price_data();
 
-        // Call the function with no instance type filter
-        let result = display_spot_data("us-east-1", None, &advisor_data, &price_data, false);
+        // Call the function with upgrade suggestions
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                suggest_upgrades: true,
+                ..Default::default()
+            },
+        );
 
         // Check that the function executed without errors
         assert!(result.is_ok());
 
-        // We can't easily test the actual table output in a unit test,
-        // but we can verify the function completed successfully
         Ok(())
     }
 
-    // Test display_spot_data with instance type filter
+    // Test display_spot_data with similar instance suggestions for risky instance types
     #[test]
-    fn test_display_spot_data_with_filter() -> Result<()> {
+    fn test_display_spot_data_with_similar_suggestions() -> Result<()> {
         // Create mock data
         let advisor_data = create_mock_advisor_data();
         let price_data = create_mock_price_data();
 
-        // Call the function with an instance type filter
-        let result = display_spot_data("us-east-1", Some("m5"), &advisor_data, &price_data, false);
+        // Call the function with similar instance suggestions
+        let result = display_spot_data(
+            "us-east-1",
+            &advisor_data,
+            &price_data,
+            &DisplayOptions {
+                instance_type: Some("m5"),
+                suggest_similar: true,
+                ..Default::default()
+            },
+        );
 
         // Check that the function executed without errors
         assert!(result.is_ok());
@@ -556,19 +4206,176 @@ mod tests {
         Ok(())
     }
 
-    // Test display_spot_data with spot price display
     #[test]
-    fn test_display_spot_data_with_spot_price() -> Result<()> {
+    fn test_display_estimate() -> Result<()> {
         // Create mock data
         let advisor_data = create_mock_advisor_data();
         let price_data = create_mock_price_data();
+        let on_demand_prices = collect_on_demand_prices(&create_mock_on_demand_data());
+        let fleet = vec![
+            FleetEntry {
+                count: 4,
+                instance_type: "m5.large".to_string(),
+            },
+            FleetEntry {
+                count: 2,
+                instance_type: "t3.medium".to_string(),
+            },
+        ];
 
-        // Call the function with spot price display enabled
-        let result = display_spot_data("us-east-1", None, &advisor_data, &price_data, true);
+        // Call the function with and without on-demand prices
+        let result = display_estimate(
+            "us-east-1",
+            &fleet,
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            Some(&on_demand_prices),
+            730.0,
+        );
+        assert!(result.is_ok());
+        let result = display_estimate(
+            "us-east-1",
+            &fleet,
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            None,
+            730.0,
+        );
+        assert!(result.is_ok());
+
+        // Unknown instance types are an error
+        let fleet = vec![FleetEntry {
+            count: 1,
+            instance_type: "x9.large".to_string(),
+        }];
+        let result = display_estimate(
+            "us-east-1",
+            &fleet,
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            None,
+            730.0,
+        );
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_simulation() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let on_demand_prices = collect_on_demand_prices(&create_mock_on_demand_data());
+
+        // Call the function with and without on-demand prices
+        let result = display_simulation(
+            "us-east-1",
+            "m5.large",
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            Some(&on_demand_prices),
+            &simulate::Workload {
+                runtime_hours: 12.0,
+                checkpoint_loss_hours: 0.5,
+            },
+        );
+        assert!(result.is_ok());
+        let result = display_simulation(
+            "us-east-1",
+            "t3.medium",
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            None,
+            &simulate::Workload {
+                runtime_hours: 12.0,
+                checkpoint_loss_hours: 12.0,
+            },
+        );
+        assert!(result.is_ok());
+
+        // Unknown instance types are an error
+        let result = display_simulation(
+            "us-east-1",
+            "x9.large",
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            None,
+            &simulate::Workload {
+                runtime_hours: 12.0,
+                checkpoint_loss_hours: 0.5,
+            },
+        );
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_strategy() -> Result<()> {
+        // Create mock data
+        let advisor_data = create_mock_advisor_data();
+        let price_data = create_mock_price_data();
+        let types = vec!["m5".to_string(), "t3".to_string()];
+
+        // Call the function
+        let result = display_strategy(
+            "us-east-1",
+            &types,
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            &DEFAULT_RISK_PENALTIES,
+        );
+        assert!(result.is_ok());
+
+        // No matching pools is an error
+        let result = display_strategy(
+            "us-east-1",
+            &["x9".to_string()],
+            &advisor_data,
+            &price_data,
+            Os::Linux,
+            &DEFAULT_RISK_PENALTIES,
+        );
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_diff() -> Result<()> {
+        // Create mock snapshots where the price of m5.large went up
+        let old = Snapshot::new(create_mock_advisor_data(), create_mock_price_data());
+        let mut new = old.clone();
+        new.price_data["config"]["regions"][0]["instanceTypes"][0]["sizes"][0]["valueColumns"][0]
+            ["prices"]["USD"] = json!("0.150");
+
+        // Call the function
+        let result = display_diff("us-east-1", &old, &new, Os::Linux);
 
         // Check that the function executed without errors
         assert!(result.is_ok());
 
         Ok(())
     }
+
+    #[test]
+    fn test_format_change() {
+        assert_eq!(
+            format_change("70%", "70%", Some("+0 pp".to_string())),
+            "70%"
+        );
+        assert_eq!(
+            format_change("70%", "65%", Some("-5 pp".to_string())),
+            "70% -> 65% (-5 pp)"
+        );
+        assert_eq!(format_change("N/A", "0.1", None), "N/A -> 0.1");
+    }
 }
@@ -0,0 +1,283 @@
+pub mod asg;
+pub mod awscli;
+pub mod batch;
+pub mod ec2_fleet;
+pub mod ecs;
+pub mod eksctl;
+pub mod emr;
+pub mod karpenter;
+pub mod nomad;
+pub mod ray;
+pub mod skypilot;
+pub mod slurm;
+pub mod spot_fleet;
+pub mod terraform;
+
+use crate::cli::{Os, SelectionArgs};
+use crate::display::InstanceData;
+use crate::instance;
+use crate::recommend::{self, AllocationStrategy, Constraints, Recommendation, StrategyAdvice};
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+/// Returns the recommended instance types meeting the selection, spread across families and
+/// ordered by effective cost
+pub fn select(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    selection: &SelectionArgs,
+    risk_penalties: &[f64],
+) -> Result<Vec<Recommendation>> {
+    let constraints = Constraints {
+        min_vcpus: selection.vcpus.and_then(|vcpus| vcpus.min).map(f64::from),
+        min_memory_gb: selection.memory,
+        max_interruption: Some(selection.max_interruption),
+    };
+    let recommendations: Vec<Recommendation> =
+        recommend::recommend(instance_data, region, os, &constraints, risk_penalties)
+            .into_iter()
+            .filter(|recommendation| {
+                selection.vcpus.is_none_or(|vcpus| {
+                    recommendation
+                        .info
+                        .cores_value()
+                        .is_some_and(|cores| vcpus.contains(cores))
+                })
+            })
+            .collect();
+
+    let mut picked = recommend::diversify(&recommendations, selection.count);
+    if picked.is_empty() {
+        return Err(anyhow!(
+            "No instance types in {} meet the constraints, try relaxing them",
+            region
+        ));
+    }
+    // Exports list the instance types in order of preference
+    picked.sort_by(|a, b| a.effective_cost.total_cmp(&b.effective_cost));
+    Ok(picked)
+}
+
+/// Maximum number of spot pools lowest-price allocation spreads across in an Auto Scaling group or
+/// an EC2 or Spot Fleet
+const MAX_SPOT_INSTANCE_POOLS: usize = 20;
+
+/// Returns the number of spot pools lowest-price allocation spreads across, one per recommended
+/// instance type up to the maximum. Lowest-price only spreads across as many pools as it's told
+/// to, while the other strategies pick their pools themselves and get None
+pub fn spot_instance_pools(
+    advice: &StrategyAdvice,
+    recommendations: &[Recommendation],
+) -> Option<usize> {
+    (advice.strategy == AllocationStrategy::LowestPrice)
+        .then(|| recommendations.len().min(MAX_SPOT_INSTANCE_POOLS))
+}
+
+/// Returns the advised spot allocation strategy of the recommendations, logging its reasons
+pub fn strategy(recommendations: &[Recommendation], os: Os) -> Result<StrategyAdvice> {
+    let advice = recommend::advise_strategy(recommendations, os.price_column())
+        .ok_or_else(|| anyhow!("No spot prices of the recommended instance types"))?;
+    for reason in &advice.reasons {
+        log::info!("{}: {}", advice.strategy.name(), reason);
+    }
+    Ok(advice)
+}
+
+/// Returns the instance types of the recommendations
+pub fn instance_types(recommendations: &[Recommendation]) -> Vec<String> {
+    recommendations
+        .iter()
+        .map(|recommendation| recommendation.instance_type.clone())
+        .collect()
+}
+
+/// Returns the Kubernetes architectures ("amd64", "arm64") of the recommendations
+pub fn architectures(recommendations: &[Recommendation]) -> Vec<&'static str> {
+    let (arm, x86): (Vec<_>, Vec<_>) = recommendations
+        .iter()
+        .partition(|r| instance::is_graviton(instance::family(&r.instance_type)));
+    [
+        (!x86.is_empty()).then_some("amd64"),
+        (!arm.is_empty()).then_some("arm64"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Returns the overrides of every recommended instance type in every Availability Zone
+pub fn overrides(instance_types: &[String], availability_zones: &[String]) -> Vec<Value> {
+    instance_types
+        .iter()
+        .flat_map(|instance_type| {
+            if availability_zones.is_empty() {
+                return vec![json!({ "InstanceType": instance_type })];
+            }
+            availability_zones
+                .iter()
+                .map(|zone| json!({ "InstanceType": instance_type, "AvailabilityZone": zone }))
+                .collect()
+        })
+        .collect()
+}
+
+/// Validates that the Availability Zones are in the region, like "us-east-1a" in "us-east-1"
+pub fn validate_availability_zones(region: &str, availability_zones: &[String]) -> Result<()> {
+    for zone in availability_zones {
+        let suffix = zone.strip_prefix(region).unwrap_or_default();
+        if suffix.is_empty() || !suffix.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(anyhow!(
+                "Availability Zone '{}' is not in region '{}' (e.g., {}a)",
+                zone,
+                region,
+                region
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns a comment line summarizing the recommendations of an export
+pub fn summary(region: &str, recommendations: &[Recommendation]) -> String {
+    let types: Vec<String> = recommendations
+        .iter()
+        .map(|r| format!("{} ({})", r.instance_type, r.info.interruption_rate))
+        .collect();
+    format!(
+        "Generated by spotter from the spot recommendations in {}: {}",
+        region,
+        types.join(", ")
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cli::{DEFAULT_RISK_PENALTIES, VcpuRange};
+    use crate::display::tests::{info, instance_data};
+
+    pub(crate) fn selection() -> SelectionArgs {
+        SelectionArgs {
+            vcpus: None,
+            memory: None,
+            max_interruption: 10.0,
+            count: 10,
+        }
+    }
+
+    /// Recommendations of m6i.xlarge and m7g.xlarge, the cheapest first
+    pub(crate) fn recommendations() -> Vec<Recommendation> {
+        let instance_data = instance_data([
+            ("m6i.xlarge", "Linux", "us-east-1", info("< 5%", "0.08")),
+            ("m7g.xlarge", "Linux", "us-east-1", info("5-10%", "0.06")),
+        ]);
+        select(
+            &instance_data,
+            "us-east-1",
+            Os::Linux,
+            &selection(),
+            &DEFAULT_RISK_PENALTIES,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_select() {
+        let instance_data = instance_data([
+            ("m6i.xlarge", "Linux", "us-east-1", info("< 5%", "0.08")),
+            (
+                "m6i.4xlarge",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.3").with_size("16", "16"),
+            ),
+            (
+                "m6i.8xlarge",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.6").with_size("32", "16"),
+            ),
+            ("c5.xlarge", "Linux", "us-east-1", info("> 20%", "0.03")),
+            ("m7g.xlarge", "Linux", "us-east-1", info("5-10%", "0.06")),
+        ]);
+        let selection = SelectionArgs {
+            vcpus: Some(VcpuRange {
+                min: Some(4),
+                max: Some(16),
+            }),
+            ..selection()
+        };
+        let picked = select(
+            &instance_data,
+            "us-east-1",
+            Os::Linux,
+            &selection,
+            &DEFAULT_RISK_PENALTIES,
+        )
+        .unwrap();
+        assert_eq!(
+            instance_types(&picked),
+            vec!["m7g.xlarge", "m6i.xlarge", "m6i.4xlarge"]
+        );
+
+        let selection = SelectionArgs {
+            max_interruption: 0.0,
+            ..selection
+        };
+        assert!(
+            select(
+                &instance_data,
+                "us-east-1",
+                Os::Linux,
+                &selection,
+                &DEFAULT_RISK_PENALTIES
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_spot_instance_pools() {
+        let advice = |strategy| StrategyAdvice {
+            strategy,
+            reasons: vec![],
+        };
+        assert_eq!(
+            spot_instance_pools(&advice(AllocationStrategy::LowestPrice), &recommendations()),
+            Some(2)
+        );
+        assert_eq!(
+            spot_instance_pools(
+                &advice(AllocationStrategy::PriceCapacityOptimized),
+                &recommendations()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_overrides_without_availability_zones() {
+        let types = vec!["m6i.xlarge".to_string()];
+        assert_eq!(
+            overrides(&types, &[]),
+            vec![json!({ "InstanceType": "m6i.xlarge" })]
+        );
+    }
+
+    #[test]
+    fn test_validate_availability_zones() {
+        let zones = |zones: &[&str]| zones.iter().map(|z| z.to_string()).collect::<Vec<_>>();
+        assert!(
+            validate_availability_zones("us-east-1", &zones(&["us-east-1a", "us-east-1f"])).is_ok()
+        );
+        assert!(validate_availability_zones("us-east-1", &zones(&["us-west-2a"])).is_err());
+        assert!(validate_availability_zones("us-east-1", &zones(&["us-east-1"])).is_err());
+        assert!(validate_availability_zones("us-east-1", &zones(&["us-east-12a"])).is_err());
+    }
+
+    #[test]
+    fn test_architectures() {
+        assert_eq!(architectures(&recommendations()), vec!["amd64", "arm64"]);
+    }
+}
@@ -0,0 +1,115 @@
+use super::{instance_types, spot_instance_pools};
+use crate::recommend::{Recommendation, StrategyAdvice};
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Options of the instances distribution of the Auto Scaling group
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsgOptions<'a> {
+    pub launch_template: &'a str,
+    /// Minimum number of on-demand instances
+    pub on_demand_base: u32,
+    /// Percentage of on-demand instances above the base
+    pub on_demand_percentage: u32,
+}
+
+/// Returns the MixedInstancesPolicy overriding the launch template with the recommended instance
+/// types in order of preference and the advised spot allocation strategy
+pub fn mixed_instances_policy(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &AsgOptions,
+) -> Value {
+    let overrides: Vec<Value> = instance_types(recommendations)
+        .into_iter()
+        .map(|instance_type| json!({ "InstanceType": instance_type }))
+        .collect();
+    let mut distribution = json!({
+        "OnDemandBaseCapacity": options.on_demand_base,
+        "OnDemandPercentageAboveBaseCapacity": options.on_demand_percentage,
+        "SpotAllocationStrategy": advice.strategy.name(),
+    });
+    if let Some(pools) = spot_instance_pools(advice, recommendations) {
+        distribution["SpotInstancePools"] = json!(pools);
+    }
+
+    json!({
+        "LaunchTemplate": {
+            "LaunchTemplateSpecification": {
+                "LaunchTemplateName": options.launch_template,
+                "Version": "$Latest",
+            },
+            "Overrides": overrides,
+        },
+        "InstancesDistribution": distribution,
+    })
+}
+
+/// Renders the MixedInstancesPolicy as JSON for `aws autoscaling` calls or CloudFormation
+pub fn render(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &AsgOptions,
+) -> Result<String> {
+    Ok(
+        serde_json::to_string_pretty(&mixed_instances_policy(recommendations, advice, options))?
+            + "\n",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+    use crate::recommend::AllocationStrategy;
+
+    fn options() -> AsgOptions<'static> {
+        AsgOptions {
+            launch_template: "web",
+            on_demand_base: 1,
+            on_demand_percentage: 0,
+        }
+    }
+
+    #[test]
+    fn test_mixed_instances_policy() {
+        let advice = StrategyAdvice {
+            strategy: AllocationStrategy::PriceCapacityOptimized,
+            reasons: vec![],
+        };
+        let policy = mixed_instances_policy(&recommendations(), &advice, &options());
+        assert_eq!(
+            policy["LaunchTemplate"]["Overrides"],
+            json!([{ "InstanceType": "m7g.xlarge" }, { "InstanceType": "m6i.xlarge" }])
+        );
+        assert_eq!(
+            policy["LaunchTemplate"]["LaunchTemplateSpecification"]["LaunchTemplateName"],
+            json!("web")
+        );
+        assert_eq!(
+            policy["InstancesDistribution"],
+            json!({
+                "OnDemandBaseCapacity": 1,
+                "OnDemandPercentageAboveBaseCapacity": 0,
+                "SpotAllocationStrategy": "price-capacity-optimized",
+            })
+        );
+    }
+
+    #[test]
+    fn test_mixed_instances_policy_with_lowest_price() {
+        let advice = StrategyAdvice {
+            strategy: AllocationStrategy::LowestPrice,
+            reasons: vec![],
+        };
+        let policy = mixed_instances_policy(&recommendations(), &advice, &options());
+        assert_eq!(
+            policy["InstancesDistribution"]["SpotAllocationStrategy"],
+            json!("lowest-price")
+        );
+        assert_eq!(
+            policy["InstancesDistribution"]["SpotInstancePools"],
+            json!(2)
+        );
+    }
+}
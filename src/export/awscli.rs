@@ -0,0 +1,236 @@
+use crate::cli::Os;
+use crate::instance;
+use std::fmt::Write;
+
+/// Options of the AWS CLI command launching a spot instance
+#[derive(Clone, Debug, PartialEq)]
+pub struct AwsCliOptions<'a> {
+    pub region: &'a str,
+    pub instance_type: &'a str,
+    pub os: Os,
+    pub count: u32,
+    /// Suggested max price (USD/hour) of the spot instances
+    pub max_price: f64,
+    /// Launch template providing the AMI and the rest of the launch configuration
+    pub launch_template: Option<&'a str>,
+    pub subnet_id: Option<&'a str>,
+    pub security_group_ids: &'a [String],
+    pub key_name: Option<&'a str>,
+    /// Emit `aws ec2 create-fleet` instead of `aws ec2 run-instances`
+    pub create_fleet: bool,
+}
+
+/// Quotes a shell word with single quotes unless it only has characters safe to leave bare
+fn shell_quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_.,/:=@%+".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Returns the SSM parameter of the latest Amazon Linux 2023 or Windows Server AMI of the
+/// instance type's architecture, resolved by EC2 at launch
+fn latest_image(instance_type: &str, os: Os) -> String {
+    let parameter = match os {
+        Os::Windows => {
+            "/aws/service/ami-windows-latest/Windows_Server-2022-English-Full-Base".to_string()
+        }
+        Os::Linux | Os::All => {
+            let architecture = if instance::is_graviton(instance::family(instance_type)) {
+                "arm64"
+            } else {
+                "x86_64"
+            };
+            format!(
+                "/aws/service/ami-amazon-linux-latest/al2023-ami-kernel-default-{}",
+                architecture
+            )
+        }
+    };
+    format!("resolve:ssm:{}", parameter)
+}
+
+fn launch_template_specification(launch_template: &str) -> String {
+    format!("LaunchTemplateName={},Version=$Latest", launch_template)
+}
+
+/// Returns the arguments of `aws ec2 run-instances` launching one-time spot instances
+fn run_instances_args(options: &AwsCliOptions) -> Vec<(&'static str, String)> {
+    let mut args = vec![
+        ("--region", options.region.to_string()),
+        ("--instance-type", options.instance_type.to_string()),
+    ];
+    match options.launch_template {
+        Some(launch_template) => args.push((
+            "--launch-template",
+            launch_template_specification(launch_template),
+        )),
+        None => args.push((
+            "--image-id",
+            latest_image(options.instance_type, options.os),
+        )),
+    }
+    args.push(("--count", options.count.to_string()));
+    if let Some(subnet_id) = options.subnet_id {
+        args.push(("--subnet-id", subnet_id.to_string()));
+    }
+    if !options.security_group_ids.is_empty() {
+        args.push(("--security-group-ids", options.security_group_ids.join(" ")));
+    }
+    if let Some(key_name) = options.key_name {
+        args.push(("--key-name", key_name.to_string()));
+    }
+    args.push((
+        "--instance-market-options",
+        format!(
+            "MarketType=spot,SpotOptions={{MaxPrice={:.4},SpotInstanceType=one-time,InstanceInterruptionBehavior=terminate}}",
+            options.max_price
+        ),
+    ));
+    args
+}
+
+/// Returns the arguments of `aws ec2 create-fleet` launching an instant fleet of spot instances
+/// from a launch template
+fn create_fleet_args(options: &AwsCliOptions) -> Vec<(&'static str, String)> {
+    let mut override_fields = vec![
+        format!("InstanceType={}", options.instance_type),
+        format!("MaxPrice={:.4}", options.max_price),
+    ];
+    if let Some(subnet_id) = options.subnet_id {
+        override_fields.push(format!("SubnetId={}", subnet_id));
+    }
+    vec![
+        ("--region", options.region.to_string()),
+        ("--type", "instant".to_string()),
+        (
+            "--spot-options",
+            "AllocationStrategy=price-capacity-optimized".to_string(),
+        ),
+        (
+            "--launch-template-configs",
+            format!(
+                "LaunchTemplateSpecification={{{}}},Overrides=[{{{}}}]",
+                launch_template_specification(options.launch_template.unwrap_or_default()),
+                override_fields.join(",")
+            ),
+        ),
+        (
+            "--target-capacity-specification",
+            format!(
+                "TotalTargetCapacity={},DefaultTargetCapacityType=spot",
+                options.count
+            ),
+        ),
+    ]
+}
+
+/// Renders a ready-to-run AWS CLI command launching spot instances of the instance type at the
+/// suggested max price, one argument per line
+pub fn render(options: &AwsCliOptions) -> String {
+    let (command, args) = if options.create_fleet {
+        ("create-fleet", create_fleet_args(options))
+    } else {
+        ("run-instances", run_instances_args(options))
+    };
+
+    let mut output = format!("aws ec2 {}", command);
+    for (flag, value) in args {
+        // Security groups are separate words of a single flag
+        let value = if flag == "--security-group-ids" {
+            value
+                .split(' ')
+                .map(shell_quote)
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else {
+            shell_quote(&value)
+        };
+        write!(output, " \\\n  {} {}", flag, value).unwrap();
+    }
+    output + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> AwsCliOptions<'static> {
+        AwsCliOptions {
+            region: "us-east-1",
+            instance_type: "m6i.xlarge",
+            os: Os::Linux,
+            count: 2,
+            max_price: 0.0912,
+            launch_template: None,
+            subnet_id: Some("subnet-1"),
+            security_group_ids: &[],
+            key_name: None,
+            create_fleet: false,
+        }
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("m6i.xlarge"), "m6i.xlarge");
+        assert_eq!(
+            shell_quote("Version=$Latest"),
+            "'Version=$Latest'".to_string()
+        );
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn test_latest_image() {
+        assert_eq!(
+            latest_image("m7g.large", Os::Linux),
+            "resolve:ssm:/aws/service/ami-amazon-linux-latest/al2023-ami-kernel-default-arm64"
+        );
+        assert!(latest_image("m6i.large", Os::Linux).ends_with("x86_64"));
+        assert!(latest_image("m6i.large", Os::Windows).contains("ami-windows-latest"));
+    }
+
+    #[test]
+    fn test_render_run_instances() {
+        assert_eq!(
+            render(&options()),
+            "aws ec2 run-instances \\
+  --region us-east-1 \\
+  --instance-type m6i.xlarge \\
+  --image-id resolve:ssm:/aws/service/ami-amazon-linux-latest/al2023-ami-kernel-default-x86_64 \\
+  --count 2 \\
+  --subnet-id subnet-1 \\
+  --instance-market-options 'MarketType=spot,SpotOptions={MaxPrice=0.0912,SpotInstanceType=one-time,InstanceInterruptionBehavior=terminate}'
+"
+        );
+
+        let security_group_ids = ["sg-1".to_string(), "sg-2".to_string()];
+        let output = render(&AwsCliOptions {
+            launch_template: Some("web"),
+            security_group_ids: &security_group_ids,
+            ..options()
+        });
+        assert!(output.contains("--launch-template 'LaunchTemplateName=web,Version=$Latest'"));
+        assert!(output.contains("--security-group-ids sg-1 sg-2"));
+        assert!(!output.contains("--image-id"));
+    }
+
+    #[test]
+    fn test_render_create_fleet() {
+        let output = render(&AwsCliOptions {
+            launch_template: Some("web"),
+            create_fleet: true,
+            ..options()
+        });
+        assert!(output.starts_with("aws ec2 create-fleet \\\n  --region us-east-1"));
+        assert!(output.contains(
+            "--launch-template-configs 'LaunchTemplateSpecification={LaunchTemplateName=web,Version=$Latest},Overrides=[{InstanceType=m6i.xlarge,MaxPrice=0.0912,SubnetId=subnet-1}]'"
+        ));
+        assert!(output.contains(
+            "--target-capacity-specification TotalTargetCapacity=2,DefaultTargetCapacityType=spot"
+        ));
+    }
+}
@@ -0,0 +1,127 @@
+use super::instance_types;
+use crate::recommend::{AllocationStrategy, Recommendation, StrategyAdvice};
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Options of the AWS Batch compute environment
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchOptions<'a> {
+    pub name: &'a str,
+    pub max_vcpus: u32,
+    pub subnets: &'a [String],
+    pub security_group_ids: &'a [String],
+    pub instance_role: &'a str,
+    /// Margin (%) over the spot prices of the suggested bid percentage
+    pub max_price_margin: f64,
+}
+
+/// Returns the name of an allocation strategy in AWS Batch, which has no lowest-price strategy
+/// and instead favors the cheapest instance types with BEST_FIT_PROGRESSIVE
+fn allocation_strategy(strategy: AllocationStrategy) -> &'static str {
+    match strategy {
+        AllocationStrategy::LowestPrice => "BEST_FIT_PROGRESSIVE",
+        AllocationStrategy::PriceCapacityOptimized => "SPOT_PRICE_CAPACITY_OPTIMIZED",
+    }
+}
+
+/// Suggests the bid percentage of on-demand prices covering every recommended instance type's
+/// spot price plus the margin (%), derived from the advisor savings. Returns `None` without any
+/// savings.
+pub fn bid_percentage(recommendations: &[Recommendation], margin: f64) -> Option<u32> {
+    recommendations
+        .iter()
+        .filter_map(|r| r.info.savings_value())
+        .map(|savings| {
+            let percentage = (1.0 - savings / 100.0) * (1.0 + margin / 100.0) * 100.0;
+            // Rounds up to a whole percentage, ignoring floating point noise like 36.00000000000001
+            (percentage - 1e-9).ceil().clamp(1.0, 100.0) as u32
+        })
+        .max()
+}
+
+/// Returns the CreateComputeEnvironment request of a managed SPOT compute environment of the
+/// recommended instance types
+pub fn compute_environment(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &BatchOptions,
+) -> Value {
+    let mut compute_resources = json!({
+        "type": "SPOT",
+        "allocationStrategy": allocation_strategy(advice.strategy),
+        "minvCpus": 0,
+        "maxvCpus": options.max_vcpus,
+        "instanceTypes": instance_types(recommendations),
+        "subnets": options.subnets,
+        "securityGroupIds": options.security_group_ids,
+        "instanceRole": options.instance_role,
+    });
+    if let Some(bid_percentage) = bid_percentage(recommendations, options.max_price_margin) {
+        compute_resources["bidPercentage"] = json!(bid_percentage);
+    }
+
+    json!({
+        "computeEnvironmentName": options.name,
+        "type": "MANAGED",
+        "state": "ENABLED",
+        "computeResources": compute_resources,
+    })
+}
+
+/// Renders the compute environment as JSON for `aws batch create-compute-environment`
+pub fn render(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &BatchOptions,
+) -> Result<String> {
+    Ok(
+        serde_json::to_string_pretty(&compute_environment(recommendations, advice, options))?
+            + "\n",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_bid_percentage() {
+        // 70% savings leave 30% of on-demand, plus a 20% margin
+        assert_eq!(bid_percentage(&recommendations(), 20.0), Some(36));
+        assert_eq!(bid_percentage(&recommendations(), 1000.0), Some(100));
+        assert_eq!(bid_percentage(&[], 20.0), None);
+    }
+
+    #[test]
+    fn test_compute_environment() {
+        let subnets = vec!["subnet-1".to_string()];
+        let options = BatchOptions {
+            name: "spot",
+            max_vcpus: 256,
+            subnets: &subnets,
+            security_group_ids: &[],
+            instance_role: "ecsInstanceRole",
+            max_price_margin: 20.0,
+        };
+        let advice = StrategyAdvice {
+            strategy: AllocationStrategy::PriceCapacityOptimized,
+            reasons: vec![],
+        };
+        let environment = compute_environment(&recommendations(), &advice, &options);
+        assert_eq!(environment["computeEnvironmentName"], json!("spot"));
+
+        let resources = &environment["computeResources"];
+        assert_eq!(resources["type"], json!("SPOT"));
+        assert_eq!(
+            resources["allocationStrategy"],
+            json!("SPOT_PRICE_CAPACITY_OPTIMIZED")
+        );
+        assert_eq!(
+            resources["instanceTypes"],
+            json!(["m7g.xlarge", "m6i.xlarge"])
+        );
+        assert_eq!(resources["subnets"], json!(["subnet-1"]));
+        assert_eq!(resources["bidPercentage"], json!(36));
+    }
+}
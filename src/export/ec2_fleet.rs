@@ -0,0 +1,96 @@
+use super::{instance_types, overrides, spot_instance_pools};
+use crate::cli::FleetType;
+use crate::recommend::{Recommendation, StrategyAdvice};
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Options of the EC2 Fleet request
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ec2FleetOptions<'a> {
+    pub target_capacity: u32,
+    pub launch_template: &'a str,
+    /// Availability Zones of the overrides, any AZ of the launch template when empty
+    pub availability_zones: &'a [String],
+    pub fleet_type: FleetType,
+}
+
+/// Returns the CreateFleet request body of the recommended instance types with the advised
+/// allocation strategy, launching spot instances only
+pub fn create_fleet_request(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &Ec2FleetOptions,
+) -> Value {
+    let mut spot_options = json!({ "AllocationStrategy": advice.strategy.name() });
+    if let Some(pools) = spot_instance_pools(advice, recommendations) {
+        spot_options["InstancePoolsToUseCount"] = json!(pools);
+    }
+
+    json!({
+        "Type": options.fleet_type.name(),
+        "SpotOptions": spot_options,
+        "LaunchTemplateConfigs": [{
+            "LaunchTemplateSpecification": {
+                "LaunchTemplateName": options.launch_template,
+                "Version": "$Latest",
+            },
+            "Overrides": overrides(&instance_types(recommendations), options.availability_zones),
+        }],
+        "TargetCapacitySpecification": {
+            "TotalTargetCapacity": options.target_capacity,
+            "SpotTargetCapacity": options.target_capacity,
+            "DefaultTargetCapacityType": "spot",
+        },
+    })
+}
+
+/// Renders the CreateFleet request body as JSON for `aws ec2 create-fleet --cli-input-json`
+pub fn render(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &Ec2FleetOptions,
+) -> Result<String> {
+    Ok(
+        serde_json::to_string_pretty(&create_fleet_request(recommendations, advice, options))?
+            + "\n",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+    use crate::recommend::AllocationStrategy;
+
+    #[test]
+    fn test_create_fleet_request() {
+        let options = Ec2FleetOptions {
+            target_capacity: 10,
+            launch_template: "batch",
+            availability_zones: &[],
+            fleet_type: FleetType::Instant,
+        };
+        let advice = StrategyAdvice {
+            strategy: AllocationStrategy::LowestPrice,
+            reasons: vec![],
+        };
+        let request = create_fleet_request(&recommendations(), &advice, &options);
+        assert_eq!(request["Type"], json!("instant"));
+        assert_eq!(
+            request["SpotOptions"],
+            json!({ "AllocationStrategy": "lowest-price", "InstancePoolsToUseCount": 2 })
+        );
+        assert_eq!(
+            request["LaunchTemplateConfigs"][0]["Overrides"],
+            json!([{ "InstanceType": "m7g.xlarge" }, { "InstanceType": "m6i.xlarge" }])
+        );
+        assert_eq!(
+            request["TargetCapacitySpecification"],
+            json!({
+                "TotalTargetCapacity": 10,
+                "SpotTargetCapacity": 10,
+                "DefaultTargetCapacityType": "spot",
+            })
+        );
+    }
+}
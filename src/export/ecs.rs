@@ -0,0 +1,135 @@
+use super::asg::{self, AsgOptions};
+use super::summary;
+use crate::recommend::{Recommendation, StrategyAdvice};
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Options of the Auto Scaling group and capacity provider of the ECS cluster
+#[derive(Clone, Debug, PartialEq)]
+pub struct EcsOptions<'a> {
+    pub cluster: &'a str,
+    pub asg: AsgOptions<'a>,
+    /// Subnets of the container instances
+    pub subnets: &'a [String],
+    pub min_size: u32,
+    pub max_size: u32,
+    /// Target utilization (%) of the container instances by managed scaling
+    pub target_capacity: u32,
+}
+
+/// Returns a CloudFormation template of a spot Auto Scaling group of the recommended instance
+/// types, its ECS capacity provider, and the cluster's default capacity provider strategy
+pub fn template(
+    region: &str,
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &EcsOptions,
+) -> Value {
+    let capacity_provider = json!({ "Ref": "SpotCapacityProvider" });
+    json!({
+        "AWSTemplateFormatVersion": "2010-09-09",
+        "Description": summary(region, recommendations),
+        "Resources": {
+            "SpotAutoScalingGroup": {
+                "Type": "AWS::AutoScaling::AutoScalingGroup",
+                "Properties": {
+                    "MinSize": options.min_size.to_string(),
+                    "MaxSize": options.max_size.to_string(),
+                    "VPCZoneIdentifier": options.subnets,
+                    // Replaces instances at an elevated risk of interruption ahead of time
+                    "CapacityRebalance": true,
+                    // Required by the managed termination protection of the capacity provider
+                    "NewInstancesProtectedFromScaleIn": true,
+                    "MixedInstancesPolicy":
+                        asg::mixed_instances_policy(recommendations, advice, &options.asg),
+                },
+            },
+            "SpotCapacityProvider": {
+                "Type": "AWS::ECS::CapacityProvider",
+                "Properties": {
+                    "AutoScalingGroupProvider": {
+                        "AutoScalingGroupArn": { "Ref": "SpotAutoScalingGroup" },
+                        "ManagedScaling": {
+                            "Status": "ENABLED",
+                            "TargetCapacity": options.target_capacity,
+                        },
+                        "ManagedTerminationProtection": "ENABLED",
+                        "ManagedDraining": "ENABLED",
+                    },
+                },
+            },
+            "ClusterCapacityProviderAssociations": {
+                "Type": "AWS::ECS::ClusterCapacityProviderAssociations",
+                "Properties": {
+                    "Cluster": options.cluster,
+                    "CapacityProviders": [capacity_provider],
+                    "DefaultCapacityProviderStrategy": [{
+                        "CapacityProvider": capacity_provider,
+                        "Weight": 1,
+                    }],
+                },
+            },
+        },
+    })
+}
+
+/// Renders the CloudFormation template as YAML for `aws cloudformation deploy`
+pub fn render(
+    region: &str,
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &EcsOptions,
+) -> Result<String> {
+    Ok(serde_yaml::to_string(&template(
+        region,
+        recommendations,
+        advice,
+        options,
+    ))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+    use crate::recommend::AllocationStrategy;
+
+    #[test]
+    fn test_template() {
+        let subnets = ["subnet-1".to_string(), "subnet-2".to_string()];
+        let options = EcsOptions {
+            cluster: "batch",
+            asg: AsgOptions {
+                launch_template: "ecs-spot",
+                on_demand_base: 0,
+                on_demand_percentage: 0,
+            },
+            subnets: &subnets,
+            min_size: 0,
+            max_size: 10,
+            target_capacity: 100,
+        };
+        let advice = StrategyAdvice {
+            strategy: AllocationStrategy::PriceCapacityOptimized,
+            reasons: vec![],
+        };
+        let template = template("us-east-1", &recommendations(), &advice, &options);
+        let resources = &template["Resources"];
+
+        let group = &resources["SpotAutoScalingGroup"]["Properties"];
+        assert_eq!(group["VPCZoneIdentifier"], json!(["subnet-1", "subnet-2"]));
+        assert_eq!(
+            group["MixedInstancesPolicy"]["LaunchTemplate"]["Overrides"],
+            json!([{ "InstanceType": "m7g.xlarge" }, { "InstanceType": "m6i.xlarge" }])
+        );
+        assert_eq!(
+            resources["SpotCapacityProvider"]["Properties"]["AutoScalingGroupProvider"]["ManagedScaling"]
+                ["TargetCapacity"],
+            json!(100)
+        );
+        assert_eq!(
+            resources["ClusterCapacityProviderAssociations"]["Properties"]["Cluster"],
+            json!("batch")
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use super::{architectures, instance_types, summary};
+use crate::recommend::Recommendation;
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Options of the eksctl managed node group
+#[derive(Clone, Debug, PartialEq)]
+pub struct EksctlOptions<'a> {
+    pub cluster: &'a str,
+    pub name: &'a str,
+    pub min_size: u32,
+    pub max_size: u32,
+}
+
+/// Keeps the recommendations of the architecture of the most recommended one, since a managed
+/// node group's AMI only runs on a single architecture
+pub fn single_architecture(recommendations: &[Recommendation]) -> Vec<Recommendation> {
+    let Some(first) = recommendations.first() else {
+        return Vec::new();
+    };
+    let architecture = architectures(std::slice::from_ref(first));
+    recommendations
+        .iter()
+        .filter(|r| architectures(std::slice::from_ref(*r)) == architecture)
+        .cloned()
+        .collect()
+}
+
+/// Returns the eksctl ClusterConfig of a spot managed node group of the recommended instance types
+pub fn cluster_config(
+    region: &str,
+    recommendations: &[Recommendation],
+    options: &EksctlOptions,
+) -> Value {
+    json!({
+        "apiVersion": "eksctl.io/v1alpha5",
+        "kind": "ClusterConfig",
+        "metadata": {
+            "name": options.cluster,
+            "region": region,
+        },
+        "managedNodeGroups": [{
+            "name": options.name,
+            "instanceTypes": instance_types(recommendations),
+            "spot": true,
+            "minSize": options.min_size,
+            "desiredCapacity": options.min_size,
+            "maxSize": options.max_size,
+            "labels": { "capacity-type": "spot" },
+        }],
+    })
+}
+
+/// Renders the ClusterConfig as YAML for `eksctl create nodegroup --config-file`
+pub fn render(
+    region: &str,
+    recommendations: &[Recommendation],
+    options: &EksctlOptions,
+) -> Result<String> {
+    Ok(format!(
+        "# {}\n{}",
+        summary(region, recommendations),
+        serde_yaml::to_string(&cluster_config(region, recommendations, options))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_single_architecture() {
+        // The Graviton m7g.xlarge is the cheapest
+        let recommendations = single_architecture(&recommendations());
+        assert_eq!(instance_types(&recommendations), vec!["m7g.xlarge"]);
+        assert!(single_architecture(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let options = EksctlOptions {
+            cluster: "my-cluster",
+            name: "spot",
+            min_size: 1,
+            max_size: 10,
+        };
+        let yaml = render("us-east-1", &recommendations(), &options).unwrap();
+        let config: Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config["metadata"]["name"], json!("my-cluster"));
+        assert_eq!(config["metadata"]["region"], json!("us-east-1"));
+
+        let node_group = &config["managedNodeGroups"][0];
+        assert_eq!(node_group["spot"], json!(true));
+        assert_eq!(
+            node_group["instanceTypes"],
+            json!(["m7g.xlarge", "m6i.xlarge"])
+        );
+        assert_eq!(node_group["desiredCapacity"], json!(1));
+        assert_eq!(node_group["maxSize"], json!(10));
+    }
+}
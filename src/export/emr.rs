@@ -0,0 +1,118 @@
+use super::instance_types;
+use crate::cli::EmrFleetType;
+use crate::recommend::Recommendation;
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+/// Maximum number of instance types of an EMR instance fleet with an allocation strategy
+pub const MAX_FLEET_INSTANCE_TYPES: usize = 30;
+
+/// Options of the EMR instance fleet
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmrOptions<'a> {
+    pub name: &'a str,
+    pub fleet_type: EmrFleetType,
+    /// Spot capacity of the fleet in vCPUs, the weighted capacity of each instance type
+    pub target_spot_capacity: u32,
+    /// Minutes to wait for the spot capacity before switching to on-demand
+    pub timeout_minutes: u32,
+}
+
+/// Returns the instance types supported by EMR, flagged "emr" in the instance types of the spot
+/// advisor data
+pub fn emr_instance_types(advisor_data: &Value) -> HashSet<String> {
+    advisor_data["instance_types"]
+        .as_object()
+        .map(|instance_types| {
+            instance_types
+                .iter()
+                .filter(|(_, info)| info["emr"].as_bool() == Some(true))
+                .map(|(instance_type, _)| instance_type.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the instance fleet config of the recommended instance types, weighted by their vCPUs
+pub fn instance_fleet(recommendations: &[Recommendation], options: &EmrOptions) -> Value {
+    let instance_type_configs: Vec<Value> = recommendations
+        .iter()
+        .zip(instance_types(recommendations))
+        .map(|(recommendation, instance_type)| {
+            let vcpus = recommendation.info.cores_value().unwrap_or(1.0).max(1.0);
+            json!({
+                "InstanceType": instance_type,
+                "WeightedCapacity": vcpus as u32,
+                "BidPriceAsPercentageOfOnDemandPrice": 100,
+            })
+        })
+        .collect();
+
+    json!([{
+        "Name": options.name,
+        "InstanceFleetType": options.fleet_type.name(),
+        "TargetSpotCapacity": options.target_spot_capacity,
+        "InstanceTypeConfigs": instance_type_configs,
+        "LaunchSpecifications": {
+            "SpotSpecification": {
+                "TimeoutDurationMinutes": options.timeout_minutes,
+                "TimeoutAction": "SWITCH_TO_ON_DEMAND",
+                "AllocationStrategy": "price-capacity-optimized",
+            },
+        },
+    }])
+}
+
+/// Renders the instance fleet config as JSON for `aws emr create-cluster --instance-fleets` or
+/// `aws emr add-instance-fleet`
+pub fn render(recommendations: &[Recommendation], options: &EmrOptions) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&instance_fleet(recommendations, options))? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_emr_instance_types() {
+        let advisor_data = json!({
+            "instance_types": {
+                "m5.xlarge": { "cores": 4, "ram_gb": 16.0, "emr": true },
+                "t3.medium": { "cores": 2, "ram_gb": 4.0, "emr": false },
+                "c5.large": { "cores": 2, "ram_gb": 4.0 }
+            }
+        });
+        assert_eq!(
+            emr_instance_types(&advisor_data),
+            HashSet::from(["m5.xlarge".to_string()])
+        );
+        assert!(emr_instance_types(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_instance_fleet() {
+        let options = EmrOptions {
+            name: "task",
+            fleet_type: EmrFleetType::Task,
+            target_spot_capacity: 64,
+            timeout_minutes: 10,
+        };
+        let fleet = &instance_fleet(&recommendations(), &options)[0];
+        assert_eq!(fleet["InstanceFleetType"], json!("TASK"));
+        assert_eq!(fleet["TargetSpotCapacity"], json!(64));
+        assert_eq!(
+            fleet["InstanceTypeConfigs"][0],
+            json!({
+                "InstanceType": "m7g.xlarge",
+                "WeightedCapacity": 4,
+                "BidPriceAsPercentageOfOnDemandPrice": 100,
+            })
+        );
+        assert_eq!(
+            fleet["LaunchSpecifications"]["SpotSpecification"]["TimeoutDurationMinutes"],
+            json!(10)
+        );
+    }
+}
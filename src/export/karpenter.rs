@@ -0,0 +1,113 @@
+use super::{architectures, instance_types, summary};
+use crate::recommend::Recommendation;
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Returns the Karpenter NodePool requiring the recommended spot instance types
+fn node_pool(name: &str, recommendations: &[Recommendation]) -> Value {
+    json!({
+        "apiVersion": "karpenter.sh/v1",
+        "kind": "NodePool",
+        "metadata": { "name": name },
+        "spec": {
+            "template": {
+                "spec": {
+                    "requirements": [
+                        {
+                            "key": "karpenter.sh/capacity-type",
+                            "operator": "In",
+                            "values": ["spot"],
+                        },
+                        {
+                            "key": "node.kubernetes.io/instance-type",
+                            "operator": "In",
+                            "values": instance_types(recommendations),
+                        },
+                        {
+                            "key": "kubernetes.io/arch",
+                            "operator": "In",
+                            "values": architectures(recommendations),
+                        },
+                    ],
+                    "nodeClassRef": {
+                        "group": "karpenter.k8s.aws",
+                        "kind": "EC2NodeClass",
+                        "name": name,
+                    },
+                },
+            },
+            "disruption": {
+                "consolidationPolicy": "WhenEmptyOrUnderutilized",
+            },
+        },
+    })
+}
+
+/// Returns the EC2NodeClass discovering the subnets and security groups of the cluster
+fn node_class(name: &str, cluster: &str) -> Value {
+    let discovery = json!([{ "tags": { "karpenter.sh/discovery": cluster } }]);
+    json!({
+        "apiVersion": "karpenter.k8s.aws/v1",
+        "kind": "EC2NodeClass",
+        "metadata": { "name": name },
+        "spec": {
+            "role": format!("KarpenterNodeRole-{}", cluster),
+            "amiSelectorTerms": [{ "alias": "al2023@latest" }],
+            "subnetSelectorTerms": discovery,
+            "securityGroupSelectorTerms": discovery,
+        },
+    })
+}
+
+/// Renders the NodePool and EC2NodeClass as a multi-document YAML
+pub fn render(
+    region: &str,
+    name: &str,
+    cluster: &str,
+    recommendations: &[Recommendation],
+) -> Result<String> {
+    Ok(format!(
+        "# {}\n{}---\n{}",
+        summary(region, recommendations),
+        serde_yaml::to_string(&node_pool(name, recommendations))?,
+        serde_yaml::to_string(&node_class(name, cluster))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_render() {
+        let yaml = render("us-east-1", "spot", "prod", &recommendations()).unwrap();
+        let documents: Vec<Value> = yaml
+            .split("---\n")
+            .map(|document| serde_yaml::from_str(document).unwrap())
+            .collect();
+
+        let requirements = &documents[0]["spec"]["template"]["spec"]["requirements"];
+        assert_eq!(requirements[0]["values"], json!(["spot"]));
+        assert_eq!(
+            requirements[1]["key"],
+            json!("node.kubernetes.io/instance-type")
+        );
+        assert_eq!(
+            requirements[1]["values"],
+            json!(["m7g.xlarge", "m6i.xlarge"])
+        );
+        assert_eq!(requirements[2]["values"], json!(["amd64", "arm64"]));
+
+        assert_eq!(documents[1]["kind"], json!("EC2NodeClass"));
+        assert_eq!(
+            documents[1]["spec"]["role"],
+            json!("KarpenterNodeRole-prod")
+        );
+        assert_eq!(
+            documents[1]["spec"]["subnetSelectorTerms"][0]["tags"]["karpenter.sh/discovery"],
+            json!("prod")
+        );
+        assert!(yaml.starts_with("# Generated by spotter"));
+    }
+}
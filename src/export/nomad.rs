@@ -0,0 +1,139 @@
+use super::terraform::{attributes, string};
+use super::{instance_types, summary};
+use crate::recommend::Recommendation;
+
+/// Options of the Nomad Autoscaler cluster scaling policy
+#[derive(Clone, Debug, PartialEq)]
+pub struct NomadOptions<'a> {
+    /// Auto Scaling group of the Nomad clients
+    pub asg_name: &'a str,
+    /// Node class of the Nomad clients in the Auto Scaling group
+    pub node_class: &'a str,
+    pub min: u32,
+    pub max: u32,
+    /// Target allocated percentage of the clients' CPU and memory
+    pub target: u32,
+}
+
+/// Renders a check scaling the cluster to a target allocated percentage of a resource
+fn check(resource: &str, target: u32) -> String {
+    format!(
+        "    check \"{resource}_allocated_percentage\" {{\n{}\n      strategy \"target-value\" {{\n{}      }}\n    }}\n",
+        attributes(
+            6,
+            &[
+                ("source", string("nomad-apm")),
+                (
+                    "query",
+                    string(&format!("percentage-allocated_{}", resource))
+                ),
+            ]
+        ),
+        attributes(8, &[("target", target.to_string())]),
+    )
+}
+
+/// Renders a Nomad Autoscaler cluster scaling policy of the Auto Scaling group, preceded by the
+/// recommended instance types of its mixed instances policy
+pub fn render(region: &str, recommendations: &[Recommendation], options: &NomadOptions) -> String {
+    let types: Vec<String> = instance_types(recommendations)
+        .iter()
+        .map(|instance_type| string(instance_type))
+        .collect();
+
+    format!(
+        "# {}\n# Spot instance types of the mixed instances policy of {}:\n# [{}]\nscaling \"{}_cluster_policy\" {{\n{}\n  policy {{\n{}\n{}\n{}\n    target \"aws-asg\" {{\n{}    }}\n  }}\n}}\n",
+        summary(region, recommendations),
+        options.asg_name,
+        types.join(", "),
+        options.node_class,
+        attributes(
+            2,
+            &[
+                ("enabled", "true".to_string()),
+                ("min", options.min.to_string()),
+                ("max", options.max.to_string()),
+            ]
+        ),
+        attributes(
+            4,
+            &[
+                ("cooldown", string("2m")),
+                ("evaluation_interval", string("1m")),
+            ]
+        ),
+        check("cpu", options.target),
+        check("mem", options.target),
+        attributes(
+            6,
+            &[
+                ("dry-run", string("false")),
+                ("aws_asg_name", string(options.asg_name)),
+                ("node_class", string(options.node_class)),
+                ("node_drain_deadline", string("5m")),
+                ("node_purge", string("true")),
+            ]
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_render() {
+        let options = NomadOptions {
+            asg_name: "nomad-clients",
+            node_class: "spot",
+            min: 1,
+            max: 10,
+            target: 70,
+        };
+        let hcl = render("us-east-1", &recommendations(), &options);
+        let body = hcl.split_once('\n').unwrap().1;
+        assert_eq!(
+            body,
+            r#"# Spot instance types of the mixed instances policy of nomad-clients:
+# ["m7g.xlarge", "m6i.xlarge"]
+scaling "spot_cluster_policy" {
+  enabled = true
+  min     = 1
+  max     = 10
+
+  policy {
+    cooldown            = "2m"
+    evaluation_interval = "1m"
+
+    check "cpu_allocated_percentage" {
+      source = "nomad-apm"
+      query  = "percentage-allocated_cpu"
+
+      strategy "target-value" {
+        target = 70
+      }
+    }
+
+    check "mem_allocated_percentage" {
+      source = "nomad-apm"
+      query  = "percentage-allocated_mem"
+
+      strategy "target-value" {
+        target = 70
+      }
+    }
+
+    target "aws-asg" {
+      dry-run             = "false"
+      aws_asg_name        = "nomad-clients"
+      node_class          = "spot"
+      node_drain_deadline = "5m"
+      node_purge          = "true"
+    }
+  }
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use super::summary;
+use crate::recommend::Recommendation;
+use anyhow::Result;
+use serde_json::{Map, Value, json};
+
+/// Name of the on-demand head node type
+const HEAD_NODE_TYPE: &str = "ray.head.default";
+
+/// Options of the Ray cluster node types
+#[derive(Clone, Debug, PartialEq)]
+pub struct RayOptions<'a> {
+    /// On-demand instance type of the head node
+    pub head_instance_type: &'a str,
+    pub min_workers: u32,
+    /// Maximum workers of each recommended instance type
+    pub max_workers: u32,
+}
+
+/// Returns the name of the worker node type of an instance type
+fn worker_node_type(instance_type: &str) -> String {
+    format!("ray.worker.{}", instance_type)
+}
+
+/// Returns the Ray resources of an instance type, its vCPUs and memory in bytes
+fn resources(recommendation: &Recommendation) -> Value {
+    let mut resources = Map::new();
+    if let Some(cores) = recommendation.info.cores_value() {
+        resources.insert("CPU".to_string(), json!(cores as u64));
+    }
+    if let Some(memory_gb) = recommendation.info.memory_gb_value() {
+        resources.insert(
+            "memory".to_string(),
+            json!((memory_gb * 1024.0 * 1024.0 * 1024.0) as u64),
+        );
+    }
+    Value::Object(resources)
+}
+
+/// Returns the head node type and available node types of a Ray cluster with a spot worker node
+/// type per recommended instance type
+pub fn node_types(recommendations: &[Recommendation], options: &RayOptions) -> Value {
+    let mut available_node_types = Map::new();
+    available_node_types.insert(
+        HEAD_NODE_TYPE.to_string(),
+        json!({
+            // Keep tasks off the head node, which must outlive spot interruptions
+            "resources": { "CPU": 0 },
+            "node_config": { "InstanceType": options.head_instance_type },
+        }),
+    );
+    for recommendation in recommendations {
+        available_node_types.insert(
+            worker_node_type(&recommendation.instance_type),
+            json!({
+                "min_workers": options.min_workers,
+                "max_workers": options.max_workers,
+                "resources": resources(recommendation),
+                "node_config": {
+                    "InstanceType": recommendation.instance_type,
+                    "InstanceMarketOptions": { "MarketType": "spot" },
+                },
+            }),
+        );
+    }
+
+    json!({
+        "head_node_type": HEAD_NODE_TYPE,
+        "available_node_types": available_node_types,
+    })
+}
+
+/// Renders the node types as YAML to merge into a Ray cluster config
+pub fn render(
+    region: &str,
+    recommendations: &[Recommendation],
+    options: &RayOptions,
+) -> Result<String> {
+    Ok(format!(
+        "# {}\n{}",
+        summary(region, recommendations),
+        serde_yaml::to_string(&node_types(recommendations, options))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_node_types() {
+        let options = RayOptions {
+            head_instance_type: "m5.large",
+            min_workers: 0,
+            max_workers: 8,
+        };
+        let node_types = node_types(&recommendations(), &options);
+        assert_eq!(node_types["head_node_type"], json!("ray.head.default"));
+
+        let available_node_types = node_types["available_node_types"].as_object().unwrap();
+        assert_eq!(available_node_types.len(), 3);
+        assert_eq!(
+            available_node_types["ray.worker.m7g.xlarge"],
+            json!({
+                "min_workers": 0,
+                "max_workers": 8,
+                "resources": { "CPU": 4, "memory": 17179869184u64 },
+                "node_config": {
+                    "InstanceType": "m7g.xlarge",
+                    "InstanceMarketOptions": { "MarketType": "spot" },
+                },
+            })
+        );
+    }
+}
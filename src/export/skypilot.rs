@@ -0,0 +1,74 @@
+use super::{instance_types, summary};
+use crate::cli::Accelerator;
+use crate::recommend::Recommendation;
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Returns the SkyPilot task resources launching spot instances of the recommended instance
+/// types in the region, in order of preference
+pub fn resources(
+    region: &str,
+    recommendations: &[Recommendation],
+    accelerator: Option<&Accelerator>,
+) -> Value {
+    let candidates: Vec<Value> = instance_types(recommendations)
+        .into_iter()
+        .map(|instance_type| json!({ "instance_type": instance_type }))
+        .collect();
+
+    let mut resources = json!({
+        "cloud": "aws",
+        "region": region,
+        "use_spot": true,
+        "ordered": candidates,
+    });
+    if let Some(accelerator) = accelerator {
+        resources["accelerators"] = json!(accelerator.to_string());
+    }
+    json!({ "resources": resources })
+}
+
+/// Renders the resources as YAML to merge into a SkyPilot task
+pub fn render(
+    region: &str,
+    recommendations: &[Recommendation],
+    accelerator: Option<&Accelerator>,
+) -> Result<String> {
+    Ok(format!(
+        "# {}\n{}",
+        summary(region, recommendations),
+        serde_yaml::to_string(&resources(region, recommendations, accelerator))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_resources() {
+        let resources = resources("us-east-1", &recommendations(), None);
+        assert_eq!(
+            resources,
+            json!({
+                "resources": {
+                    "cloud": "aws",
+                    "region": "us-east-1",
+                    "use_spot": true,
+                    "ordered": [
+                        { "instance_type": "m7g.xlarge" },
+                        { "instance_type": "m6i.xlarge" },
+                    ],
+                },
+            })
+        );
+
+        let accelerator = Accelerator {
+            model: "a10g".to_string(),
+            count: 1,
+        };
+        let resources = super::resources("us-east-1", &recommendations(), Some(&accelerator));
+        assert_eq!(resources["resources"]["accelerators"], json!("A10G:1"));
+    }
+}
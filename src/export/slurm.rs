@@ -0,0 +1,115 @@
+use super::summary;
+use crate::instance;
+use crate::recommend::Recommendation;
+
+/// Share of an instance type's memory a node reports as RealMemory, leaving room for the OS so
+/// that nodes register with their configured memory
+const REAL_MEMORY_FRACTION: f64 = 0.95;
+
+/// Options of the Slurm cloud partition
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlurmOptions<'a> {
+    pub partition: &'a str,
+    /// Maximum dynamic nodes of each recommended instance type
+    pub max_nodes: u32,
+}
+
+/// Returns the node name prefix of an instance type in the partition, like "spot-dy-m6i-xlarge"
+/// for the dynamic nodes of ParallelCluster, since node names can't have dots
+fn node_prefix(partition: &str, instance_type: &str) -> String {
+    format!("{}-dy-{}", partition, instance_type.replace('.', "-"))
+}
+
+/// Returns the scheduling weight of an instance type, its effective cost per vCPU-hour in
+/// hundredths of a cent, since Slurm allocates the nodes of the lowest weight first
+fn weight(recommendation: &Recommendation) -> Option<u64> {
+    let vcpus = recommendation
+        .info
+        .cores_value()
+        .filter(|vcpus| *vcpus > 0.0)?;
+    Some(((recommendation.effective_cost / vcpus * 10000.0).round() as u64).max(1))
+}
+
+/// Returns the NodeName line of the dynamic cloud nodes of an instance type
+fn node_definition(recommendation: &Recommendation, options: &SlurmOptions) -> Option<String> {
+    let instance_type = &recommendation.instance_type;
+    let vcpus = recommendation.info.cores_value()?;
+    let real_memory = recommendation.info.memory_gb_value()? * 1024.0 * REAL_MEMORY_FRACTION;
+    let architecture = if instance::is_graviton(instance::family(instance_type)) {
+        "arm64"
+    } else {
+        "x86_64"
+    };
+    Some(format!(
+        "NodeName={}-[1-{}] CPUs={} RealMemory={} Feature=spot,{},{} Weight={} State=CLOUD",
+        node_prefix(options.partition, instance_type),
+        options.max_nodes,
+        vcpus,
+        real_memory.floor(),
+        instance_type,
+        architecture,
+        weight(recommendation)?,
+    ))
+}
+
+/// Renders the slurm.conf node definitions of a cloud-bursting partition of spot nodes, the
+/// resume program launching each node as a spot instance of the instance type of its features
+pub fn render(region: &str, recommendations: &[Recommendation], options: &SlurmOptions) -> String {
+    let (definitions, nodes): (Vec<String>, Vec<String>) = recommendations
+        .iter()
+        .filter_map(|recommendation| {
+            let definition = node_definition(recommendation, options)?;
+            let nodes = format!(
+                "{}-[1-{}]",
+                node_prefix(options.partition, &recommendation.instance_type),
+                options.max_nodes
+            );
+            Some((definition, nodes))
+        })
+        .unzip();
+
+    format!(
+        "# {}\n# The ResumeProgram launches each node as a spot instance of the instance type of its features\n{}\nPartitionName={} Nodes={} Default=NO MaxTime=INFINITE State=UP\n",
+        summary(region, recommendations),
+        definitions.join("\n"),
+        options.partition,
+        nodes.join(","),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_render() {
+        let options = SlurmOptions {
+            partition: "spot",
+            max_nodes: 10,
+        };
+        let recommendations = recommendations();
+        let conf = render("us-east-1", &recommendations, &options);
+        let lines: Vec<&str> = conf.lines().skip(2).collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "NodeName=spot-dy-m7g-xlarge-[1-10] CPUs=4 RealMemory=15564 Feature=spot,m7g.xlarge,arm64 Weight={} State=CLOUD",
+                weight(&recommendations[0]).unwrap()
+            )
+        );
+        assert!(lines[1].starts_with("NodeName=spot-dy-m6i-xlarge-[1-10] CPUs=4"));
+        assert!(lines[1].contains("Feature=spot,m6i.xlarge,x86_64"));
+        assert_eq!(
+            lines[2],
+            "PartitionName=spot Nodes=spot-dy-m7g-xlarge-[1-10],spot-dy-m6i-xlarge-[1-10] Default=NO MaxTime=INFINITE State=UP"
+        );
+    }
+
+    #[test]
+    fn test_weight() {
+        let recommendations = recommendations();
+        // The cheaper per vCPU, the sooner allocated
+        assert!(weight(&recommendations[0]).unwrap() < weight(&recommendations[1]).unwrap());
+    }
+}
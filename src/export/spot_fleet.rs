@@ -0,0 +1,101 @@
+use super::{instance_types, overrides, spot_instance_pools};
+use crate::recommend::{AllocationStrategy, Recommendation, StrategyAdvice};
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Options of the Spot Fleet request
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpotFleetOptions<'a> {
+    pub target_capacity: u32,
+    pub iam_fleet_role: &'a str,
+    pub launch_template: &'a str,
+    /// Availability Zones of the overrides, any AZ of the launch template when empty
+    pub availability_zones: &'a [String],
+    /// Whether the fleet maintains its capacity or only requests it once
+    pub maintain: bool,
+}
+
+/// Returns the name of an allocation strategy in Spot Fleet requests, which are camel case
+fn allocation_strategy(strategy: AllocationStrategy) -> &'static str {
+    match strategy {
+        AllocationStrategy::LowestPrice => "lowestPrice",
+        AllocationStrategy::PriceCapacityOptimized => "priceCapacityOptimized",
+    }
+}
+
+/// Returns the SpotFleetRequestConfig of the recommended instance type and Availability Zone
+/// combinations with the advised allocation strategy
+pub fn spot_fleet_request_config(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &SpotFleetOptions,
+) -> Value {
+    let overrides = overrides(&instance_types(recommendations), options.availability_zones);
+    let mut config = json!({
+        "IamFleetRole": options.iam_fleet_role,
+        "AllocationStrategy": allocation_strategy(advice.strategy),
+        "TargetCapacity": options.target_capacity,
+        "Type": if options.maintain { "maintain" } else { "request" },
+        "TerminateInstancesWithExpiration": true,
+        "LaunchTemplateConfigs": [{
+            "LaunchTemplateSpecification": {
+                "LaunchTemplateName": options.launch_template,
+                "Version": "$Latest",
+            },
+            "Overrides": overrides,
+        }],
+    });
+    if let Some(pools) = spot_instance_pools(advice, recommendations) {
+        config["InstancePoolsToUseCount"] = json!(pools);
+    }
+    config
+}
+
+/// Renders the SpotFleetRequestConfig as JSON for `aws ec2 request-spot-fleet`
+pub fn render(
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &SpotFleetOptions,
+) -> Result<String> {
+    Ok(
+        serde_json::to_string_pretty(&spot_fleet_request_config(recommendations, advice, options))?
+            + "\n",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+
+    #[test]
+    fn test_spot_fleet_request_config() {
+        let zones = vec!["us-east-1a".to_string(), "us-east-1b".to_string()];
+        let options = SpotFleetOptions {
+            target_capacity: 20,
+            iam_fleet_role: "arn:aws:iam::123456789012:role/fleet",
+            launch_template: "web",
+            availability_zones: &zones,
+            maintain: true,
+        };
+        let advice = StrategyAdvice {
+            strategy: AllocationStrategy::PriceCapacityOptimized,
+            reasons: vec![],
+        };
+        let config = spot_fleet_request_config(&recommendations(), &advice, &options);
+        assert_eq!(
+            config["AllocationStrategy"],
+            json!("priceCapacityOptimized")
+        );
+        assert_eq!(config["TargetCapacity"], json!(20));
+        assert_eq!(config["Type"], json!("maintain"));
+        assert_eq!(config.get("InstancePoolsToUseCount"), None);
+
+        let overrides = &config["LaunchTemplateConfigs"][0]["Overrides"];
+        assert_eq!(overrides.as_array().unwrap().len(), 4);
+        assert_eq!(
+            overrides[1],
+            json!({ "InstanceType": "m7g.xlarge", "AvailabilityZone": "us-east-1b" })
+        );
+    }
+}
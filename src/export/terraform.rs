@@ -0,0 +1,189 @@
+use super::{instance_types, spot_instance_pools, summary};
+use crate::recommend::{Recommendation, StrategyAdvice};
+
+/// Options of the Terraform snippet
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerraformOptions<'a> {
+    /// Terraform expression of the launch template ID, like "aws_launch_template.this.id"
+    pub launch_template_id: &'a str,
+    /// Minimum number of on-demand instances
+    pub on_demand_base: u32,
+    /// Percentage of on-demand instances above the base
+    pub on_demand_percentage: u32,
+    /// Only render the override blocks instead of the whole mixed_instances_policy block
+    pub overrides_only: bool,
+}
+
+/// Renders attributes at an indentation, aligning their equal signs like `terraform fmt`
+pub(super) fn attributes(indent: usize, attributes: &[(&str, String)]) -> String {
+    let width = attributes
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    attributes
+        .iter()
+        .map(|(name, value)| format!("{:indent$}{:width$} = {}\n", "", name, value))
+        .collect()
+}
+
+/// Returns a quoted HCL string
+pub(super) fn string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Renders an override block per recommended instance type, in order of preference
+fn override_blocks(indent: usize, recommendations: &[Recommendation]) -> String {
+    instance_types(recommendations)
+        .iter()
+        .map(|instance_type| {
+            format!(
+                "{:indent$}override {{\n{}{:indent$}}}\n",
+                "",
+                attributes(indent + 2, &[("instance_type", string(instance_type))]),
+                "",
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the recommendations as the mixed_instances_policy block of an aws_autoscaling_group,
+/// or only its launch template override blocks
+pub fn render(
+    region: &str,
+    recommendations: &[Recommendation],
+    advice: &StrategyAdvice,
+    options: &TerraformOptions,
+) -> String {
+    let header = format!("# {}\n", summary(region, recommendations));
+    if options.overrides_only {
+        return header + &override_blocks(0, recommendations);
+    }
+
+    let mut distribution = vec![
+        (
+            "on_demand_base_capacity",
+            options.on_demand_base.to_string(),
+        ),
+        (
+            "on_demand_percentage_above_base_capacity",
+            options.on_demand_percentage.to_string(),
+        ),
+        ("spot_allocation_strategy", string(advice.strategy.name())),
+    ];
+    if let Some(pools) = spot_instance_pools(advice, recommendations) {
+        distribution.push(("spot_instance_pools", pools.to_string()));
+    }
+
+    format!(
+        "{}mixed_instances_policy {{\n  instances_distribution {{\n{}  }}\n\n  launch_template {{\n    launch_template_specification {{\n{}    }}\n\n{}  }}\n}}\n",
+        header,
+        attributes(4, &distribution),
+        attributes(
+            6,
+            &[
+                ("launch_template_id", options.launch_template_id.to_string()),
+                ("version", string("$Latest")),
+            ]
+        ),
+        override_blocks(4, recommendations),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::recommendations;
+    use crate::recommend::AllocationStrategy;
+
+    fn options(overrides_only: bool) -> TerraformOptions<'static> {
+        TerraformOptions {
+            launch_template_id: "aws_launch_template.this.id",
+            on_demand_base: 0,
+            on_demand_percentage: 0,
+            overrides_only,
+        }
+    }
+
+    fn advice() -> StrategyAdvice {
+        StrategyAdvice {
+            strategy: AllocationStrategy::PriceCapacityOptimized,
+            reasons: vec![],
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let hcl = render("us-east-1", &recommendations(), &advice(), &options(false));
+        let body = hcl.split_once('\n').unwrap().1;
+        assert_eq!(
+            body,
+            r#"mixed_instances_policy {
+  instances_distribution {
+    on_demand_base_capacity                  = 0
+    on_demand_percentage_above_base_capacity = 0
+    spot_allocation_strategy                 = "price-capacity-optimized"
+  }
+
+  launch_template {
+    launch_template_specification {
+      launch_template_id = aws_launch_template.this.id
+      version            = "$Latest"
+    }
+
+    override {
+      instance_type = "m7g.xlarge"
+    }
+
+    override {
+      instance_type = "m6i.xlarge"
+    }
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_with_lowest_price() {
+        let advice = StrategyAdvice {
+            strategy: AllocationStrategy::LowestPrice,
+            reasons: vec![],
+        };
+        let spot_instance_pools = |recommendations: &[Recommendation]| {
+            let hcl = render("us-east-1", recommendations, &advice, &options(false));
+            hcl.lines()
+                .find_map(|line| line.trim().strip_prefix("spot_instance_pools"))
+                .map(|value| value.trim_start_matches([' ', '=']).to_string())
+        };
+        assert_eq!(
+            spot_instance_pools(&recommendations()).as_deref(),
+            Some("2")
+        );
+
+        // Lowest-price spreads across at most 20 pools
+        let many: Vec<Recommendation> = (1..=25)
+            .map(|size| Recommendation {
+                instance_type: format!("m6i.{}xlarge", size),
+                ..recommendations()[0].clone()
+            })
+            .collect();
+        assert_eq!(spot_instance_pools(&many).as_deref(), Some("20"));
+    }
+
+    #[test]
+    fn test_render_overrides_only() {
+        let hcl = render("us-east-1", &recommendations(), &advice(), &options(true));
+        assert!(hcl.starts_with("# Generated by spotter"));
+        assert!(hcl.ends_with(
+            "override {\n  instance_type = \"m7g.xlarge\"\n}\n\noverride {\n  instance_type = \"m6i.xlarge\"\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_string() {
+        assert_eq!(string("m5.large"), "\"m5.large\"");
+        assert_eq!(string("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+}
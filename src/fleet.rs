@@ -0,0 +1,88 @@
+use anyhow::{Result, anyhow};
+
+/// Instance type and its count in a fleet
+#[derive(Clone, Debug, PartialEq)]
+pub struct FleetEntry {
+    pub count: u32,
+    pub instance_type: String,
+}
+
+/// Parses a fleet spec of comma-separated entries like "4x m6i.2xlarge, 2x r6g.xlarge",
+/// where an entry without a count is a single instance
+pub fn parse_spec(spec: &str) -> Result<Vec<FleetEntry>> {
+    let mut fleet = Vec::new();
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (count, instance_type) = match entry.split_once(char::is_whitespace) {
+            Some((count, instance_type)) => {
+                let count = count
+                    .strip_suffix(['x', 'X'])
+                    .unwrap_or(count)
+                    .parse::<u32>()
+                    .map_err(|_| anyhow!("Invalid count in fleet spec entry '{}'", entry))?;
+                (count, instance_type.trim())
+            }
+            None => (1, entry),
+        };
+
+        if count == 0 {
+            return Err(anyhow!(
+                "Count must be positive in fleet spec entry '{}'",
+                entry
+            ));
+        }
+        if !instance_type.contains('.') || instance_type.contains(char::is_whitespace) {
+            return Err(anyhow!(
+                "Invalid instance type in fleet spec entry '{}'",
+                entry
+            ));
+        }
+
+        fleet.push(FleetEntry {
+            count,
+            instance_type: instance_type.to_string(),
+        });
+    }
+
+    if fleet.is_empty() {
+        return Err(anyhow!("Fleet spec is empty"));
+    }
+
+    Ok(fleet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(count: u32, instance_type: &str) -> FleetEntry {
+        FleetEntry {
+            count,
+            instance_type: instance_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        assert_eq!(
+            parse_spec("4x m6i.2xlarge, 2x r6g.xlarge").unwrap(),
+            vec![entry(4, "m6i.2xlarge"), entry(2, "r6g.xlarge")]
+        );
+
+        // The "x" after the count is optional, and an entry without a count is a single instance
+        assert_eq!(
+            parse_spec("3 c5.large,m5.xlarge,").unwrap(),
+            vec![entry(3, "c5.large"), entry(1, "m5.xlarge")]
+        );
+    }
+
+    #[test]
+    fn test_parse_spec_invalid() {
+        assert!(parse_spec("").is_err());
+        assert!(parse_spec(" , ").is_err());
+        assert!(parse_spec("0x m5.large").is_err());
+        assert!(parse_spec("fourx m5.large").is_err());
+        assert!(parse_spec("4x m5").is_err());
+        assert!(parse_spec("4x m5 large").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::check::Check;
+use anyhow::{Context, Result};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// Environment variable of the file GitHub Actions renders as the job summary
+const STEP_SUMMARY_ENV: &str = "GITHUB_STEP_SUMMARY";
+
+/// Escapes the message of a workflow command, which ends at a line break
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property of a workflow command, where ':' and ',' separate properties
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+fn format_value(value: Option<f64>, format: impl Fn(f64) -> String) -> String {
+    value.map(format).unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Returns the Markdown report of the checks
+pub fn summary_markdown(region: &str, checks: &[Check]) -> String {
+    let passed = checks.iter().filter(|check| check.passed()).count();
+    let mut markdown = format!(
+        "### Spot check in {}\n\n{} of {} instance types passed\n\n",
+        region,
+        passed,
+        checks.len()
+    );
+    markdown.push_str("| Instance Type | Interruption Rate | Spot Price | Savings | Result |\n");
+    markdown.push_str("| --- | --- | --- | --- | --- |\n");
+    for check in checks {
+        let result = if check.passed() {
+            ":white_check_mark: OK".to_string()
+        } else {
+            format!(":x: {}", check.violations.join("; "))
+        };
+        markdown.push_str(&format!(
+            "| `{}` | {} | {} | {} | {} |\n",
+            check.instance_type,
+            check.interruption_rate,
+            format_value(check.price, |p| format!("${:.4}/h", p)),
+            format_value(check.savings, |s| format!("{}%", s)),
+            result
+        ));
+    }
+    markdown
+}
+
+/// Returns a ::warning:: workflow command per violated threshold
+pub fn warning_annotations(region: &str, checks: &[Check]) -> Vec<String> {
+    checks
+        .iter()
+        .flat_map(|check| {
+            check.violations.iter().map(move |violation| {
+                format!(
+                    "::warning title={}::{}",
+                    escape_property(&format!("Spot check failed for {}", check.instance_type)),
+                    escape_data(&format!(
+                        "{} in {}: {}",
+                        check.instance_type, region, violation
+                    ))
+                )
+            })
+        })
+        .collect()
+}
+
+/// Prints the warning annotations and appends the report to the job summary, skipping the
+/// summary outside GitHub Actions
+pub fn report(region: &str, checks: &[Check]) -> Result<()> {
+    for annotation in warning_annotations(region, checks) {
+        println!("{}", annotation);
+    }
+
+    let Some(path) = env::var_os(STEP_SUMMARY_ENV) else {
+        log::warn!(
+            "{} is not set, skipping the job summary (not running in GitHub Actions?)",
+            STEP_SUMMARY_ENV
+        );
+        return Ok(());
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open the job summary {}", path.to_string_lossy()))?;
+    file.write_all(summary_markdown(region, checks).as_bytes())
+        .context("Failed to write the job summary")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checks() -> Vec<Check> {
+        vec![
+            Check {
+                instance_type: "m6i.4xlarge".to_string(),
+                interruption_rate: "5-10%".to_string(),
+                price: Some(0.35),
+                savings: Some(70.0),
+                violations: vec![],
+            },
+            Check {
+                instance_type: "m6i.8xlarge".to_string(),
+                interruption_rate: "10-15%".to_string(),
+                price: None,
+                savings: Some(60.0),
+                violations: vec![
+                    "interruption rate 10-15% is above 10%".to_string(),
+                    "spot price is unavailable".to_string(),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_summary_markdown() {
+        let markdown = summary_markdown("us-east-1", &checks());
+        assert!(
+            markdown.starts_with("### Spot check in us-east-1\n\n1 of 2 instance types passed\n")
+        );
+        assert!(
+            markdown
+                .contains("| `m6i.4xlarge` | 5-10% | $0.3500/h | 70% | :white_check_mark: OK |\n")
+        );
+        assert!(markdown.contains(
+            "| `m6i.8xlarge` | 10-15% | N/A | 60% | :x: interruption rate 10-15% is above 10%; spot price is unavailable |\n"
+        ));
+    }
+
+    #[test]
+    fn test_warning_annotations() {
+        assert_eq!(
+            warning_annotations("us-east-1", &checks()),
+            vec![
+                "::warning title=Spot check failed for m6i.8xlarge::m6i.8xlarge in us-east-1: interruption rate 10-15%25 is above 10%25",
+                "::warning title=Spot check failed for m6i.8xlarge::m6i.8xlarge in us-east-1: spot price is unavailable",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_data("10%\nnext"), "10%25%0Anext");
+        assert_eq!(escape_property("a: b, c"), "a%3A b%2C c");
+    }
+}
@@ -0,0 +1,142 @@
+use crate::cli::{GpuFilterArgs, Os};
+use crate::display::InstanceData;
+use crate::specs::InstanceSpecs;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Spot offer of an accelerated instance type, priced per GPU
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuOffer {
+    pub instance_type: String,
+    /// Model like "NVIDIA A10G"
+    pub gpu_model: String,
+    pub gpu_count: u32,
+    /// Memory of each GPU in GiB
+    pub gpu_memory_gib: Option<f64>,
+    pub interruption_rate: String,
+    pub spot_price: Option<f64>,
+    /// Spot price per GPU-hour
+    pub price_per_gpu: Option<f64>,
+}
+
+/// Returns whether an instance type has GPUs meeting the requirements, of the model and with at
+/// least the memory per GPU
+pub fn matches(specs: &InstanceSpecs, filter: &GpuFilterArgs) -> bool {
+    specs.gpus.iter().any(|gpu| {
+        filter
+            .gpu_model
+            .as_deref()
+            .is_none_or(|model| gpu.is_model(model))
+            && filter.min_gpu_memory.is_none_or(|min_gpu_memory| {
+                gpu.memory_mib
+                    .is_some_and(|memory_mib| f64::from(memory_mib) / 1024.0 >= min_gpu_memory)
+            })
+    })
+}
+
+/// Returns the spot offers of the instance types with GPUs in a region, the cheapest per GPU-hour
+/// first and the ones without a spot price last
+pub fn gpu_offers(
+    instance_data: &InstanceData,
+    specs: &HashMap<String, InstanceSpecs>,
+    region: &str,
+    os: Os,
+) -> Vec<GpuOffer> {
+    let os_name = os.advisor_keys()[0];
+    let mut offers: Vec<GpuOffer> = instance_data
+        .iter()
+        .filter(|((_, os_key), _)| os_key == os_name)
+        .filter_map(|((instance_type, _), region_map)| {
+            let info = region_map.get(region)?;
+            let gpus = &specs.get(instance_type)?.gpus;
+            let gpu = gpus.first()?;
+            let gpu_count: u32 = gpus.iter().map(|gpu| gpu.count).sum();
+            let spot_price = info.spot_price_value(os.price_column());
+            Some(GpuOffer {
+                instance_type: instance_type.clone(),
+                gpu_model: [&gpu.manufacturer, &gpu.name]
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                gpu_count,
+                gpu_memory_gib: gpu
+                    .memory_mib
+                    .map(|memory_mib| f64::from(memory_mib) / 1024.0),
+                interruption_rate: info.interruption_rate.clone(),
+                price_per_gpu: spot_price
+                    .filter(|_| gpu_count > 0)
+                    .map(|price| price / f64::from(gpu_count)),
+                spot_price,
+            })
+        })
+        .collect();
+
+    offers.sort_by(|a, b| match (a.price_per_gpu, b.price_per_gpu) {
+        (Some(a_price), Some(b_price)) => a_price.total_cmp(&b_price),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.instance_type.cmp(&b.instance_type),
+    });
+    offers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+    use crate::specs::Gpu;
+
+    fn specs(name: &str, count: u32, memory_mib: u32) -> InstanceSpecs {
+        InstanceSpecs {
+            gpus: vec![Gpu {
+                manufacturer: Some("NVIDIA".to_string()),
+                name: Some(name.to_string()),
+                count,
+                memory_mib: Some(memory_mib),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let a100 = specs("A100", 8, 40960);
+        let filter = |gpu_model: Option<&str>, min_gpu_memory: Option<f64>| GpuFilterArgs {
+            gpu_model: gpu_model.map(str::to_string),
+            min_gpu_memory,
+        };
+        assert!(matches(&a100, &filter(None, None)));
+        assert!(matches(&a100, &filter(Some("a100"), Some(24.0))));
+        assert!(!matches(&a100, &filter(Some("h100"), None)));
+        assert!(!matches(&a100, &filter(None, Some(80.0))));
+        assert!(!matches(&InstanceSpecs::default(), &filter(None, None)));
+    }
+
+    #[test]
+    fn test_gpu_offers() {
+        let instance_data = instance_data([
+            ("g5.xlarge", "Linux", "us-east-1", info("5-10%", "0.40")),
+            ("g5.12xlarge", "Linux", "us-east-1", info("5-10%", "1.20")),
+            ("p4d.24xlarge", "Linux", "us-east-1", info("5-10%", "N/A")),
+            ("m5.xlarge", "Linux", "us-east-1", info("5-10%", "0.05")),
+        ]);
+        let specs = HashMap::from([
+            ("g5.xlarge".to_string(), specs("A10G", 1, 24576)),
+            ("g5.12xlarge".to_string(), specs("A10G", 4, 24576)),
+            ("p4d.24xlarge".to_string(), specs("A100", 8, 40960)),
+            ("m5.xlarge".to_string(), InstanceSpecs::default()),
+        ]);
+
+        let offers = gpu_offers(&instance_data, &specs, "us-east-1", Os::Linux);
+        let types: Vec<&str> = offers.iter().map(|o| o.instance_type.as_str()).collect();
+        // $0.30 per GPU-hour beats $0.40, unpriced offers last, no GPU no offer
+        assert_eq!(types, vec!["g5.12xlarge", "g5.xlarge", "p4d.24xlarge"]);
+        assert_eq!(offers[0].gpu_model, "NVIDIA A10G");
+        assert_eq!(offers[0].gpu_count, 4);
+        assert_eq!(offers[0].gpu_memory_gib, Some(24.0));
+        assert!((offers[0].price_per_gpu.unwrap() - 0.3).abs() < 1e-9);
+        assert_eq!(offers[2].price_per_gpu, None);
+    }
+}
@@ -0,0 +1,699 @@
+use crate::cli::PriceColumn;
+use crate::display::{self, InstanceData, InstanceInfo};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "duckdb")]
+mod duckdb_store;
+#[cfg(feature = "postgres")]
+mod postgres_store;
+
+/// Spot market state of an instance type in a region at a point in time
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryRecord {
+    /// Unix time (seconds) the record was taken at
+    pub recorded_at: i64,
+    pub region: String,
+    pub instance_type: String,
+    pub os: String,
+    pub interruption_rate: String,
+    /// Savings over on-demand (%)
+    pub savings: Option<f64>,
+    pub spot_price: Option<f64>,
+}
+
+impl HistoryRecord {
+    /// Creates the record of the instance info of an instance type, OS and region
+    pub fn new(
+        recorded_at: i64,
+        region: &str,
+        instance_type: &str,
+        os_name: &str,
+        info: &InstanceInfo,
+    ) -> Self {
+        Self {
+            recorded_at,
+            region: region.to_string(),
+            instance_type: instance_type.to_string(),
+            os: os_name.to_string(),
+            interruption_rate: info.interruption_rate.clone(),
+            savings: info.savings_value(),
+            spot_price: info.spot_price_value(os_price_column(os_name)),
+        }
+    }
+}
+
+/// Header of the CSV price log
+const CSV_HEADER: &str = "timestamp,region,instance_type,os,interruption_rate,savings,spot_price";
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Appends the records to a CSV price log, writing the header when the file is new or empty
+pub fn append_csv(path: &Path, records: &[HistoryRecord]) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open CSV file '{}'", path.display()))?;
+
+    let mut contents = String::new();
+    if file.metadata()?.len() == 0 {
+        contents.push_str(CSV_HEADER);
+        contents.push('\n');
+    }
+    let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    for record in records {
+        contents.push_str(
+            &[
+                display::format_rfc3339(record.recorded_at),
+                csv_field(&record.region),
+                csv_field(&record.instance_type),
+                csv_field(&record.os),
+                csv_field(&record.interruption_rate),
+                optional(record.savings),
+                optional(record.spot_price),
+            ]
+            .join(","),
+        );
+        contents.push('\n');
+    }
+
+    // A single write keeps the rows of concurrent runs from interleaving
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+/// Relative price change (%) below which a price is considered stable
+const STABLE_PRICE_CHANGE: f64 = 5.0;
+
+/// Aggregates over the history of an instance type in a region
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistorySummary {
+    pub records: usize,
+    pub first_recorded_at: i64,
+    pub last_recorded_at: i64,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub avg_price: Option<f64>,
+    pub first_price: Option<f64>,
+    pub last_price: Option<f64>,
+    /// Interruption rate changes as (recorded at, old rate, new rate)
+    pub interruption_changes: Vec<(i64, String, String)>,
+}
+
+impl HistorySummary {
+    /// Returns the price change (%) between the first and the last record
+    pub fn price_change(&self) -> Option<f64> {
+        let first = self.first_price.filter(|p| *p > 0.0)?;
+        Some((self.last_price? / first - 1.0) * 100.0)
+    }
+
+    /// Returns a one-word summary of the price trend
+    pub fn trend(&self) -> &'static str {
+        match self.price_change() {
+            Some(change) if change >= STABLE_PRICE_CHANGE => "Rising",
+            Some(change) if change <= -STABLE_PRICE_CHANGE => "Falling",
+            Some(_) => "Stable",
+            None => "Unknown",
+        }
+    }
+}
+
+/// Summarizes records ordered by time, or returns None when there are none
+pub fn summarize(records: &[HistoryRecord]) -> Option<HistorySummary> {
+    let first = records.first()?;
+    let last = records.last()?;
+
+    let prices: Vec<f64> = records.iter().filter_map(|r| r.spot_price).collect();
+    let interruption_changes = records
+        .windows(2)
+        .filter(|pair| pair[0].interruption_rate != pair[1].interruption_rate)
+        .map(|pair| {
+            (
+                pair[1].recorded_at,
+                pair[0].interruption_rate.clone(),
+                pair[1].interruption_rate.clone(),
+            )
+        })
+        .collect();
+
+    Some(HistorySummary {
+        records: records.len(),
+        first_recorded_at: first.recorded_at,
+        last_recorded_at: last.recorded_at,
+        min_price: prices.iter().copied().reduce(f64::min),
+        max_price: prices.iter().copied().reduce(f64::max),
+        avg_price: (!prices.is_empty()).then(|| prices.iter().sum::<f64>() / prices.len() as f64),
+        first_price: records.iter().find_map(|r| r.spot_price),
+        last_price: records.iter().rev().find_map(|r| r.spot_price),
+        interruption_changes,
+    })
+}
+
+/// Characters of a sparkline, from the lowest to the highest value
+const SPARKLINE_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Returns a unicode sparkline of the values scaled between their minimum and maximum, flat at
+/// the bottom when every value is the same
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let top = (SPARKLINE_CHARS.len() - 1) as f64;
+
+    values
+        .iter()
+        .map(|value| {
+            let level = if max > min {
+                ((value - min) / (max - min) * top).round() as usize
+            } else {
+                0
+            };
+            SPARKLINE_CHARS[level]
+        })
+        .collect()
+}
+
+/// Returns a sparkline of the spot prices of the last samples of each instance type of a region
+/// and OS, leaving out instance types without recorded prices
+pub fn trends<'a>(
+    store: &mut dyn HistoryStore,
+    region: &str,
+    os_name: &str,
+    instance_types: impl IntoIterator<Item = &'a str>,
+    samples: usize,
+) -> Result<HashMap<String, String>> {
+    let mut trends = HashMap::new();
+    for instance_type in instance_types {
+        let records = store.query(instance_type, region, os_name, 0)?;
+        let prices: Vec<f64> = records.iter().filter_map(|r| r.spot_price).collect();
+        let recent = &prices[prices.len().saturating_sub(samples)..];
+        if !recent.is_empty() {
+            trends.insert(instance_type.to_string(), sparkline(recent));
+        }
+    }
+    Ok(trends)
+}
+
+/// Returns the coefficient of variation (%) of the values, their standard deviation relative to
+/// their mean, or None with fewer than two values or a zero mean
+pub fn coefficient_of_variation(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    if mean == 0.0 {
+        return None;
+    }
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64;
+    Some(variance.sqrt() / mean * 100.0)
+}
+
+/// Returns the volatility of the spot prices recorded since a Unix time (seconds) of each instance
+/// type of a region and OS, as the coefficient of variation (%), leaving out instance types with
+/// fewer than two recorded prices
+pub fn volatilities<'a>(
+    store: &mut dyn HistoryStore,
+    region: &str,
+    os_name: &str,
+    instance_types: impl IntoIterator<Item = &'a str>,
+    since: i64,
+) -> Result<HashMap<String, f64>> {
+    let mut volatilities = HashMap::new();
+    for instance_type in instance_types {
+        let records = store.query(instance_type, region, os_name, since)?;
+        let prices: Vec<f64> = records.iter().filter_map(|r| r.spot_price).collect();
+        if let Some(volatility) = coefficient_of_variation(&prices) {
+            volatilities.insert(instance_type.to_string(), volatility);
+        }
+    }
+    Ok(volatilities)
+}
+
+/// Returns the price column of an OS name of the spot advisor data
+pub fn os_price_column(os_name: &str) -> PriceColumn {
+    if os_name == "Windows" {
+        PriceColumn::Mswin
+    } else {
+        PriceColumn::Linux
+    }
+}
+
+/// Returns a record per instance type, OS and region of the instance data, ordered so rows of
+/// the same instance type are written together
+pub fn history_records(instance_data: &InstanceData, recorded_at: i64) -> Vec<HistoryRecord> {
+    let mut records: Vec<HistoryRecord> = instance_data
+        .iter()
+        .flat_map(|((instance_type, os_name), region_map)| {
+            region_map.iter().map(move |(region, info)| {
+                HistoryRecord::new(recorded_at, region, instance_type, os_name, info)
+            })
+        })
+        .collect();
+
+    records.sort_by(|a, b| {
+        (&a.instance_type, &a.region, &a.os).cmp(&(&b.instance_type, &b.region, &b.os))
+    });
+    records
+}
+
+/// Storage of the spot history
+pub trait HistoryStore {
+    /// Appends the records, returning the number of written rows. Records already written for
+    /// the same time are replaced.
+    fn insert(&mut self, records: &[HistoryRecord]) -> Result<usize>;
+
+    /// Returns the records of an instance type, region and OS since a Unix time, ordered by time
+    fn query(
+        &mut self,
+        instance_type: &str,
+        region: &str,
+        os: &str,
+        since: i64,
+    ) -> Result<Vec<HistoryRecord>>;
+}
+
+/// Storage backend selected by the --db value
+#[derive(Clone, Debug, PartialEq)]
+pub enum Backend {
+    /// SQLite database file, the default
+    Sqlite(PathBuf),
+    /// DuckDB database file, selected by a "duckdb://" prefix or the ".duckdb" extension
+    DuckDb(PathBuf),
+    /// PostgreSQL connection URL ("postgres://" or "postgresql://")
+    Postgres(String),
+}
+
+impl Backend {
+    pub fn parse(db: &str) -> Self {
+        if db.starts_with("postgres://") || db.starts_with("postgresql://") {
+            return Backend::Postgres(db.to_string());
+        }
+        if let Some(path) = db.strip_prefix("duckdb://") {
+            return Backend::DuckDb(PathBuf::from(path));
+        }
+        if let Some(path) = db.strip_prefix("sqlite://") {
+            return Backend::Sqlite(PathBuf::from(path));
+        }
+
+        let path = PathBuf::from(db);
+        if path.extension().is_some_and(|ext| ext == "duckdb") {
+            Backend::DuckDb(path)
+        } else {
+            Backend::Sqlite(path)
+        }
+    }
+
+    /// Opens the store, creating its schema when needed
+    pub fn open(&self) -> Result<Box<dyn HistoryStore + Send>> {
+        match self {
+            Backend::Sqlite(path) => Ok(Box::new(SqliteHistory::open(path)?)),
+            #[cfg(feature = "duckdb")]
+            Backend::DuckDb(path) => Ok(Box::new(duckdb_store::DuckDbHistory::open(path)?)),
+            #[cfg(not(feature = "duckdb"))]
+            Backend::DuckDb(_) => Err(anyhow::anyhow!(
+                "DuckDB storage requires building spotter with the 'duckdb' feature"
+            )),
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(url) => Ok(Box::new(postgres_store::PostgresHistory::connect(url)?)),
+            #[cfg(not(feature = "postgres"))]
+            Backend::Postgres(_) => Err(anyhow::anyhow!(
+                "PostgreSQL storage requires building spotter with the 'postgres' feature"
+            )),
+        }
+    }
+}
+
+/// Columns of the spot history table, in the order every backend reads and writes them
+pub const HISTORY_COLUMNS: &str =
+    "recorded_at, region, instance_type, os, interruption_rate, savings, spot_price";
+
+/// Spot history stored in a SQLite database
+pub struct SqliteHistory {
+    conn: Connection,
+}
+
+impl SqliteHistory {
+    /// Opens the database, creating it and its schema when needed
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open database '{}'", path.display()))?;
+        Self::new(conn)
+    }
+
+    fn new(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS spot_history (
+                recorded_at INTEGER NOT NULL,
+                region TEXT NOT NULL,
+                instance_type TEXT NOT NULL,
+                os TEXT NOT NULL,
+                interruption_rate TEXT NOT NULL,
+                savings REAL,
+                spot_price REAL,
+                PRIMARY KEY (recorded_at, region, instance_type, os)
+            );
+            CREATE INDEX IF NOT EXISTS spot_history_instance_type
+                ON spot_history (instance_type, region, recorded_at);",
+        )?;
+        Ok(Self { conn })
+    }
+}
+
+impl HistoryStore for SqliteHistory {
+    fn insert(&mut self, records: &[HistoryRecord]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut written = 0;
+        {
+            let mut stmt = tx.prepare(&format!(
+                "INSERT OR REPLACE INTO spot_history ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                HISTORY_COLUMNS
+            ))?;
+            for record in records {
+                written += stmt.execute(params![
+                    record.recorded_at,
+                    record.region,
+                    record.instance_type,
+                    record.os,
+                    record.interruption_rate,
+                    record.savings,
+                    record.spot_price,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(written)
+    }
+
+    fn query(
+        &mut self,
+        instance_type: &str,
+        region: &str,
+        os: &str,
+        since: i64,
+    ) -> Result<Vec<HistoryRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM spot_history
+             WHERE instance_type = ?1 AND region = ?2 AND os = ?3 AND recorded_at >= ?4
+             ORDER BY recorded_at",
+            HISTORY_COLUMNS
+        ))?;
+        let records = stmt
+            .query_map(params![instance_type, region, os, since], |row| {
+                Ok(HistoryRecord {
+                    recorded_at: row.get(0)?,
+                    region: row.get(1)?,
+                    instance_type: row.get(2)?,
+                    os: row.get(3)?,
+                    interruption_rate: row.get(4)?,
+                    savings: row.get(5)?,
+                    spot_price: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            ("m5.large", "Linux", "us-east-1", info("< 5%", "0.1")),
+            (
+                "m5.large",
+                "Linux",
+                "eu-west-1",
+                info("5-10%", "0.11").with_savings("60%"),
+            ),
+            (
+                "m5.large",
+                "Windows",
+                "us-east-1",
+                info("> 20%", "0.1")
+                    .with_savings("40%")
+                    .with_windows_spot_price("0.2"),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_history_records() {
+        let records = history_records(&sample_data(), 1_700_000_000);
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0].region, "eu-west-1");
+        assert_eq!(records[0].spot_price, Some(0.11));
+
+        // Windows records use the Windows spot price
+        let windows = records.iter().find(|r| r.os == "Windows").unwrap();
+        assert_eq!(windows.interruption_rate, "> 20%");
+        assert_eq!(windows.savings, Some(40.0));
+        assert_eq!(windows.spot_price, Some(0.2));
+        assert!(records.iter().all(|r| r.recorded_at == 1_700_000_000));
+    }
+
+    #[test]
+    fn test_sqlite_history_insert() {
+        let mut history = SqliteHistory::new(Connection::open_in_memory().unwrap()).unwrap();
+        let records = history_records(&sample_data(), 1_700_000_000);
+
+        assert_eq!(history.insert(&records).unwrap(), 3);
+        // Recording the same time again replaces the rows
+        assert_eq!(history.insert(&records).unwrap(), 3);
+
+        let count: i64 = history
+            .conn
+            .query_row("SELECT COUNT(*) FROM spot_history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    fn record(recorded_at: i64, interruption_rate: &str, spot_price: Option<f64>) -> HistoryRecord {
+        HistoryRecord {
+            recorded_at,
+            region: "us-east-1".to_string(),
+            instance_type: "m5.xlarge".to_string(),
+            os: "Linux".to_string(),
+            interruption_rate: interruption_rate.to_string(),
+            savings: Some(70.0),
+            spot_price,
+        }
+    }
+
+    #[test]
+    fn test_sqlite_history_query() {
+        let mut history = SqliteHistory::new(Connection::open_in_memory().unwrap()).unwrap();
+        history
+            .insert(&[
+                record(300, "< 5%", Some(0.12)),
+                record(100, "< 5%", Some(0.1)),
+                record(200, "5-10%", Some(0.11)),
+            ])
+            .unwrap();
+
+        let records = history
+            .query("m5.xlarge", "us-east-1", "Linux", 150)
+            .unwrap();
+        let times: Vec<i64> = records.iter().map(|r| r.recorded_at).collect();
+        assert_eq!(times, vec![200, 300]);
+        assert_eq!(records[0], record(200, "5-10%", Some(0.11)));
+
+        assert!(
+            history
+                .query("m5.xlarge", "eu-west-1", "Linux", 0)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.1, 0.2, 0.3, 0.45, 0.8]), "▁▂▃▅█");
+        assert_eq!(sparkline(&[0.1, 0.1]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_trends() {
+        let mut history = SqliteHistory::new(Connection::open_in_memory().unwrap()).unwrap();
+        history
+            .insert(&[
+                record(100, "< 5%", Some(0.3)),
+                record(200, "< 5%", Some(0.1)),
+                record(300, "< 5%", None),
+                record(400, "< 5%", Some(0.2)),
+            ])
+            .unwrap();
+
+        let trends = trends(
+            &mut history,
+            "us-east-1",
+            "Linux",
+            ["m5.xlarge", "c5.large"],
+            2,
+        )
+        .unwrap();
+        // Only the last two prices, and nothing for instance types without history
+        assert_eq!(
+            trends,
+            HashMap::from([("m5.xlarge".to_string(), "▁█".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_coefficient_of_variation() {
+        // Standard deviation 0.01 of a mean 0.1
+        let cv = coefficient_of_variation(&[0.09, 0.11]).unwrap();
+        assert!((cv - 10.0).abs() < 1e-9);
+        assert_eq!(coefficient_of_variation(&[0.1, 0.1]), Some(0.0));
+        assert_eq!(coefficient_of_variation(&[0.1]), None);
+        assert_eq!(coefficient_of_variation(&[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_volatilities() {
+        let mut history = SqliteHistory::new(Connection::open_in_memory().unwrap()).unwrap();
+        history
+            .insert(&[
+                record(100, "< 5%", Some(0.5)),
+                record(200, "< 5%", Some(0.09)),
+                record(300, "< 5%", None),
+                record(400, "< 5%", Some(0.11)),
+            ])
+            .unwrap();
+
+        // The price before the window is left out
+        let volatilities = volatilities(
+            &mut history,
+            "us-east-1",
+            "Linux",
+            ["m5.xlarge", "c5.large"],
+            200,
+        )
+        .unwrap();
+        assert_eq!(volatilities.len(), 1);
+        assert!((volatilities["m5.xlarge"] - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("< 5%"), "< 5%");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_append_csv() {
+        let path = std::env::temp_dir().join(format!("spotter-test-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        append_csv(&path, &[record(0, "< 5%", Some(0.1))]).unwrap();
+        append_csv(&path, &[record(3600, "5-10%", None)]).unwrap();
+
+        // The header is only written once
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            contents,
+            format!(
+                "{}\n{}\n{}\n",
+                CSV_HEADER,
+                "1970-01-01T00:00:00Z,us-east-1,m5.xlarge,Linux,< 5%,70,0.1",
+                "1970-01-01T01:00:00Z,us-east-1,m5.xlarge,Linux,5-10%,70,"
+            )
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_backend_parse() {
+        assert_eq!(
+            Backend::parse("spot.db"),
+            Backend::Sqlite(PathBuf::from("spot.db"))
+        );
+        assert_eq!(
+            Backend::parse("sqlite:///var/lib/spot.db"),
+            Backend::Sqlite(PathBuf::from("/var/lib/spot.db"))
+        );
+        assert_eq!(
+            Backend::parse("spot.duckdb"),
+            Backend::DuckDb(PathBuf::from("spot.duckdb"))
+        );
+        assert_eq!(
+            Backend::parse("duckdb://spot.db"),
+            Backend::DuckDb(PathBuf::from("spot.db"))
+        );
+        assert_eq!(
+            Backend::parse("postgres://user@localhost/spot"),
+            Backend::Postgres("postgres://user@localhost/spot".to_string())
+        );
+        assert_eq!(
+            Backend::parse("postgresql://localhost/spot"),
+            Backend::Postgres("postgresql://localhost/spot".to_string())
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        assert_eq!(summarize(&[]), None);
+
+        let summary = summarize(&[
+            record(100, "< 5%", Some(0.1)),
+            record(200, "5-10%", None),
+            record(300, "5-10%", Some(0.14)),
+            record(400, "< 5%", Some(0.12)),
+        ])
+        .unwrap();
+        assert_eq!(summary.records, 4);
+        assert_eq!(summary.first_recorded_at, 100);
+        assert_eq!(summary.last_recorded_at, 400);
+        assert_eq!(summary.min_price, Some(0.1));
+        assert_eq!(summary.max_price, Some(0.14));
+        assert!((summary.avg_price.unwrap() - 0.12).abs() < 1e-9);
+        assert_eq!(
+            summary.interruption_changes,
+            vec![
+                (200, "< 5%".to_string(), "5-10%".to_string()),
+                (400, "5-10%".to_string(), "< 5%".to_string()),
+            ]
+        );
+        assert!((summary.price_change().unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(summary.trend(), "Rising");
+    }
+
+    #[test]
+    fn test_trend() {
+        let trend = |first: f64, last: f64| {
+            summarize(&[
+                record(100, "< 5%", Some(first)),
+                record(200, "< 5%", Some(last)),
+            ])
+            .unwrap()
+            .trend()
+        };
+        assert_eq!(trend(0.1, 0.1), "Stable");
+        assert_eq!(trend(0.1, 0.103), "Stable");
+        assert_eq!(trend(0.1, 0.09), "Falling");
+        assert_eq!(
+            summarize(&[record(100, "< 5%", None)]).unwrap().trend(),
+            "Unknown"
+        );
+    }
+}
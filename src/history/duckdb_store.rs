@@ -0,0 +1,119 @@
+use super::{HISTORY_COLUMNS, HistoryRecord, HistoryStore};
+use anyhow::{Context, Result};
+use duckdb::{Connection, params};
+use std::path::Path;
+
+/// Spot history stored in a DuckDB database
+pub struct DuckDbHistory {
+    conn: Connection,
+}
+
+impl DuckDbHistory {
+    /// Opens the database, creating it and its schema when needed
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open DuckDB database '{}'", path.display()))?;
+        Self::new(conn)
+    }
+
+    fn new(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS spot_history (
+                recorded_at BIGINT NOT NULL,
+                region VARCHAR NOT NULL,
+                instance_type VARCHAR NOT NULL,
+                os VARCHAR NOT NULL,
+                interruption_rate VARCHAR NOT NULL,
+                savings DOUBLE,
+                spot_price DOUBLE,
+                PRIMARY KEY (recorded_at, region, instance_type, os)
+            );",
+        )?;
+        Ok(Self { conn })
+    }
+}
+
+impl HistoryStore for DuckDbHistory {
+    fn insert(&mut self, records: &[HistoryRecord]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut written = 0;
+        {
+            let mut stmt = tx.prepare(&format!(
+                "INSERT OR REPLACE INTO spot_history ({}) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                HISTORY_COLUMNS
+            ))?;
+            for record in records {
+                written += stmt.execute(params![
+                    record.recorded_at,
+                    record.region,
+                    record.instance_type,
+                    record.os,
+                    record.interruption_rate,
+                    record.savings,
+                    record.spot_price,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(written)
+    }
+
+    fn query(
+        &mut self,
+        instance_type: &str,
+        region: &str,
+        os: &str,
+        since: i64,
+    ) -> Result<Vec<HistoryRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM spot_history
+             WHERE instance_type = ? AND region = ? AND os = ? AND recorded_at >= ?
+             ORDER BY recorded_at",
+            HISTORY_COLUMNS
+        ))?;
+        let records = stmt
+            .query_map(params![instance_type, region, os, since], |row| {
+                Ok(HistoryRecord {
+                    recorded_at: row.get(0)?,
+                    region: row.get(1)?,
+                    instance_type: row.get(2)?,
+                    os: row.get(3)?,
+                    interruption_rate: row.get(4)?,
+                    savings: row.get(5)?,
+                    spot_price: row.get(6)?,
+                })
+            })?
+            .collect::<duckdb::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(recorded_at: i64, spot_price: Option<f64>) -> HistoryRecord {
+        HistoryRecord {
+            recorded_at,
+            region: "us-east-1".to_string(),
+            instance_type: "m5.xlarge".to_string(),
+            os: "Linux".to_string(),
+            interruption_rate: "< 5%".to_string(),
+            savings: Some(70.0),
+            spot_price,
+        }
+    }
+
+    #[test]
+    fn test_duckdb_history() {
+        let mut history = DuckDbHistory::new(Connection::open_in_memory().unwrap()).unwrap();
+        let records = [record(200, Some(0.11)), record(100, None)];
+
+        assert_eq!(history.insert(&records).unwrap(), 2);
+        // Recording the same time again replaces the rows
+        assert_eq!(history.insert(&records).unwrap(), 2);
+
+        let queried = history.query("m5.xlarge", "us-east-1", "Linux", 0).unwrap();
+        assert_eq!(queried, vec![record(100, None), record(200, Some(0.11))]);
+    }
+}
@@ -0,0 +1,93 @@
+use super::{HISTORY_COLUMNS, HistoryRecord, HistoryStore};
+use anyhow::{Context, Result};
+use postgres::{Client, NoTls};
+
+/// Spot history stored in a PostgreSQL database, letting teams centralize the history
+pub struct PostgresHistory {
+    client: Client,
+}
+
+impl PostgresHistory {
+    /// Connects to the database, creating the schema when needed
+    pub fn connect(url: &str) -> Result<Self> {
+        let mut client = Client::connect(url, NoTls).context("Failed to connect to PostgreSQL")?;
+        client.batch_execute(
+            "CREATE TABLE IF NOT EXISTS spot_history (
+                recorded_at BIGINT NOT NULL,
+                region TEXT NOT NULL,
+                instance_type TEXT NOT NULL,
+                os TEXT NOT NULL,
+                interruption_rate TEXT NOT NULL,
+                savings DOUBLE PRECISION,
+                spot_price DOUBLE PRECISION,
+                PRIMARY KEY (recorded_at, region, instance_type, os)
+            );
+            CREATE INDEX IF NOT EXISTS spot_history_instance_type
+                ON spot_history (instance_type, region, recorded_at);",
+        )?;
+        Ok(Self { client })
+    }
+}
+
+impl HistoryStore for PostgresHistory {
+    fn insert(&mut self, records: &[HistoryRecord]) -> Result<usize> {
+        let mut tx = self.client.transaction()?;
+        let stmt = tx.prepare(&format!(
+            "INSERT INTO spot_history ({}) VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (recorded_at, region, instance_type, os) DO UPDATE SET
+                interruption_rate = EXCLUDED.interruption_rate,
+                savings = EXCLUDED.savings,
+                spot_price = EXCLUDED.spot_price",
+            HISTORY_COLUMNS
+        ))?;
+
+        let mut written = 0;
+        for record in records {
+            written += tx.execute(
+                &stmt,
+                &[
+                    &record.recorded_at,
+                    &record.region,
+                    &record.instance_type,
+                    &record.os,
+                    &record.interruption_rate,
+                    &record.savings,
+                    &record.spot_price,
+                ],
+            )? as usize;
+        }
+        tx.commit()?;
+        Ok(written)
+    }
+
+    fn query(
+        &mut self,
+        instance_type: &str,
+        region: &str,
+        os: &str,
+        since: i64,
+    ) -> Result<Vec<HistoryRecord>> {
+        let rows = self.client.query(
+            &format!(
+                "SELECT {} FROM spot_history
+                 WHERE instance_type = $1 AND region = $2 AND os = $3 AND recorded_at >= $4
+                 ORDER BY recorded_at",
+                HISTORY_COLUMNS
+            ),
+            &[&instance_type, &region, &os, &since],
+        )?;
+
+        Ok(rows
+            .iter()
+            .map(|row| HistoryRecord {
+                recorded_at: row.get(0),
+                region: row.get(1),
+                instance_type: row.get(2),
+                os: row.get(3),
+                interruption_rate: row.get(4),
+                savings: row.get(5),
+                spot_price: row.get(6),
+            })
+            .collect())
+    }
+}
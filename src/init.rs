@@ -0,0 +1,186 @@
+use crate::cache::DEFAULT_CACHE_TTL;
+use crate::cli::{self, Os, PriceColumn};
+use crate::wizard;
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// Answers of `spotter init`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answers {
+    pub region: String,
+    pub os: Os,
+    pub spot_price: bool,
+    pub price_columns: Vec<PriceColumn>,
+    pub cache_ttl: String,
+}
+
+/// Returns the command line name of a value of a flag, like "mswin"
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Quotes a TOML string
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+/// Returns the contents of the config file of the answers
+pub fn render(answers: &Answers) -> String {
+    let price_columns: Vec<String> = answers
+        .price_columns
+        .iter()
+        .map(|column| toml_string(&value_name(column)))
+        .collect();
+    format!(
+        "# spotter config, generated by `spotter init`. Flags on the command line take precedence.\n\
+         region = {}\n\
+         os = {}\n\
+         spot_price = {}\n\
+         price_columns = [{}]\n\
+         cache_ttl = {}\n",
+        toml_string(&answers.region),
+        toml_string(&value_name(&answers.os)),
+        answers.spot_price,
+        price_columns.join(", "),
+        toml_string(&answers.cache_ttl),
+    )
+}
+
+/// Asks for the default region, OS, columns and cache settings
+pub fn ask(default_region: &str) -> Result<Answers> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(anyhow!(
+            "Generating the config file requires an interactive terminal"
+        ));
+    }
+
+    let default_index = cli::VALID_AWS_REGIONS
+        .iter()
+        .position(|(code, _)| *code == default_region)
+        .unwrap_or(0);
+    let region = Select::new()
+        .with_prompt("Default region")
+        .items(&wizard::region_labels())
+        .default(default_index)
+        .interact()
+        .context("Failed to ask for the region")?;
+
+    let os_names: Vec<String> = Os::value_variants().iter().map(value_name).collect();
+    let os = Select::new()
+        .with_prompt("Operating system")
+        .items(&os_names)
+        .default(0)
+        .interact()
+        .context("Failed to ask for the operating system")?;
+
+    let spot_price = Confirm::new()
+        .with_prompt("Show spot and on-demand prices?")
+        .default(true)
+        .interact()
+        .context("Failed to ask for the price columns")?;
+    let column_headers: Vec<&str> = PriceColumn::value_variants()
+        .iter()
+        .map(PriceColumn::header)
+        .collect();
+    let default_columns: Vec<bool> = PriceColumn::value_variants()
+        .iter()
+        .map(|column| matches!(column, PriceColumn::Linux | PriceColumn::Mswin))
+        .collect();
+    let price_columns = if spot_price {
+        MultiSelect::new()
+            .with_prompt("Spot price columns (space to toggle)")
+            .items(&column_headers)
+            .defaults(&default_columns)
+            .interact()
+            .context("Failed to ask for the price columns")?
+            .into_iter()
+            .map(|index| PriceColumn::value_variants()[index])
+            .collect()
+    } else {
+        vec![PriceColumn::Linux, PriceColumn::Mswin]
+    };
+
+    let cache_ttl: String = Input::new()
+        .with_prompt("Reuse fetched data for")
+        .default(DEFAULT_CACHE_TTL.to_string())
+        .validate_with(|ttl: &String| cli::parse_duration(ttl).map(|_| ()))
+        .interact_text()
+        .context("Failed to ask for the cache TTL")?;
+
+    Ok(Answers {
+        region: cli::VALID_AWS_REGIONS[region].0.to_string(),
+        os: Os::value_variants()[os],
+        spot_price,
+        price_columns,
+        cache_ttl,
+    })
+}
+
+/// Writes the config file, refusing to replace an existing one unless forced
+pub fn write(path: &Path, contents: &str, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(anyhow!(
+            "Config file '{}' already exists. Use --force to replace it",
+            path.display()
+        ));
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write config file '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn answers() -> Answers {
+        Answers {
+            region: "eu-west-1".to_string(),
+            os: Os::Windows,
+            spot_price: true,
+            price_columns: vec![PriceColumn::Mswin, PriceColumn::Suse],
+            cache_ttl: "6h".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let contents = render(&answers());
+        assert!(contents.contains("region = \"eu-west-1\"\n"));
+        assert!(contents.contains("os = \"windows\"\n"));
+        assert!(contents.contains("price_columns = [\"mswin\", \"suse\"]\n"));
+
+        // The generated file is a valid config
+        let config = Config::parse(&contents).unwrap();
+        assert_eq!(config.region, Some("eu-west-1".to_string()));
+        assert_eq!(config.spot_price, Some(true));
+        assert_eq!(config.cache_ttl, Some("6h".to_string()));
+    }
+
+    #[test]
+    fn test_write() {
+        let dir = std::env::temp_dir().join(format!("spotter-test-init-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("spotter").join("config.toml");
+
+        write(&path, "region = \"us-east-1\"\n", false).unwrap();
+        assert!(write(&path, "region = \"eu-west-1\"\n", false).is_err());
+        write(&path, "region = \"eu-west-1\"\n", true).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "region = \"eu-west-1\"\n"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+/// Returns the family of an instance type (e.g., "m5" for "m5.large")
+pub fn family(instance_type: &str) -> &str {
+    instance_type.split('.').next().unwrap_or(instance_type)
+}
+
+/// Returns the size of an instance type (e.g., "large" for "m5.large")
+pub fn size(instance_type: &str) -> Option<&str> {
+    instance_type.split_once('.').map(|(_, size)| size)
+}
+
+/// Returns the generation of an instance family (e.g., 6 for "m6i")
+pub fn generation(family: &str) -> Option<u32> {
+    let digits: String = family
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+/// Splits an instance family into its class, generation, and the remaining letters
+/// (e.g., ("m", 6, "gd") for "m6gd")
+pub fn parse_family(family: &str) -> Option<(&str, u32, &str)> {
+    let class_end = family.find(|c: char| c.is_ascii_digit())?;
+    let rest = &family[class_end..];
+    let generation_end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let generation = rest[..generation_end].parse().ok()?;
+    Some((&family[..class_end], generation, &rest[generation_end..]))
+}
+
+/// Returns the processor letter of an instance family, if any
+/// ('a' for AMD, 'g' for Graviton, 'i' for Intel)
+pub fn processor(family: &str) -> Option<char> {
+    let (_, _, letters) = parse_family(family)?;
+    letters
+        .chars()
+        .next()
+        .filter(|c| matches!(c, 'a' | 'g' | 'i'))
+}
+
+/// Returns true if the instance family runs on AWS Graviton (ARM) processors
+pub fn is_graviton(family: &str) -> bool {
+    processor(family) == Some('g')
+}
+
+/// Returns the attribute letters of an instance family other than the processor
+/// (e.g., "d" for "m5ad")
+pub fn attributes(family: &str) -> &str {
+    let Some((_, _, letters)) = parse_family(family) else {
+        return "";
+    };
+    match processor(family) {
+        Some(_) => &letters[1..],
+        None => letters,
+    }
+}
+
+/// Returns the Graviton equivalents of an x86 instance type among the given instance types,
+/// i.e. the same class, size and attributes (e.g., "m6g.2xlarge" and "m7g.2xlarge" for
+/// "m5.2xlarge"), ordered by generation
+pub fn graviton_equivalents<'a>(
+    instance_type: &str,
+    instance_types: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let source_family = family(instance_type);
+    let (Some((class, _, _)), Some(source_size)) =
+        (parse_family(source_family), size(instance_type))
+    else {
+        return Vec::new();
+    };
+    let source_attributes = attributes(source_family);
+
+    let mut equivalents: Vec<&str> = instance_types
+        .into_iter()
+        .filter(|candidate| {
+            let candidate_family = family(candidate);
+            size(candidate) == Some(source_size)
+                && is_graviton(candidate_family)
+                && parse_family(candidate_family).is_some_and(|(c, _, _)| c == class)
+                && attributes(candidate_family) == source_attributes
+        })
+        .collect();
+    equivalents.sort_by_key(|candidate| (generation(family(candidate)), *candidate));
+    equivalents.dedup();
+    equivalents
+}
+
+/// Returns the newer generations of an instance type among the given instance types,
+/// i.e. the same class, size, attributes and processor vendor (e.g., "m6i.large" and
+/// "m7i.large" for "m5.large"), ordered by generation
+pub fn newer_generations<'a>(
+    instance_type: &str,
+    instance_types: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let source_family = family(instance_type);
+    let (Some((class, source_generation, _)), Some(source_size)) =
+        (parse_family(source_family), size(instance_type))
+    else {
+        return Vec::new();
+    };
+
+    // Intel families have no processor letter before the 6th generation (e.g., m5 -> m6i)
+    let vendor = |family: &str| match processor(family) {
+        Some('i') | None => 'i',
+        Some(c) => c,
+    };
+    let source_vendor = vendor(source_family);
+    let source_attributes = attributes(source_family);
+
+    let mut newer: Vec<&str> = instance_types
+        .into_iter()
+        .filter(|candidate| {
+            let candidate_family = family(candidate);
+            size(candidate) == Some(source_size)
+                && vendor(candidate_family) == source_vendor
+                && attributes(candidate_family) == source_attributes
+                && parse_family(candidate_family)
+                    .is_some_and(|(c, g, _)| c == class && g > source_generation)
+        })
+        .collect();
+    newer.sort_by_key(|candidate| (generation(family(candidate)), *candidate));
+    newer.dedup();
+    newer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_family() {
+        assert_eq!(family("m5.large"), "m5");
+        assert_eq!(family("m6i.2xlarge"), "m6i");
+        assert_eq!(family("m5"), "m5");
+    }
+
+    #[test]
+    fn test_size() {
+        assert_eq!(size("m5.large"), Some("large"));
+        assert_eq!(size("c7gn.metal"), Some("metal"));
+        assert_eq!(size("m5"), None);
+    }
+
+    #[test]
+    fn test_generation() {
+        assert_eq!(generation("m5"), Some(5));
+        assert_eq!(generation("m6i"), Some(6));
+        assert_eq!(generation("x2iedn"), Some(2));
+        assert_eq!(generation("p4d"), Some(4));
+        assert_eq!(generation("mac"), None);
+    }
+
+    #[test]
+    fn test_parse_family() {
+        assert_eq!(parse_family("m6gd"), Some(("m", 6, "gd")));
+        assert_eq!(parse_family("m5"), Some(("m", 5, "")));
+        assert_eq!(parse_family("im4gn"), Some(("im", 4, "gn")));
+        assert_eq!(parse_family("mac"), None);
+    }
+
+    #[test]
+    fn test_processor_and_attributes() {
+        assert_eq!(processor("m5"), None);
+        assert_eq!(processor("m5a"), Some('a'));
+        assert_eq!(processor("m6i"), Some('i'));
+        assert_eq!(processor("c7gn"), Some('g'));
+        assert!(is_graviton("t4g"));
+        assert!(!is_graviton("t3"));
+        assert_eq!(attributes("m5ad"), "d");
+        assert_eq!(attributes("m5d"), "d");
+        assert_eq!(attributes("c6gn"), "n");
+        assert_eq!(attributes("m6i"), "");
+    }
+
+    #[test]
+    fn test_graviton_equivalents() {
+        let instance_types = [
+            "m5.2xlarge",
+            "m7g.2xlarge",
+            "m6g.2xlarge",
+            "m6g.xlarge",
+            "m6gd.2xlarge",
+            "c6g.2xlarge",
+            "m6i.2xlarge",
+        ];
+        assert_eq!(
+            graviton_equivalents("m5.2xlarge", instance_types),
+            vec!["m6g.2xlarge", "m7g.2xlarge"]
+        );
+        assert_eq!(
+            graviton_equivalents("m5d.2xlarge", instance_types),
+            vec!["m6gd.2xlarge"]
+        );
+        assert!(graviton_equivalents("r5.2xlarge", instance_types).is_empty());
+        assert!(graviton_equivalents("m5", instance_types).is_empty());
+    }
+
+    #[test]
+    fn test_newer_generations() {
+        let instance_types = [
+            "m4.large",
+            "m5.large",
+            "m6i.large",
+            "m7i.large",
+            "m6a.large",
+            "m6g.large",
+            "m6i.xlarge",
+            "m6id.large",
+            "c6i.large",
+        ];
+        assert_eq!(
+            newer_generations("m4.large", instance_types),
+            vec!["m5.large", "m6i.large", "m7i.large"]
+        );
+        assert_eq!(
+            newer_generations("m5.large", instance_types),
+            vec!["m6i.large", "m7i.large"]
+        );
+        assert_eq!(
+            newer_generations("m5a.large", instance_types),
+            vec!["m6a.large"]
+        );
+        assert!(newer_generations("m7i.large", instance_types).is_empty());
+    }
+}
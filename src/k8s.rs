@@ -0,0 +1,730 @@
+use crate::cli::Os;
+use crate::display::{InstanceData, InstanceInfo};
+use crate::instance;
+use crate::recommend::{self, Constraints};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Well-known label of the instance type of a node
+pub const INSTANCE_TYPE_LABEL: &str = "node.kubernetes.io/instance-type";
+
+/// Karpenter label of the capacity type ("spot" or "on-demand") of a node
+pub const CAPACITY_TYPE_LABEL: &str = "karpenter.sh/capacity-type";
+
+/// EKS managed node group label of the capacity type ("SPOT" or "ON_DEMAND") of a node
+const EKS_CAPACITY_TYPE_LABEL: &str = "eks.amazonaws.com/capacityType";
+
+/// Labels of a node derived from its instance type, which requirements on can be evaluated
+/// against the advisor data
+const INSTANCE_LABELS: &[&str] = &[
+    INSTANCE_TYPE_LABEL,
+    "karpenter.k8s.aws/instance-family",
+    "karpenter.k8s.aws/instance-category",
+    "karpenter.k8s.aws/instance-generation",
+    "karpenter.k8s.aws/instance-size",
+    "karpenter.k8s.aws/instance-cpu",
+    "karpenter.k8s.aws/instance-memory",
+    "kubernetes.io/arch",
+];
+
+/// Node selector requirement of a Karpenter NodePool
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Requirement {
+    pub key: String,
+    pub operator: String,
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+/// Returns the value of a label derived from an instance type, None when the advisor data lacks it
+fn label_value(key: &str, instance_type: &str, info: &InstanceInfo) -> Option<String> {
+    let family = instance::family(instance_type);
+    match key {
+        INSTANCE_TYPE_LABEL => Some(instance_type.to_string()),
+        "karpenter.k8s.aws/instance-family" => Some(family.to_string()),
+        "karpenter.k8s.aws/instance-category" => {
+            instance::parse_family(family).map(|(class, ..)| class.to_string())
+        }
+        "karpenter.k8s.aws/instance-generation" => {
+            instance::generation(family).map(|generation| generation.to_string())
+        }
+        "karpenter.k8s.aws/instance-size" => instance::size(instance_type).map(str::to_string),
+        "karpenter.k8s.aws/instance-cpu" => info.cores_value().map(|cores| cores.to_string()),
+        "karpenter.k8s.aws/instance-memory" => info
+            .memory_gb_value()
+            .map(|memory_gb| ((memory_gb * 1024.0).round()).to_string()),
+        "kubernetes.io/arch" => Some(
+            if instance::is_graviton(family) {
+                "arm64"
+            } else {
+                "amd64"
+            }
+            .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+impl Requirement {
+    /// Returns whether the requirement is on a label derived from the instance type
+    pub fn is_instance_requirement(&self) -> bool {
+        INSTANCE_LABELS.contains(&self.key.as_str())
+    }
+
+    /// Returns whether a label value satisfies the requirement
+    fn matches(&self, value: Option<&str>) -> bool {
+        let bound = || {
+            self.values
+                .first()
+                .and_then(|bound| bound.parse::<f64>().ok())
+        };
+        let number = || value.and_then(|value| value.parse::<f64>().ok());
+        match self.operator.as_str() {
+            "In" => value.is_some_and(|value| self.values.iter().any(|v| v == value)),
+            "NotIn" => value.is_none_or(|value| self.values.iter().all(|v| v != value)),
+            "Exists" => value.is_some(),
+            "DoesNotExist" => value.is_none(),
+            "Gt" => number().zip(bound()).is_some_and(|(n, bound)| n > bound),
+            "Lt" => number().zip(bound()).is_some_and(|(n, bound)| n < bound),
+            _ => true,
+        }
+    }
+
+    /// Returns whether an instance type satisfies the requirement, true for requirements on other
+    /// labels
+    pub fn allows(&self, instance_type: &str, info: &InstanceInfo) -> bool {
+        !self.is_instance_requirement()
+            || self.matches(label_value(&self.key, instance_type, info).as_deref())
+    }
+}
+
+/// Returns the requirements of a NodePool's node template
+pub fn requirements(node_pool: &Value) -> Result<Vec<Requirement>> {
+    let requirements = &node_pool["spec"]["template"]["spec"]["requirements"];
+    if requirements.is_null() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_value(requirements.clone()).context("Invalid NodePool requirements")
+}
+
+/// Returns whether the requirements allow spot capacity, which Karpenter doesn't launch unless
+/// the capacity type requirement allows it
+pub fn allows_spot(requirements: &[Requirement]) -> bool {
+    requirements
+        .iter()
+        .any(|requirement| requirement.key == CAPACITY_TYPE_LABEL)
+        && requirements
+            .iter()
+            .filter(|requirement| requirement.key == CAPACITY_TYPE_LABEL)
+            .all(|requirement| requirement.matches(Some("spot")))
+}
+
+/// Returns the instance types of the advisor data in a region satisfying all the requirements,
+/// sorted by name
+pub fn allowed_instance_types(
+    requirements: &[Requirement],
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+) -> Vec<String> {
+    let os_name = os.advisor_keys()[0];
+    let mut instance_types: Vec<String> = instance_data
+        .iter()
+        .filter(|((_, os_key), _)| os_key == os_name)
+        .filter_map(|((instance_type, _), region_map)| {
+            let info = region_map.get(region)?;
+            requirements
+                .iter()
+                .all(|requirement| requirement.allows(instance_type, info))
+                .then(|| instance_type.clone())
+        })
+        .collect();
+    instance_types.sort();
+    instance_types
+}
+
+/// Nodes of the cluster of an instance type and capacity type
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeGroup {
+    pub instance_type: String,
+    /// "spot", "on-demand", or "unknown" without a capacity type label
+    pub capacity_type: String,
+    pub nodes: usize,
+}
+
+/// Returns the capacity type of a node from the Karpenter or EKS managed node group label
+pub fn capacity_type(labels: &BTreeMap<String, String>) -> String {
+    if let Some(capacity_type) = labels.get(CAPACITY_TYPE_LABEL) {
+        return capacity_type.clone();
+    }
+    match labels.get(EKS_CAPACITY_TYPE_LABEL).map(String::as_str) {
+        Some("SPOT") => "spot".to_string(),
+        Some("ON_DEMAND") => "on-demand".to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Groups the nodes by their instance type and capacity type labels, the most nodes first, leaving
+/// out nodes without an instance type label
+pub fn group_nodes(node_labels: &[BTreeMap<String, String>]) -> Vec<NodeGroup> {
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    for labels in node_labels {
+        let Some(instance_type) = labels.get(INSTANCE_TYPE_LABEL) else {
+            continue;
+        };
+        *counts
+            .entry((instance_type.clone(), capacity_type(labels)))
+            .or_default() += 1;
+    }
+
+    let mut groups: Vec<NodeGroup> = counts
+        .into_iter()
+        .map(|((instance_type, capacity_type), nodes)| NodeGroup {
+            instance_type,
+            capacity_type,
+            nodes,
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.nodes
+            .cmp(&a.nodes)
+            .then_with(|| a.instance_type.cmp(&b.instance_type))
+            .then_with(|| a.capacity_type.cmp(&b.capacity_type))
+    });
+    groups
+}
+
+/// Fraction of a node's vCPUs and memory allocatable to pods, the rest reserved for the kubelet,
+/// the system daemons and eviction
+const ALLOCATABLE_FRACTION: f64 = 0.9;
+
+/// Resource requests of a pod
+#[derive(Clone, Debug, PartialEq)]
+pub struct PodRequests {
+    pub name: String,
+    pub vcpus: f64,
+    pub memory_gib: f64,
+}
+
+/// Cheapest number of nodes of an instance type hosting every pod of a workload
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    pub instance_type: String,
+    pub interruption_rate: String,
+    pub nodes: usize,
+    /// Spot price of all the nodes (USD/hour)
+    pub hourly_cost: f64,
+    /// Hourly cost inflated by the risk penalty of the interruption rate
+    pub effective_cost: f64,
+    /// Requested share (%) of the nodes' vCPUs
+    pub vcpu_utilization: f64,
+    /// Requested share (%) of the nodes' memory
+    pub memory_utilization: f64,
+}
+
+/// Parses a CPU quantity like "500m" or "2" into vCPUs
+fn parse_cpu(quantity: &str) -> Option<f64> {
+    match quantity.strip_suffix('m') {
+        Some(millicores) => millicores.parse::<f64>().ok().map(|m| m / 1000.0),
+        None => quantity.parse().ok(),
+    }
+}
+
+/// Parses a memory quantity like "512Mi", "1G" or "1073741824" into GiB
+fn parse_memory_gib(quantity: &str) -> Option<f64> {
+    const SUFFIXES: &[(&str, f64)] = &[
+        ("Ki", 1024.0),
+        ("Mi", 1048576.0),
+        ("Gi", 1073741824.0),
+        ("Ti", 1099511627776.0),
+        ("k", 1e3),
+        ("M", 1e6),
+        ("G", 1e9),
+        ("T", 1e12),
+    ];
+    let (number, multiplier) = SUFFIXES
+        .iter()
+        .find_map(|(suffix, multiplier)| {
+            quantity
+                .strip_suffix(suffix)
+                .map(|number| (number, *multiplier))
+        })
+        .unwrap_or((quantity, 1.0));
+    let bytes = number.parse::<f64>().ok()? * multiplier;
+    Some(bytes / 1073741824.0)
+}
+
+/// Returns the summed requests of containers, a missing request counting as zero
+fn container_requests(containers: &Value) -> Vec<(f64, f64)> {
+    containers
+        .as_array()
+        .map(|containers| {
+            containers
+                .iter()
+                .map(|container| {
+                    let requests = &container["resources"]["requests"];
+                    (
+                        requests["cpu"].as_str().and_then(parse_cpu).unwrap_or(0.0),
+                        requests["memory"]
+                            .as_str()
+                            .and_then(parse_memory_gib)
+                            .unwrap_or(0.0),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the effective requests of a pod, the larger of its containers' sum and its largest
+/// init container, or None for a finished pod
+pub fn pod_requests(pod: &Value) -> Option<PodRequests> {
+    if matches!(
+        pod["status"]["phase"].as_str(),
+        Some("Succeeded") | Some("Failed")
+    ) {
+        return None;
+    }
+    let containers = container_requests(&pod["spec"]["containers"]);
+    let init_containers = container_requests(&pod["spec"]["initContainers"]);
+    let sum = |f: fn(&(f64, f64)) -> f64| containers.iter().map(f).sum::<f64>();
+    let max = |f: fn(&(f64, f64)) -> f64| init_containers.iter().map(f).fold(0.0, f64::max);
+
+    Some(PodRequests {
+        name: pod["metadata"]["name"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        vcpus: sum(|r| r.0).max(max(|r| r.0)),
+        memory_gib: sum(|r| r.1).max(max(|r| r.1)),
+    })
+}
+
+/// Returns the number of nodes of the vCPUs and memory (GiB) hosting every pod by first-fit
+/// decreasing bin-packing, or None when a pod fits no node
+pub fn pack(pods: &[PodRequests], vcpus: f64, memory_gib: f64) -> Option<usize> {
+    let vcpus = vcpus * ALLOCATABLE_FRACTION;
+    let memory_gib = memory_gib * ALLOCATABLE_FRACTION;
+    let size = |pod: &PodRequests| (pod.vcpus / vcpus).max(pod.memory_gib / memory_gib);
+
+    let mut pods: Vec<&PodRequests> = pods.iter().collect();
+    pods.sort_by(|a, b| size(b).total_cmp(&size(a)));
+
+    // Remaining vCPUs and memory of each node
+    let mut nodes: Vec<(f64, f64)> = Vec::new();
+    for pod in pods {
+        if size(pod) > 1.0 {
+            return None;
+        }
+        match nodes
+            .iter_mut()
+            .find(|(cpu, memory)| pod.vcpus <= *cpu && pod.memory_gib <= *memory)
+        {
+            Some((cpu, memory)) => {
+                *cpu -= pod.vcpus;
+                *memory -= pod.memory_gib;
+            }
+            None => nodes.push((vcpus - pod.vcpus, memory_gib - pod.memory_gib)),
+        }
+    }
+    Some(nodes.len())
+}
+
+/// Returns the instance types within the maximum interruption rate (%) hosting the pods, the
+/// lowest effective cost of their nodes first
+pub fn fit(
+    pods: &[PodRequests],
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    max_interruption: f64,
+    risk_penalties: &[f64],
+) -> Vec<Fit> {
+    let constraints = Constraints {
+        max_interruption: Some(max_interruption),
+        ..Default::default()
+    };
+    let total_vcpus: f64 = pods.iter().map(|pod| pod.vcpus).sum();
+    let total_memory_gib: f64 = pods.iter().map(|pod| pod.memory_gib).sum();
+
+    let mut fits: Vec<Fit> =
+        recommend::recommend(instance_data, region, os, &constraints, risk_penalties)
+            .into_iter()
+            .filter_map(|recommendation| {
+                let info = &recommendation.info;
+                let vcpus = info.cores_value().filter(|vcpus| *vcpus > 0.0)?;
+                let memory_gib = info.memory_gb_value().filter(|memory| *memory > 0.0)?;
+                let nodes = pack(pods, vcpus, memory_gib)?;
+                let node_count = nodes as f64;
+                Some(Fit {
+                    interruption_rate: info.interruption_rate.clone(),
+                    nodes,
+                    hourly_cost: info.spot_price_value(os.price_column())? * node_count,
+                    effective_cost: recommendation.effective_cost * node_count,
+                    vcpu_utilization: total_vcpus / (vcpus * node_count) * 100.0,
+                    memory_utilization: total_memory_gib / (memory_gib * node_count) * 100.0,
+                    instance_type: recommendation.instance_type,
+                })
+            })
+            .collect();
+    fits.sort_by(|a, b| {
+        a.effective_cost
+            .total_cmp(&b.effective_cost)
+            .then_with(|| a.nodes.cmp(&b.nodes))
+            .then_with(|| a.instance_type.cmp(&b.instance_type))
+    });
+    fits
+}
+
+#[cfg(feature = "k8s")]
+async fn client(context: Option<&str>) -> Result<kube::Client> {
+    let config = match context {
+        Some(context) => {
+            let options = kube::config::KubeConfigOptions {
+                context: Some(context.to_string()),
+                ..Default::default()
+            };
+            kube::Config::from_kubeconfig(&options).await?
+        }
+        None => kube::Config::infer().await?,
+    };
+    kube::Client::try_from(config).context("Failed to create the Kubernetes client")
+}
+
+/// Fetches a Karpenter NodePool from the cluster of the kubeconfig's context
+#[cfg(feature = "k8s")]
+pub async fn fetch_node_pool(context: Option<&str>, name: &str) -> Result<Value> {
+    use kube::api::{Api, ApiResource, DynamicObject, GroupVersionKind};
+
+    let gvk = GroupVersionKind::gvk("karpenter.sh", "v1", "NodePool");
+    let api: Api<DynamicObject> =
+        Api::all_with(client(context).await?, &ApiResource::from_gvk(&gvk));
+    let node_pool = api
+        .get(name)
+        .await
+        .with_context(|| format!("Failed to get NodePool '{}'", name))?;
+    Ok(serde_json::to_value(node_pool)?)
+}
+
+/// Fetches the labels of every node of the cluster of the kubeconfig's context
+#[cfg(feature = "k8s")]
+pub async fn fetch_node_labels(context: Option<&str>) -> Result<Vec<BTreeMap<String, String>>> {
+    use k8s_openapi::api::core::v1::Node;
+    use kube::api::{Api, ListParams};
+
+    let api: Api<Node> = Api::all(client(context).await?);
+    let nodes = api
+        .list(&ListParams::default())
+        .await
+        .context("Failed to list the nodes")?;
+    Ok(nodes
+        .items
+        .into_iter()
+        .map(|node| node.metadata.labels.unwrap_or_default())
+        .collect())
+}
+
+/// Fetches the pods of a namespace of the cluster of the kubeconfig's context
+#[cfg(feature = "k8s")]
+pub async fn fetch_pods(context: Option<&str>, namespace: &str) -> Result<Vec<Value>> {
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::api::{Api, ListParams};
+
+    let api: Api<Pod> = Api::namespaced(client(context).await?, namespace);
+    let pods = api
+        .list(&ListParams::default())
+        .await
+        .with_context(|| format!("Failed to list the pods of namespace '{}'", namespace))?;
+    pods.items
+        .into_iter()
+        .map(|pod| Ok(serde_json::to_value(pod)?))
+        .collect()
+}
+
+#[cfg(not(feature = "k8s"))]
+pub async fn fetch_node_pool(_context: Option<&str>, _name: &str) -> Result<Value> {
+    Err(feature_error())
+}
+
+#[cfg(not(feature = "k8s"))]
+pub async fn fetch_node_labels(_context: Option<&str>) -> Result<Vec<BTreeMap<String, String>>> {
+    Err(feature_error())
+}
+
+#[cfg(not(feature = "k8s"))]
+pub async fn fetch_pods(_context: Option<&str>, _namespace: &str) -> Result<Vec<Value>> {
+    Err(feature_error())
+}
+
+#[cfg(not(feature = "k8s"))]
+fn feature_error() -> anyhow::Error {
+    anyhow::anyhow!("Kubernetes commands require building spotter with the 'k8s' feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+    use serde_json::json;
+
+    fn requirement(key: &str, operator: &str, values: &[&str]) -> Requirement {
+        Requirement {
+            key: key.to_string(),
+            operator: operator.to_string(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_requirement_allows() {
+        let info = info("< 5%", "0.05").with_size("4", "16");
+        let allows = |key, operator, values: &[&str]| {
+            requirement(key, operator, values).allows("m6g.xlarge", &info)
+        };
+        assert!(allows(
+            INSTANCE_TYPE_LABEL,
+            "In",
+            &["m6g.xlarge", "m6i.xlarge"]
+        ));
+        assert!(!allows(INSTANCE_TYPE_LABEL, "NotIn", &["m6g.xlarge"]));
+        assert!(allows(
+            "karpenter.k8s.aws/instance-category",
+            "In",
+            &["c", "m"]
+        ));
+        assert!(allows(
+            "karpenter.k8s.aws/instance-generation",
+            "Gt",
+            &["5"]
+        ));
+        assert!(!allows("karpenter.k8s.aws/instance-cpu", "Lt", &["4"]));
+        assert!(allows(
+            "karpenter.k8s.aws/instance-memory",
+            "In",
+            &["16384"]
+        ));
+        assert!(allows("karpenter.k8s.aws/instance-size", "In", &["xlarge"]));
+        assert!(!allows("kubernetes.io/arch", "In", &["amd64"]));
+        // Requirements on labels of other things than the instance type allow every type
+        assert!(allows("topology.kubernetes.io/zone", "In", &["us-east-1a"]));
+    }
+
+    #[test]
+    fn test_requirements() {
+        let node_pool = json!({
+            "spec": {
+                "template": {
+                    "spec": {
+                        "requirements": [
+                            { "key": CAPACITY_TYPE_LABEL, "operator": "In", "values": ["spot"] },
+                            { "key": "kubernetes.io/arch", "operator": "Exists" }
+                        ]
+                    }
+                }
+            }
+        });
+        let requirements = requirements(&node_pool).unwrap();
+        assert_eq!(
+            requirements[1],
+            requirement("kubernetes.io/arch", "Exists", &[])
+        );
+        assert!(allows_spot(&requirements));
+        assert!(requirements(&json!({})).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_allows_spot() {
+        assert!(!allows_spot(&[]));
+        assert!(allows_spot(&[requirement(
+            CAPACITY_TYPE_LABEL,
+            "In",
+            &["spot", "on-demand"]
+        )]));
+        assert!(!allows_spot(&[requirement(
+            CAPACITY_TYPE_LABEL,
+            "NotIn",
+            &["spot"]
+        )]));
+    }
+
+    fn labels(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_capacity_type() {
+        assert_eq!(
+            capacity_type(&labels(&[(CAPACITY_TYPE_LABEL, "spot")])),
+            "spot"
+        );
+        assert_eq!(
+            capacity_type(&labels(&[(EKS_CAPACITY_TYPE_LABEL, "ON_DEMAND")])),
+            "on-demand"
+        );
+        assert_eq!(capacity_type(&labels(&[])), "unknown");
+    }
+
+    #[test]
+    fn test_group_nodes() {
+        let groups = group_nodes(&[
+            labels(&[
+                (INSTANCE_TYPE_LABEL, "m6i.xlarge"),
+                (CAPACITY_TYPE_LABEL, "spot"),
+            ]),
+            labels(&[
+                (INSTANCE_TYPE_LABEL, "m6i.xlarge"),
+                (CAPACITY_TYPE_LABEL, "on-demand"),
+            ]),
+            labels(&[
+                (INSTANCE_TYPE_LABEL, "m6i.xlarge"),
+                (EKS_CAPACITY_TYPE_LABEL, "SPOT"),
+            ]),
+            labels(&[("kubernetes.io/os", "linux")]),
+        ]);
+        assert_eq!(
+            groups,
+            vec![
+                NodeGroup {
+                    instance_type: "m6i.xlarge".to_string(),
+                    capacity_type: "spot".to_string(),
+                    nodes: 2,
+                },
+                NodeGroup {
+                    instance_type: "m6i.xlarge".to_string(),
+                    capacity_type: "on-demand".to_string(),
+                    nodes: 1,
+                },
+            ]
+        );
+    }
+
+    fn pod(vcpus: f64, memory_gib: f64) -> PodRequests {
+        PodRequests {
+            name: "pod".to_string(),
+            vcpus,
+            memory_gib,
+        }
+    }
+
+    #[test]
+    fn test_parse_quantities() {
+        assert_eq!(parse_cpu("500m"), Some(0.5));
+        assert_eq!(parse_cpu("2"), Some(2.0));
+        assert_eq!(parse_cpu("x"), None);
+        assert_eq!(parse_memory_gib("512Mi"), Some(0.5));
+        assert_eq!(parse_memory_gib("2Gi"), Some(2.0));
+        assert_eq!(parse_memory_gib("1073741824"), Some(1.0));
+        assert!((parse_memory_gib("1G").unwrap() - 0.9313).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_pod_requests() {
+        let pod = json!({
+            "metadata": { "name": "worker" },
+            "spec": {
+                "containers": [
+                    { "resources": { "requests": { "cpu": "500m", "memory": "1Gi" } } },
+                    { "resources": { "requests": { "cpu": "250m" } } }
+                ],
+                "initContainers": [
+                    { "resources": { "requests": { "cpu": "100m", "memory": "2Gi" } } }
+                ]
+            },
+            "status": { "phase": "Running" }
+        });
+        assert_eq!(
+            pod_requests(&pod),
+            Some(PodRequests {
+                name: "worker".to_string(),
+                vcpus: 0.75,
+                memory_gib: 2.0,
+            })
+        );
+        assert_eq!(
+            pod_requests(&json!({ "status": { "phase": "Succeeded" } })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pack() {
+        // 0.9 of 4 vCPUs hosts two 1.5 vCPU pods per node
+        let pods = vec![pod(1.5, 1.0); 5];
+        assert_eq!(pack(&pods, 4.0, 16.0), Some(3));
+        // Memory bound, 0.9 of 4 GiB hosting three 1 GiB pods per node
+        assert_eq!(pack(&pods, 16.0, 4.0), Some(2));
+        // A pod larger than a node
+        assert_eq!(pack(&[pod(8.0, 1.0)], 4.0, 16.0), None);
+        assert_eq!(pack(&[], 4.0, 16.0), Some(0));
+    }
+
+    #[test]
+    fn test_fit() {
+        let instance_data = instance_data([
+            (
+                "m6i.xlarge",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.05").with_size("4", "16"),
+            ),
+            (
+                "m6i.4xlarge",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.05").with_size("16", "64"),
+            ),
+        ]);
+        let fits = fit(
+            &vec![pod(3.0, 4.0); 4],
+            &instance_data,
+            "us-east-1",
+            Os::Linux,
+            10.0,
+            &crate::cli::DEFAULT_RISK_PENALTIES,
+        );
+        // A single larger node hosts all the pods, while the smaller nodes only host one each
+        assert_eq!(fits[0].instance_type, "m6i.4xlarge");
+        assert_eq!(fits[0].nodes, 1);
+        assert!((fits[0].hourly_cost - 0.05).abs() < 1e-9);
+        assert!((fits[0].vcpu_utilization - 75.0).abs() < 1e-9);
+        assert_eq!(fits[1].instance_type, "m6i.xlarge");
+        assert_eq!(fits[1].nodes, 4);
+    }
+
+    #[test]
+    fn test_allowed_instance_types() {
+        let instance_data = instance_data([
+            (
+                "m6i.xlarge",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.05").with_size("4", "16"),
+            ),
+            (
+                "m6i.4xlarge",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.05").with_size("16", "64"),
+            ),
+            (
+                "c5.xlarge",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.05").with_size("4", "8"),
+            ),
+        ]);
+        let requirements = [
+            requirement("karpenter.k8s.aws/instance-generation", "Gt", &["5"]),
+            requirement("karpenter.k8s.aws/instance-cpu", "Lt", &["9"]),
+        ];
+        assert_eq!(
+            allowed_instance_types(&requirements, &instance_data, "us-east-1", Os::Linux),
+            vec!["m6i.xlarge".to_string()]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use crate::cli::{AwsArgs, Os};
+use anyhow::{Context, Result};
+use aws_sdk_ec2::primitives::DateTime;
+use aws_sdk_ec2::types::{InstanceType, SpotPrice};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::SystemTime;
+
+/// Instance types per DescribeSpotPriceHistory request, keeping the filter well under the API limits
+const INSTANCE_TYPES_PER_REQUEST: usize = 100;
+
+/// Current spot price of an instance type in an Availability Zone
+#[derive(Clone, Debug, PartialEq)]
+pub struct AzPrice {
+    pub instance_type: String,
+    pub availability_zone: String,
+    pub price: f64,
+    /// Unix time (seconds) the price took effect
+    pub timestamp: i64,
+}
+
+/// Spot prices of instance types per Availability Zone, as rows of a table
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AzPriceTable {
+    pub availability_zones: Vec<String>,
+    /// Prices of each instance type, one per Availability Zone
+    pub rows: Vec<(String, Vec<Option<f64>>)>,
+}
+
+/// Returns the product description of the OS in DescribeSpotPriceHistory, following the price
+/// column of the OS
+pub fn product_description(os: Os) -> &'static str {
+    match os {
+        Os::Windows => "Windows",
+        Os::Linux | Os::All => "Linux/UNIX",
+    }
+}
+
+/// Returns the price of an item of DescribeSpotPriceHistory, or None when a field is missing
+fn az_price(spot_price: SpotPrice) -> Option<AzPrice> {
+    Some(AzPrice {
+        instance_type: spot_price.instance_type()?.as_str().to_string(),
+        availability_zone: spot_price.availability_zone()?.to_string(),
+        price: spot_price.spot_price()?.parse().ok()?,
+        timestamp: spot_price.timestamp()?.secs(),
+    })
+}
+
+/// Keeps the latest price of each instance type and Availability Zone
+fn latest(prices: Vec<AzPrice>) -> Vec<AzPrice> {
+    let mut latest: BTreeMap<(String, String), AzPrice> = BTreeMap::new();
+    for price in prices {
+        let key = (price.instance_type.clone(), price.availability_zone.clone());
+        if latest
+            .get(&key)
+            .is_none_or(|current| current.timestamp < price.timestamp)
+        {
+            latest.insert(key, price);
+        }
+    }
+    latest.into_values().collect()
+}
+
+/// Pivots the prices into a row per instance type and a column per Availability Zone
+pub fn pivot(prices: &[AzPrice]) -> AzPriceTable {
+    let availability_zones: Vec<String> = prices
+        .iter()
+        .map(|p| p.availability_zone.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut rows: BTreeMap<&str, Vec<Option<f64>>> = BTreeMap::new();
+    for price in prices {
+        let row = rows
+            .entry(&price.instance_type)
+            .or_insert_with(|| vec![None; availability_zones.len()]);
+        if let Ok(index) = availability_zones.binary_search(&price.availability_zone) {
+            row[index] = Some(price.price);
+        }
+    }
+
+    AzPriceTable {
+        availability_zones,
+        rows: rows
+            .into_iter()
+            .map(|(instance_type, prices)| (instance_type.to_string(), prices))
+            .collect(),
+    }
+}
+
+/// Fetches the current spot prices per Availability Zone of the instance types (every instance
+/// type when empty) with DescribeSpotPriceHistory, using the credentials of the AWS environment
+pub async fn fetch_az_prices(
+    aws: &AwsArgs,
+    region: &str,
+    instance_types: &[String],
+    os: Os,
+) -> Result<Vec<AzPrice>> {
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_ec2::Client::new(&config);
+    // A start time of now only returns the prices in effect
+    let now = DateTime::from(SystemTime::now());
+
+    let batches: Vec<&[String]> = if instance_types.is_empty() {
+        vec![&[]]
+    } else {
+        instance_types.chunks(INSTANCE_TYPES_PER_REQUEST).collect()
+    };
+    let mut prices = Vec::new();
+    for batch in batches {
+        let history = client
+            .describe_spot_price_history()
+            .start_time(now)
+            .product_descriptions(product_description(os))
+            .set_instance_types(
+                (!batch.is_empty())
+                    .then(|| batch.iter().map(|t| InstanceType::from(t.as_str())).collect()),
+            )
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to describe the spot price history in {} (are AWS credentials configured?)",
+                    region
+                )
+            })?;
+        prices.extend(history.into_iter().filter_map(az_price));
+    }
+
+    Ok(latest(prices))
+}
+
+/// Fetches every spot price change of an instance type since a Unix time (seconds) in each
+/// Availability Zone with DescribeSpotPriceHistory, which keeps 90 days of history, using the
+/// credentials of the AWS environment
+pub async fn fetch_price_history(
+    aws: &AwsArgs,
+    region: &str,
+    instance_type: &str,
+    os: Os,
+    since: i64,
+) -> Result<Vec<AzPrice>> {
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_ec2::Client::new(&config);
+    let history = client
+        .describe_spot_price_history()
+        .start_time(DateTime::from_secs(since))
+        .end_time(DateTime::from(SystemTime::now()))
+        .product_descriptions(product_description(os))
+        .instance_types(InstanceType::from(instance_type))
+        .into_paginator()
+        .items()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to describe the spot price history in {} (are AWS credentials configured?)",
+                region
+            )
+        })?;
+
+    Ok(history.into_iter().filter_map(az_price).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(instance_type: &str, az: &str, price: f64, timestamp: i64) -> AzPrice {
+        AzPrice {
+            instance_type: instance_type.to_string(),
+            availability_zone: az.to_string(),
+            price,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_product_description() {
+        assert_eq!(product_description(Os::Linux), "Linux/UNIX");
+        assert_eq!(product_description(Os::Windows), "Windows");
+        assert_eq!(product_description(Os::All), "Linux/UNIX");
+    }
+
+    #[test]
+    fn test_latest() {
+        let prices = latest(vec![
+            price("m5.large", "us-east-1a", 0.04, 100),
+            price("m5.large", "us-east-1a", 0.05, 200),
+            price("m5.large", "us-east-1b", 0.03, 100),
+        ]);
+        assert_eq!(
+            prices,
+            vec![
+                price("m5.large", "us-east-1a", 0.05, 200),
+                price("m5.large", "us-east-1b", 0.03, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pivot() {
+        let table = pivot(&[
+            price("m5.large", "us-east-1b", 0.03, 100),
+            price("c5.large", "us-east-1c", 0.02, 100),
+            price("m5.large", "us-east-1a", 0.05, 100),
+        ]);
+        assert_eq!(
+            table.availability_zones,
+            vec!["us-east-1a", "us-east-1b", "us-east-1c"]
+        );
+        assert_eq!(
+            table.rows,
+            vec![
+                ("c5.large".to_string(), vec![None, None, Some(0.02)]),
+                ("m5.large".to_string(), vec![Some(0.05), Some(0.03), None]),
+            ]
+        );
+
+        assert_eq!(pivot(&[]), AzPriceTable::default());
+    }
+}
@@ -1,14 +1,87 @@
+mod alert;
+mod audit;
 mod aws;
+mod cache;
+mod chart;
+mod check;
 mod cli;
+mod clouds;
+mod config;
+mod dataset;
+mod diff;
 mod display;
+mod doctor;
+mod export;
+mod fleet;
+mod github;
+mod gpu;
+mod history;
+mod init;
+mod instance;
+mod k8s;
+mod live;
+mod notify;
+mod optimizer;
+mod pick;
+mod provider;
+mod publish;
+mod recommend;
+mod region;
+mod score;
+mod serve;
+mod simulate;
+mod snapshot;
+mod sources;
+mod specs;
+mod spend;
+mod telemetry;
+mod top;
+mod version;
+mod when;
+mod wizard;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Result, anyhow};
+use clap::{CommandFactory, FromArgMatches};
+use provider::SpotProvider;
 use reqwest::Client;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = cli::Cli::parse();
+    let args = config::expand_alias(std::env::args_os().collect()).unwrap_or_else(|e| {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    });
+    let matches = cli::Cli::command().get_matches_from(args);
+    let mut cli = cli::Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if cli.version {
+        let info = version::BuildInfo::current(&cli);
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        } else {
+            println!("{}", info.text());
+        }
+        return Ok(());
+    }
+    let applied = match config::resolve_path(cli.config.as_deref()) {
+        // The config file being generated may not be valid yet
+        _ if matches!(cli.command, Some(cli::Command::Init { .. })) => Ok(()),
+        Some((path, required)) => {
+            let profile = cli.profile.clone();
+            config::Config::load(&path, required || profile.is_some()).and_then(|config| {
+                config.apply_with_profile(profile.as_deref(), &mut cli, &matches)
+            })
+        }
+        None if cli.profile.is_some() => Err(anyhow!(
+            "No config file to read profiles from. Please set --config"
+        )),
+        None => Ok(()),
+    };
+    if let Err(e) = applied {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
 
     // Validate CLI arguments
     if let Err(e) = cli.validate() {
@@ -21,16 +94,1498 @@ async fn main() -> Result<()> {
         .init();
 
     let client = Client::new();
-    let advisor_data = aws::fetch_spot_advisor_data(&client).await?;
-    let price_data = aws::fetch_spot_price_data(&client).await?;
 
-    display::display_spot_data(
-        &cli.region,
-        cli.instance_type.as_deref(),
-        &advisor_data,
-        &price_data,
-        cli.spot_price,
-    )?;
+    let cache = if cli.no_cache {
+        None
+    } else {
+        cache::resolve_dir(cli.cache_dir.as_deref())
+            .map(|dir| cache::Cache::new(dir, Duration::from_secs_f64(cli.cache_ttl * 3600.0)))
+    };
+
+    match &cli.command {
+        Some(cli::Command::Regions) => {
+            let (advisor_data, price_data) =
+                dataset::load_data(&cli, &client, cache.as_ref()).await?;
+            display::display_regions(&advisor_data, &price_data)?;
+        }
+        Some(cli::Command::Families) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            display::display_families(&dataset.region, &dataset.advisor_data, &dataset.price_data)?;
+        }
+        Some(cli::Command::Describe { instance_type }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            display::display_instance_detail(
+                &dataset.region,
+                instance_type,
+                &dataset.advisor_data,
+                &dataset.price_data,
+            )?;
+        }
+        Some(cli::Command::Compare { instance_types }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            display::display_comparison(
+                &dataset.region,
+                instance_types,
+                &dataset.advisor_data,
+                &dataset.price_data,
+            )?;
+        }
+        Some(cli::Command::Stats { instance_type }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            display::display_stats(
+                &dataset.region,
+                instance_type.as_deref(),
+                &dataset.advisor_data,
+                &dataset.price_data,
+                cli.os,
+            )?;
+        }
+        Some(cli::Command::Estimate { spec, hours }) => {
+            let fleet = fleet::parse_spec(spec)?;
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let on_demand_prices = dataset.on_demand_prices(&cli, &client).await;
+            display::display_estimate(
+                &dataset.region,
+                &fleet,
+                &dataset.advisor_data,
+                &dataset.price_data,
+                cli.os,
+                on_demand_prices.as_ref(),
+                *hours,
+            )?;
+        }
+        Some(cli::Command::Simulate {
+            instance_type,
+            runtime,
+            checkpoint_loss,
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let on_demand_prices = dataset.on_demand_prices(&cli, &client).await;
+            let workload = simulate::Workload {
+                runtime_hours: *runtime,
+                // Without checkpoints an interruption loses all the work done so far
+                checkpoint_loss_hours: checkpoint_loss.unwrap_or(*runtime),
+            };
+            display::display_simulation(
+                &dataset.region,
+                instance_type,
+                &dataset.advisor_data,
+                &dataset.price_data,
+                cli.os,
+                on_demand_prices.as_ref(),
+                &workload,
+            )?;
+        }
+        Some(cli::Command::Strategy { types }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            display::display_strategy(
+                &dataset.region,
+                types,
+                &dataset.advisor_data,
+                &dataset.price_data,
+                cli.os,
+                &cli.risk_penalties,
+            )?;
+        }
+        Some(cli::Command::Score {
+            types,
+            target_capacity,
+            regions,
+            single_az,
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let regions = if regions.is_empty() {
+                vec![dataset.region.clone()]
+            } else {
+                regions.clone()
+            };
+            for region in &regions {
+                cli::validate_region(region)?;
+            }
+            let scores = score::fetch_scores(
+                &cli.aws,
+                cli.aws.aws_region.as_deref().unwrap_or(&dataset.region),
+                types,
+                *target_capacity,
+                &regions,
+                *single_az,
+            )
+            .await?;
+            let scores = score::placement_scores(
+                scores,
+                |region| {
+                    display::collect_instance_data(
+                        region,
+                        &dataset.advisor_data,
+                        &dataset.price_data,
+                        cli.os,
+                    )
+                },
+                cli.os,
+                types,
+            )?;
+            display::display_placement_scores(&scores)?;
+        }
+        Some(cli::Command::Export { target }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let mut instance_data = dataset.instance_data(cli.os);
+            if let cli::ExportTarget::EmrFleet { .. } = target {
+                let emr_instance_types = export::emr::emr_instance_types(&dataset.advisor_data);
+                instance_data
+                    .retain(|(instance_type, _), _| emr_instance_types.contains(instance_type));
+            }
+            if let cli::ExportTarget::Skypilot {
+                gpus, gpu_filter, ..
+            } = target
+                && (gpus.is_some() || !gpu_filter.is_empty())
+            {
+                let specs = specs::fetch_specs(&cli.aws, &dataset.region, &[]).await?;
+                instance_data.retain(|(instance_type, _), _| {
+                    specs.get(instance_type).is_some_and(|specs| {
+                        gpus.as_ref().is_none_or(|accelerator| {
+                            specs.gpu_count(&accelerator.model) == accelerator.count
+                        }) && gpu::matches(specs, gpu_filter)
+                    })
+                });
+            }
+            let mut recommendations = match target.selection() {
+                Some(selection) => export::select(
+                    &instance_data,
+                    &dataset.region,
+                    cli.os,
+                    selection,
+                    &cli.risk_penalties,
+                )?,
+                None => Vec::new(),
+            };
+            let output = match target {
+                cli::ExportTarget::Karpenter { name, cluster, .. } => {
+                    export::karpenter::render(&dataset.region, name, cluster, &recommendations)?
+                }
+                cli::ExportTarget::AsgPolicy {
+                    launch_template,
+                    on_demand_base,
+                    on_demand_percentage,
+                    ..
+                } => {
+                    let advice = export::strategy(&recommendations, cli.os)?;
+                    let options = export::asg::AsgOptions {
+                        launch_template,
+                        on_demand_base: *on_demand_base,
+                        on_demand_percentage: *on_demand_percentage,
+                    };
+                    export::asg::render(&recommendations, &advice, &options)?
+                }
+                cli::ExportTarget::SpotFleet {
+                    target_capacity,
+                    iam_fleet_role,
+                    launch_template,
+                    availability_zones,
+                    one_time,
+                    ..
+                } => {
+                    export::validate_availability_zones(&dataset.region, availability_zones)?;
+                    let advice = export::strategy(&recommendations, cli.os)?;
+                    let options = export::spot_fleet::SpotFleetOptions {
+                        target_capacity: *target_capacity,
+                        iam_fleet_role,
+                        launch_template,
+                        availability_zones,
+                        maintain: !one_time,
+                    };
+                    export::spot_fleet::render(&recommendations, &advice, &options)?
+                }
+                cli::ExportTarget::Ec2Fleet {
+                    target_capacity,
+                    launch_template,
+                    availability_zones,
+                    fleet_type,
+                    ..
+                } => {
+                    export::validate_availability_zones(&dataset.region, availability_zones)?;
+                    let advice = export::strategy(&recommendations, cli.os)?;
+                    let options = export::ec2_fleet::Ec2FleetOptions {
+                        target_capacity: *target_capacity,
+                        launch_template,
+                        availability_zones,
+                        fleet_type: *fleet_type,
+                    };
+                    export::ec2_fleet::render(&recommendations, &advice, &options)?
+                }
+                cli::ExportTarget::Terraform {
+                    launch_template_id,
+                    on_demand_base,
+                    on_demand_percentage,
+                    overrides_only,
+                    ..
+                } => {
+                    let advice = export::strategy(&recommendations, cli.os)?;
+                    let options = export::terraform::TerraformOptions {
+                        launch_template_id,
+                        on_demand_base: *on_demand_base,
+                        on_demand_percentage: *on_demand_percentage,
+                        overrides_only: *overrides_only,
+                    };
+                    export::terraform::render(&dataset.region, &recommendations, &advice, &options)
+                }
+                cli::ExportTarget::Eksctl {
+                    cluster,
+                    name,
+                    min_size,
+                    max_size,
+                    ..
+                } => {
+                    let node_group_types = export::eksctl::single_architecture(&recommendations);
+                    if node_group_types.len() < recommendations.len() {
+                        log::warn!(
+                            "Left out {} recommended instance types of another architecture than {}",
+                            recommendations.len() - node_group_types.len(),
+                            node_group_types[0].instance_type
+                        );
+                    }
+                    let options = export::eksctl::EksctlOptions {
+                        cluster,
+                        name,
+                        min_size: *min_size,
+                        max_size: *max_size,
+                    };
+                    export::eksctl::render(&dataset.region, &node_group_types, &options)?
+                }
+                cli::ExportTarget::Batch {
+                    name,
+                    max_vcpus,
+                    subnets,
+                    security_group_ids,
+                    instance_role,
+                    max_price_margin,
+                    ..
+                } => {
+                    let advice = export::strategy(&recommendations, cli.os)?;
+                    let options = export::batch::BatchOptions {
+                        name,
+                        max_vcpus: *max_vcpus,
+                        subnets,
+                        security_group_ids,
+                        instance_role,
+                        max_price_margin: *max_price_margin,
+                    };
+                    export::batch::render(&recommendations, &advice, &options)?
+                }
+                cli::ExportTarget::EmrFleet {
+                    name,
+                    fleet_type,
+                    target_spot_capacity,
+                    timeout_minutes,
+                    ..
+                } => {
+                    if recommendations.len() > export::emr::MAX_FLEET_INSTANCE_TYPES {
+                        log::warn!(
+                            "An instance fleet takes up to {} instance types, leaving out {}",
+                            export::emr::MAX_FLEET_INSTANCE_TYPES,
+                            recommendations.len() - export::emr::MAX_FLEET_INSTANCE_TYPES
+                        );
+                        recommendations.truncate(export::emr::MAX_FLEET_INSTANCE_TYPES);
+                    }
+                    let options = export::emr::EmrOptions {
+                        name,
+                        fleet_type: *fleet_type,
+                        target_spot_capacity: *target_spot_capacity,
+                        timeout_minutes: *timeout_minutes,
+                    };
+                    export::emr::render(&recommendations, &options)?
+                }
+                cli::ExportTarget::Ray {
+                    head_instance_type,
+                    min_workers,
+                    max_workers,
+                    ..
+                } => {
+                    let options = export::ray::RayOptions {
+                        head_instance_type,
+                        min_workers: *min_workers,
+                        max_workers: *max_workers,
+                    };
+                    export::ray::render(&dataset.region, &recommendations, &options)?
+                }
+                cli::ExportTarget::Skypilot { gpus, .. } => {
+                    export::skypilot::render(&dataset.region, &recommendations, gpus.as_ref())?
+                }
+                cli::ExportTarget::Nomad {
+                    asg_name,
+                    node_class,
+                    min,
+                    max,
+                    target,
+                    ..
+                } => {
+                    let options = export::nomad::NomadOptions {
+                        asg_name,
+                        node_class,
+                        min: *min,
+                        max: *max,
+                        target: *target,
+                    };
+                    export::nomad::render(&dataset.region, &recommendations, &options)
+                }
+                cli::ExportTarget::Slurm {
+                    partition,
+                    max_nodes,
+                    ..
+                } => {
+                    let options = export::slurm::SlurmOptions {
+                        partition,
+                        max_nodes: *max_nodes,
+                    };
+                    export::slurm::render(&dataset.region, &recommendations, &options)
+                }
+                cli::ExportTarget::Ecs {
+                    cluster,
+                    launch_template,
+                    subnets,
+                    min_size,
+                    max_size,
+                    on_demand_base,
+                    on_demand_percentage,
+                    target_capacity,
+                    ..
+                } => {
+                    let advice = export::strategy(&recommendations, cli.os)?;
+                    let options = export::ecs::EcsOptions {
+                        cluster,
+                        asg: export::asg::AsgOptions {
+                            launch_template,
+                            on_demand_base: *on_demand_base,
+                            on_demand_percentage: *on_demand_percentage,
+                        },
+                        subnets,
+                        min_size: *min_size,
+                        max_size: *max_size,
+                        target_capacity: *target_capacity,
+                    };
+                    export::ecs::render(&dataset.region, &recommendations, &advice, &options)?
+                }
+                cli::ExportTarget::Awscli {
+                    instance_type,
+                    count,
+                    max_price_margin,
+                    launch_template,
+                    subnet_id,
+                    security_group_ids,
+                    key_name,
+                    create_fleet,
+                } => {
+                    let os_name = cli.os.advisor_keys()[0];
+                    let max_price = instance_data
+                        .get(&(instance_type.clone(), os_name.to_string()))
+                        .and_then(|region_map| region_map.get(&dataset.region))
+                        .and_then(|info| {
+                            info.suggested_max_price(cli.os.price_column(), *max_price_margin)
+                        })
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "No {} spot price of {} in {}",
+                                os_name,
+                                instance_type,
+                                dataset.region
+                            )
+                        })?;
+                    let options = export::awscli::AwsCliOptions {
+                        region: &dataset.region,
+                        instance_type,
+                        os: cli.os,
+                        count: *count,
+                        max_price,
+                        launch_template: launch_template.as_deref(),
+                        subnet_id: subnet_id.as_deref(),
+                        security_group_ids,
+                        key_name: key_name.as_deref(),
+                        create_fleet: *create_fleet,
+                    };
+                    export::awscli::render(&options)
+                }
+            };
+            print!("{}", output);
+        }
+        Some(cli::Command::Spend) => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+            let spends = spend::fetch_spend(&cli.aws, now).await?;
+            let (advisor_data, price_data) =
+                dataset::load_data(&cli, &client, cache.as_ref()).await?;
+            let overlays = spend::overlay(
+                spends,
+                |region| display::collect_instance_data(region, &advisor_data, &price_data, cli.os),
+                cli.os,
+            );
+            display::display_spend(&overlays)?;
+        }
+        Some(cli::Command::Audit {
+            target: Some(cli::AuditTarget::Asg { name }),
+            max_interruption,
+            ..
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let asg = audit::fetch_asg_instance_types(&cli.aws, &dataset.region, name).await?;
+            if asg.attribute_based {
+                log::warn!(
+                    "Auto Scaling group '{}' selects some instance types by attributes, which aren't audited",
+                    name
+                );
+            }
+            let instance_data = dataset.instance_data(cli.os);
+            let ratings = audit::rate_pools(
+                &asg.instance_types,
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                *max_interruption,
+            );
+            display::display_pool_ratings(
+                &format!("Auto Scaling group '{}'", name),
+                &dataset.region,
+                cli.os,
+                &ratings,
+            )?;
+        }
+        Some(cli::Command::K8s {
+            action: cli::K8sAction::NodePool { name },
+            context,
+            max_interruption,
+        }) => {
+            let node_pool = k8s::fetch_node_pool(context.as_deref(), name).await?;
+            let requirements = k8s::requirements(&node_pool)?;
+            if !k8s::allows_spot(&requirements) {
+                log::warn!(
+                    "NodePool '{}' doesn't allow the spot capacity type, launching on-demand nodes",
+                    name
+                );
+            }
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let instance_types =
+                k8s::allowed_instance_types(&requirements, &instance_data, &dataset.region, cli.os);
+            let ratings = audit::rate_pools(
+                &instance_types,
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                *max_interruption,
+            );
+            display::display_pool_ratings(
+                &format!("NodePool '{}'", name),
+                &dataset.region,
+                cli.os,
+                &ratings,
+            )?;
+        }
+        Some(cli::Command::K8s {
+            action: cli::K8sAction::Nodes,
+            context,
+            max_interruption,
+        }) => {
+            let node_labels = k8s::fetch_node_labels(context.as_deref()).await?;
+            let groups = k8s::group_nodes(&node_labels);
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let mut instance_types: Vec<String> = groups
+                .iter()
+                .map(|group| group.instance_type.clone())
+                .collect();
+            instance_types.sort();
+            instance_types.dedup();
+            let ratings = audit::rate_pools(
+                &instance_types,
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                *max_interruption,
+            );
+            display::display_node_groups(&dataset.region, cli.os, &groups, &ratings)?;
+        }
+        Some(cli::Command::K8s {
+            action: cli::K8sAction::Fit { namespace, count },
+            context,
+            max_interruption,
+        }) => {
+            let pods: Vec<k8s::PodRequests> = k8s::fetch_pods(context.as_deref(), namespace)
+                .await?
+                .iter()
+                .filter_map(k8s::pod_requests)
+                .collect();
+            if pods.is_empty() {
+                return Err(anyhow!(
+                    "No running or pending pods in namespace '{}'",
+                    namespace
+                ));
+            }
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let mut fits = k8s::fit(
+                &pods,
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                *max_interruption,
+                &cli.risk_penalties,
+            );
+            fits.truncate(*count);
+            display::display_fits(namespace, &dataset.region, &pods, &fits)?;
+        }
+        Some(cli::Command::Audit {
+            target: None,
+            max_interruption,
+            max_premium,
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instances = audit::fetch_running_instances(&cli.aws, &dataset.region).await?;
+            let instance_data = display::collect_instance_data(
+                &dataset.region,
+                &dataset.advisor_data,
+                &dataset.price_data,
+                cli::Os::All,
+            );
+
+            let mut az_prices = Vec::new();
+            for os in [cli::Os::Linux, cli::Os::Windows] {
+                let types: Vec<String> = instances
+                    .iter()
+                    .filter(|instance| instance.os == os)
+                    .map(|instance| instance.instance_type.clone())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+                if types.is_empty() {
+                    continue;
+                }
+                let prices = live::fetch_az_prices(&cli.aws, &dataset.region, &types, os).await?;
+                az_prices.extend(prices.into_iter().map(|price| (os, price)));
+            }
+
+            let thresholds = audit::AuditThresholds {
+                max_interruption: *max_interruption,
+                max_premium: *max_premium,
+            };
+            let findings = audit::audit(
+                &instances,
+                &instance_data,
+                &dataset.region,
+                &az_prices,
+                &thresholds,
+            );
+            display::display_audit(&dataset.region, &findings)?;
+        }
+        Some(cli::Command::Cache { action }) => {
+            let cache = cache
+                .as_ref()
+                .ok_or_else(|| anyhow!("Caching is disabled or no cache directory was found"))?;
+            match action {
+                cli::CacheAction::Info => display::display_cache_info(cache)?,
+                cli::CacheAction::Clear => {
+                    let removed = cache.clear()?;
+                    println!(
+                        "Removed {} cached files from {}",
+                        removed,
+                        cache.dir().display()
+                    );
+                }
+                cli::CacheAction::Refresh => {
+                    // A zero TTL treats every entry as stale, re-downloading all of them
+                    let refresh = cache::Cache::new(cache.dir().to_path_buf(), Duration::ZERO);
+                    // The Azure spot prices are refreshed for the region the commands load
+                    let config = provider::ProviderConfig {
+                        region: cli
+                            .region
+                            .clone()
+                            .or_else(|| cli.provider.default_region().map(str::to_string)),
+                        ..provider::ProviderConfig::new(cli.provider, &cli)
+                    };
+                    provider::Backend::new(&config, &client, Some(&refresh))
+                        .fetch_data()
+                        .await?;
+                    println!("Refreshed the cache in {}", cache.dir().display());
+                }
+            }
+        }
+        Some(cli::Command::Snapshot { action }) => match action {
+            cli::SnapshotAction::Save { path } => {
+                let (advisor_data, price_data) =
+                    dataset::load_data(&cli, &client, cache.as_ref()).await?;
+                snapshot::Snapshot::new(advisor_data, price_data).save(path)?;
+                println!("Saved snapshot to {}", path.display());
+            }
+        },
+        Some(cli::Command::Diff { old, new }) => {
+            let region = dataset::resolve_region(&cli, &client).await;
+            let old = snapshot::Snapshot::load(old)?;
+            let new = snapshot::Snapshot::load(new)?;
+            display::display_diff(&region, &old, &new, cli.os)?;
+        }
+        Some(cli::Command::Record { interval, db }) => {
+            let _telemetry = cli.otlp_endpoint.as_deref().map(telemetry::init);
+            let backend = history::Backend::parse(db);
+            // Stores block, so they're kept off the async worker
+            let mut store = tokio::task::block_in_place(|| backend.open())?;
+            let interval = Duration::from_secs_f64(interval * 3600.0);
+            loop {
+                // Always fetch fresh data so each record reflects the market at that time
+                match aws::fetch_data(&client, None).await {
+                    Ok((advisor_data, price_data)) => {
+                        // Every region is recorded, the region only matters for logging
+                        let instance_data = display::collect_instance_data(
+                            region::DEFAULT_REGION,
+                            &advisor_data,
+                            &price_data,
+                            cli::Os::All,
+                        );
+                        let recorded_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                        let records = history::history_records(&instance_data, recorded_at as i64);
+                        let written = tokio::task::block_in_place(|| {
+                            telemetry::phase("store", || store.insert(&records))
+                        })?;
+                        log::info!("Recorded {} rows", written);
+                    }
+                    Err(e) => log::warn!("Failed to fetch data: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        }
+        Some(cli::Command::History {
+            instance_type,
+            since,
+            db,
+        }) => {
+            let region = dataset::resolve_region(&cli, &client).await;
+            let records = recorded_history(&cli, db, instance_type, &region, *since)?;
+            let summary = history::summarize(&records).ok_or_else(|| {
+                anyhow!(
+                    "No history of instance type '{}' recorded in region '{}'",
+                    instance_type,
+                    region
+                )
+            })?;
+            display::display_history(&region, instance_type, &summary, cli.os.price_column())?;
+        }
+        Some(cli::Command::Chart {
+            instance_type,
+            since,
+            db,
+            out,
+        }) => {
+            let region = dataset::resolve_region(&cli, &client).await;
+            let records = recorded_history(&cli, db, instance_type, &region, *since)?;
+            chart::render(out, &region, instance_type, &records)?;
+            println!("Wrote chart to {}", out.display());
+        }
+        Some(cli::Command::When {
+            instance_type,
+            since,
+            db,
+        }) => {
+            let region = dataset::resolve_region(&cli, &client).await;
+            let (samples, source) = match db {
+                Some(db) => {
+                    let records = recorded_history(&cli, db, instance_type, &region, *since)?;
+                    (when::recorded_samples(&records), "Recorded history")
+                }
+                None => {
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+                    let start = now - (since * 3600.0) as i64;
+                    let changes =
+                        live::fetch_price_history(&cli.aws, &region, instance_type, cli.os, start)
+                            .await?;
+                    (
+                        when::hourly_samples(&changes, start, now),
+                        "EC2 spot price history",
+                    )
+                }
+            };
+            let report = when::analyze(&samples, when::BEST_SLOTS).ok_or_else(|| {
+                anyhow!(
+                    "No spot prices of instance type '{}' found in region '{}'",
+                    instance_type,
+                    region
+                )
+            })?;
+            display::display_when(
+                &region,
+                instance_type,
+                source,
+                &report,
+                cli.os.price_column(),
+            )?;
+        }
+        Some(cli::Command::Graviton { instance_type }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            display::display_graviton_equivalents(
+                &dataset.region,
+                instance_type,
+                &dataset.advisor_data,
+                &dataset.price_data,
+                cli.os,
+            )?;
+        }
+        Some(cli::Command::Gpu { filter }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let mut specs = specs::fetch_specs(&cli.aws, &dataset.region, &[]).await?;
+            specs.retain(|_, specs| gpu::matches(specs, filter));
+            let instance_data = dataset.instance_data(cli.os);
+            let offers = gpu::gpu_offers(&instance_data, &specs, &dataset.region, cli.os);
+            display::display_gpu_offers(&dataset.region, cli.os, &offers)?;
+        }
+        Some(cli::Command::CompareClouds {
+            vcpus,
+            memory,
+            count,
+            gcp_region,
+            azure_region,
+        }) => {
+            let aws_region = dataset::resolve_region(&cli, &client).await;
+            let mut matches = Vec::new();
+            for (cloud, region) in [
+                (cli::Provider::Aws, &aws_region),
+                (cli::Provider::Gcp, gcp_region),
+                (cli::Provider::Azure, azure_region),
+            ] {
+                let config = provider::ProviderConfig {
+                    region: Some(region.clone()),
+                    ..provider::ProviderConfig::new(cloud, &cli)
+                };
+                let provider = provider::Backend::new(&config, &client, cache.as_ref());
+                // A cloud failing to load, like GCP without an API key, leaves the others
+                match provider.fetch_market().await {
+                    Ok(market) => matches.extend(clouds::closest(
+                        provider.name(),
+                        &market,
+                        region,
+                        *vcpus,
+                        *memory,
+                        *count,
+                    )),
+                    Err(e) => log::warn!("Skipping {}: {}", provider.name(), e),
+                }
+            }
+            display::display_cloud_matches(*vcpus, *memory, &matches)?;
+        }
+        Some(cli::Command::Recommend {
+            max_interruption,
+            from_compute_optimizer: true,
+            ..
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let rightsizings = optimizer::fetch_rightsizing(&cli.aws, &dataset.region).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let moves = optimizer::spot_moves(
+                rightsizings,
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                *max_interruption,
+                &cli.risk_penalties,
+            );
+            display::display_spot_moves(&dataset.region, &moves, cli.os.price_column())?;
+        }
+        Some(cli::Command::Recommend {
+            vcpus,
+            memory,
+            max_interruption,
+            limit,
+            from_compute_optimizer: false,
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let constraints = recommend::Constraints {
+                min_vcpus: vcpus.map(f64::from),
+                min_memory_gb: *memory,
+                max_interruption: *max_interruption,
+            };
+            let mut recommendations = recommend::recommend(
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                &constraints,
+                &cli.risk_penalties,
+            );
+            recommendations.truncate(*limit);
+            display::display_recommendations(
+                &dataset.region,
+                &recommendations,
+                cli.os.price_column(),
+            )?;
+        }
+        Some(cli::Command::Pareto {
+            min_vcpus,
+            min_memory,
+            max_interruption,
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let constraints = recommend::Constraints {
+                min_vcpus: min_vcpus.map(f64::from),
+                min_memory_gb: *min_memory,
+                max_interruption: *max_interruption,
+            };
+            let recommendations = recommend::recommend(
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                &constraints,
+                &cli.risk_penalties,
+            );
+            let frontier = recommend::pareto_frontier(&recommendations, cli.os.price_column());
+            display::display_recommendations(&dataset.region, &frontier, cli.os.price_column())?;
+        }
+        Some(cli::Command::Diversify {
+            vcpus,
+            memory,
+            max_interruption,
+            count,
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let constraints = recommend::Constraints {
+                min_vcpus: vcpus.map(f64::from),
+                min_memory_gb: *memory,
+                max_interruption: Some(*max_interruption),
+            };
+            let recommendations = recommend::recommend(
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                &constraints,
+                &cli.risk_penalties,
+            );
+            let picked = recommend::diversify(&recommendations, *count);
+            if picked.len() < *count {
+                log::warn!(
+                    "Only {} instance types meet the constraints, fewer than the requested {}",
+                    picked.len(),
+                    count
+                );
+            }
+            display::display_recommendations(&dataset.region, &picked, cli.os.price_column())?;
+        }
+        Some(cli::Command::Alert {
+            rules,
+            rules_file,
+            slack_webhook,
+            webhook,
+            sns_topic,
+            smtp,
+            desktop_notify,
+            interval,
+        }) => {
+            let mut rules = rules.clone();
+            let mut notifier_config = notify::NotifierConfig {
+                slack_webhook: slack_webhook.clone(),
+                webhook: webhook.clone(),
+                sns_topic: sns_topic.clone(),
+                desktop: *desktop_notify,
+            };
+            // Notifiers on the command line take precedence over the ones of the rule file
+            if let Some(path) = rules_file {
+                let file = alert::RuleFile::load(path, cli.provider)?;
+                log::info!("Loaded {} rules from {}", file.rules.len(), path.display());
+                rules.extend(file.rules);
+                notifier_config = notifier_config.or(file.notifiers);
+            }
+            let mut notifiers = notifier_config.notifiers()?;
+            if let Some(config) = smtp.config()? {
+                notifiers.push(notify::Notifier::Email(Box::new(config)));
+            }
+            let mut tripped = HashSet::new();
+            match interval {
+                None => {
+                    check_alerts(
+                        &cli,
+                        &client,
+                        cache.as_ref(),
+                        &rules,
+                        &notifiers,
+                        &mut tripped,
+                    )
+                    .await?
+                }
+                Some(interval) => {
+                    let interval = Duration::from_secs_f64(interval * 3600.0);
+                    loop {
+                        if let Err(e) = check_alerts(
+                            &cli,
+                            &client,
+                            cache.as_ref(),
+                            &rules,
+                            &notifiers,
+                            &mut tripped,
+                        )
+                        .await
+                        {
+                            log::warn!("Failed to check the alert rules: {}", e);
+                        }
+                        tokio::time::sleep(interval).await;
+                    }
+                }
+            }
+        }
+        Some(cli::Command::Doctor) => {
+            let cache_dir = cache::resolve_dir(cli.cache_dir.as_deref());
+            let diagnoses = doctor::diagnose(&client, cache_dir.as_deref()).await;
+            display::display_diagnoses(&diagnoses)?;
+            if diagnoses
+                .iter()
+                .any(|diagnosis| diagnosis.status == doctor::Status::Failed)
+            {
+                std::process::exit(1);
+            }
+        }
+        Some(cli::Command::Init { force }) => {
+            let (path, _) = config::resolve_path(cli.config.as_deref()).ok_or_else(|| {
+                anyhow!("Failed to locate the config directory. Please set --config")
+            })?;
+            let region = dataset::resolve_region(&cli, &client).await;
+            let answers = init::ask(&region)?;
+            init::write(&path, &init::render(&answers), *force)?;
+            println!("Wrote config to {}", path.display());
+        }
+        Some(cli::Command::Pick { instance_type }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let candidates = pick::candidates(
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                instance_type.as_deref(),
+            );
+            match pick::pick(&candidates)? {
+                Some(instance_type) => println!("{}", instance_type),
+                // Nothing is printed so scripts can tell the pick was cancelled
+                None => std::process::exit(1),
+            }
+        }
+        Some(cli::Command::Watch {
+            interval,
+            instance_type,
+            notify_above,
+            notify_below,
+        }) => {
+            let region = dataset::resolve_region(&cli, &client).await;
+            let interval = Duration::from_secs_f64(interval * 3600.0);
+            let notifiers = [notify::Notifier::Desktop];
+            let mut tripped = HashSet::new();
+            loop {
+                // The cache is bypassed so each refresh shows the latest data
+                match dataset::Dataset::load_in(region.clone(), &cli, &client, None).await {
+                    Ok(dataset) => {
+                        // Clear the screen and move the cursor home before redrawing
+                        print!("\x1B[2J\x1B[H");
+                        if let Err(e) =
+                            render_table(&cli, &client, &dataset, instance_type.as_deref()).await
+                        {
+                            log::warn!("Failed to render the table: {}", e);
+                        }
+
+                        if notify_above.is_some() || notify_below.is_some() {
+                            let instance_data = dataset.instance_data(cli.os);
+                            let rules = watch_rules(
+                                &instance_data,
+                                &region,
+                                cli.os,
+                                instance_type.as_deref(),
+                                *notify_above,
+                                *notify_below,
+                            )?;
+                            let triggers = rules
+                                .iter()
+                                .filter_map(|rule| alert::evaluate(rule, &instance_data, cli.os))
+                                .collect();
+                            let new_triggers = alert::started_tripping(triggers, &mut tripped);
+                            if !new_triggers.is_empty() {
+                                notify::send_all(&client, &cli.aws, &notifiers, &new_triggers)
+                                    .await;
+                            }
+                        }
+                    }
+                    Err(e) => log::warn!("Failed to refresh the data: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        }
+        Some(cli::Command::Top {
+            by,
+            limit,
+            interval,
+            instance_type,
+        }) => {
+            let region = dataset::resolve_region(&cli, &client).await;
+            let interval = Duration::from_secs_f64(interval * 3600.0);
+            let mut previous: Vec<String> = Vec::new();
+            loop {
+                // The cache is bypassed so each refresh ranks the latest data
+                match dataset::Dataset::load_in(region.clone(), &cli, &client, None).await {
+                    Ok(dataset) => {
+                        let instance_data = dataset.instance_data(cli.os);
+                        let ranked = top::rank(
+                            &instance_data,
+                            &region,
+                            cli.os,
+                            instance_type.as_deref(),
+                            *by,
+                            *limit,
+                        );
+                        let movements: Vec<String> = ranked
+                            .iter()
+                            .enumerate()
+                            .map(|(rank, r)| top::movement(&previous, &r.instance_type, rank))
+                            .collect();
+
+                        // Clear the screen and move the cursor home before redrawing
+                        print!("\x1B[2J\x1B[H");
+                        display::display_top(&region, &ranked, &movements, cli.os.price_column())?;
+                        previous = ranked.into_iter().map(|r| r.instance_type).collect();
+                    }
+                    Err(e) => log::warn!("Failed to refresh the data: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        }
+        Some(cli::Command::Check {
+            instance_types,
+            max_interruption,
+            max_price,
+            min_savings,
+            github_summary,
+        }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let thresholds = check::Thresholds {
+                max_interruption: *max_interruption,
+                max_price: *max_price,
+                min_savings: *min_savings,
+            };
+            let checks = check::check(
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                instance_types,
+                &thresholds,
+            )?;
+            display::display_checks(&dataset.region, &checks)?;
+            if *github_summary {
+                github::report(&dataset.region, &checks)?;
+            }
+            if checks.iter().any(|check| !check.passed()) {
+                std::process::exit(check::VIOLATION_EXIT_CODE);
+            }
+        }
+        Some(cli::Command::Publish { target }) => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            match target {
+                cli::PublishTarget::Cloudwatch {
+                    namespace,
+                    instance_types,
+                } => {
+                    let gauges =
+                        publish::gauges(&instance_data, &dataset.region, cli.os, instance_types);
+                    publish::publish_cloudwatch(&cli.aws, namespace, &dataset.region, &gauges)
+                        .await?;
+                    println!(
+                        "Published {} metrics to CloudWatch namespace '{}' in {}",
+                        gauges.len(),
+                        namespace,
+                        dataset.region
+                    );
+                }
+                cli::PublishTarget::Datadog {
+                    api_key,
+                    site,
+                    statsd,
+                    instance_types,
+                } => {
+                    let gauges =
+                        publish::gauges(&instance_data, &dataset.region, cli.os, instance_types);
+                    match api_key {
+                        Some(api_key) => {
+                            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                            publish::publish_datadog_api(&client, site, api_key, &gauges, now)
+                                .await?;
+                            println!("Submitted {} metrics to the {} API", gauges.len(), site);
+                        }
+                        None => {
+                            publish::publish_statsd(statsd, &gauges).await?;
+                            println!("Sent {} metrics to DogStatsD at {}", gauges.len(), statsd);
+                        }
+                    }
+                }
+            }
+        }
+        Some(cli::Command::Sources) => {
+            display::display_sources(&sources::data_sources(&cli), cache.as_ref())?;
+        }
+        Some(cli::Command::Serve {
+            http,
+            grpc,
+            auth_token,
+            rate_limit,
+        }) => {
+            if auth_token.is_none() && !http.ip().is_loopback() {
+                log::warn!(
+                    "Serving on {} without --auth-token, anyone who can reach it can query the API",
+                    http
+                );
+            }
+            let _telemetry = cli.otlp_endpoint.as_deref().map(telemetry::init);
+            let (advisor_data, price_data) =
+                dataset::load_data(&cli, &client, cache.as_ref()).await?;
+            let options = serve::ServeOptions {
+                auth_token: auth_token.clone(),
+                rate_limit: *rate_limit,
+                default_region: dataset::resolve_region(&cli, &client).await,
+            };
+            let state =
+                serve::AppState::new(serve::Dataset::new(&advisor_data, &price_data), &options);
+            // Snapshots never change, so only fetched data is refreshed
+            if cli.from_snapshot.is_none() {
+                serve::spawn_refresh(
+                    state.clone(),
+                    provider::ProviderConfig::new(cli.provider, &cli),
+                    client.clone(),
+                    cache.clone(),
+                    Duration::from_secs_f64(cli.cache_ttl * 3600.0),
+                );
+            }
+            serve::serve(*http, *grpc, state).await?;
+        }
+        None if wizard::should_run() => {
+            let default_region = dataset::resolve_region(&cli, &client).await;
+            let (region, constraints) = wizard::ask(&default_region)?;
+            let dataset = dataset::Dataset::load_in(region, &cli, &client, cache.as_ref()).await?;
+            let instance_data = dataset.instance_data(cli.os);
+            let mut recommendations = recommend::recommend(
+                &instance_data,
+                &dataset.region,
+                cli.os,
+                &constraints,
+                &cli.risk_penalties,
+            );
+            recommendations.truncate(wizard::RECOMMENDATION_LIMIT);
+            display::display_recommendations(
+                &dataset.region,
+                &recommendations,
+                cli.os.price_column(),
+            )?;
+        }
+        None => {
+            let dataset = dataset::Dataset::load(&cli, &client, cache.as_ref()).await?;
+            render_table(&cli, &client, &dataset, cli.instance_type.as_deref()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Evaluates the alert rules and notifies of the rules that started tripping since the last check
+async fn check_alerts(
+    cli: &cli::Cli,
+    client: &Client,
+    cache: Option<&cache::Cache>,
+    rules: &[alert::Rule],
+    notifiers: &[notify::Notifier],
+    tripped: &mut HashSet<String>,
+) -> Result<()> {
+    let (advisor_data, price_data) = dataset::load_data(cli, client, cache).await?;
+    // Every region is collected, each rule names its own
+    let mut instance_data =
+        display::collect_instance_data(region::DEFAULT_REGION, &advisor_data, &price_data, cli.os);
+
+    // On-demand prices are only fetched for the regions of rules comparing with them
+    let on_demand_regions: BTreeSet<&str> = rules
+        .iter()
+        .filter(|rule| rule.uses(alert::Field::OnDemand))
+        .map(|rule| rule.region.as_str())
+        .collect();
+    for region in on_demand_regions {
+        let Some(on_demand_prices) = dataset::on_demand_prices(cli, client, region).await else {
+            continue;
+        };
+        for ((instance_type, _), region_map) in instance_data.iter_mut() {
+            if let Some(info) = region_map.get_mut(region)
+                && let Some(on_demand_price) = on_demand_prices.get(instance_type)
+            {
+                info.on_demand_price = on_demand_price.clone();
+            }
+        }
+    }
+
+    let triggers: Vec<alert::Trigger> = rules
+        .iter()
+        .filter_map(|rule| alert::evaluate(rule, &instance_data, cli.os))
+        .collect();
+    let new_triggers = alert::started_tripping(triggers, tripped);
+
+    if new_triggers.is_empty() {
+        log::info!("No alert rules started tripping");
+        return Ok(());
+    }
+
+    display::display_triggers(&new_triggers)?;
+    notify::send_all(client, &cli.aws, notifiers, &new_triggers).await;
+
+    Ok(())
+}
+
+/// Renders the instance table of the region with the display options of the command line
+async fn render_table(
+    cli: &cli::Cli,
+    client: &Client,
+    dataset: &dataset::Dataset,
+    instance_type: Option<&str>,
+) -> Result<()> {
+    let region = dataset.region.as_str();
+    let (advisor_data, price_data) = (&dataset.advisor_data, &dataset.price_data);
+    // On-demand prices are only shown alongside spot prices, and cap the suggested max price
+    let on_demand_prices = if cli.spot_price || cli.suggest_max_price {
+        dataset.on_demand_prices(cli, client).await
+    } else {
+        None
+    };
+    let price_columns: &[cli::PriceColumn] = if cli.spot_price {
+        &cli.price_columns
+    } else {
+        &[]
+    };
+    let trends = if cli.trend {
+        price_trends(cli, region, advisor_data, price_data, instance_type)
+    } else {
+        None
+    };
+    let volatilities = if cli.volatility || cli.max_volatility.is_some() {
+        price_volatilities(cli, region, advisor_data, price_data, instance_type)
+    } else {
+        None
+    };
+    let specs = if cli.specs {
+        // Without a filter every instance type is described
+        let instance_types = match instance_type {
+            Some(_) => {
+                matching_instance_types(cli, region, advisor_data, price_data, instance_type)
+            }
+            None => Vec::new(),
+        };
+        match specs::fetch_specs(&cli.aws, region, &instance_types).await {
+            Ok(specs) => Some(specs),
+            Err(e) => {
+                log::warn!("Failed to fetch instance specs: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let options = display::DisplayOptions {
+        instance_type,
+        price_columns,
+        os: cli.os,
+        sort_by: cli.sort_by,
+        max_price_per_vcpu: cli.max_price_per_vcpu,
+        max_price_per_gib: cli.max_price_per_gib,
+        hours_per_month: cli.hours_per_month,
+        on_demand_prices: on_demand_prices.as_ref(),
+        risk_penalties: &cli.risk_penalties,
+        suggest_upgrades: cli.suggest_upgrades,
+        // Similar instance types are only suggested for queried instance types
+        suggest_similar: instance_type.is_some() && !cli.no_suggestions,
+        group_by: cli.group_by,
+        histogram: cli.histogram,
+        max_price_margin: cli.suggest_max_price.then_some(cli.max_price_margin),
+        append_csv: cli.append_csv.as_deref(),
+        trends: trends.as_ref(),
+        specs: specs.as_ref(),
+        volatilities: volatilities.as_ref(),
+        max_volatility: cli.max_volatility,
+    };
+    telemetry::phase("render", || {
+        display::display_spot_data(region, advisor_data, price_data, &options)
+    })?;
 
+    if cli.live {
+        // Without a filter every instance type is fetched, in a single paginated request
+        let instance_types = match instance_type {
+            Some(_) => {
+                matching_instance_types(cli, region, advisor_data, price_data, instance_type)
+            }
+            None => Vec::new(),
+        };
+        if instance_type.is_some() && instance_types.is_empty() {
+            return Ok(());
+        }
+        match live::fetch_az_prices(&cli.aws, region, &instance_types, cli.os).await {
+            Ok(prices) => display::display_az_prices(region, &live::pivot(&prices))?,
+            Err(e) => log::warn!("Failed to fetch live spot prices: {:#}", e),
+        }
+    }
     Ok(())
 }
+
+/// Returns the instance types of the region matching the filter, sorted by name
+fn matching_instance_types(
+    cli: &cli::Cli,
+    region: &str,
+    advisor_data: &serde_json::Value,
+    price_data: &serde_json::Value,
+    instance_type: Option<&str>,
+) -> Vec<String> {
+    let os_name = cli.os.advisor_keys()[0];
+    let instance_data = display::collect_instance_data(region, advisor_data, price_data, cli.os);
+    let mut instance_types: Vec<String> = instance_data
+        .iter()
+        .filter(|((name, instance_os), region_map)| {
+            instance_os == os_name
+                && region_map.contains_key(region)
+                && instance_type.is_none_or(|filter| display::matches_instance_filter(name, filter))
+        })
+        .map(|((name, _), _)| name.clone())
+        .collect();
+    instance_types.sort();
+    instance_types
+}
+
+/// Returns the records of an instance type in the region over the last hours from the history
+/// database written by `spotter record`
+fn recorded_history(
+    cli: &cli::Cli,
+    db: &str,
+    instance_type: &str,
+    region: &str,
+    since: f64,
+) -> Result<Vec<history::HistoryRecord>> {
+    let backend = history::Backend::parse(db);
+    // Opening a SQLite database that doesn't exist would create an empty one
+    if let history::Backend::Sqlite(path) = &backend
+        && !path.exists()
+    {
+        return Err(anyhow!(
+            "Database '{}' not found. Record the history with `spotter record` first",
+            path.display()
+        ));
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let since = now - (since * 3600.0) as i64;
+    tokio::task::block_in_place(|| {
+        backend
+            .open()?
+            .query(instance_type, region, cli.os.advisor_keys()[0], since)
+    })
+}
+
+/// Returns the history database of the --trend sparklines and --volatility, or None when it
+/// doesn't exist
+fn trend_backend(cli: &cli::Cli, feature: &str) -> Option<history::Backend> {
+    let backend = history::Backend::parse(&cli.trend_db);
+    // Opening a SQLite database that doesn't exist would create an empty one
+    if let history::Backend::Sqlite(path) = &backend
+        && !path.exists()
+    {
+        log::warn!(
+            "Database '{}' not found, record the history with `spotter record` to show {}",
+            path.display(),
+            feature
+        );
+        return None;
+    }
+    Some(backend)
+}
+
+/// Returns the sparklines of the recorded spot prices of the instance types matching the filter,
+/// or None when the history database doesn't exist or can't be read
+fn price_trends(
+    cli: &cli::Cli,
+    region: &str,
+    advisor_data: &serde_json::Value,
+    price_data: &serde_json::Value,
+    instance_type: Option<&str>,
+) -> Option<HashMap<String, String>> {
+    let backend = trend_backend(cli, "trends")?;
+    let instance_types =
+        matching_instance_types(cli, region, advisor_data, price_data, instance_type);
+    let trends = tokio::task::block_in_place(|| {
+        history::trends(
+            backend.open()?.as_mut(),
+            region,
+            cli.os.advisor_keys()[0],
+            instance_types.iter().map(String::as_str),
+            cli.trend_samples,
+        )
+    });
+    match trends {
+        Ok(trends) => Some(trends),
+        Err(e) => {
+            log::warn!("Failed to read the price history: {}", e);
+            None
+        }
+    }
+}
+
+/// Returns the volatilities of the spot prices recorded over the volatility window of the instance
+/// types matching the filter, or None when the history database doesn't exist or can't be read
+fn price_volatilities(
+    cli: &cli::Cli,
+    region: &str,
+    advisor_data: &serde_json::Value,
+    price_data: &serde_json::Value,
+    instance_type: Option<&str>,
+) -> Option<HashMap<String, f64>> {
+    let backend = trend_backend(cli, "volatility")?;
+    let instance_types =
+        matching_instance_types(cli, region, advisor_data, price_data, instance_type);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    let since = now - (cli.volatility_window * 3600.0) as i64;
+    let volatilities = tokio::task::block_in_place(|| {
+        history::volatilities(
+            backend.open()?.as_mut(),
+            region,
+            cli.os.advisor_keys()[0],
+            instance_types.iter().map(String::as_str),
+            since,
+        )
+    });
+    match volatilities {
+        Ok(volatilities) => Some(volatilities),
+        Err(e) => {
+            log::warn!("Failed to read the price history: {}", e);
+            None
+        }
+    }
+}
+
+/// Returns the alert rules on the spot price of the instance types matching the filter in the
+/// region, for the thresholds of watch mode
+fn watch_rules(
+    instance_data: &display::InstanceData,
+    region: &str,
+    os: cli::Os,
+    instance_type: Option<&str>,
+    notify_above: Option<f64>,
+    notify_below: Option<f64>,
+) -> Result<Vec<alert::Rule>> {
+    let os_name = os.advisor_keys()[0];
+    let mut rules = Vec::new();
+    for ((name, instance_os), region_map) in instance_data {
+        if instance_os != os_name
+            || !region_map.contains_key(region)
+            || instance_type.is_some_and(|filter| !display::matches_instance_filter(name, filter))
+        {
+            continue;
+        }
+        if let Some(price) = notify_above {
+            rules.push(alert::parse_rule(&format!(
+                "{}@{} price>{}",
+                name, region, price
+            ))?);
+        }
+        if let Some(price) = notify_below {
+            rules.push(alert::parse_rule(&format!(
+                "{}@{} price<{}",
+                name, region, price
+            ))?);
+        }
+    }
+    Ok(rules)
+}
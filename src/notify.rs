@@ -0,0 +1,522 @@
+use crate::alert::Trigger;
+use crate::cli::AwsArgs;
+use anyhow::{Context, Result, anyhow};
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Destination of the alerts of tripped rules
+#[derive(Clone, Debug, PartialEq)]
+pub enum Notifier {
+    /// Slack incoming webhook URL
+    Slack(String),
+    /// URL receiving a generic JSON payload
+    Webhook(String),
+    /// ARN of an SNS topic
+    Sns(String),
+    /// SMTP server and addresses of an email
+    Email(Box<SmtpConfig>),
+    /// Native desktop notification
+    Desktop,
+}
+
+/// Notifiers given on the command line or in the notifiers section of a rule file
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifierConfig {
+    pub slack_webhook: Option<String>,
+    pub webhook: Option<String>,
+    pub sns_topic: Option<String>,
+    pub desktop: bool,
+}
+
+impl NotifierConfig {
+    /// Returns the config with the notifiers missing from this one taken from the other
+    pub fn or(self, other: NotifierConfig) -> Self {
+        Self {
+            slack_webhook: self.slack_webhook.or(other.slack_webhook),
+            webhook: self.webhook.or(other.webhook),
+            sns_topic: self.sns_topic.or(other.sns_topic),
+            desktop: self.desktop || other.desktop,
+        }
+    }
+
+    pub fn notifiers(&self) -> Result<Vec<Notifier>> {
+        let mut notifiers = Vec::new();
+        if let Some(url) = &self.slack_webhook {
+            notifiers.push(Notifier::Slack(url.clone()));
+        }
+        if let Some(url) = &self.webhook {
+            notifiers.push(Notifier::Webhook(url.clone()));
+        }
+        if let Some(topic_arn) = &self.sns_topic {
+            notifiers.push(Notifier::Sns(parse_sns_topic(topic_arn)?));
+        }
+        if self.desktop {
+            notifiers.push(Notifier::Desktop);
+        }
+        Ok(notifiers)
+    }
+}
+
+/// Port of SMTP servers expecting TLS from the start rather than upgrading with STARTTLS
+const SMTPS_PORT: u16 = 465;
+
+/// SMTP server sending the emails and their addresses
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    /// Username and password, when the server requires authentication
+    pub credentials: Option<(String, String)>,
+    pub from: Mailbox,
+    pub to: Vec<Mailbox>,
+}
+
+impl SmtpConfig {
+    /// Returns the config with the addresses parsed, like "Spotter <spotter@example.com>"
+    pub fn new(
+        host: &str,
+        port: u16,
+        credentials: Option<(String, String)>,
+        from: &str,
+        to: &[String],
+    ) -> Result<Self> {
+        let parse = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .with_context(|| format!("Invalid email address '{}'", address))
+        };
+        if to.is_empty() {
+            return Err(anyhow!("At least one recipient is required to send emails"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            credentials,
+            from: parse(from)?,
+            to: to
+                .iter()
+                .map(|address| parse(address))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl Notifier {
+    pub async fn send(&self, client: &Client, aws: &AwsArgs, triggers: &[Trigger]) -> Result<()> {
+        match self {
+            Notifier::Slack(url) => post_json(client, url, &slack_message(triggers)).await,
+            Notifier::Webhook(url) => post_json(client, url, &webhook_payload(triggers)).await,
+            Notifier::Sns(topic_arn) => publish_sns(aws, topic_arn, triggers).await,
+            Notifier::Email(config) => send_email(config, triggers).await,
+            Notifier::Desktop => show_desktop(triggers).await,
+        }
+    }
+
+    /// Returns the name of the notifier for logging, without secrets like webhook URLs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Notifier::Slack(_) => "Slack",
+            Notifier::Webhook(_) => "webhook",
+            Notifier::Sns(_) => "SNS",
+            Notifier::Email(_) => "email",
+            Notifier::Desktop => "desktop",
+        }
+    }
+}
+
+/// Sends the triggers to every notifier, warning about the ones that fail
+pub async fn send_all(
+    client: &Client,
+    aws: &AwsArgs,
+    notifiers: &[Notifier],
+    triggers: &[Trigger],
+) {
+    for notifier in notifiers {
+        match notifier.send(client, aws, triggers).await {
+            Ok(()) => log::info!("Notified {} of {} rules", notifier.name(), triggers.len()),
+            Err(e) => log::warn!("Failed to notify {}: {}", notifier.name(), e),
+        }
+    }
+}
+
+async fn post_json(client: &Client, url: &str, body: &Value) -> Result<()> {
+    let response = client
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .context("Failed to send the notification")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "Notification failed with HTTP {}: {}",
+            status,
+            body.trim()
+        ));
+    }
+    Ok(())
+}
+
+fn format_value(value: Option<f64>, format: impl Fn(f64) -> String) -> String {
+    value.map(format).unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Returns a plain text line describing a trigger and its values
+fn text_line(trigger: &Trigger) -> String {
+    format!(
+        "{} in {} tripped '{}': price {}, interruption rate {}, savings {}",
+        trigger.instance_type,
+        trigger.region,
+        trigger.rule,
+        format_value(trigger.price, |p| format!("${:.4}/h", p)),
+        trigger.interruption_rate,
+        format_value(trigger.savings, |s| format!("{}%", s)),
+    )
+}
+
+/// Returns a line describing a trigger, with Slack mrkdwn formatting
+fn slack_line(trigger: &Trigger) -> String {
+    format!(
+        "*{}* in *{}* tripped `{}`: price {}, interruption rate {}, savings {}",
+        trigger.instance_type,
+        trigger.region,
+        trigger.rule,
+        format_value(trigger.price, |p| format!("${:.4}/h", p)),
+        trigger.interruption_rate,
+        format_value(trigger.savings, |s| format!("{}%", s)),
+    )
+}
+
+/// Returns the Slack message of the triggers, with a section block per trigger
+pub fn slack_message(triggers: &[Trigger]) -> Value {
+    let lines: Vec<String> = triggers.iter().map(slack_line).collect();
+    let mut blocks = vec![json!({
+        "type": "header",
+        "text": {"type": "plain_text", "text": format!(":rotating_light: {} spot alert rules tripped", triggers.len())},
+    })];
+    blocks.extend(lines.iter().map(|line| {
+        json!({
+            "type": "section",
+            "text": {"type": "mrkdwn", "text": line},
+        })
+    }));
+
+    json!({
+        // Shown in notifications, where blocks aren't rendered
+        "text": lines.join("\n"),
+        "blocks": blocks,
+    })
+}
+
+/// Returns the generic webhook payload of the triggers. The "text" summary lets chat services
+/// accepting Slack-compatible payloads (e.g., Mattermost) show it as is.
+pub fn webhook_payload(triggers: &[Trigger]) -> Value {
+    let lines: Vec<String> = triggers
+        .iter()
+        .map(|trigger| {
+            format!(
+                "{} in {} tripped '{}'",
+                trigger.instance_type, trigger.region, trigger.rule
+            )
+        })
+        .collect();
+
+    json!({
+        "text": lines.join("\n"),
+        "triggers": triggers,
+    })
+}
+
+/// Returns the region of an SNS topic ARN like "arn:aws:sns:us-east-1:123456789012:alerts"
+fn sns_topic_region(topic_arn: &str) -> Option<&str> {
+    match topic_arn.split(':').collect::<Vec<_>>()[..] {
+        ["arn", _, "sns", region, account, name]
+            if !region.is_empty() && !account.is_empty() && !name.is_empty() =>
+        {
+            Some(region)
+        }
+        _ => None,
+    }
+}
+
+/// Validates an SNS topic ARN passed to --sns-topic
+pub fn parse_sns_topic(topic_arn: &str) -> Result<String> {
+    sns_topic_region(topic_arn).ok_or_else(|| {
+        anyhow!(
+            "Invalid SNS topic ARN '{}'. Please use arn:aws:sns:REGION:ACCOUNT:TOPIC",
+            topic_arn
+        )
+    })?;
+    Ok(topic_arn.to_string())
+}
+
+/// Returns the subject of the emails and SNS messages of the triggers
+fn subject(triggers: &[Trigger]) -> String {
+    format!("Spotter: {} spot alert rules tripped", triggers.len())
+}
+
+/// Returns the SNS message of the triggers, with a plain text summary for email and SMS
+/// subscriptions and the webhook payload for Lambda, SQS and HTTP(S) subscriptions
+pub fn sns_message(triggers: &[Trigger]) -> Value {
+    let payload = webhook_payload(triggers);
+    let text = payload["text"].as_str().unwrap_or_default().to_string();
+    let payload = payload.to_string();
+
+    json!({
+        "default": text,
+        "email": text,
+        "sms": text,
+        "lambda": payload,
+        "sqs": payload,
+        "http": payload,
+        "https": payload,
+    })
+}
+
+async fn publish_sns(aws: &AwsArgs, topic_arn: &str, triggers: &[Trigger]) -> Result<()> {
+    let region = sns_topic_region(topic_arn)
+        .ok_or_else(|| anyhow!("Invalid SNS topic ARN '{}'", topic_arn))?;
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_sns::Client::new(&config);
+
+    client
+        .publish()
+        .topic_arn(topic_arn)
+        .subject(subject(triggers))
+        .message_structure("json")
+        .message(sns_message(triggers).to_string())
+        .send()
+        .await
+        .with_context(|| format!("Failed to publish to SNS topic '{}'", topic_arn))?;
+    Ok(())
+}
+
+/// Returns the plain text body of the email of the triggers
+fn email_body(triggers: &[Trigger]) -> String {
+    let mut body = String::from("The following spot alert rules tripped:\n\n");
+    for trigger in triggers {
+        body.push_str(&format!("- {}\n", text_line(trigger)));
+    }
+    body
+}
+
+async fn send_email(config: &SmtpConfig, triggers: &[Trigger]) -> Result<()> {
+    let mut builder = Message::builder()
+        .from(config.from.clone())
+        .subject(subject(triggers))
+        .header(ContentType::TEXT_PLAIN);
+    for to in &config.to {
+        builder = builder.to(to.clone());
+    }
+    let message = builder
+        .body(email_body(triggers))
+        .context("Failed to build the email")?;
+
+    let transport = if config.port == SMTPS_PORT {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+    }
+    .with_context(|| format!("Invalid SMTP server '{}'", config.host))?
+    .port(config.port);
+    let transport = match &config.credentials {
+        Some((username, password)) => {
+            transport.credentials(Credentials::new(username.clone(), password.clone()))
+        }
+        None => transport,
+    };
+
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("Failed to send the email through {}", config.host))?;
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+async fn show_desktop(triggers: &[Trigger]) -> Result<()> {
+    let summary = subject(triggers);
+    let body: Vec<String> = triggers.iter().map(text_line).collect();
+    let body = body.join("\n");
+
+    // Notifications are shown over D-Bus, Windows or macOS APIs that block
+    tokio::task::spawn_blocking(move || {
+        notify_rust::Notification::new()
+            .appname("spotter")
+            .summary(&summary)
+            .body(&body)
+            .show()
+            .map(|_| ())
+    })
+    .await?
+    .context("Failed to show the desktop notification")
+}
+
+#[cfg(not(feature = "desktop"))]
+async fn show_desktop(_triggers: &[Trigger]) -> Result<()> {
+    Err(anyhow!(
+        "Desktop notifications require building spotter with the 'desktop' feature"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger() -> Trigger {
+        Trigger {
+            rule: "m5.xlarge@eu-west-1 price>0.10".to_string(),
+            instance_type: "m5.xlarge".to_string(),
+            region: "eu-west-1".to_string(),
+            price: Some(0.1234),
+            interruption_rate: "5-10%".to_string(),
+            savings: None,
+        }
+    }
+
+    #[test]
+    fn test_slack_message() {
+        let message = slack_message(&[trigger()]);
+        let line = "*m5.xlarge* in *eu-west-1* tripped `m5.xlarge@eu-west-1 price>0.10`: price $0.1234/h, interruption rate 5-10%, savings N/A";
+        assert_eq!(message["text"], line);
+
+        let blocks = message["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[0]["text"]["text"],
+            ":rotating_light: 1 spot alert rules tripped"
+        );
+        assert_eq!(blocks[1]["text"]["type"], "mrkdwn");
+        assert_eq!(blocks[1]["text"]["text"], line);
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let payload = webhook_payload(&[trigger()]);
+        assert_eq!(
+            payload["text"],
+            "m5.xlarge in eu-west-1 tripped 'm5.xlarge@eu-west-1 price>0.10'"
+        );
+        assert_eq!(
+            payload["triggers"],
+            json!([{
+                "rule": "m5.xlarge@eu-west-1 price>0.10",
+                "instance_type": "m5.xlarge",
+                "region": "eu-west-1",
+                "price": 0.1234,
+                "interruption_rate": "5-10%",
+                "savings": null,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_parse_sns_topic() {
+        let topic_arn = "arn:aws:sns:us-east-1:123456789012:alerts";
+        assert_eq!(parse_sns_topic(topic_arn).unwrap(), topic_arn);
+        assert_eq!(sns_topic_region(topic_arn), Some("us-east-1"));
+
+        assert!(parse_sns_topic("alerts").is_err());
+        assert!(parse_sns_topic("arn:aws:sqs:us-east-1:123456789012:alerts").is_err());
+        assert!(parse_sns_topic("arn:aws:sns::123456789012:alerts").is_err());
+        assert!(parse_sns_topic("arn:aws:sns:us-east-1:123456789012").is_err());
+    }
+
+    #[test]
+    fn test_sns_message() {
+        assert_eq!(subject(&[trigger()]), "Spotter: 1 spot alert rules tripped");
+
+        let message = sns_message(&[trigger()]);
+        let text = "m5.xlarge in eu-west-1 tripped 'm5.xlarge@eu-west-1 price>0.10'";
+        assert_eq!(message["default"], text);
+        assert_eq!(message["email"], text);
+
+        let payload: Value = serde_json::from_str(message["lambda"].as_str().unwrap()).unwrap();
+        assert_eq!(payload, webhook_payload(&[trigger()]));
+    }
+
+    #[test]
+    fn test_email_body() {
+        assert_eq!(
+            email_body(&[trigger()]),
+            "The following spot alert rules tripped:\n\n- m5.xlarge in eu-west-1 tripped 'm5.xlarge@eu-west-1 price>0.10': price $0.1234/h, interruption rate 5-10%, savings N/A\n"
+        );
+    }
+
+    #[test]
+    fn test_smtp_config() {
+        let config = SmtpConfig::new(
+            "smtp.example.com",
+            587,
+            None,
+            "Spotter <spotter@example.com>",
+            &["ops@example.com".to_string()],
+        )
+        .unwrap();
+        assert_eq!(config.from.email.to_string(), "spotter@example.com");
+        assert_eq!(config.to.len(), 1);
+
+        assert!(
+            SmtpConfig::new(
+                "smtp.example.com",
+                587,
+                None,
+                "spotter",
+                &["ops@example.com".to_string()]
+            )
+            .is_err()
+        );
+        assert!(
+            SmtpConfig::new("smtp.example.com", 587, None, "spotter@example.com", &[]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_notifier_config() {
+        let cli = NotifierConfig {
+            slack_webhook: Some("https://hooks.slack.com/services/T/B/X".to_string()),
+            ..Default::default()
+        };
+        let file = NotifierConfig {
+            slack_webhook: Some("https://hooks.slack.com/services/T/B/Y".to_string()),
+            sns_topic: Some("arn:aws:sns:us-east-1:123456789012:alerts".to_string()),
+            desktop: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            cli.or(file).notifiers().unwrap(),
+            vec![
+                Notifier::Slack("https://hooks.slack.com/services/T/B/X".to_string()),
+                Notifier::Sns("arn:aws:sns:us-east-1:123456789012:alerts".to_string()),
+                Notifier::Desktop,
+            ]
+        );
+
+        let invalid = NotifierConfig {
+            sns_topic: Some("alerts".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.notifiers().is_err());
+    }
+
+    #[test]
+    fn test_notifier_name() {
+        let notifier = Notifier::Slack("https://hooks.slack.com/services/T/B/secret".to_string());
+        assert_eq!(notifier.name(), "Slack");
+        let notifier = Notifier::Webhook("https://example.com/hooks/secret".to_string());
+        assert_eq!(notifier.name(), "webhook");
+        let notifier = Notifier::Sns("arn:aws:sns:us-east-1:123456789012:alerts".to_string());
+        assert_eq!(notifier.name(), "SNS");
+        assert_eq!(Notifier::Desktop.name(), "desktop");
+    }
+}
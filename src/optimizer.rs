@@ -0,0 +1,208 @@
+use crate::cli::{AwsArgs, Os};
+use crate::display::InstanceData;
+use crate::recommend::{Constraints, Recommendation};
+use anyhow::{Context, Result};
+use aws_sdk_computeoptimizer::types::InstanceRecommendation;
+
+/// Rightsizing recommendation of an EC2 instance from AWS Compute Optimizer
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rightsizing {
+    /// Name tag of the instance, or its ARN without one
+    pub workload: String,
+    pub current_instance_type: String,
+    /// Finding like "Overprovisioned" or "Optimized"
+    pub finding: String,
+    /// Recommended instance types, best ranked first
+    pub options: Vec<String>,
+}
+
+/// Workload to move to a spot instance type, the cheapest recommended option by effective cost
+#[derive(Clone, Debug)]
+pub struct SpotMove {
+    pub rightsizing: Rightsizing,
+    /// None when no recommended option is available on spot within the interruption rate
+    pub suggestion: Option<Recommendation>,
+}
+
+fn rightsizing(recommendation: &InstanceRecommendation) -> Option<Rightsizing> {
+    let mut options: Vec<(i32, String)> = recommendation
+        .recommendation_options()
+        .iter()
+        .filter_map(|option| Some((option.rank(), option.instance_type()?.to_string())))
+        .collect();
+    options.sort();
+
+    Some(Rightsizing {
+        workload: recommendation
+            .instance_name()
+            .filter(|name| !name.is_empty())
+            .or(recommendation.instance_arn())?
+            .to_string(),
+        current_instance_type: recommendation.current_instance_type()?.to_string(),
+        finding: recommendation
+            .finding()
+            .map_or("N/A", |finding| finding.as_str())
+            .to_string(),
+        options: options
+            .into_iter()
+            .map(|(_, instance_type)| instance_type)
+            .collect(),
+    })
+}
+
+/// Fetches the EC2 rightsizing recommendations of the account in a region from AWS Compute
+/// Optimizer, which must be opted in
+pub async fn fetch_rightsizing(aws: &AwsArgs, region: &str) -> Result<Vec<Rightsizing>> {
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_computeoptimizer::Client::new(&config);
+
+    let mut rightsizings = Vec::new();
+    let mut next_token = None;
+    loop {
+        let output = client
+            .get_ec2_instance_recommendations()
+            .set_next_token(next_token)
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to get Compute Optimizer recommendations in {} (is the account opted in and are AWS credentials configured?)",
+                    region
+                )
+            })?;
+        rightsizings.extend(
+            output
+                .instance_recommendations()
+                .iter()
+                .filter_map(rightsizing),
+        );
+        next_token = output.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+    Ok(rightsizings)
+}
+
+/// Cross-references the rightsizing recommendations with the spot data, suggesting the recommended
+/// option with the lowest effective cost within the maximum interruption rate (%) per workload
+pub fn spot_moves(
+    rightsizings: Vec<Rightsizing>,
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    max_interruption: Option<f64>,
+    risk_penalties: &[f64],
+) -> Vec<SpotMove> {
+    let os_name = os.advisor_keys()[0];
+    let price_column = os.price_column();
+    let constraints = Constraints {
+        max_interruption,
+        ..Default::default()
+    };
+
+    rightsizings
+        .into_iter()
+        .map(|rightsizing| {
+            let suggestion = rightsizing
+                .options
+                .iter()
+                .filter_map(|instance_type| {
+                    let info = instance_data
+                        .get(&(instance_type.clone(), os_name.to_string()))?
+                        .get(region)?;
+                    if !constraints.matches(info) {
+                        return None;
+                    }
+                    Some(Recommendation {
+                        instance_type: instance_type.clone(),
+                        effective_cost: info.effective_cost(price_column, risk_penalties)?,
+                        info: info.clone(),
+                    })
+                })
+                .min_by(|a, b| a.effective_cost.total_cmp(&b.effective_cost));
+            SpotMove {
+                rightsizing,
+                suggestion,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::DEFAULT_RISK_PENALTIES;
+    use crate::display::tests::{info, instance_data};
+    use aws_sdk_computeoptimizer::types::{Finding, InstanceRecommendationOption};
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            ("m6i.xlarge", "Linux", "us-east-1", info("< 5%", "0.08")),
+            ("m6a.xlarge", "Linux", "us-east-1", info("5-10%", "0.07")),
+            ("c6i.xlarge", "Linux", "us-east-1", info("> 20%", "0.03")),
+        ])
+    }
+
+    #[test]
+    fn test_rightsizing() {
+        let recommendation = InstanceRecommendation::builder()
+            .instance_arn("arn:aws:ec2:us-east-1:123456789012:instance/i-1")
+            .instance_name("web")
+            .current_instance_type("m5.2xlarge")
+            .finding(Finding::Overprovisioned)
+            .recommendation_options(
+                InstanceRecommendationOption::builder()
+                    .instance_type("m6a.xlarge")
+                    .rank(2)
+                    .build(),
+            )
+            .recommendation_options(
+                InstanceRecommendationOption::builder()
+                    .instance_type("m6i.xlarge")
+                    .rank(1)
+                    .build(),
+            )
+            .build();
+        assert_eq!(
+            rightsizing(&recommendation),
+            Some(Rightsizing {
+                workload: "web".to_string(),
+                current_instance_type: "m5.2xlarge".to_string(),
+                finding: "Overprovisioned".to_string(),
+                options: vec!["m6i.xlarge".to_string(), "m6a.xlarge".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_spot_moves() {
+        let rightsizing = |options: &[&str]| Rightsizing {
+            workload: "web".to_string(),
+            current_instance_type: "m5.2xlarge".to_string(),
+            finding: "Overprovisioned".to_string(),
+            options: options.iter().map(|o| o.to_string()).collect(),
+        };
+        let moves = spot_moves(
+            vec![
+                rightsizing(&["m6i.xlarge", "m6a.xlarge", "c6i.xlarge"]),
+                rightsizing(&["c6i.xlarge", "r7i.xlarge"]),
+            ],
+            &sample_data(),
+            "us-east-1",
+            Os::Linux,
+            Some(10.0),
+            &DEFAULT_RISK_PENALTIES,
+        );
+
+        // The 5-10% pool is still cheaper after its risk penalty, the > 20% one is too risky
+        assert_eq!(
+            moves[0]
+                .suggestion
+                .as_ref()
+                .map(|s| s.instance_type.as_str()),
+            Some("m6a.xlarge")
+        );
+        assert!(moves[1].suggestion.is_none());
+    }
+}
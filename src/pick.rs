@@ -0,0 +1,122 @@
+use crate::cli::Os;
+use crate::display::{self, InstanceData};
+use anyhow::{Context, Result, anyhow};
+use dialoguer::FuzzySelect;
+use dialoguer::console::Term;
+
+/// Instance type offered by the picker, with its price and interruption rate inline
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub instance_type: String,
+    pub label: String,
+}
+
+/// Returns the instance types of the region matching the filter, sorted by name, with labels
+/// aligned into columns
+pub fn candidates(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    filter: Option<&str>,
+) -> Vec<Candidate> {
+    let os_name = os.advisor_keys()[0];
+    let price_column = os.price_column();
+    let mut infos: Vec<(&str, &display::InstanceInfo)> = instance_data
+        .iter()
+        .filter(|((name, instance_os), _)| {
+            instance_os == os_name
+                && filter.is_none_or(|filter| display::matches_instance_filter(name, filter))
+        })
+        .filter_map(|((name, _), region_map)| Some((name.as_str(), region_map.get(region)?)))
+        .collect();
+    infos.sort_by(|a, b| a.0.cmp(b.0));
+
+    let width = infos.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    infos
+        .into_iter()
+        .map(|(name, info)| {
+            let price = info
+                .spot_price_value(price_column)
+                .map(|p| format!("${:.4}/h", p))
+                .unwrap_or_else(|| "N/A".to_string());
+            Candidate {
+                instance_type: name.to_string(),
+                label: format!(
+                    "{:<width$}  {:>10}  {:>6} interruption  {:>4} savings",
+                    name,
+                    price,
+                    info.interruption_rate,
+                    info.savings,
+                    width = width
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Opens a fuzzy finder over the candidates on the terminal, returning the picked instance type,
+/// or None when the picker is cancelled with Esc
+pub fn pick(candidates: &[Candidate]) -> Result<Option<String>> {
+    if candidates.is_empty() {
+        return Err(anyhow!("No instance types to pick from"));
+    }
+    // The picker is drawn on stderr so only the picked instance type goes to stdout
+    if !Term::stderr().is_term() {
+        return Err(anyhow!(
+            "Picking an instance type requires an interactive terminal"
+        ));
+    }
+
+    let labels: Vec<&str> = candidates.iter().map(|c| c.label.as_str()).collect();
+    let selection = FuzzySelect::new()
+        .with_prompt("Instance type")
+        .items(&labels)
+        .default(0)
+        .interact_opt()
+        .context("Failed to run the picker")?;
+    Ok(selection.map(|index| candidates[index].instance_type.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            ("m5.xlarge", "Linux", "us-east-1", info("< 5%", "0.0821")),
+            ("c5.large", "Linux", "us-east-1", info("5-10%", "N/A")),
+            ("m5.24xlarge", "Linux", "us-east-1", info("10-15%", "1.5")),
+        ])
+    }
+
+    #[test]
+    fn test_candidates() {
+        let candidates = candidates(&sample_data(), "us-east-1", Os::Linux, None);
+        let instance_types: Vec<&str> = candidates
+            .iter()
+            .map(|c| c.instance_type.as_str())
+            .collect();
+        assert_eq!(instance_types, vec!["c5.large", "m5.24xlarge", "m5.xlarge"]);
+        assert_eq!(
+            candidates[2].label,
+            "m5.xlarge     $0.0821/h    < 5% interruption   70% savings"
+        );
+        assert_eq!(
+            candidates[0].label,
+            "c5.large            N/A   5-10% interruption   70% savings"
+        );
+    }
+
+    #[test]
+    fn test_candidates_filtered() {
+        let filtered = candidates(&sample_data(), "us-east-1", Os::Linux, Some("m5"));
+        assert_eq!(filtered.len(), 2);
+        assert!(candidates(&sample_data(), "eu-west-1", Os::Linux, None).is_empty());
+    }
+
+    #[test]
+    fn test_pick_without_candidates() {
+        assert!(pick(&[]).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+pub mod azure;
+pub mod gcp;
+
+use crate::aws;
+use crate::cache::Cache;
+use crate::cli::{Cli, Os, PriceColumn, Provider};
+use crate::display::{self, InstanceData};
+use anyhow::Result;
+use reqwest::Client;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Machine shape of an instance type in a cloud's catalog
+#[derive(Clone, Debug, PartialEq)]
+pub struct MachineShape {
+    pub instance_type: String,
+    pub vcpus: u32,
+    pub memory_gb: f64,
+}
+
+/// Spot offer of an instance type for an OS in a region, with its interruption (eviction) signal
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpotOffer {
+    pub region: String,
+    pub instance_type: String,
+    /// OS of the advisor data, "Linux" or "Windows"
+    pub os: &'static str,
+    /// Interruption rate bucket (0: "< 5%" to 4: "> 20%"), None without a signal
+    pub interruption_bucket: Option<usize>,
+    /// Savings over on-demand (%)
+    pub savings: Option<u64>,
+    /// Spot price (USD/hour)
+    pub spot_price: Option<f64>,
+}
+
+/// Instance catalog, spot prices and interruption signals of every region of a cloud, the
+/// regions being those of the offers
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpotMarket {
+    pub shapes: Vec<MachineShape>,
+    pub offers: Vec<SpotOffer>,
+}
+
+impl SpotMarket {
+    /// Returns the market as advisor data and price data, in the shape of the AWS spot advisor
+    /// data and spot price data every table and recommendation is built from
+    pub fn into_data(self) -> (Value, Value) {
+        let instance_types: Map<String, Value> = self
+            .shapes
+            .iter()
+            .map(|shape| {
+                (
+                    shape.instance_type.clone(),
+                    json!({ "cores": shape.vcpus, "ram_gb": shape.memory_gb }),
+                )
+            })
+            .collect();
+
+        let mut spot_advisor: BTreeMap<&str, BTreeMap<&str, Map<String, Value>>> = BTreeMap::new();
+        let mut prices: BTreeMap<&str, BTreeMap<&str, Vec<Value>>> = BTreeMap::new();
+        for offer in &self.offers {
+            let mut signal = Map::new();
+            if let Some(bucket) = offer.interruption_bucket {
+                signal.insert("r".to_string(), json!(bucket));
+            }
+            if let Some(savings) = offer.savings {
+                signal.insert("s".to_string(), json!(savings));
+            }
+            spot_advisor
+                .entry(&offer.region)
+                .or_default()
+                .entry(offer.os)
+                .or_default()
+                .insert(offer.instance_type.clone(), Value::Object(signal));
+
+            if let Some(spot_price) = offer.spot_price {
+                // Spot price data columns are named "linux" and "mswin"
+                let column = if offer.os == "Windows" {
+                    "mswin"
+                } else {
+                    "linux"
+                };
+                prices
+                    .entry(&offer.region)
+                    .or_default()
+                    .entry(&offer.instance_type)
+                    .or_default()
+                    .push(json!({
+                        "name": column,
+                        "prices": { "USD": format!("{:.6}", spot_price) },
+                    }));
+            }
+        }
+
+        let price_regions: Vec<Value> = prices
+            .into_iter()
+            .map(|(region, instance_types)| {
+                let sizes: Vec<Value> = instance_types
+                    .into_iter()
+                    .map(|(instance_type, value_columns)| {
+                        json!({
+                            "size": instance_type,
+                            "instanceType": instance_type,
+                            "valueColumns": value_columns,
+                        })
+                    })
+                    .collect();
+                json!({
+                    "region": region,
+                    "instanceTypes": [{ "type": "spot", "sizes": sizes }],
+                })
+            })
+            .collect();
+
+        (
+            json!({ "instance_types": instance_types, "spot_advisor": spot_advisor }),
+            json!({ "config": { "regions": price_regions } }),
+        )
+    }
+}
+
+/// Backend of the spot data of a cloud, so that clouds can be added without touching the tables
+/// and recommendations built from the data
+pub trait SpotProvider {
+    /// Name of the cloud, like "aws"
+    fn name(&self) -> &'static str;
+
+    /// Fetches the instance catalog, spot prices and interruption signals of every region
+    async fn fetch_market(&self) -> Result<SpotMarket>;
+
+    /// Fetches the advisor data and price data every table and recommendation is built from
+    async fn fetch_data(&self) -> Result<(Value, Value)> {
+        Ok(self.fetch_market().await?.into_data())
+    }
+}
+
+/// AWS spot data from the public spot advisor data and spot price data
+pub struct AwsProvider<'a> {
+    pub client: &'a Client,
+    pub cache: Option<&'a Cache>,
+}
+
+/// Returns the market of the instance data of every region, both OSes' offers included
+fn market_of_instance_data(instance_data: &InstanceData) -> SpotMarket {
+    let mut shapes: BTreeMap<&str, MachineShape> = BTreeMap::new();
+    let mut offers = Vec::new();
+    for ((instance_type, os_name), region_map) in instance_data {
+        let (os, column) = match os_name.as_str() {
+            "Windows" => ("Windows", PriceColumn::Mswin),
+            _ => ("Linux", PriceColumn::Linux),
+        };
+        for (region, info) in region_map {
+            if let (Some(vcpus), Some(memory_gb)) = (info.cores_value(), info.memory_gb_value()) {
+                shapes.entry(instance_type).or_insert_with(|| MachineShape {
+                    instance_type: instance_type.clone(),
+                    vcpus: vcpus as u32,
+                    memory_gb,
+                });
+            }
+            offers.push(SpotOffer {
+                region: region.clone(),
+                instance_type: instance_type.clone(),
+                os,
+                interruption_bucket: info.interruption_bucket(),
+                savings: info.savings_value().map(|savings| savings as u64),
+                spot_price: info.spot_price_value(column),
+            });
+        }
+    }
+    offers.sort_by(|a, b| {
+        (&a.region, &a.instance_type, a.os).cmp(&(&b.region, &b.instance_type, b.os))
+    });
+    SpotMarket {
+        shapes: shapes.into_values().collect(),
+        offers,
+    }
+}
+
+impl SpotProvider for AwsProvider<'_> {
+    fn name(&self) -> &'static str {
+        "aws"
+    }
+
+    async fn fetch_market(&self) -> Result<SpotMarket> {
+        let (advisor_data, price_data) = self.fetch_data().await?;
+        // The region only scopes the debug logs, every region is collected
+        let instance_data =
+            display::collect_instance_data("us-east-1", &advisor_data, &price_data, Os::All);
+        Ok(market_of_instance_data(&instance_data))
+    }
+
+    /// Fetches the spot advisor data and spot price data as is, reusing fresh cached data
+    async fn fetch_data(&self) -> Result<(Value, Value)> {
+        aws::fetch_data(self.client, self.cache).await
+    }
+}
+
+/// Cloud of the spot data with the options of its backend, owned so that it can outlive the
+/// command line, like in the refresh task of serve mode
+#[derive(Clone, Debug)]
+pub struct ProviderConfig {
+    pub provider: Provider,
+    pub gcp_api_key: Option<String>,
+    pub gcp_interruption_rate: Option<f64>,
+    pub azure_eviction_rates: Option<PathBuf>,
+    pub azure_vm_sizes: Option<PathBuf>,
+    /// Region the spot data is fetched for, or None for every region. Only the Azure spot
+    /// prices are fetched per region
+    pub region: Option<String>,
+}
+
+impl ProviderConfig {
+    /// Returns the config of a provider with the backend options of the command line, fetching
+    /// every region
+    pub fn new(provider: Provider, cli: &Cli) -> Self {
+        Self {
+            provider,
+            gcp_api_key: cli.gcp_api_key.clone(),
+            gcp_interruption_rate: cli.gcp_interruption_rate,
+            azure_eviction_rates: cli.azure_eviction_rates.clone(),
+            azure_vm_sizes: cli.azure_vm_sizes.clone(),
+            region: None,
+        }
+    }
+}
+
+/// Backend of the spot data of --provider
+pub enum Backend<'a> {
+    Aws(AwsProvider<'a>),
+    Gcp(gcp::GcpProvider<'a>),
+    Azure(azure::AzureProvider<'a>),
+}
+
+impl<'a> Backend<'a> {
+    /// Returns the backend of a provider, fetching with the client and reusing the cache
+    pub fn new(config: &'a ProviderConfig, client: &'a Client, cache: Option<&'a Cache>) -> Self {
+        match config.provider {
+            Provider::Aws => Backend::Aws(AwsProvider { client, cache }),
+            Provider::Gcp => Backend::Gcp(gcp::GcpProvider {
+                client,
+                cache,
+                api_key: config.gcp_api_key.as_deref(),
+                interruption_rate: config.gcp_interruption_rate,
+            }),
+            Provider::Azure => Backend::Azure(azure::AzureProvider {
+                client,
+                cache,
+                region: config.region.as_deref(),
+                eviction_rates: config.azure_eviction_rates.as_deref(),
+                vm_sizes: config.azure_vm_sizes.as_deref(),
+            }),
+        }
+    }
+}
+
+impl SpotProvider for Backend<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            Backend::Aws(provider) => provider.name(),
+            Backend::Gcp(provider) => provider.name(),
+            Backend::Azure(provider) => provider.name(),
+        }
+    }
+
+    async fn fetch_market(&self) -> Result<SpotMarket> {
+        match self {
+            Backend::Aws(provider) => provider.fetch_market().await,
+            Backend::Gcp(provider) => provider.fetch_market().await,
+            Backend::Azure(provider) => provider.fetch_market().await,
+        }
+    }
+
+    async fn fetch_data(&self) -> Result<(Value, Value)> {
+        match self {
+            Backend::Aws(provider) => provider.fetch_data().await,
+            Backend::Gcp(provider) => provider.fetch_data().await,
+            Backend::Azure(provider) => provider.fetch_data().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market() -> SpotMarket {
+        SpotMarket {
+            shapes: vec![MachineShape {
+                instance_type: "n2-standard-8".to_string(),
+                vcpus: 8,
+                memory_gb: 32.0,
+            }],
+            offers: vec![
+                SpotOffer {
+                    region: "us-central1".to_string(),
+                    instance_type: "n2-standard-8".to_string(),
+                    os: "Linux",
+                    interruption_bucket: None,
+                    savings: Some(70),
+                    spot_price: Some(0.0933),
+                },
+                SpotOffer {
+                    region: "europe-west4".to_string(),
+                    instance_type: "n2-standard-8".to_string(),
+                    os: "Linux",
+                    interruption_bucket: Some(1),
+                    savings: None,
+                    spot_price: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_into_data() {
+        let (advisor_data, price_data) = market().into_data();
+        let instance_data =
+            display::collect_instance_data("us-central1", &advisor_data, &price_data, Os::Linux);
+        let regions = &instance_data[&("n2-standard-8".to_string(), "Linux".to_string())];
+
+        let info = &regions["us-central1"];
+        assert_eq!(info.interruption_rate, "N/A");
+        assert_eq!(info.savings, "70%");
+        assert_eq!(info.spot_price_value(PriceColumn::Linux), Some(0.0933));
+        assert_eq!(info.cores_value(), Some(8.0));
+        assert_eq!(info.memory_gb_value(), Some(32.0));
+
+        let info = &regions["europe-west4"];
+        assert_eq!(info.interruption_rate, "5-10%");
+        assert_eq!(info.savings, "N/A");
+        assert_eq!(info.spot_price_value(PriceColumn::Linux), None);
+    }
+
+    #[test]
+    fn test_market_of_instance_data() {
+        let (advisor_data, price_data) = market().into_data();
+        let instance_data =
+            display::collect_instance_data("us-central1", &advisor_data, &price_data, Os::All);
+        let round_trip = market_of_instance_data(&instance_data);
+        assert_eq!(round_trip.shapes, market().shapes);
+        // Offers are sorted by region
+        assert_eq!(round_trip.offers[0], market().offers[1]);
+        assert_eq!(round_trip.offers[1], market().offers[0]);
+    }
+}
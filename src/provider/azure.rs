@@ -0,0 +1,508 @@
+use super::{MachineShape, SpotMarket, SpotOffer, SpotProvider};
+use crate::aws::DataSource;
+use crate::cache::{self, Cache};
+use crate::telemetry;
+use anyhow::{Context, Result};
+use reqwest::{Client, Url};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Azure Retail Prices API
+pub const RETAIL_PRICES_URL: &str = "https://prices.azure.com/api/retail/prices";
+
+/// Spot prices of the Azure Retail Prices API, the spot data of --provider azure
+pub const DATA_SOURCE: DataSource = DataSource {
+    name: "Azure Retail Prices API (spot prices of virtual machines)",
+    url: RETAIL_PRICES_URL,
+    auth: "None (public)",
+    cache_file: Some(cache::AZURE_SPOT_PRICES_FILE),
+};
+
+/// Filter of the spot prices of virtual machines in the Retail Prices API
+const SPOT_FILTER: &str = "serviceName eq 'Virtual Machines' and priceType eq 'Consumption' and contains(skuName, 'Spot')";
+
+/// Returns the filter of the spot prices, narrowed to a region when one is given
+fn spot_filter(region: Option<&str>) -> String {
+    match region {
+        Some(region) => format!(
+            "{} and armRegionName eq '{}'",
+            SPOT_FILTER,
+            region.replace('\'', "''")
+        ),
+        None => SPOT_FILTER.to_string(),
+    }
+}
+
+/// Eviction rates of the Azure Resource Graph, in the order of the interruption rate buckets
+const EVICTION_RATES: [&str; 5] = ["0-5", "5-10", "10-15", "15-20", "20+"];
+
+/// Returns the vCPUs and memory (GiB) of a VM size like "Standard_D4s_v5", parsed from its name
+/// and the memory per vCPU of its series, or None for series without a fixed ratio
+fn machine_shape(vm_size: &str) -> Option<(u32, f64)> {
+    let name = vm_size.strip_prefix("Standard_")?;
+    let (name, version) = match name.rsplit_once("_v") {
+        Some((name, version)) => (name, version.parse::<u32>().ok()?),
+        None => (name, 1),
+    };
+    let family_len = name.find(|c: char| c.is_ascii_digit())?;
+    let (family, rest) = name.split_at(family_len);
+    let digits_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (vcpus, features) = rest.split_at(digits_len);
+    // Constrained vCPU sizes like "E8-4ds_v5" have fewer active vCPUs than their memory suggests
+    if features.contains('-') {
+        return None;
+    }
+    let vcpus: u32 = vcpus.parse().ok()?;
+
+    let memory_per_vcpu = match family {
+        "D" if version >= 3 && features.contains('l') => 2.0,
+        "D" if version >= 3 => 4.0,
+        "E" if version >= 3 => 8.0,
+        "F" => 2.0,
+        "L" if version >= 2 => 8.0,
+        _ => return None,
+    };
+    Some((vcpus, memory_per_vcpu * f64::from(vcpus)))
+}
+
+/// Parses the VM sizes exported with the Azure CLI into their vCPUs and memory (GiB), keyed by
+/// VM size
+///
+/// The export is the JSON output of `az vm list-skus --resource-type virtualMachines`, whose
+/// capabilities hold the vCPUs and memory, or of `az vm list-sizes`
+fn parse_vm_sizes(export: &Value) -> HashMap<String, (u32, f64)> {
+    let rows = export.as_array().cloned().unwrap_or_default();
+    rows.iter()
+        .filter_map(|row| {
+            let vm_size = row["name"].as_str()?;
+            let shape = match row["capabilities"].as_array() {
+                Some(capabilities) => {
+                    let capability = |name: &str| {
+                        capabilities
+                            .iter()
+                            .find(|capability| capability["name"] == name)?["value"]
+                            .as_str()
+                    };
+                    (
+                        capability("vCPUs")?.parse().ok()?,
+                        capability("MemoryGB")?.parse().ok()?,
+                    )
+                }
+                None => (
+                    u32::try_from(row["numberOfCores"].as_u64()?).ok()?,
+                    row["memoryInMB"].as_f64()? / 1024.0,
+                ),
+            };
+            Some((vm_size.to_string(), shape))
+        })
+        .collect()
+}
+
+/// Returns the interruption rate bucket of an Azure eviction rate like "5-10"
+fn eviction_bucket(eviction_rate: &str) -> Option<usize> {
+    EVICTION_RATES
+        .iter()
+        .position(|rate| *rate == eviction_rate)
+}
+
+/// Parses the eviction rates exported from the Azure Resource Graph, keyed by region and VM size
+///
+/// The export is the JSON output of
+/// `az graph query -q "SpotResources | where type =~ 'microsoft.compute/skuspotevictionrate/location' | project skuName = tostring(sku.name), location, evictionRate = tostring(properties.evictionRate)"`
+fn parse_eviction_rates(export: &Value) -> HashMap<(String, String), usize> {
+    let rows = export
+        .get("data")
+        .unwrap_or(export)
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    rows.iter()
+        .filter_map(|row| {
+            let vm_size = row["skuName"].as_str()?;
+            let region = row["location"].as_str()?;
+            let bucket = eviction_bucket(row["evictionRate"].as_str()?)?;
+            Some(((region.to_lowercase(), vm_size.to_string()), bucket))
+        })
+        .collect()
+}
+
+/// Returns the market of the spot price items of the Retail Prices API, with the eviction rates
+/// where available. The shapes come from the exported VM sizes, falling back to the names of the
+/// VM sizes
+fn market(
+    items: &[Value],
+    eviction_rates: &HashMap<(String, String), usize>,
+    vm_sizes: &HashMap<String, (u32, f64)>,
+) -> SpotMarket {
+    // The lowest price of each VM size, OS and region, since a size can be listed in more than
+    // one product
+    let mut prices: BTreeMap<(String, String, &'static str), f64> = BTreeMap::new();
+    for item in items {
+        let (Some(vm_size), Some(region), Some(sku_name), Some(price)) = (
+            item["armSkuName"].as_str(),
+            item["armRegionName"].as_str(),
+            item["skuName"].as_str(),
+            item["retailPrice"].as_f64(),
+        ) else {
+            continue;
+        };
+        if !sku_name.ends_with(" Spot") || price <= 0.0 || vm_size.is_empty() {
+            continue;
+        }
+        let os = match item["productName"].as_str() {
+            Some(product) if product.ends_with(" Windows") => "Windows",
+            _ => "Linux",
+        };
+        prices
+            .entry((region.to_string(), vm_size.to_string(), os))
+            .and_modify(|lowest| *lowest = lowest.min(price))
+            .or_insert(price);
+    }
+
+    let mut market = SpotMarket::default();
+    let mut shapes: BTreeMap<String, MachineShape> = BTreeMap::new();
+    let mut shapeless = 0;
+    for ((region, vm_size, os), spot_price) in prices {
+        let shape = vm_sizes
+            .get(&vm_size)
+            .copied()
+            .or_else(|| machine_shape(&vm_size));
+        if let Some((vcpus, memory_gb)) = shape {
+            shapes
+                .entry(vm_size.clone())
+                .or_insert_with(|| MachineShape {
+                    instance_type: vm_size.clone(),
+                    vcpus,
+                    memory_gb,
+                });
+        } else {
+            shapeless += 1;
+        }
+        market.offers.push(SpotOffer {
+            interruption_bucket: eviction_rates
+                .get(&(region.clone(), vm_size.clone()))
+                .copied(),
+            region,
+            instance_type: vm_size,
+            os,
+            savings: None,
+            spot_price: Some(spot_price),
+        });
+    }
+    if shapeless > 0 {
+        log::warn!(
+            "{} of {} Azure spot offers have VM sizes of unknown vCPUs and memory, left out by \
+             the vCPU and memory filters. Pass --azure-vm-sizes to know them",
+            shapeless,
+            market.offers.len()
+        );
+    }
+    market.shapes = shapes.into_values().collect();
+    market
+}
+
+/// Azure Spot VM data from the Retail Prices API, with the eviction rates exported from the
+/// Azure Resource Graph
+pub struct AzureProvider<'a> {
+    pub client: &'a Client,
+    pub cache: Option<&'a Cache>,
+    /// Region the spot prices are fetched for, or None for every region
+    pub region: Option<&'a str>,
+    /// Eviction rates exported with `az graph query`
+    pub eviction_rates: Option<&'a Path>,
+    /// VM sizes exported with `az vm list-skus` or `az vm list-sizes`
+    pub vm_sizes: Option<&'a Path>,
+}
+
+impl AzureProvider<'_> {
+    /// Fetches every page of the spot prices of virtual machines in the region, reusing fresh
+    /// cached prices
+    async fn fetch_items(&self) -> Result<Vec<Value>> {
+        let cache_file = cache::azure_spot_prices_file(self.region);
+        if let Some(text) = self.cache.and_then(|cache| cache.read(&cache_file)) {
+            log::info!("Using cached {}", cache_file);
+            return Ok(serde_json::from_str(&text)?);
+        }
+
+        log::info!("Fetching Azure spot prices...");
+        let mut items = Vec::new();
+        let mut url =
+            Url::parse_with_params(RETAIL_PRICES_URL, &[("$filter", spot_filter(self.region))])?;
+        loop {
+            let page: Value = self
+                .client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            items.extend(page["Items"].as_array().cloned().unwrap_or_default());
+            match page["NextPageLink"].as_str() {
+                Some(link) if !link.is_empty() => url = Url::parse(link)?,
+                _ => break,
+            }
+        }
+        log::debug!("Fetched {} Azure spot prices", items.len());
+
+        if let Some(cache) = self.cache
+            && let Err(e) = cache.write(&cache_file, &serde_json::to_string(&items)?)
+        {
+            log::warn!("Failed to cache {}: {}", cache_file, e);
+        }
+        Ok(items)
+    }
+
+    /// Reads the exported eviction rates, if any
+    fn read_eviction_rates(&self) -> Result<HashMap<(String, String), usize>> {
+        let Some(path) = self.eviction_rates else {
+            log::info!("No --azure-eviction-rates given, interruption rates are unavailable");
+            return Ok(HashMap::new());
+        };
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read eviction rates from {}", path.display()))?;
+        let export: Value = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse eviction rates from {}", path.display()))?;
+        Ok(parse_eviction_rates(&export))
+    }
+
+    /// Reads the exported VM sizes, if any
+    fn read_vm_sizes(&self) -> Result<HashMap<String, (u32, f64)>> {
+        let Some(path) = self.vm_sizes else {
+            return Ok(HashMap::new());
+        };
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read VM sizes from {}", path.display()))?;
+        let export: Value = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse VM sizes from {}", path.display()))?;
+        Ok(parse_vm_sizes(&export))
+    }
+}
+
+impl SpotProvider for AzureProvider<'_> {
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    async fn fetch_market(&self) -> Result<SpotMarket> {
+        let eviction_rates = self.read_eviction_rates()?;
+        let vm_sizes = self.read_vm_sizes()?;
+        let items = telemetry::phase_async("fetch", self.fetch_items()).await?;
+        Ok(market(&items, &eviction_rates, &vm_sizes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(vm_size: &str, region: &str, sku_name: &str, product: &str, price: f64) -> Value {
+        json!({
+            "currencyCode": "USD",
+            "retailPrice": price,
+            "unitPrice": price,
+            "armRegionName": region,
+            "skuName": sku_name,
+            "armSkuName": vm_size,
+            "productName": product,
+            "serviceName": "Virtual Machines",
+            "unitOfMeasure": "1 Hour",
+            "type": "Consumption",
+        })
+    }
+
+    #[test]
+    fn test_machine_shape() {
+        assert_eq!(machine_shape("Standard_D4s_v5"), Some((4, 16.0)));
+        assert_eq!(machine_shape("Standard_D8pls_v5"), Some((8, 16.0)));
+        assert_eq!(machine_shape("Standard_E16ads_v5"), Some((16, 128.0)));
+        assert_eq!(machine_shape("Standard_F2s_v2"), Some((2, 4.0)));
+        assert_eq!(machine_shape("Standard_L8s_v3"), Some((8, 64.0)));
+        assert_eq!(machine_shape("Standard_E8-4ds_v5"), None);
+        assert_eq!(machine_shape("Standard_D2_v2"), None);
+        assert_eq!(machine_shape("Standard_B2ms"), None);
+    }
+
+    #[test]
+    fn test_spot_filter() {
+        assert_eq!(spot_filter(None), SPOT_FILTER);
+        assert_eq!(
+            spot_filter(Some("westeurope")),
+            format!("{} and armRegionName eq 'westeurope'", SPOT_FILTER)
+        );
+    }
+
+    #[test]
+    fn test_parse_eviction_rates() {
+        let export = json!({
+            "count": 2,
+            "data": [
+                { "skuName": "Standard_D4s_v5", "location": "eastus", "evictionRate": "0-5" },
+                { "skuName": "Standard_F2s_v2", "location": "EastUS", "evictionRate": "20+" },
+                { "skuName": "Standard_E4s_v5", "location": "eastus", "evictionRate": "unknown" },
+            ],
+        });
+        let rates = parse_eviction_rates(&export);
+        assert_eq!(rates.len(), 2);
+        assert_eq!(
+            rates[&("eastus".to_string(), "Standard_D4s_v5".to_string())],
+            0
+        );
+        assert_eq!(
+            rates[&("eastus".to_string(), "Standard_F2s_v2".to_string())],
+            4
+        );
+
+        let export = json!([{ "skuName": "Standard_D4s_v5", "location": "westus2", "evictionRate": "10-15" }]);
+        assert_eq!(
+            parse_eviction_rates(&export)[&("westus2".to_string(), "Standard_D4s_v5".to_string())],
+            2
+        );
+    }
+
+    #[test]
+    fn test_parse_vm_sizes() {
+        let export = json!([
+            {
+                "name": "Standard_B2ms",
+                "capabilities": [
+                    { "name": "vCPUs", "value": "2" },
+                    { "name": "MemoryGB", "value": "8" },
+                ],
+            },
+            { "name": "Standard_NC6s_v3", "capabilities": [] },
+        ]);
+        assert_eq!(
+            parse_vm_sizes(&export),
+            HashMap::from([("Standard_B2ms".to_string(), (2, 8.0))])
+        );
+
+        let export = json!([{ "name": "Standard_M8ms", "numberOfCores": 8, "memoryInMB": 224000 }]);
+        assert_eq!(parse_vm_sizes(&export)["Standard_M8ms"], (8, 218.75));
+    }
+
+    #[test]
+    fn test_market() {
+        let items = [
+            item(
+                "Standard_D4s_v5",
+                "eastus",
+                "D4s v5 Spot",
+                "Virtual Machines Dsv5 Series",
+                0.04,
+            ),
+            item(
+                "Standard_D4s_v5",
+                "eastus",
+                "D4s v5 Spot",
+                "Virtual Machines Dv5 Series",
+                0.035,
+            ),
+            item(
+                "Standard_D4s_v5",
+                "eastus",
+                "D4s v5 Spot",
+                "Virtual Machines Dsv5 Series Windows",
+                0.08,
+            ),
+            item(
+                "Standard_D4s_v5",
+                "eastus",
+                "D4s v5 Low Priority",
+                "Virtual Machines Dsv5 Series",
+                0.03,
+            ),
+            item(
+                "Standard_B2ms",
+                "westus2",
+                "B2ms Spot",
+                "Virtual Machines BS Series",
+                0.02,
+            ),
+        ];
+        let eviction_rates =
+            HashMap::from([(("eastus".to_string(), "Standard_D4s_v5".to_string()), 1)]);
+        let market = market(&items, &eviction_rates, &HashMap::new());
+
+        assert_eq!(
+            market.shapes,
+            vec![MachineShape {
+                instance_type: "Standard_D4s_v5".to_string(),
+                vcpus: 4,
+                memory_gb: 16.0,
+            }]
+        );
+        assert_eq!(
+            market.offers,
+            vec![
+                SpotOffer {
+                    region: "eastus".to_string(),
+                    instance_type: "Standard_D4s_v5".to_string(),
+                    os: "Linux",
+                    interruption_bucket: Some(1),
+                    savings: None,
+                    spot_price: Some(0.035),
+                },
+                SpotOffer {
+                    region: "eastus".to_string(),
+                    instance_type: "Standard_D4s_v5".to_string(),
+                    os: "Windows",
+                    interruption_bucket: Some(1),
+                    savings: None,
+                    spot_price: Some(0.08),
+                },
+                SpotOffer {
+                    region: "westus2".to_string(),
+                    instance_type: "Standard_B2ms".to_string(),
+                    os: "Linux",
+                    interruption_bucket: None,
+                    savings: None,
+                    spot_price: Some(0.02),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_market_with_vm_sizes() {
+        let items = [
+            item(
+                "Standard_B2ms",
+                "westus2",
+                "B2ms Spot",
+                "Virtual Machines BS Series",
+                0.02,
+            ),
+            item(
+                "Standard_D4s_v5",
+                "westus2",
+                "D4s v5 Spot",
+                "Virtual Machines Dsv5 Series",
+                0.04,
+            ),
+        ];
+        let vm_sizes = HashMap::from([("Standard_B2ms".to_string(), (2, 8.0))]);
+        let market = market(&items, &HashMap::new(), &vm_sizes);
+
+        assert_eq!(
+            market.shapes,
+            vec![
+                MachineShape {
+                    instance_type: "Standard_B2ms".to_string(),
+                    vcpus: 2,
+                    memory_gb: 8.0,
+                },
+                MachineShape {
+                    instance_type: "Standard_D4s_v5".to_string(),
+                    vcpus: 4,
+                    memory_gb: 16.0,
+                },
+            ]
+        );
+        assert_eq!(market.offers.len(), 2);
+    }
+}
@@ -0,0 +1,521 @@
+use super::{MachineShape, SpotMarket, SpotOffer, SpotProvider};
+use crate::aws::DataSource;
+use crate::cache::{self, Cache};
+use crate::recommend::INTERRUPTION_RATE_UPPER_BOUNDS;
+use crate::telemetry;
+use anyhow::{Result, anyhow};
+use reqwest::{Client, Url};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// SKUs of the Compute Engine service in the Cloud Billing Catalog API
+pub const SKUS_URL: &str = "https://cloudbilling.googleapis.com/v1/services/6F81-5844-456A/skus";
+
+/// Compute Engine SKUs of the Cloud Billing Catalog API, the spot data of --provider gcp
+pub const DATA_SOURCE: DataSource = DataSource {
+    name: "Cloud Billing Catalog API (Compute Engine SKUs)",
+    url: SKUS_URL,
+    auth: "Google Cloud API key (--gcp-api-key)",
+    cache_file: Some(cache::GCP_SKUS_FILE),
+};
+
+/// A predefined machine series, whose machine types are named like "n2-standard-8" and priced
+/// per vCPU and GiB of memory
+struct MachineSeries {
+    series: &'static str,
+    /// Name of the series in the SKU descriptions, like "N2"
+    sku_name: &'static str,
+    /// Machine type suffixes with their memory (GiB) per vCPU
+    types: &'static [(&'static str, f64)],
+    /// vCPUs of the machine types
+    sizes: &'static [u32],
+}
+
+const STANDARD_TYPES: &[(&str, f64)] = &[("standard", 4.0), ("highmem", 8.0), ("highcpu", 1.0)];
+const STANDARD_TYPES_HIGHCPU_2: &[(&str, f64)] =
+    &[("standard", 4.0), ("highmem", 8.0), ("highcpu", 2.0)];
+
+/// Machine series of the general-purpose and compute-optimized machine types with spot SKUs
+const MACHINE_SERIES: &[MachineSeries] = &[
+    MachineSeries {
+        series: "e2",
+        sku_name: "E2",
+        types: STANDARD_TYPES,
+        sizes: &[2, 4, 8, 16, 32],
+    },
+    MachineSeries {
+        series: "n2",
+        sku_name: "N2",
+        types: STANDARD_TYPES,
+        sizes: &[2, 4, 8, 16, 32, 48, 64, 80, 96, 128],
+    },
+    MachineSeries {
+        series: "n2d",
+        sku_name: "N2D",
+        types: STANDARD_TYPES,
+        sizes: &[2, 4, 8, 16, 32, 48, 64, 80, 96, 128, 224],
+    },
+    MachineSeries {
+        series: "n4",
+        sku_name: "N4",
+        types: STANDARD_TYPES_HIGHCPU_2,
+        sizes: &[2, 4, 8, 16, 32, 48, 64, 80],
+    },
+    MachineSeries {
+        series: "c3",
+        sku_name: "C3",
+        types: STANDARD_TYPES_HIGHCPU_2,
+        sizes: &[4, 8, 22, 44, 88, 176],
+    },
+    MachineSeries {
+        series: "c3d",
+        sku_name: "C3D",
+        types: STANDARD_TYPES_HIGHCPU_2,
+        sizes: &[4, 8, 16, 30, 60, 90, 180, 360],
+    },
+    MachineSeries {
+        series: "c2d",
+        sku_name: "C2D",
+        types: STANDARD_TYPES_HIGHCPU_2,
+        sizes: &[2, 4, 8, 16, 32, 56, 112],
+    },
+    MachineSeries {
+        series: "t2d",
+        sku_name: "T2D",
+        types: &[("standard", 4.0)],
+        sizes: &[1, 2, 4, 8, 16, 32, 48, 60],
+    },
+    MachineSeries {
+        series: "t2a",
+        sku_name: "T2A",
+        types: &[("standard", 4.0)],
+        sizes: &[1, 2, 4, 8, 16, 32, 48],
+    },
+];
+
+/// Resource a SKU prices
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Resource {
+    /// Per vCPU-hour
+    Core,
+    /// Per GiB-hour
+    Ram,
+}
+
+/// Hourly unit price of a resource of a machine series in the regions of a SKU
+#[derive(Clone, Debug, PartialEq)]
+struct ResourcePrice {
+    sku_name: String,
+    resource: Resource,
+    spot: bool,
+    regions: Vec<String>,
+    price: f64,
+}
+
+/// Returns the unit price of a SKU described like "Spot Preemptible N2 Instance Core running in
+/// Americas", or None for SKUs of other resources like custom machine types or commitments
+fn resource_price(sku: &Value) -> Option<ResourcePrice> {
+    let category = &sku["category"];
+    if category["resourceFamily"] != "Compute" {
+        return None;
+    }
+    let spot = match category["usageType"].as_str()? {
+        "Preemptible" => true,
+        "OnDemand" => false,
+        _ => return None,
+    };
+
+    let description = sku["description"].as_str()?;
+    let description = description
+        .strip_prefix("Spot Preemptible ")
+        .or_else(|| description.strip_prefix("Preemptible "))
+        .unwrap_or(description);
+    let (sku_name, rest) = description.split_once(' ')?;
+    let rest = rest
+        .strip_prefix("AMD ")
+        .or_else(|| rest.strip_prefix("Arm "))
+        .unwrap_or(rest);
+    let resource = if rest.starts_with("Instance Core running in ") {
+        Resource::Core
+    } else if rest.starts_with("Instance Ram running in ") {
+        Resource::Ram
+    } else {
+        return None;
+    };
+
+    let rate = sku["pricingInfo"][0]["pricingExpression"]["tieredRates"]
+        .as_array()?
+        .last()?;
+    let units: f64 = rate["unitPrice"]["units"].as_str()?.parse().ok()?;
+    let nanos = rate["unitPrice"]["nanos"].as_f64().unwrap_or_default();
+    let regions = sku["serviceRegions"]
+        .as_array()?
+        .iter()
+        .filter_map(|region| region.as_str().map(str::to_string))
+        .collect();
+
+    Some(ResourcePrice {
+        sku_name: sku_name.to_string(),
+        resource,
+        spot,
+        regions,
+        price: units + nanos / 1e9,
+    })
+}
+
+/// Returns the interruption rate bucket of an interruption rate (%), the first bucket whose upper
+/// bound it doesn't exceed
+fn interruption_bucket(rate: f64) -> usize {
+    INTERRUPTION_RATE_UPPER_BOUNDS
+        .iter()
+        .position(|upper_bound| rate <= *upper_bound)
+        .unwrap_or(INTERRUPTION_RATE_UPPER_BOUNDS.len() - 1)
+}
+
+/// Returns the market of the predefined machine types priced by the SKUs. GCP doesn't publish
+/// preemption rates, so every offer has the bucket of the assumed interruption rate (%), or no
+/// interruption signal without one
+fn market(skus: &[Value], interruption_rate: Option<f64>) -> SpotMarket {
+    let mut prices: HashMap<(String, String, Resource, bool), f64> = HashMap::new();
+    for price in skus.iter().filter_map(resource_price) {
+        for region in &price.regions {
+            prices.insert(
+                (
+                    price.sku_name.clone(),
+                    region.clone(),
+                    price.resource,
+                    price.spot,
+                ),
+                price.price,
+            );
+        }
+    }
+    let mut regions: Vec<&str> = prices
+        .keys()
+        .map(|(_, region, _, _)| region.as_str())
+        .collect();
+    regions.sort_unstable();
+    regions.dedup();
+
+    let mut market = SpotMarket::default();
+    for series in MACHINE_SERIES {
+        for (suffix, memory_per_vcpu) in series.types {
+            for &vcpus in series.sizes {
+                let instance_type = format!("{}-{}-{}", series.series, suffix, vcpus);
+                let memory_gb = memory_per_vcpu * f64::from(vcpus);
+                let hourly = |region: &str, spot: bool| {
+                    let price = |resource| {
+                        prices
+                            .get(&(
+                                series.sku_name.to_string(),
+                                region.to_string(),
+                                resource,
+                                spot,
+                            ))
+                            .copied()
+                    };
+                    Some(
+                        f64::from(vcpus) * price(Resource::Core)?
+                            + memory_gb * price(Resource::Ram)?,
+                    )
+                };
+
+                for region in &regions {
+                    let Some(spot_price) = hourly(region, true) else {
+                        continue;
+                    };
+                    let savings = hourly(region, false)
+                        .filter(|on_demand| *on_demand > 0.0)
+                        .map(|on_demand| ((1.0 - spot_price / on_demand) * 100.0).round() as u64);
+                    market.offers.push(SpotOffer {
+                        region: region.to_string(),
+                        instance_type: instance_type.clone(),
+                        os: "Linux",
+                        interruption_bucket: interruption_rate.map(interruption_bucket),
+                        savings,
+                        spot_price: Some(spot_price),
+                    });
+                }
+                market.shapes.push(MachineShape {
+                    instance_type,
+                    vcpus,
+                    memory_gb,
+                });
+            }
+        }
+    }
+    market
+}
+
+/// GCP Spot VM data from the Compute Engine SKUs of the Cloud Billing Catalog API
+pub struct GcpProvider<'a> {
+    pub client: &'a Client,
+    pub cache: Option<&'a Cache>,
+    /// API key of a project with the Cloud Billing API enabled
+    pub api_key: Option<&'a str>,
+    /// Interruption rate (%) assumed for every offer
+    pub interruption_rate: Option<f64>,
+}
+
+impl GcpProvider<'_> {
+    /// Fetches every page of the Compute Engine SKUs, reusing fresh cached SKUs
+    async fn fetch_skus(&self) -> Result<Vec<Value>> {
+        if let Some(text) = self
+            .cache
+            .and_then(|cache| cache.read(cache::GCP_SKUS_FILE))
+        {
+            log::info!("Using cached {}", cache::GCP_SKUS_FILE);
+            return Ok(serde_json::from_str(&text)?);
+        }
+
+        let api_key = self.api_key.ok_or_else(|| {
+            anyhow!("--provider gcp requires an API key of the Cloud Billing API, set with --gcp-api-key or GOOGLE_API_KEY")
+        })?;
+        log::info!("Fetching Compute Engine SKUs...");
+        let mut skus = Vec::new();
+        let mut page_token = String::new();
+        loop {
+            let url = Url::parse_with_params(
+                SKUS_URL,
+                &[
+                    ("key", api_key),
+                    ("currencyCode", "USD"),
+                    ("pageSize", "5000"),
+                    ("pageToken", page_token.as_str()),
+                ],
+            )?;
+            let page: Value = self
+                .client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            skus.extend(page["skus"].as_array().cloned().unwrap_or_default());
+            match page["nextPageToken"].as_str() {
+                Some(token) if !token.is_empty() => page_token = token.to_string(),
+                _ => break,
+            }
+        }
+        log::debug!("Fetched {} Compute Engine SKUs", skus.len());
+
+        if let Some(cache) = self.cache
+            && let Err(e) = cache.write(cache::GCP_SKUS_FILE, &serde_json::to_string(&skus)?)
+        {
+            log::warn!("Failed to cache {}: {}", cache::GCP_SKUS_FILE, e);
+        }
+        Ok(skus)
+    }
+}
+
+impl SpotProvider for GcpProvider<'_> {
+    fn name(&self) -> &'static str {
+        "gcp"
+    }
+
+    async fn fetch_market(&self) -> Result<SpotMarket> {
+        let skus = telemetry::phase_async("fetch", self.fetch_skus()).await?;
+        Ok(market(&skus, self.interruption_rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{DEFAULT_RISK_PENALTIES, Os};
+    use crate::display;
+    use crate::recommend::{Constraints, recommend};
+    use serde_json::json;
+
+    fn sku(description: &str, usage_type: &str, regions: &[&str], nanos: u64) -> Value {
+        json!({
+            "description": description,
+            "category": {
+                "serviceDisplayName": "Compute Engine",
+                "resourceFamily": "Compute",
+                "resourceGroup": "CPU",
+                "usageType": usage_type,
+            },
+            "serviceRegions": regions,
+            "pricingInfo": [{
+                "pricingExpression": {
+                    "usageUnit": "h",
+                    "tieredRates": [{
+                        "startUsageAmount": 0,
+                        "unitPrice": { "currencyCode": "USD", "units": "0", "nanos": nanos },
+                    }],
+                },
+            }],
+        })
+    }
+
+    #[test]
+    fn test_resource_price() {
+        let price = resource_price(&sku(
+            "Spot Preemptible N2D AMD Instance Core running in Americas",
+            "Preemptible",
+            &["us-central1", "us-east1"],
+            6_000_000,
+        ))
+        .unwrap();
+        assert_eq!(price.sku_name, "N2D");
+        assert_eq!(price.resource, Resource::Core);
+        assert!(price.spot);
+        assert_eq!(price.regions, vec!["us-central1", "us-east1"]);
+        assert!((price.price - 0.006).abs() < 1e-12);
+
+        let price = resource_price(&sku(
+            "N2 Instance Ram running in Belgium",
+            "OnDemand",
+            &["europe-west1"],
+            4_000_000,
+        ))
+        .unwrap();
+        assert_eq!((price.resource, price.spot), (Resource::Ram, false));
+
+        for (description, usage_type) in [
+            (
+                "Spot Preemptible N2 Custom Instance Core running in Americas",
+                "Preemptible",
+            ),
+            ("Commitment v1: N2 Cpu in Americas for 1 Year", "Commit1Yr"),
+            (
+                "Spot Preemptible Nvidia Tesla T4 GPU running in Americas",
+                "Preemptible",
+            ),
+        ] {
+            assert_eq!(
+                resource_price(&sku(description, usage_type, &["us-central1"], 1)),
+                None
+            );
+        }
+    }
+
+    fn e2_skus() -> Vec<Value> {
+        vec![
+            sku(
+                "Spot Preemptible E2 Instance Core running in Americas",
+                "Preemptible",
+                &["us-central1"],
+                7_000_000,
+            ),
+            sku(
+                "Spot Preemptible E2 Instance Ram running in Americas",
+                "Preemptible",
+                &["us-central1"],
+                1_000_000,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_market() {
+        let skus = [
+            sku(
+                "Spot Preemptible E2 Instance Core running in Americas",
+                "Preemptible",
+                &["us-central1"],
+                7_000_000,
+            ),
+            sku(
+                "Spot Preemptible E2 Instance Ram running in Americas",
+                "Preemptible",
+                &["us-central1"],
+                1_000_000,
+            ),
+            sku(
+                "E2 Instance Core running in Americas",
+                "OnDemand",
+                &["us-central1"],
+                21_000_000,
+            ),
+            sku(
+                "E2 Instance Ram running in Americas",
+                "OnDemand",
+                &["us-central1"],
+                3_000_000,
+            ),
+            // Without spot RAM prices, N2 machine types aren't offered
+            sku(
+                "Spot Preemptible N2 Instance Core running in Americas",
+                "Preemptible",
+                &["us-central1"],
+                8_000_000,
+            ),
+        ];
+        let market = market(&skus, None);
+
+        let shape = market
+            .shapes
+            .iter()
+            .find(|shape| shape.instance_type == "e2-highmem-4")
+            .unwrap();
+        assert_eq!((shape.vcpus, shape.memory_gb), (4, 32.0));
+
+        let offer = market
+            .offers
+            .iter()
+            .find(|offer| offer.instance_type == "e2-standard-8")
+            .unwrap();
+        assert_eq!(offer.region, "us-central1");
+        // 8 vCPUs at $0.007 and 32 GiB at $0.001
+        assert!((offer.spot_price.unwrap() - 0.088).abs() < 1e-9);
+        assert_eq!(offer.savings, Some(67));
+        assert_eq!(offer.interruption_bucket, None);
+
+        assert!(
+            market
+                .offers
+                .iter()
+                .all(|offer| offer.instance_type.starts_with("e2-"))
+        );
+    }
+
+    #[test]
+    fn test_interruption_bucket() {
+        assert_eq!(interruption_bucket(0.0), 0);
+        assert_eq!(interruption_bucket(5.0), 0);
+        assert_eq!(interruption_bucket(15.0), 2);
+        assert_eq!(interruption_bucket(50.0), 4);
+    }
+
+    #[test]
+    fn test_recommend_market() {
+        let constraints = Constraints {
+            min_vcpus: Some(8.0),
+            min_memory_gb: Some(32.0),
+            max_interruption: Some(15.0),
+        };
+        let recommend_in = |interruption_rate: Option<f64>, constraints: &Constraints| {
+            let (advisor_data, price_data) = market(&e2_skus(), interruption_rate).into_data();
+            let instance_data = display::collect_instance_data(
+                "us-central1",
+                &advisor_data,
+                &price_data,
+                Os::Linux,
+            );
+            recommend(
+                &instance_data,
+                "us-central1",
+                Os::Linux,
+                constraints,
+                &DEFAULT_RISK_PENALTIES,
+            )
+        };
+
+        // Without an assumed interruption rate, no offer can be ranked by effective cost
+        assert!(recommend_in(None, &constraints).is_empty());
+
+        let recommendations = recommend_in(Some(15.0), &constraints);
+        assert!(!recommendations.is_empty());
+        assert_eq!(recommendations[0].instance_type, "e2-standard-8");
+
+        // The assumed rate is above a 10% maximum interruption rate
+        let constraints = Constraints {
+            max_interruption: Some(10.0),
+            ..constraints
+        };
+        assert!(recommend_in(Some(15.0), &constraints).is_empty());
+    }
+}
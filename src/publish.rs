@@ -0,0 +1,412 @@
+use crate::cli::{AwsArgs, Os};
+use crate::display::{self, InstanceData};
+use crate::history;
+use anyhow::{Context, Result, anyhow};
+use aws_sdk_cloudwatch::types::{Dimension, MetricDatum, StandardUnit};
+use reqwest::Client;
+use serde_json::{Value, json};
+
+/// Maximum number of metric datums of a single PutMetricData request
+const CLOUDWATCH_BATCH_SIZE: usize = 1000;
+
+/// Maximum number of series of a single Datadog API request
+const DATADOG_BATCH_SIZE: usize = 1000;
+
+/// Maximum size of a DogStatsD packet, keeping it within the MTU of most networks
+const STATSD_MAX_PACKET_SIZE: usize = 1432;
+
+/// Metric type of gauges in the Datadog API
+const DATADOG_GAUGE_TYPE: u32 = 3;
+
+/// Metric published per instance type
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    /// Spot price per hour (USD)
+    SpotPrice,
+    /// Interruption rate bucket, from 0 (< 5%) to 4 (> 20%)
+    InterruptionBucket,
+    /// Savings over on-demand (%)
+    Savings,
+}
+
+impl Metric {
+    /// Returns the CloudWatch metric name
+    pub fn cloudwatch_name(&self) -> &'static str {
+        match self {
+            Metric::SpotPrice => "SpotPrice",
+            Metric::InterruptionBucket => "InterruptionBucket",
+            Metric::Savings => "Savings",
+        }
+    }
+
+    /// Returns the Datadog metric name
+    pub fn datadog_name(&self) -> &'static str {
+        match self {
+            Metric::SpotPrice => "spotter.spot_price",
+            Metric::InterruptionBucket => "spotter.interruption_bucket",
+            Metric::Savings => "spotter.savings",
+        }
+    }
+
+    fn cloudwatch_unit(&self) -> StandardUnit {
+        match self {
+            Metric::Savings => StandardUnit::Percent,
+            Metric::SpotPrice | Metric::InterruptionBucket => StandardUnit::None,
+        }
+    }
+}
+
+/// Current value of a metric of an instance type
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gauge {
+    pub metric: Metric,
+    pub instance_type: String,
+    pub region: String,
+    pub os: String,
+    pub value: f64,
+}
+
+impl Gauge {
+    /// Returns the Datadog tags of the gauge
+    fn datadog_tags(&self) -> Vec<String> {
+        vec![
+            format!("instance_type:{}", self.instance_type),
+            format!("region:{}", self.region),
+            format!("os:{}", self.os.to_lowercase()),
+        ]
+    }
+}
+
+/// Returns the gauges of the instance types of a region and OS matching any of the filters
+/// (family, size or instance type), ordered by instance type
+pub fn gauges(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    filters: &[String],
+) -> Vec<Gauge> {
+    let mut instances: Vec<(&str, &str, &display::InstanceInfo)> = instance_data
+        .iter()
+        .filter(|((instance_type, os_name), _)| {
+            os.advisor_keys().contains(&os_name.as_str())
+                && (filters.is_empty()
+                    || filters
+                        .iter()
+                        .any(|filter| display::matches_instance_filter(instance_type, filter)))
+        })
+        .filter_map(|((instance_type, os_name), region_map)| {
+            Some((
+                instance_type.as_str(),
+                os_name.as_str(),
+                region_map.get(region)?,
+            ))
+        })
+        .collect();
+    instances.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    let mut gauges = Vec::new();
+    for (instance_type, os_name, info) in instances {
+        let values = [
+            (
+                Metric::SpotPrice,
+                info.spot_price_value(history::os_price_column(os_name)),
+            ),
+            (
+                Metric::InterruptionBucket,
+                info.interruption_bucket().map(|bucket| bucket as f64),
+            ),
+            (Metric::Savings, info.savings_value()),
+        ];
+        for (metric, value) in values {
+            // Metrics without data, like prices missing from the price data, are skipped
+            let Some(value) = value else {
+                continue;
+            };
+            gauges.push(Gauge {
+                metric,
+                instance_type: instance_type.to_string(),
+                region: region.to_string(),
+                os: os_name.to_string(),
+                value,
+            });
+        }
+    }
+    gauges
+}
+
+fn metric_datum(gauge: &Gauge) -> MetricDatum {
+    MetricDatum::builder()
+        .metric_name(gauge.metric.cloudwatch_name())
+        .dimensions(
+            Dimension::builder()
+                .name("InstanceType")
+                .value(&gauge.instance_type)
+                .build(),
+        )
+        .dimensions(Dimension::builder().name("OS").value(&gauge.os).build())
+        .value(gauge.value)
+        .unit(gauge.metric.cloudwatch_unit())
+        .build()
+}
+
+/// Publishes the gauges as custom CloudWatch metrics of a namespace in their region, using the
+/// credentials of the AWS environment
+pub async fn publish_cloudwatch(
+    aws: &AwsArgs,
+    namespace: &str,
+    region: &str,
+    gauges: &[Gauge],
+) -> Result<()> {
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_cloudwatch::Client::new(&config);
+
+    for batch in gauges.chunks(CLOUDWATCH_BATCH_SIZE) {
+        client
+            .put_metric_data()
+            .namespace(namespace)
+            .set_metric_data(Some(batch.iter().map(metric_datum).collect()))
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to publish metrics to CloudWatch namespace '{}' in {}",
+                    namespace, region
+                )
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Returns the DogStatsD packets of the gauges, each holding as many newline-separated
+/// metrics as fit
+pub fn statsd_packets(gauges: &[Gauge]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut packet = String::new();
+    for gauge in gauges {
+        let line = format!(
+            "{}:{}|g|#{}",
+            gauge.metric.datadog_name(),
+            gauge.value,
+            gauge.datadog_tags().join(",")
+        );
+        if !packet.is_empty() && packet.len() + 1 + line.len() > STATSD_MAX_PACKET_SIZE {
+            packets.push(std::mem::take(&mut packet));
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(&line);
+    }
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+    packets
+}
+
+/// Sends the gauges to a DogStatsD agent over UDP (e.g., "127.0.0.1:8125")
+pub async fn publish_statsd(addr: &str, gauges: &[Gauge]) -> Result<()> {
+    let socket = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+    socket
+        .connect(addr)
+        .await
+        .with_context(|| format!("Failed to reach the DogStatsD agent at {}", addr))?;
+
+    for packet in statsd_packets(gauges) {
+        socket.send(packet.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Returns the body of a Datadog v2 series request of the gauges
+pub fn datadog_series(gauges: &[Gauge], timestamp: u64) -> Value {
+    let series: Vec<Value> = gauges
+        .iter()
+        .map(|gauge| {
+            json!({
+                "metric": gauge.metric.datadog_name(),
+                "type": DATADOG_GAUGE_TYPE,
+                "points": [{"timestamp": timestamp, "value": gauge.value}],
+                "tags": gauge.datadog_tags(),
+            })
+        })
+        .collect();
+    json!({ "series": series })
+}
+
+/// Submits the gauges to the Datadog API of a site (e.g., "datadoghq.com")
+pub async fn publish_datadog_api(
+    client: &Client,
+    site: &str,
+    api_key: &str,
+    gauges: &[Gauge],
+    timestamp: u64,
+) -> Result<()> {
+    let url = format!("https://api.{}/api/v2/series", site);
+    for batch in gauges.chunks(DATADOG_BATCH_SIZE) {
+        let response = client
+            .post(&url)
+            .header("DD-API-KEY", api_key)
+            .json(&datadog_series(batch, timestamp))
+            .send()
+            .await
+            .with_context(|| format!("Failed to submit metrics to {}", url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Datadog API returned HTTP {}: {}",
+                status,
+                body.trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            ("m5.large", "Linux", "us-east-1", info("5-10%", "0.04")),
+            (
+                "m6i.large",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "N/A").with_savings("65%"),
+            ),
+            (
+                "m5.large",
+                "Windows",
+                "us-east-1",
+                info("5-10%", "0.04")
+                    .with_savings("40%")
+                    .with_windows_spot_price("0.5"),
+            ),
+            (
+                "c5.large",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.03").with_savings("60%"),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_gauges() {
+        let filters = vec!["m5".to_string(), "m6i".to_string()];
+        let published = gauges(&sample_data(), "us-east-1", Os::Linux, &filters);
+
+        let values: Vec<(&str, Metric, f64)> = published
+            .iter()
+            .map(|g| (g.instance_type.as_str(), g.metric, g.value))
+            .collect();
+        // The m6i.large price is missing, so only its bucket and savings are published
+        assert_eq!(
+            values,
+            vec![
+                ("m5.large", Metric::SpotPrice, 0.04),
+                ("m5.large", Metric::InterruptionBucket, 1.0),
+                ("m5.large", Metric::Savings, 70.0),
+                ("m6i.large", Metric::InterruptionBucket, 0.0),
+                ("m6i.large", Metric::Savings, 65.0),
+            ]
+        );
+        assert!(
+            published
+                .iter()
+                .all(|g| g.region == "us-east-1" && g.os == "Linux")
+        );
+
+        // Windows gauges use the Windows spot price
+        let published = gauges(&sample_data(), "us-east-1", Os::Windows, &[]);
+        assert_eq!(published.len(), 3);
+        assert_eq!(published[0].value, 0.5);
+
+        assert!(gauges(&sample_data(), "eu-west-1", Os::All, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_metric_datum() {
+        let gauge = Gauge {
+            metric: Metric::Savings,
+            instance_type: "m5.large".to_string(),
+            region: "us-east-1".to_string(),
+            os: "Linux".to_string(),
+            value: 70.0,
+        };
+        let datum = metric_datum(&gauge);
+        assert_eq!(datum.metric_name(), Some("Savings"));
+        assert_eq!(datum.value(), Some(70.0));
+        assert_eq!(datum.unit(), Some(&StandardUnit::Percent));
+        let dimensions: Vec<(Option<&str>, Option<&str>)> = datum
+            .dimensions()
+            .iter()
+            .map(|d| (d.name(), d.value()))
+            .collect();
+        assert_eq!(
+            dimensions,
+            vec![
+                (Some("InstanceType"), Some("m5.large")),
+                (Some("OS"), Some("Linux"))
+            ]
+        );
+    }
+
+    fn gauge(metric: Metric, instance_type: &str, value: f64) -> Gauge {
+        Gauge {
+            metric,
+            instance_type: instance_type.to_string(),
+            region: "us-east-1".to_string(),
+            os: "Linux".to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_statsd_packets() {
+        let gauges = vec![
+            gauge(Metric::SpotPrice, "m5.large", 0.04),
+            gauge(Metric::InterruptionBucket, "m5.large", 1.0),
+        ];
+        assert_eq!(
+            statsd_packets(&gauges),
+            vec![
+                "spotter.spot_price:0.04|g|#instance_type:m5.large,region:us-east-1,os:linux\n\
+                 spotter.interruption_bucket:1|g|#instance_type:m5.large,region:us-east-1,os:linux"
+            ]
+        );
+
+        // Packets are split before exceeding the maximum size
+        let gauges = vec![gauge(Metric::Savings, "m5.large", 70.0); 100];
+        let packets = statsd_packets(&gauges);
+        assert!(packets.len() > 1);
+        assert!(packets.iter().all(|p| p.len() <= STATSD_MAX_PACKET_SIZE));
+        assert_eq!(
+            packets.iter().map(|p| p.lines().count()).sum::<usize>(),
+            100
+        );
+
+        assert!(statsd_packets(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_datadog_series() {
+        let series = datadog_series(&[gauge(Metric::SpotPrice, "m5.large", 0.04)], 1700000000);
+        assert_eq!(
+            series,
+            json!({
+                "series": [{
+                    "metric": "spotter.spot_price",
+                    "type": 3,
+                    "points": [{"timestamp": 1700000000, "value": 0.04}],
+                    "tags": ["instance_type:m5.large", "region:us-east-1", "os:linux"],
+                }]
+            })
+        );
+    }
+}
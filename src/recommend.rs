@@ -0,0 +1,547 @@
+use crate::cli::{Os, PriceColumn};
+use crate::display::{InstanceData, InstanceInfo};
+use crate::instance;
+use std::collections::HashSet;
+
+/// Upper bound (%) of each interruption rate bucket
+pub const INTERRUPTION_RATE_UPPER_BOUNDS: [f64; 5] = [5.0, 10.0, 15.0, 20.0, 100.0];
+
+/// Resource and risk constraints instance types must meet to be recommended
+#[derive(Clone, Debug, Default)]
+pub struct Constraints {
+    pub min_vcpus: Option<f64>,
+    pub min_memory_gb: Option<f64>,
+    /// Maximum interruption rate (%)
+    pub max_interruption: Option<f64>,
+}
+
+impl Constraints {
+    /// Returns true if the instance info meets every constraint
+    pub fn matches(&self, info: &InstanceInfo) -> bool {
+        let at_least = |min: Option<f64>, value: Option<f64>| match (min, value) {
+            (Some(min), Some(value)) => value >= min,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        let interruption_ok = match (self.max_interruption, info.interruption_bucket()) {
+            (Some(max), Some(bucket)) => INTERRUPTION_RATE_UPPER_BOUNDS[bucket] <= max,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        at_least(self.min_vcpus, info.cores_value())
+            && at_least(self.min_memory_gb, info.memory_gb_value())
+            && interruption_ok
+    }
+}
+
+/// An instance type meeting the constraints, with its risk-adjusted cost
+#[derive(Clone, Debug)]
+pub struct Recommendation {
+    pub instance_type: String,
+    pub info: InstanceInfo,
+    pub effective_cost: f64,
+}
+
+/// Returns the instance types of a region meeting the constraints, ordered by effective cost
+pub fn recommend(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    constraints: &Constraints,
+    risk_penalties: &[f64],
+) -> Vec<Recommendation> {
+    let os_name = os.advisor_keys()[0];
+    let price_column: PriceColumn = os.price_column();
+
+    let mut recommendations: Vec<Recommendation> = instance_data
+        .iter()
+        .filter(|((_, instance_os), _)| instance_os == os_name)
+        .filter_map(|((instance_type, _), region_map)| {
+            let info = region_map.get(region)?;
+            if !constraints.matches(info) {
+                return None;
+            }
+            let effective_cost = info.effective_cost(price_column, risk_penalties)?;
+            Some(Recommendation {
+                instance_type: instance_type.clone(),
+                info: info.clone(),
+                effective_cost,
+            })
+        })
+        .collect();
+
+    recommendations.sort_by(|a, b| {
+        a.effective_cost
+            .total_cmp(&b.effective_cost)
+            .then_with(|| a.instance_type.cmp(&b.instance_type))
+    });
+
+    log::debug!(
+        "Found {} instance types meeting the constraints in region {}",
+        recommendations.len(),
+        region
+    );
+
+    recommendations
+}
+
+/// Maximum number of similar instance types suggested for a risky instance type
+pub const MAX_SIMILAR_INSTANCES: usize = 5;
+
+/// Returns instance types with the same vCPUs and similar memory (half to double) as the given
+/// one but a lower interruption rate, ordered by effective cost
+pub fn similar_instances(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    instance_type: &str,
+    info: &InstanceInfo,
+    risk_penalties: &[f64],
+) -> Vec<Recommendation> {
+    let (Some(cores), Some(memory_gb), Some(bucket)) = (
+        info.cores_value(),
+        info.memory_gb_value(),
+        info.interruption_bucket(),
+    ) else {
+        return Vec::new();
+    };
+
+    let constraints = Constraints {
+        min_vcpus: Some(cores),
+        min_memory_gb: Some(memory_gb / 2.0),
+        max_interruption: None,
+    };
+
+    recommend(instance_data, region, os, &constraints, risk_penalties)
+        .into_iter()
+        .filter(|r| {
+            r.instance_type != instance_type
+                && r.info.cores_value() == Some(cores)
+                && r.info
+                    .memory_gb_value()
+                    .is_some_and(|m| m <= memory_gb * 2.0)
+                && r.info.interruption_bucket().is_some_and(|b| b < bucket)
+        })
+        .take(MAX_SIMILAR_INSTANCES)
+        .collect()
+}
+
+/// Picks up to `count` recommendations spread across as many instance families as possible,
+/// so a fleet built from them is less exposed to correlated interruptions
+pub fn diversify(recommendations: &[Recommendation], count: usize) -> Vec<Recommendation> {
+    let mut picked: Vec<Recommendation> = Vec::new();
+    let mut families: HashSet<&str> = HashSet::new();
+
+    // First pass picks the cheapest instance type of each family
+    for recommendation in recommendations {
+        if picked.len() >= count {
+            break;
+        }
+        if families.insert(instance::family(&recommendation.instance_type)) {
+            picked.push(recommendation.clone());
+        }
+    }
+
+    // Fill the rest with other sizes of the families already picked
+    for recommendation in recommendations {
+        if picked.len() >= count {
+            break;
+        }
+        if !picked
+            .iter()
+            .any(|p| p.instance_type == recommendation.instance_type)
+        {
+            picked.push(recommendation.clone());
+        }
+    }
+
+    picked
+}
+
+/// EC2 Fleet / Auto Scaling spot allocation strategy
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AllocationStrategy {
+    LowestPrice,
+    PriceCapacityOptimized,
+}
+
+impl AllocationStrategy {
+    /// Returns the name of the strategy used in fleet requests
+    pub fn name(&self) -> &'static str {
+        match self {
+            AllocationStrategy::LowestPrice => "lowest-price",
+            AllocationStrategy::PriceCapacityOptimized => "price-capacity-optimized",
+        }
+    }
+}
+
+/// Recommended allocation strategy and the reasons behind it
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyAdvice {
+    pub strategy: AllocationStrategy,
+    pub reasons: Vec<String>,
+}
+
+/// Minimum number of pools for lowest-price to have somewhere to fall back to
+const MIN_LOWEST_PRICE_POOLS: usize = 3;
+
+/// Price difference (%) between the cheapest and the median pool below which picking the pools
+/// with the most capacity costs little extra
+const MIN_LOWEST_PRICE_DISCOUNT: f64 = 10.0;
+
+/// Advises whether lowest-price or price-capacity-optimized allocation likely performs better
+/// for the pools, based on the number of pools, the interruption rate of the cheapest pool and
+/// the price spread. Returns `None` when no pool has a spot price.
+pub fn advise_strategy(
+    pools: &[Recommendation],
+    price_column: PriceColumn,
+) -> Option<StrategyAdvice> {
+    let mut priced: Vec<(&Recommendation, f64)> = pools
+        .iter()
+        .filter_map(|r| Some((r, r.info.spot_price_value(price_column)?)))
+        .collect();
+    priced.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let (cheapest, cheapest_price) = *priced.first()?;
+    let median_price = priced[priced.len() / 2].1;
+    let discount = if median_price > 0.0 {
+        (1.0 - cheapest_price / median_price) * 100.0
+    } else {
+        0.0
+    };
+
+    let mut capacity_reasons = Vec::new();
+    if priced.len() < MIN_LOWEST_PRICE_POOLS {
+        capacity_reasons.push(format!(
+            "Only {} pools: lowest-price concentrates the fleet in the cheapest pool with little room to fall back",
+            priced.len()
+        ));
+    }
+    if cheapest.info.interruption_bucket().is_none_or(|b| b >= 2) {
+        capacity_reasons.push(format!(
+            "The cheapest pool {} has an interruption rate of {}, so lowest-price would land in a pool likely to be reclaimed",
+            cheapest.instance_type, cheapest.info.interruption_rate
+        ));
+    }
+    if discount < MIN_LOWEST_PRICE_DISCOUNT {
+        capacity_reasons.push(format!(
+            "The cheapest pool is only {:.1}% below the median price, so favoring capacity costs little extra",
+            discount
+        ));
+    }
+
+    if !capacity_reasons.is_empty() {
+        return Some(StrategyAdvice {
+            strategy: AllocationStrategy::PriceCapacityOptimized,
+            reasons: capacity_reasons,
+        });
+    }
+
+    Some(StrategyAdvice {
+        strategy: AllocationStrategy::LowestPrice,
+        reasons: vec![
+            format!(
+                "The cheapest pool {} is {:.1}% below the median price with an interruption rate of {}",
+                cheapest.instance_type, discount, cheapest.info.interruption_rate
+            ),
+            format!(
+                "{} pools leave room to fall back when the cheapest pool runs out of capacity",
+                priced.len()
+            ),
+        ],
+    })
+}
+
+/// Returns the recommendations not dominated by any other one over
+/// (spot price, interruption rate, vCPUs, memory), keeping their order
+pub fn pareto_frontier(
+    recommendations: &[Recommendation],
+    price_column: PriceColumn,
+) -> Vec<Recommendation> {
+    // Lower price and interruption rate, and higher vCPUs and memory are better
+    let objectives = |r: &Recommendation| -> Option<[f64; 4]> {
+        Some([
+            r.info.spot_price_value(price_column)?,
+            r.info.interruption_bucket()? as f64,
+            -r.info.cores_value()?,
+            -r.info.memory_gb_value()?,
+        ])
+    };
+    let dominates = |a: &[f64; 4], b: &[f64; 4]| {
+        a.iter().zip(b).all(|(a, b)| a <= b) && a.iter().zip(b).any(|(a, b)| a < b)
+    };
+
+    let candidates: Vec<(&Recommendation, [f64; 4])> = recommendations
+        .iter()
+        .filter_map(|r| Some((r, objectives(r)?)))
+        .collect();
+
+    candidates
+        .iter()
+        .filter(|(_, a)| !candidates.iter().any(|(_, b)| dominates(b, a)))
+        .map(|(r, _)| (*r).clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            (
+                "m5.2xlarge",
+                "Linux",
+                "eu-west-1",
+                info("5-10%", "0.15").with_size("8", "32"),
+            ),
+            (
+                "m6i.2xlarge",
+                "Linux",
+                "eu-west-1",
+                info("< 5%", "0.16").with_size("8", "32"),
+            ),
+            (
+                "c5.2xlarge",
+                "Linux",
+                "eu-west-1",
+                info("< 5%", "0.12").with_size("8", "16"),
+            ),
+            (
+                "r5.2xlarge",
+                "Linux",
+                "eu-west-1",
+                info("> 20%", "0.10").with_size("8", "64"),
+            ),
+            (
+                "m5.xlarge",
+                "Linux",
+                "eu-west-1",
+                info("< 5%", "0.08").with_size("4", "16"),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_constraints_matches() {
+        let constraints = Constraints {
+            min_vcpus: Some(8.0),
+            min_memory_gb: Some(32.0),
+            max_interruption: Some(10.0),
+        };
+        assert!(constraints.matches(&info("5-10%", "0.15").with_size("8", "32")));
+        assert!(!constraints.matches(&info("10-15%", "0.15").with_size("8", "32")));
+        assert!(!constraints.matches(&info("< 5%", "0.15").with_size("4", "32")));
+        assert!(!constraints.matches(&info("< 5%", "0.15").with_size("8", "16")));
+        assert!(!constraints.matches(&info("N/A", "0.15").with_size("8", "32")));
+
+        // No constraints match everything
+        assert!(Constraints::default().matches(&info("N/A", "N/A").with_size("N/A", "N/A")));
+    }
+
+    #[test]
+    fn test_recommend() {
+        let constraints = Constraints {
+            min_vcpus: Some(8.0),
+            min_memory_gb: Some(32.0),
+            max_interruption: Some(10.0),
+        };
+        let recommendations = recommend(
+            &sample_data(),
+            "eu-west-1",
+            Os::Linux,
+            &constraints,
+            &[0.0, 10.0, 20.0, 30.0, 50.0],
+        );
+
+        // m5.2xlarge: 0.15 * 1.1 = 0.165, m6i.2xlarge: 0.16 * 1.0 = 0.16
+        let instance_types: Vec<&str> = recommendations
+            .iter()
+            .map(|r| r.instance_type.as_str())
+            .collect();
+        assert_eq!(instance_types, vec!["m6i.2xlarge", "m5.2xlarge"]);
+    }
+
+    #[test]
+    fn test_recommend_other_region() {
+        let recommendations = recommend(
+            &sample_data(),
+            "us-east-1",
+            Os::Linux,
+            &Constraints::default(),
+            &[0.0, 10.0, 20.0, 30.0, 50.0],
+        );
+        assert!(recommendations.is_empty());
+    }
+
+    fn recommendation(instance_type: &str, effective_cost: f64) -> Recommendation {
+        Recommendation {
+            instance_type: instance_type.to_string(),
+            info: info("< 5%", &effective_cost.to_string()).with_size("8", "32"),
+            effective_cost,
+        }
+    }
+
+    #[test]
+    fn test_diversify() {
+        let recommendations = vec![
+            recommendation("m5.2xlarge", 0.10),
+            recommendation("m5d.2xlarge", 0.11),
+            recommendation("m5.4xlarge", 0.12),
+            recommendation("c5.4xlarge", 0.13),
+            recommendation("r5.2xlarge", 0.14),
+        ];
+
+        // Different families come first, ordered by effective cost
+        let picked: Vec<String> = diversify(&recommendations, 3)
+            .into_iter()
+            .map(|r| r.instance_type)
+            .collect();
+        assert_eq!(picked, vec!["m5.2xlarge", "m5d.2xlarge", "c5.4xlarge"]);
+
+        // Other sizes of the same family fill the rest
+        let picked: Vec<String> = diversify(&recommendations, 5)
+            .into_iter()
+            .map(|r| r.instance_type)
+            .collect();
+        assert_eq!(
+            picked,
+            vec![
+                "m5.2xlarge",
+                "m5d.2xlarge",
+                "c5.4xlarge",
+                "r5.2xlarge",
+                "m5.4xlarge"
+            ]
+        );
+
+        // Fewer recommendations than requested
+        assert_eq!(diversify(&recommendations, 10).len(), 5);
+    }
+
+    #[test]
+    fn test_pareto_frontier() {
+        let recommendations = vec![
+            Recommendation {
+                instance_type: "c5.xlarge".to_string(),
+                info: info("< 5%", "0.05").with_size("4", "8"),
+                effective_cost: 0.05,
+            },
+            // Dominated by c5.xlarge: pricier, same risk and vCPUs, same memory
+            Recommendation {
+                instance_type: "c5a.xlarge".to_string(),
+                info: info("< 5%", "0.06").with_size("4", "8"),
+                effective_cost: 0.06,
+            },
+            // More memory for a higher price
+            Recommendation {
+                instance_type: "m5.xlarge".to_string(),
+                info: info("< 5%", "0.07").with_size("4", "16"),
+                effective_cost: 0.07,
+            },
+            // Cheapest but riskier
+            Recommendation {
+                instance_type: "c6i.xlarge".to_string(),
+                info: info("10-15%", "0.04").with_size("4", "8"),
+                effective_cost: 0.044,
+            },
+            // Unknown price can't be compared
+            Recommendation {
+                instance_type: "x1.xlarge".to_string(),
+                info: info("< 5%", "N/A").with_size("4", "8"),
+                effective_cost: 0.0,
+            },
+        ];
+
+        let frontier: Vec<String> = pareto_frontier(&recommendations, PriceColumn::Linux)
+            .into_iter()
+            .map(|r| r.instance_type)
+            .collect();
+        assert_eq!(frontier, vec!["c5.xlarge", "m5.xlarge", "c6i.xlarge"]);
+    }
+
+    #[test]
+    fn test_similar_instances() {
+        let mut data = sample_data();
+        let risky = info("> 20%", "0.09").with_size("8", "32");
+        data.extend(instance_data([(
+            "m5a.2xlarge",
+            "Linux",
+            "eu-west-1",
+            risky.clone(),
+        )]));
+
+        let similar: Vec<String> = similar_instances(
+            &data,
+            "eu-west-1",
+            Os::Linux,
+            "m5a.2xlarge",
+            &risky,
+            &[0.0, 10.0, 20.0, 30.0, 50.0],
+        )
+        .into_iter()
+        .map(|r| r.instance_type)
+        .collect();
+
+        // r5.2xlarge is as risky, m5.xlarge has fewer vCPUs
+        assert_eq!(similar, vec!["c5.2xlarge", "m6i.2xlarge", "m5.2xlarge"]);
+    }
+
+    fn pool(instance_type: &str, interruption_rate: &str, price: &str) -> Recommendation {
+        Recommendation {
+            instance_type: instance_type.to_string(),
+            info: info(interruption_rate, price).with_size("8", "32"),
+            effective_cost: price.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_advise_strategy_lowest_price() {
+        let pools = vec![
+            pool("m5.2xlarge", "5-10%", "0.15"),
+            pool("m6i.2xlarge", "< 5%", "0.10"),
+            pool("c6i.2xlarge", "< 5%", "0.14"),
+        ];
+        let advice = advise_strategy(&pools, PriceColumn::Linux).unwrap();
+        assert_eq!(advice.strategy, AllocationStrategy::LowestPrice);
+        assert_eq!(advice.reasons.len(), 2);
+        assert!(advice.reasons[0].contains("m6i.2xlarge"));
+    }
+
+    #[test]
+    fn test_advise_strategy_price_capacity_optimized() {
+        // The cheapest pool is risky
+        let pools = vec![
+            pool("m5.2xlarge", "> 20%", "0.10"),
+            pool("m6i.2xlarge", "< 5%", "0.15"),
+            pool("c6i.2xlarge", "< 5%", "0.14"),
+        ];
+        let advice = advise_strategy(&pools, PriceColumn::Linux).unwrap();
+        assert_eq!(advice.strategy, AllocationStrategy::PriceCapacityOptimized);
+        assert_eq!(advice.reasons.len(), 1);
+
+        // Too few pools with close prices
+        let pools = vec![
+            pool("m6i.2xlarge", "< 5%", "0.15"),
+            pool("c6i.2xlarge", "< 5%", "0.14"),
+        ];
+        let advice = advise_strategy(&pools, PriceColumn::Linux).unwrap();
+        assert_eq!(advice.strategy, AllocationStrategy::PriceCapacityOptimized);
+        assert_eq!(advice.reasons.len(), 2);
+
+        // No priced pools
+        assert_eq!(advise_strategy(&[], PriceColumn::Linux), None);
+    }
+
+    #[test]
+    fn test_allocation_strategy_name() {
+        assert_eq!(AllocationStrategy::LowestPrice.name(), "lowest-price");
+        assert_eq!(
+            AllocationStrategy::PriceCapacityOptimized.name(),
+            "price-capacity-optimized"
+        );
+    }
+}
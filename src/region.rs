@@ -0,0 +1,181 @@
+use reqwest::Client;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Region used when it can't be detected from the AWS environment
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+pub const IMDS_TOKEN_URL: &str = "http://169.254.169.254/latest/api/token";
+pub const IMDS_REGION_URL: &str = "http://169.254.169.254/latest/meta-data/placement/region";
+
+/// Resolves the region to use, in the same order as the AWS CLI:
+/// the --region flag, AWS_REGION/AWS_DEFAULT_REGION, the active profile in ~/.aws/config,
+/// and finally EC2 instance metadata when running on an instance
+pub async fn resolve_region(region: Option<&str>, client: &Client) -> String {
+    if let Some(region) = region {
+        return region.to_string();
+    }
+
+    if let Some(region) = region_from_env() {
+        log::debug!("Using region '{}' from environment variables", region);
+        return region;
+    }
+
+    if let Some(region) = region_from_config() {
+        log::debug!("Using region '{}' from AWS config file", region);
+        return region;
+    }
+
+    if let Some(region) = region_from_imds(client).await {
+        log::debug!("Using region '{}' from EC2 instance metadata", region);
+        return region;
+    }
+
+    log::info!(
+        "Could not detect region from the AWS environment, using {}",
+        DEFAULT_REGION
+    );
+    DEFAULT_REGION.to_string()
+}
+
+fn region_from_env() -> Option<String> {
+    ["AWS_REGION", "AWS_DEFAULT_REGION"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .map(|value| value.trim().to_string())
+}
+
+fn region_from_config() -> Option<String> {
+    let path = std::env::var_os("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".aws").join("config")))?;
+    let contents = std::fs::read_to_string(&path).ok()?;
+    let profile = std::env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
+
+    parse_config_region(&contents, &profile)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Extracts the region of a profile from the contents of an AWS config file
+pub fn parse_config_region(contents: &str, profile: &str) -> Option<String> {
+    let section = if profile == "default" {
+        "default".to_string()
+    } else {
+        format!("profile {}", profile)
+    };
+
+    let mut in_section = false;
+    for line in contents.lines() {
+        let line = line.trim();
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+            continue;
+        }
+
+        if !in_section {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=')
+            && key.trim() == "region"
+            && !value.trim().is_empty()
+        {
+            return Some(value.trim().to_string());
+        }
+    }
+
+    None
+}
+
+async fn region_from_imds(client: &Client) -> Option<String> {
+    if std::env::var("AWS_EC2_METADATA_DISABLED").is_ok_and(|v| v.eq_ignore_ascii_case("true")) {
+        return None;
+    }
+
+    // IMDSv2 requires a session token; keep timeouts short since we're usually not on EC2
+    let timeout = Duration::from_secs(1);
+    let token = client
+        .put(IMDS_TOKEN_URL)
+        .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
+        .timeout(timeout)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+
+    let region = client
+        .get(IMDS_REGION_URL)
+        .header("X-aws-ec2-metadata-token", token)
+        .timeout(timeout)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+
+    Some(region.trim().to_string()).filter(|region| !region.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "
+[default]
+region = eu-west-1
+output = json
+
+[profile prod]
+output = text
+region=ap-northeast-1
+
+[profile empty]
+region =
+";
+
+    #[test]
+    fn test_parse_config_region_default_profile() {
+        assert_eq!(
+            parse_config_region(CONFIG, "default"),
+            Some("eu-west-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_config_region_named_profile() {
+        assert_eq!(
+            parse_config_region(CONFIG, "prod"),
+            Some("ap-northeast-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_config_region_missing() {
+        assert_eq!(parse_config_region(CONFIG, "empty"), None);
+        assert_eq!(parse_config_region(CONFIG, "unknown"), None);
+        assert_eq!(parse_config_region("", "default"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_region_from_flag() {
+        let client = Client::new();
+        assert_eq!(
+            resolve_region(Some("sa-east-1"), &client).await,
+            "sa-east-1"
+        );
+    }
+}
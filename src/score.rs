@@ -0,0 +1,167 @@
+use crate::cli::{AwsArgs, Os};
+use crate::display::InstanceData;
+use anyhow::{Context, Result, anyhow};
+
+/// Spot placement score of the requested instance types in a region or Availability Zone, from 1
+/// (unlikely to succeed) to 10 (very likely)
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacementScore {
+    pub region: String,
+    /// Availability Zone ID (e.g., 'use1-az1'), only scored per AZ with --single-az
+    pub availability_zone_id: Option<String>,
+    pub score: i32,
+    /// Interruption rates of the requested instance types in the region, from the advisor data
+    pub interruption_rates: String,
+}
+
+/// Sorts the scores from the best, then by region and AZ
+fn sort_scores(scores: &mut [PlacementScore]) {
+    scores.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.region.cmp(&b.region))
+            .then_with(|| a.availability_zone_id.cmp(&b.availability_zone_id))
+    });
+}
+
+/// Returns the advisor interruption rates of the instance types in a region, like
+/// "m5.xlarge < 5%, m6i.xlarge 5-10%"
+pub fn interruption_rates(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    instance_types: &[String],
+) -> String {
+    let os_name = os.advisor_keys()[0];
+    instance_types
+        .iter()
+        .map(|instance_type| {
+            let rate = instance_data
+                .get(&(instance_type.clone(), os_name.to_string()))
+                .and_then(|region_map| region_map.get(region))
+                .map_or("N/A", |info| info.interruption_rate.as_str());
+            format!("{} {}", instance_type, rate)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Fetches the spot placement scores of the instance types in the regions with the EC2
+/// GetSpotPlacementScores API, calling it in a region
+pub async fn fetch_scores(
+    aws: &AwsArgs,
+    region: &str,
+    instance_types: &[String],
+    target_capacity: i32,
+    regions: &[String],
+    single_az: bool,
+) -> Result<Vec<(String, Option<String>, i32)>> {
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_ec2::Client::new(&config);
+
+    let scores = client
+        .get_spot_placement_scores()
+        .set_instance_types(Some(instance_types.to_vec()))
+        .target_capacity(target_capacity)
+        .set_region_names(Some(regions.to_vec()))
+        .single_availability_zone(single_az)
+        .into_paginator()
+        .items()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .context("Failed to get spot placement scores (are AWS credentials configured?)")?;
+
+    Ok(scores
+        .into_iter()
+        .filter_map(|score| {
+            Some((
+                score.region()?.to_string(),
+                score.availability_zone_id().map(str::to_string),
+                score.score()?,
+            ))
+        })
+        .collect())
+}
+
+/// Joins the placement scores with the advisor interruption rates of each region, best first
+pub fn placement_scores(
+    scores: Vec<(String, Option<String>, i32)>,
+    instance_data_of: impl Fn(&str) -> InstanceData,
+    os: Os,
+    instance_types: &[String],
+) -> Result<Vec<PlacementScore>> {
+    if scores.is_empty() {
+        return Err(anyhow!(
+            "No spot placement scores returned, try a lower target capacity"
+        ));
+    }
+
+    let mut placement_scores: Vec<PlacementScore> = scores
+        .into_iter()
+        .map(|(region, availability_zone_id, score)| {
+            let instance_data = instance_data_of(&region);
+            PlacementScore {
+                interruption_rates: interruption_rates(&instance_data, &region, os, instance_types),
+                region,
+                availability_zone_id,
+                score,
+            }
+        })
+        .collect();
+    sort_scores(&mut placement_scores);
+    Ok(placement_scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    /// Instance data of m5.xlarge, calm only in us-east-1
+    fn region_data(region: &str) -> InstanceData {
+        let rate = if region == "us-east-1" {
+            "< 5%"
+        } else {
+            "10-15%"
+        };
+        instance_data([("m5.xlarge", "Linux", region, info(rate, "0.05"))])
+    }
+
+    #[test]
+    fn test_interruption_rates() {
+        let types = vec!["m5.xlarge".to_string(), "m6i.xlarge".to_string()];
+        assert_eq!(
+            interruption_rates(&region_data("us-east-1"), "us-east-1", Os::Linux, &types),
+            "m5.xlarge < 5%, m6i.xlarge N/A"
+        );
+    }
+
+    #[test]
+    fn test_placement_scores() {
+        let types = vec!["m5.xlarge".to_string()];
+        let scores = placement_scores(
+            vec![
+                ("us-west-2".to_string(), None, 3),
+                ("us-east-1".to_string(), None, 9),
+                ("eu-west-1".to_string(), None, 3),
+            ],
+            region_data,
+            Os::Linux,
+            &types,
+        )
+        .unwrap();
+        let order: Vec<(&str, i32)> = scores
+            .iter()
+            .map(|s| (s.region.as_str(), s.score))
+            .collect();
+        assert_eq!(
+            order,
+            vec![("us-east-1", 9), ("eu-west-1", 3), ("us-west-2", 3)]
+        );
+        assert_eq!(scores[0].interruption_rates, "m5.xlarge < 5%");
+        assert_eq!(scores[2].interruption_rates, "m5.xlarge 10-15%");
+
+        assert!(placement_scores(vec![], region_data, Os::Linux, &types).is_err());
+    }
+}
@@ -0,0 +1,772 @@
+use crate::cache::Cache;
+use crate::cli::{self, Os};
+use crate::display::{self, InstanceData, InstanceInfo};
+use crate::history;
+use crate::instance;
+use crate::provider::{Backend, ProviderConfig, SpotProvider};
+use crate::recommend::Constraints;
+use crate::region;
+use crate::telemetry;
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, Path, Query, Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use clap::ValueEnum;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+mod dashboard;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod rate_limit;
+
+use rate_limit::RateLimiter;
+
+/// Instance data of every region and OS held in memory by the server
+#[derive(Clone, Debug)]
+pub struct Dataset {
+    pub instance_data: InstanceData,
+    pub regions: BTreeSet<String>,
+}
+
+impl Dataset {
+    pub fn new(advisor_data: &Value, price_data: &Value) -> Self {
+        // Every region is collected, the region only matters for logging
+        let instance_data = display::collect_instance_data(
+            region::DEFAULT_REGION,
+            advisor_data,
+            price_data,
+            Os::All,
+        );
+        let regions = instance_data
+            .values()
+            .flat_map(|region_map| region_map.keys().cloned())
+            .collect();
+
+        Self {
+            instance_data,
+            regions,
+        }
+    }
+}
+
+/// Number of refreshes buffered for WebSocket clients that are slow to receive them
+const UPDATE_CHANNEL_CAPACITY: usize = 16;
+
+/// Access control of the server
+#[derive(Clone, Debug, Default)]
+pub struct ServeOptions {
+    /// Bearer token every request must carry
+    pub auth_token: Option<String>,
+    /// Maximum number of requests per minute of each client IP, unlimited when 0
+    pub rate_limit: u32,
+    /// Region of the dashboard when none is queried, the resolved region of the command line
+    pub default_region: String,
+}
+
+/// State shared by the request handlers
+#[derive(Clone)]
+pub struct AppState {
+    dataset: Arc<RwLock<Dataset>>,
+    /// Rows changed by each refresh, pushed to the WebSocket clients
+    updates: broadcast::Sender<Arc<Vec<RowUpdate>>>,
+    auth_token: Option<Arc<str>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    default_region: Arc<str>,
+}
+
+impl AppState {
+    pub fn new(dataset: Dataset, options: &ServeOptions) -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        Self {
+            dataset: Arc::new(RwLock::new(dataset)),
+            updates,
+            auth_token: options.auth_token.as_deref().map(Arc::from),
+            rate_limiter: (options.rate_limit > 0)
+                .then(|| Arc::new(RateLimiter::new(options.rate_limit))),
+            default_region: Arc::from(options.default_region.as_str()),
+        }
+    }
+
+    fn dataset(&self) -> RwLockReadGuard<'_, Dataset> {
+        self.dataset.read().unwrap()
+    }
+
+    /// Replaces the served data, notifying the WebSocket clients of the changed rows
+    fn replace(&self, dataset: Dataset) {
+        let changes = {
+            let mut current = self.dataset.write().unwrap();
+            let changes = changed_rows(&current, &dataset);
+            *current = dataset;
+            changes
+        };
+
+        log::debug!("Refresh changed {} rows", changes.len());
+        // Sending only fails when no client is connected
+        if !changes.is_empty() {
+            let _ = self.updates.send(Arc::new(changes));
+        }
+    }
+}
+
+/// Region entry of the /v1/regions response
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RegionEntry {
+    pub region: String,
+    pub name: Option<&'static str>,
+}
+
+/// Instance entry of the /v1/regions/{region}/instances response
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct InstanceEntry {
+    pub instance_type: String,
+    pub os: String,
+    pub interruption_rate: String,
+    pub savings: Option<f64>,
+    pub spot_price: Option<f64>,
+    pub vcpus: Option<f64>,
+    pub memory_gb: Option<f64>,
+}
+
+/// Row of a region pushed over the /ws endpoint when its spot price or interruption rate changed
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RowUpdate {
+    pub region: String,
+    #[serde(flatten)]
+    pub entry: InstanceEntry,
+}
+
+/// Query parameters of the /v1/regions/{region}/instances endpoint
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct InstanceQuery {
+    /// Instance family (e.g., "m6i")
+    pub family: Option<String>,
+    /// Maximum interruption rate (%)
+    pub max_interruption: Option<f64>,
+    pub min_vcpus: Option<f64>,
+    pub min_memory: Option<f64>,
+    /// "linux" (default), "windows" or "all"
+    pub os: Option<String>,
+}
+
+/// Query parameters of the /dashboard page and the /ws endpoint
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RegionQuery {
+    /// Region to show, the default region on the dashboard and every region over /ws when not
+    /// given
+    pub region: Option<String>,
+}
+
+/// Error response with a JSON body of the form {"error": "..."}
+#[derive(Debug, PartialEq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: String) -> Self {
+        Self { status, message }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// Returns the regions of the dataset with their names
+pub fn list_regions(dataset: &Dataset) -> Vec<RegionEntry> {
+    dataset
+        .regions
+        .iter()
+        .map(|region| RegionEntry {
+            region: region.clone(),
+            name: cli::region_name(region),
+        })
+        .collect()
+}
+
+/// Parses the OS of a query, Linux when not given
+fn parse_os(os: Option<&str>) -> Result<Os, ApiError> {
+    match os {
+        Some(os) => Os::from_str(os, true).map_err(|_| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid OS '{}'. Please use linux, windows or all", os),
+            )
+        }),
+        None => Ok(Os::default()),
+    }
+}
+
+/// Returns the instance types of a region matching the query, ordered by instance type and OS
+pub fn query_instances(
+    dataset: &Dataset,
+    region: &str,
+    query: &InstanceQuery,
+) -> Result<Vec<InstanceEntry>, ApiError> {
+    if !dataset.regions.contains(region) {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Region '{}' not found", region),
+        ));
+    }
+
+    let os = parse_os(query.os.as_deref())?;
+
+    let constraints = Constraints {
+        min_vcpus: query.min_vcpus,
+        min_memory_gb: query.min_memory,
+        max_interruption: query.max_interruption,
+    };
+
+    let mut entries: Vec<InstanceEntry> = dataset
+        .instance_data
+        .iter()
+        .filter(|((instance_type, os_name), _)| {
+            os.advisor_keys().contains(&os_name.as_str())
+                && query
+                    .family
+                    .as_deref()
+                    .is_none_or(|family| instance::family(instance_type) == family)
+        })
+        .filter_map(|((instance_type, os_name), region_map)| {
+            let info = region_map.get(region)?;
+            if !constraints.matches(info) {
+                return None;
+            }
+
+            Some(instance_entry(instance_type, os_name, info))
+        })
+        .collect();
+
+    entries.sort_by(|a, b| (&a.instance_type, &a.os).cmp(&(&b.instance_type, &b.os)));
+    Ok(entries)
+}
+
+fn instance_entry(instance_type: &str, os_name: &str, info: &InstanceInfo) -> InstanceEntry {
+    InstanceEntry {
+        instance_type: instance_type.to_string(),
+        os: os_name.to_string(),
+        interruption_rate: info.interruption_rate.clone(),
+        savings: info.savings_value(),
+        spot_price: info.spot_price_value(history::os_price_column(os_name)),
+        vcpus: info.cores_value(),
+        memory_gb: info.memory_gb_value(),
+    }
+}
+
+/// Returns the rows that are new or whose spot price or interruption rate changed between two
+/// datasets, ordered by region, instance type and OS
+pub fn changed_rows(old: &Dataset, new: &Dataset) -> Vec<RowUpdate> {
+    let mut rows: Vec<RowUpdate> = new
+        .instance_data
+        .iter()
+        .flat_map(|(key, region_map)| {
+            let (instance_type, os_name) = key;
+            let column = history::os_price_column(os_name);
+            let old_regions = old.instance_data.get(key);
+            region_map.iter().filter_map(move |(region, info)| {
+                let unchanged = old_regions
+                    .and_then(|old_regions| old_regions.get(region))
+                    .is_some_and(|old_info| {
+                        old_info.spot_price(column) == info.spot_price(column)
+                            && old_info.interruption_rate == info.interruption_rate
+                    });
+                (!unchanged).then(|| RowUpdate {
+                    region: region.clone(),
+                    entry: instance_entry(instance_type, os_name, info),
+                })
+            })
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        (&a.region, &a.entry.instance_type, &a.entry.os).cmp(&(
+            &b.region,
+            &b.entry.instance_type,
+            &b.entry.os,
+        ))
+    });
+    rows
+}
+
+async fn regions_handler(State(state): State<AppState>) -> Json<Vec<RegionEntry>> {
+    Json(list_regions(&state.dataset()))
+}
+
+async fn instances_handler(
+    State(state): State<AppState>,
+    Path(region): Path<String>,
+    Query(query): Query<InstanceQuery>,
+) -> Result<Json<Vec<InstanceEntry>>, ApiError> {
+    query_instances(&state.dataset(), &region, &query).map(Json)
+}
+
+/// Returns the region the dashboard shows when none is queried: the default region, or the first
+/// region of the data when it has no offers there
+fn default_region<'a>(dataset: &'a Dataset, region: &'a str) -> &'a str {
+    if dataset.regions.contains(region) {
+        region
+    } else {
+        dataset.regions.first().map_or(region, String::as_str)
+    }
+}
+
+async fn dashboard_handler(
+    State(state): State<AppState>,
+    Query(query): Query<RegionQuery>,
+) -> Result<Html<String>, ApiError> {
+    let dataset = state.dataset();
+    let region = match &query.region {
+        Some(region) => region,
+        None => default_region(&dataset, &state.default_region),
+    };
+    // Every OS is listed, the page filters them like any other column
+    let instance_query = InstanceQuery {
+        os: Some("all".to_string()),
+        ..Default::default()
+    };
+    let entries = query_instances(&dataset, region, &instance_query)?;
+    Ok(Html(telemetry::phase("render", || {
+        dashboard::render(&dataset, region, &entries)
+    })))
+}
+
+async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<RegionQuery>,
+) -> Response {
+    let updates = state.updates.subscribe();
+    ws.on_upgrade(move |socket| push_updates(socket, updates, query.region))
+}
+
+/// Pushes the changed rows of every refresh, optionally of a single region, as a JSON array
+/// until the client disconnects
+async fn push_updates(
+    mut socket: WebSocket,
+    mut updates: broadcast::Receiver<Arc<Vec<RowUpdate>>>,
+    region: Option<String>,
+) {
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let rows = match update {
+                    Ok(rows) => rows,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("WebSocket client missed {} refreshes", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let rows: Vec<&RowUpdate> = rows
+                    .iter()
+                    .filter(|row| region.as_deref().is_none_or(|region| row.region == region))
+                    .collect();
+                if rows.is_empty() {
+                    continue;
+                }
+
+                let Ok(text) = serde_json::to_string(&rows) else {
+                    continue;
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Anything else the client sends is ignored
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Returns true if the headers carry the bearer token
+pub fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| bearer_matches(value, token))
+}
+
+/// Returns true if an Authorization value is the bearer token
+fn bearer_matches(authorization: &str, token: &str) -> bool {
+    authorization
+        .strip_prefix("Bearer ")
+        .is_some_and(|given| token_matches(given, token))
+}
+
+/// Returns true if the given token is the token, compared in constant time
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Pages browsers open themselves, which can't send an Authorization header, so they may carry
+/// the token in a token query parameter instead
+const BROWSER_PATHS: [&str; 2] = ["/dashboard", "/ws"];
+
+/// Query parameter of the token on the browser pages
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Returns true if the request carries the bearer token, or the token query parameter on a
+/// browser page
+fn is_request_authorized(request: &Request, token: &str) -> bool {
+    if is_authorized(request.headers(), token) {
+        return true;
+    }
+    BROWSER_PATHS.contains(&request.uri().path())
+        && Query::<TokenQuery>::try_from_uri(request.uri())
+            .ok()
+            .and_then(|Query(query)| query.token)
+            .is_some_and(|given| token_matches(&given, token))
+}
+
+/// Rejects requests over the rate limit of their client IP or without the bearer token
+async fn guard(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Limiting first keeps clients from guessing the token at full speed
+    if let Some(limiter) = &state.rate_limiter
+        && !limiter.check(addr.ip(), Instant::now())
+    {
+        log::debug!("Rate limited {}", addr.ip());
+        return ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many requests, please retry later".to_string(),
+        )
+        .into_response();
+    }
+
+    if let Some(token) = &state.auth_token
+        && !is_request_authorized(&request, token)
+    {
+        let mut response = ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid bearer token".to_string(),
+        )
+        .into_response();
+        response
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+        return response;
+    }
+
+    next.run(request).await
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/v1/regions", get(regions_handler))
+        .route("/v1/regions/{region}/instances", get(instances_handler))
+        .route("/dashboard", get(dashboard_handler))
+        .route("/ws", get(ws_handler))
+        .layer(middleware::from_fn_with_state(state.clone(), guard))
+        .with_state(state)
+}
+
+/// Re-fetches the data of the provider every interval in the background, keeping the served data
+/// on failure
+pub fn spawn_refresh(
+    state: AppState,
+    provider: ProviderConfig,
+    client: Client,
+    cache: Option<Cache>,
+    interval: Duration,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let backend = Backend::new(&provider, &client, cache.as_ref());
+            match backend.fetch_data().await {
+                Ok((advisor_data, price_data)) => {
+                    state.replace(Dataset::new(&advisor_data, &price_data));
+                    log::info!("Refreshed the served data");
+                }
+                Err(e) => log::warn!("Failed to refresh data: {}", e),
+            }
+        }
+    });
+}
+
+/// Serves the HTTP API, and the gRPC service alongside it when given an address
+pub async fn serve(addr: SocketAddr, grpc_addr: Option<SocketAddr>, state: AppState) -> Result<()> {
+    #[cfg(not(feature = "grpc"))]
+    if grpc_addr.is_some() {
+        return Err(anyhow::anyhow!(
+            "The gRPC service requires building spotter with the 'grpc' feature"
+        ));
+    }
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    log::info!("Listening on http://{}", addr);
+
+    // Client addresses are needed for the per-IP rate limit
+    let http = async {
+        axum::serve(
+            listener,
+            router(state.clone()).into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .context("HTTP server failed")
+    };
+
+    match grpc_addr {
+        #[cfg(feature = "grpc")]
+        Some(grpc_addr) => {
+            tokio::try_join!(http, grpc::serve(grpc_addr, state))?;
+        }
+        _ => http.await?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    pub(super) fn dataset() -> Dataset {
+        let instance_data = instance_data([
+            (
+                "m6i.large",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.04").with_size("2", "8"),
+            ),
+            (
+                "m6i.xlarge",
+                "Linux",
+                "us-east-1",
+                info("10-15%", "0.08").with_size("4", "8"),
+            ),
+            (
+                "m6i.large",
+                "Windows",
+                "us-east-1",
+                info("< 5%", "0.04")
+                    .with_size("2", "8")
+                    .with_windows_spot_price("0.5"),
+            ),
+            (
+                "c6i.large",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.03").with_size("2", "8"),
+            ),
+        ]);
+
+        Dataset {
+            instance_data,
+            regions: BTreeSet::from(["us-east-1".to_string()]),
+        }
+    }
+
+    fn instance_types(entries: &[InstanceEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.instance_type.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_regions() {
+        assert_eq!(
+            list_regions(&dataset()),
+            vec![RegionEntry {
+                region: "us-east-1".to_string(),
+                name: Some("US East (N. Virginia)"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_query_instances() {
+        let dataset = dataset();
+
+        let entries = query_instances(&dataset, "us-east-1", &InstanceQuery::default()).unwrap();
+        assert_eq!(
+            instance_types(&entries),
+            vec!["c6i.large", "m6i.large", "m6i.xlarge"]
+        );
+        assert_eq!(entries[0].spot_price, Some(0.03));
+        assert_eq!(entries[0].savings, Some(70.0));
+        assert_eq!(entries[0].vcpus, Some(2.0));
+
+        let query = InstanceQuery {
+            family: Some("m6i".to_string()),
+            max_interruption: Some(10.0),
+            ..Default::default()
+        };
+        let entries = query_instances(&dataset, "us-east-1", &query).unwrap();
+        assert_eq!(instance_types(&entries), vec!["m6i.large"]);
+
+        // Windows entries use the Windows spot price
+        let query = InstanceQuery {
+            os: Some("windows".to_string()),
+            ..Default::default()
+        };
+        let entries = query_instances(&dataset, "us-east-1", &query).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].os, "Windows");
+        assert_eq!(entries[0].spot_price, Some(0.5));
+    }
+
+    #[test]
+    fn test_query_instances_errors() {
+        let dataset = dataset();
+
+        let err = query_instances(&dataset, "eu-west-1", &InstanceQuery::default()).unwrap_err();
+        assert_eq!(err.status, StatusCode::NOT_FOUND);
+
+        let query = InstanceQuery {
+            os: Some("macos".to_string()),
+            ..Default::default()
+        };
+        let err = query_instances(&dataset, "us-east-1", &query).unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_default_region() {
+        let dataset = dataset();
+        assert_eq!(default_region(&dataset, "us-east-1"), "us-east-1");
+        // A region without offers falls back to the first region of the data
+        assert_eq!(default_region(&dataset, "us-central1"), "us-east-1");
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let headers = |value: &str| HeaderMap::from_iter([(AUTHORIZATION, value.parse().unwrap())]);
+
+        assert!(is_authorized(&headers("Bearer secret"), "secret"));
+        assert!(!is_authorized(&headers("Bearer wrong!"), "secret"));
+        assert!(!is_authorized(&headers("Bearer secret2"), "secret"));
+        assert!(!is_authorized(&headers("Basic secret"), "secret"));
+        assert!(!is_authorized(&HeaderMap::new(), "secret"));
+    }
+
+    #[tokio::test]
+    async fn test_browser_pages_with_auth_token() {
+        let options = ServeOptions {
+            auth_token: Some("secret".to_string()),
+            default_region: "us-east-1".to_string(),
+            ..Default::default()
+        };
+        let state = AppState::new(dataset(), &options);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router(state).into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        });
+
+        let client = Client::new();
+        let get = |path: &str| {
+            client
+                .get(format!("http://{}{}", addr, path))
+                // WebSocket handshake, ignored by the other routes
+                .header("Connection", "Upgrade")
+                .header("Upgrade", "websocket")
+                .header("Sec-WebSocket-Version", "13")
+                .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+                .send()
+        };
+
+        assert_eq!(
+            get("/dashboard").await.unwrap().status(),
+            StatusCode::UNAUTHORIZED
+        );
+        let response = get("/dashboard?token=secret").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.text().await.unwrap().contains("m6i.large"));
+        assert_eq!(
+            get("/dashboard?token=wrong").await.unwrap().status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        assert_eq!(get("/ws").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            get("/ws?token=secret").await.unwrap().status(),
+            StatusCode::SWITCHING_PROTOCOLS
+        );
+
+        // The JSON API only takes the Authorization header
+        assert_eq!(
+            get("/v1/regions?token=secret").await.unwrap().status(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[test]
+    fn test_changed_rows() {
+        let old = dataset();
+        let mut new = dataset();
+
+        // A price change, an interruption rate change and a new region
+        let linux = |instance_type: &str| (instance_type.to_string(), "Linux".to_string());
+        let regions = new.instance_data.get_mut(&linux("m6i.large")).unwrap();
+        regions.get_mut("us-east-1").unwrap().linux_spot_price = "0.05".to_string();
+        regions.insert(
+            "eu-west-1".to_string(),
+            info("< 5%", "0.04").with_size("2", "8"),
+        );
+        let regions = new.instance_data.get_mut(&linux("m6i.xlarge")).unwrap();
+        regions.get_mut("us-east-1").unwrap().interruption_rate = "> 20%".to_string();
+        // Savings alone isn't pushed
+        let regions = new.instance_data.get_mut(&linux("c6i.large")).unwrap();
+        regions.get_mut("us-east-1").unwrap().savings = "50%".to_string();
+
+        let rows = changed_rows(&old, &new);
+        let keys: Vec<(&str, &str)> = rows
+            .iter()
+            .map(|row| (row.region.as_str(), row.entry.instance_type.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("eu-west-1", "m6i.large"),
+                ("us-east-1", "m6i.large"),
+                ("us-east-1", "m6i.xlarge"),
+            ]
+        );
+        assert_eq!(rows[1].entry.spot_price, Some(0.05));
+        assert_eq!(rows[2].entry.interruption_rate, "> 20%");
+
+        assert!(changed_rows(&old, &old).is_empty());
+
+        // Rows are flattened with their region
+        let json = serde_json::to_value(&rows[1]).unwrap();
+        assert_eq!(json["region"], "us-east-1");
+        assert_eq!(json["instance_type"], "m6i.large");
+    }
+}
@@ -0,0 +1,187 @@
+use super::{Dataset, InstanceEntry};
+use crate::cli;
+use crate::display::INTERRUPTION_RATES;
+use std::fmt::Write;
+
+/// Sorts the table by the clicked column and filters its rows by the typed text, so the page
+/// works without any further requests
+const SCRIPT: &str = r#"
+const table = document.getElementById("instances");
+const rows = () => Array.from(table.tBodies[0].rows);
+document.getElementById("filter").addEventListener("input", (e) => {
+  const text = e.target.value.toLowerCase();
+  rows().forEach((row) => {
+    row.hidden = !row.textContent.toLowerCase().includes(text);
+  });
+});
+table.tHead.querySelectorAll("th").forEach((th, i) => {
+  th.addEventListener("click", () => {
+    const asc = th.dataset.order !== "asc";
+    table.tHead.querySelectorAll("th").forEach((h) => delete h.dataset.order);
+    th.dataset.order = asc ? "asc" : "desc";
+    const key = (row) => {
+      const cell = row.cells[i];
+      return cell.dataset.value !== undefined ? parseFloat(cell.dataset.value) : cell.textContent;
+    };
+    const sorted = rows().sort((a, b) => {
+      const [x, y] = [key(a), key(b)];
+      const cmp = typeof x === "number" ? (isNaN(x) ? 1 : isNaN(y) ? -1 : x - y) : x.localeCompare(y);
+      return asc ? cmp : -cmp;
+    });
+    sorted.forEach((row) => table.tBodies[0].appendChild(row));
+  });
+});
+document.getElementById("region").addEventListener("change", (e) => {
+  // Other parameters, like the token, are kept
+  const params = new URLSearchParams(window.location.search);
+  params.set("region", e.target.value);
+  window.location.search = params.toString();
+});
+"#;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; }
+th { cursor: pointer; background: #f0f0f0; user-select: none; }
+th[data-order="asc"]::after { content: " \25B2"; }
+th[data-order="desc"]::after { content: " \25BC"; }
+td.number { text-align: right; }
+"#;
+
+/// Escapes text for use in HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns a numeric cell, sorted by its value and showing "N/A" when there's none
+fn number_cell(value: Option<f64>, text: impl Fn(f64) -> String) -> String {
+    match value {
+        Some(value) => format!(
+            r#"<td class="number" data-value="{}">{}</td>"#,
+            value,
+            text(value)
+        ),
+        None => r#"<td class="number" data-value="NaN">N/A</td>"#.to_string(),
+    }
+}
+
+/// Renders the dashboard page of a region with a row per instance type and OS
+pub fn render(dataset: &Dataset, region: &str, entries: &[InstanceEntry]) -> String {
+    let mut options = String::new();
+    for r in &dataset.regions {
+        let selected = if r == region { " selected" } else { "" };
+        let name = cli::region_name(r)
+            .map(|n| format!(" ({})", n))
+            .unwrap_or_default();
+        let _ = write!(
+            options,
+            r#"<option value="{0}"{1}>{0}{2}</option>"#,
+            escape(r),
+            selected,
+            escape(&name)
+        );
+    }
+
+    let mut rows = String::new();
+    for entry in entries {
+        let _ = write!(
+            rows,
+            "<tr><td>{}</td><td>{}</td><td data-value=\"{}\">{}</td>{}{}{}{}</tr>",
+            escape(&entry.instance_type),
+            escape(&entry.os),
+            // Sort the interruption rate by its bucket rather than its text
+            INTERRUPTION_RATES
+                .iter()
+                .position(|rate| *rate == entry.interruption_rate)
+                .map_or(f64::NAN, |bucket| bucket as f64),
+            escape(&entry.interruption_rate),
+            number_cell(entry.savings, |v| format!("{}%", v)),
+            number_cell(entry.spot_price, |v| format!("${:.4}", v)),
+            number_cell(entry.vcpus, |v| v.to_string()),
+            number_cell(entry.memory_gb, |v| v.to_string()),
+        );
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Spotter - {region}</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<h1>Spot Instances in {region}</h1>
+<p>
+<label>Region <select id="region">{options}</select></label>
+<label>Filter <input id="filter" type="search" placeholder="e.g., m6i, Linux, &lt; 5%"></label>
+</p>
+<table id="instances">
+<thead><tr><th>Instance Type</th><th>OS</th><th>Interruption Rate</th><th>Savings</th><th>Spot Price</th><th>vCPUs</th><th>Memory (GB)</th></tr></thead>
+<tbody>{rows}</tbody>
+</table>
+<p>{count} rows</p>
+<script>{SCRIPT}</script>
+</body>
+</html>
+"#,
+        region = escape(region),
+        count = entries.len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("m5.large"), "m5.large");
+        assert_eq!(
+            escape(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let dataset = Dataset {
+            instance_data: HashMap::new(),
+            regions: BTreeSet::from(["eu-west-1".to_string(), "us-east-1".to_string()]),
+        };
+        let entries = vec![InstanceEntry {
+            instance_type: "m6i.large".to_string(),
+            os: "Linux".to_string(),
+            interruption_rate: "< 5%".to_string(),
+            savings: Some(70.0),
+            spot_price: Some(0.04),
+            vcpus: Some(2.0),
+            memory_gb: None,
+        }];
+
+        let html = render(&dataset, "us-east-1", &entries);
+        assert!(html.contains("<h1>Spot Instances in us-east-1</h1>"));
+        assert!(html.contains(r#"<option value="us-east-1" selected>"#));
+        assert!(html.contains(r#"<option value="eu-west-1">"#));
+        assert!(
+            html.contains(r#"<tr><td>m6i.large</td><td>Linux</td><td data-value="0">&lt; 5%</td>"#)
+        );
+        assert!(html.contains(r#"<td class="number" data-value="0.04">$0.0400</td>"#));
+        // Missing values sort last and show as N/A
+        assert!(html.contains(r#"<td class="number" data-value="NaN">N/A</td>"#));
+        assert!(html.contains("<p>1 rows</p>"));
+    }
+}
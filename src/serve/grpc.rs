@@ -0,0 +1,297 @@
+use super::{
+    ApiError, AppState, Dataset, InstanceEntry, InstanceQuery, bearer_matches, instance_entry,
+    parse_os, query_instances,
+};
+use crate::cli::{self, DEFAULT_RISK_PENALTIES};
+use crate::recommend::{self, Constraints};
+use anyhow::{Context, Result};
+use axum::http::StatusCode;
+use std::net::SocketAddr;
+use std::time::Instant;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("spotter.v1");
+}
+
+use proto::spot_service_server::{SpotService, SpotServiceServer};
+
+/// Number of recommendations returned when the request doesn't limit them
+const DEFAULT_RECOMMEND_LIMIT: usize = 10;
+
+impl From<ApiError> for Status {
+    fn from(error: ApiError) -> Self {
+        match error.status {
+            StatusCode::NOT_FOUND => Status::not_found(error.message),
+            StatusCode::BAD_REQUEST => Status::invalid_argument(error.message),
+            _ => Status::internal(error.message),
+        }
+    }
+}
+
+impl From<InstanceEntry> for proto::Instance {
+    fn from(entry: InstanceEntry) -> Self {
+        Self {
+            instance_type: entry.instance_type,
+            os: entry.os,
+            interruption_rate: entry.interruption_rate,
+            savings: entry.savings,
+            spot_price: entry.spot_price,
+            vcpus: entry.vcpus,
+            memory_gb: entry.memory_gb,
+        }
+    }
+}
+
+fn instances_response(entries: Vec<InstanceEntry>) -> proto::InstancesResponse {
+    proto::InstancesResponse {
+        instances: entries.into_iter().map(proto::Instance::from).collect(),
+    }
+}
+
+fn query_response(
+    dataset: &Dataset,
+    request: proto::QueryRequest,
+) -> Result<proto::InstancesResponse, Status> {
+    let query = InstanceQuery {
+        family: request.family,
+        max_interruption: request.max_interruption,
+        min_vcpus: request.min_vcpus,
+        min_memory: request.min_memory,
+        os: request.os,
+    };
+    Ok(instances_response(query_instances(
+        dataset,
+        &request.region,
+        &query,
+    )?))
+}
+
+fn recommend_response(
+    dataset: &Dataset,
+    request: proto::RecommendRequest,
+) -> Result<proto::RecommendResponse, Status> {
+    if !dataset.regions.contains(&request.region) {
+        return Err(Status::not_found(format!(
+            "Region '{}' not found",
+            request.region
+        )));
+    }
+
+    let os = parse_os(request.os.as_deref())?;
+    let risk_penalties = if request.risk_penalties.is_empty() {
+        DEFAULT_RISK_PENALTIES.to_vec()
+    } else {
+        cli::validate_risk_penalties(&request.risk_penalties)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        request.risk_penalties
+    };
+    let limit = match request.limit {
+        0 => DEFAULT_RECOMMEND_LIMIT,
+        limit => limit as usize,
+    };
+
+    let constraints = Constraints {
+        min_vcpus: request.min_vcpus,
+        min_memory_gb: request.min_memory,
+        max_interruption: request.max_interruption,
+    };
+    let os_name = os.advisor_keys()[0];
+    let recommendations = recommend::recommend(
+        &dataset.instance_data,
+        &request.region,
+        os,
+        &constraints,
+        &risk_penalties,
+    )
+    .into_iter()
+    .take(limit)
+    .map(|recommendation| proto::Recommendation {
+        instance: Some(
+            instance_entry(&recommendation.instance_type, os_name, &recommendation.info).into(),
+        ),
+        effective_cost: recommendation.effective_cost,
+    })
+    .collect();
+
+    Ok(proto::RecommendResponse { recommendations })
+}
+
+fn compare_response(
+    dataset: &Dataset,
+    request: proto::CompareRequest,
+) -> Result<proto::InstancesResponse, Status> {
+    let query = InstanceQuery {
+        os: request.os,
+        ..Default::default()
+    };
+    let entries = query_instances(dataset, &request.region, &query)?;
+
+    // Instance types are returned in the requested order
+    let mut compared = Vec::new();
+    for instance_type in &request.instance_types {
+        let matching: Vec<InstanceEntry> = entries
+            .iter()
+            .filter(|entry| &entry.instance_type == instance_type)
+            .cloned()
+            .collect();
+        if matching.is_empty() {
+            return Err(Status::not_found(format!(
+                "Instance type '{}' not found in region '{}'",
+                instance_type, request.region
+            )));
+        }
+        compared.extend(matching);
+    }
+
+    Ok(instances_response(compared))
+}
+
+/// gRPC SpotService backed by the data served over HTTP
+pub struct SpotServer {
+    state: AppState,
+}
+
+#[tonic::async_trait]
+impl SpotService for SpotServer {
+    async fn query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<proto::InstancesResponse>, Status> {
+        query_response(&self.state.dataset(), request.into_inner()).map(Response::new)
+    }
+
+    async fn recommend(
+        &self,
+        request: Request<proto::RecommendRequest>,
+    ) -> Result<Response<proto::RecommendResponse>, Status> {
+        recommend_response(&self.state.dataset(), request.into_inner()).map(Response::new)
+    }
+
+    async fn compare(
+        &self,
+        request: Request<proto::CompareRequest>,
+    ) -> Result<Response<proto::InstancesResponse>, Status> {
+        compare_response(&self.state.dataset(), request.into_inner()).map(Response::new)
+    }
+}
+
+/// Applies the rate limit and the bearer token of the HTTP API to every call
+fn guard(state: &AppState, request: Request<()>) -> Result<Request<()>, Status> {
+    if let Some(limiter) = &state.rate_limiter
+        && let Some(addr) = request.remote_addr()
+        && !limiter.check(addr.ip(), Instant::now())
+    {
+        return Err(Status::resource_exhausted(
+            "Too many requests, please retry later",
+        ));
+    }
+
+    if let Some(token) = &state.auth_token {
+        let authorized = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| bearer_matches(value, token));
+        if !authorized {
+            return Err(Status::unauthenticated("Missing or invalid bearer token"));
+        }
+    }
+
+    Ok(request)
+}
+
+pub async fn serve(addr: SocketAddr, state: AppState) -> Result<()> {
+    log::info!("Serving gRPC on {}", addr);
+
+    let interceptor_state = state.clone();
+    let service =
+        SpotServiceServer::with_interceptor(SpotServer { state }, move |request: Request<()>| {
+            guard(&interceptor_state, request)
+        });
+
+    Server::builder()
+        .add_service(service)
+        .serve(addr)
+        .await
+        .context("gRPC server failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serve::tests::dataset;
+
+    #[test]
+    fn test_query_response() {
+        let request = proto::QueryRequest {
+            region: "us-east-1".to_string(),
+            family: Some("m6i".to_string()),
+            ..Default::default()
+        };
+        let response = query_response(&dataset(), request).unwrap();
+        let instance_types: Vec<&str> = response
+            .instances
+            .iter()
+            .map(|i| i.instance_type.as_str())
+            .collect();
+        assert_eq!(instance_types, vec!["m6i.large", "m6i.xlarge"]);
+
+        let request = proto::QueryRequest {
+            region: "eu-west-1".to_string(),
+            ..Default::default()
+        };
+        let status = query_response(&dataset(), request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[test]
+    fn test_recommend_response() {
+        let request = proto::RecommendRequest {
+            region: "us-east-1".to_string(),
+            limit: 2,
+            ..Default::default()
+        };
+        let response = recommend_response(&dataset(), request).unwrap();
+        let instance_types: Vec<&str> = response
+            .recommendations
+            .iter()
+            .map(|r| r.instance.as_ref().unwrap().instance_type.as_str())
+            .collect();
+        assert_eq!(instance_types, vec!["c6i.large", "m6i.large"]);
+        assert_eq!(response.recommendations[0].effective_cost, 0.03);
+
+        let request = proto::RecommendRequest {
+            region: "us-east-1".to_string(),
+            risk_penalties: vec![1.0],
+            ..Default::default()
+        };
+        let status = recommend_response(&dataset(), request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_compare_response() {
+        let request = proto::CompareRequest {
+            region: "us-east-1".to_string(),
+            instance_types: vec!["m6i.xlarge".to_string(), "c6i.large".to_string()],
+            os: None,
+        };
+        let response = compare_response(&dataset(), request).unwrap();
+        let instance_types: Vec<&str> = response
+            .instances
+            .iter()
+            .map(|i| i.instance_type.as_str())
+            .collect();
+        assert_eq!(instance_types, vec!["m6i.xlarge", "c6i.large"]);
+
+        let request = proto::CompareRequest {
+            region: "us-east-1".to_string(),
+            instance_types: vec!["x1.large".to_string()],
+            os: None,
+        };
+        let status = compare_response(&dataset(), request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+}
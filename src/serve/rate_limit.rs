@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Length of the window requests are counted in
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Number of tracked clients above which expired windows are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Fixed-window rate limiter allowing each client IP a number of requests per window
+pub struct RateLimiter {
+    limit: u32,
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a request of the client, returning false when it exceeds the limit of the window
+    pub fn check(&self, ip: IpAddr, now: Instant) -> bool {
+        let mut windows = self.windows.lock().unwrap();
+
+        if windows.len() > PRUNE_THRESHOLD {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < RATE_LIMIT_WINDOW);
+        }
+
+        let (start, count) = windows.entry(ip).or_insert((now, 0));
+        if now.duration_since(*start) >= RATE_LIMIT_WINDOW {
+            *start = now;
+            *count = 0;
+        }
+
+        *count += 1;
+        *count <= self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(2);
+        let client: IpAddr = "192.0.2.1".parse().unwrap();
+        let other: IpAddr = "192.0.2.2".parse().unwrap();
+        let now = Instant::now();
+
+        assert!(limiter.check(client, now));
+        assert!(limiter.check(client, now + Duration::from_secs(1)));
+        assert!(!limiter.check(client, now + Duration::from_secs(2)));
+
+        // Clients are limited separately
+        assert!(limiter.check(other, now + Duration::from_secs(2)));
+
+        // The count resets with the next window
+        assert!(limiter.check(client, now + RATE_LIMIT_WINDOW));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::cli::DEFAULT_HOURS_PER_MONTH;
+
+/// Assumed monthly interruption probability of each interruption rate bucket, the midpoint of
+/// the bucket and 25% for the open-ended "> 20%" bucket
+pub const INTERRUPTION_PROBABILITIES: [f64; 5] = [0.025, 0.075, 0.125, 0.175, 0.25];
+
+/// Batch workload to simulate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Workload {
+    /// Uninterrupted runtime in hours
+    pub runtime_hours: f64,
+    /// Work lost per interruption since the last checkpoint in hours
+    pub checkpoint_loss_hours: f64,
+}
+
+/// Expected outcome of running a workload on spot
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulation {
+    /// Assumed probability of an interruption within a month
+    pub monthly_interruption_probability: f64,
+    /// Expected number of interruptions before the workload completes
+    pub expected_interruptions: f64,
+    /// Expected wall-clock hours including the rework after interruptions
+    pub wall_clock_hours: f64,
+    /// Expected spot cost including the rework
+    pub spot_cost: f64,
+    /// On-demand cost of running the workload without interruptions
+    pub on_demand_cost: Option<f64>,
+}
+
+/// Simulates a workload on spot, where each interruption loses up to the checkpoint loss of work.
+///
+/// Interruptions are modeled as a Poisson process with the hourly rate derived from the monthly
+/// interruption probability of the bucket, so the expected wall-clock time `W` satisfies
+/// `W = runtime + rate * W * loss`. Returns `None` when the rework outpaces the progress and the
+/// workload is expected never to complete.
+pub fn simulate(
+    workload: &Workload,
+    interruption_bucket: usize,
+    spot_price: f64,
+    on_demand_price: Option<f64>,
+) -> Option<Simulation> {
+    let probability = *INTERRUPTION_PROBABILITIES.get(interruption_bucket)?;
+    let hourly_rate = -(1.0 - probability).ln() / DEFAULT_HOURS_PER_MONTH;
+
+    let runtime_hours = workload.runtime_hours;
+    // Work lost per interruption can't exceed the runtime itself
+    let loss = workload.checkpoint_loss_hours.min(runtime_hours);
+    let rework_ratio = hourly_rate * loss;
+    if rework_ratio >= 1.0 {
+        return None;
+    }
+
+    let wall_clock_hours = runtime_hours / (1.0 - rework_ratio);
+
+    Some(Simulation {
+        monthly_interruption_probability: probability,
+        expected_interruptions: hourly_rate * wall_clock_hours,
+        wall_clock_hours,
+        spot_cost: wall_clock_hours * spot_price,
+        on_demand_cost: on_demand_price.map(|price| runtime_hours * price),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workload(runtime_hours: f64, checkpoint_loss_hours: f64) -> Workload {
+        Workload {
+            runtime_hours,
+            checkpoint_loss_hours,
+        }
+    }
+
+    #[test]
+    fn test_simulate_without_loss() {
+        // Interruptions without any lost work don't add wall-clock time
+        let simulation = simulate(&workload(12.0, 0.0), 0, 10.0, Some(30.0)).unwrap();
+        assert_eq!(simulation.wall_clock_hours, 12.0);
+        assert_eq!(simulation.spot_cost, 120.0);
+        assert_eq!(simulation.on_demand_cost, Some(360.0));
+        assert!(simulation.expected_interruptions > 0.0);
+    }
+
+    #[test]
+    fn test_simulate_with_loss() {
+        let low = simulate(&workload(12.0, 0.5), 0, 10.0, None).unwrap();
+        let high = simulate(&workload(12.0, 0.5), 4, 10.0, None).unwrap();
+
+        assert!(low.wall_clock_hours > 12.0);
+        assert!(high.wall_clock_hours > low.wall_clock_hours);
+        assert!(high.expected_interruptions > low.expected_interruptions);
+        assert_eq!(high.monthly_interruption_probability, 0.25);
+        assert_eq!(high.on_demand_cost, None);
+
+        // The loss is capped at the runtime
+        assert_eq!(
+            simulate(&workload(1.0, 5.0), 4, 10.0, None),
+            simulate(&workload(1.0, 1.0), 4, 10.0, None)
+        );
+    }
+
+    #[test]
+    fn test_simulate_never_completes() {
+        // An hourly rate of about 1/2500 with 5000 hours lost per interruption never completes
+        assert_eq!(simulate(&workload(5000.0, 5000.0), 4, 10.0, None), None);
+
+        // Unknown buckets can't be simulated
+        assert_eq!(simulate(&workload(12.0, 0.5), 5, 10.0, None), None);
+    }
+}
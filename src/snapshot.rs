@@ -0,0 +1,135 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the snapshot file format
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Point-in-time copy of the spot advisor data and the spot price data
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    /// Unix time (seconds) the snapshot was taken at
+    pub created_at: u64,
+    pub advisor_data: Value,
+    pub price_data: Value,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of the data as of now
+    pub fn new(advisor_data: Value, price_data: Value) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            version: SNAPSHOT_VERSION,
+            created_at,
+            advisor_data,
+            price_data,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string(self)?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write snapshot '{}'", path.display()))?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read snapshot '{}'", path.display()))?;
+        let snapshot: Snapshot = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid snapshot '{}'", path.display()))?;
+
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(anyhow!(
+                "Unsupported snapshot version {} in '{}' (expected {})",
+                snapshot.version,
+                path.display(),
+                SNAPSHOT_VERSION
+            ));
+        }
+        if !snapshot.advisor_data["spot_advisor"].is_object() {
+            return Err(anyhow!(
+                "Invalid snapshot '{}': advisor_data has no spot_advisor object",
+                path.display()
+            ));
+        }
+        if !snapshot.price_data["config"]["regions"].is_array() {
+            return Err(anyhow!(
+                "Invalid snapshot '{}': price_data has no config.regions array",
+                path.display()
+            ));
+        }
+
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("spotter-test-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_snapshot_save_load() {
+        let path = temp_path("snapshot");
+        let snapshot = Snapshot::new(
+            json!({"spot_advisor": {}}),
+            json!({"config": {"regions": []}}),
+        );
+        assert!(snapshot.created_at > 0);
+
+        snapshot.save(&path).unwrap();
+        assert_eq!(Snapshot::load(&path).unwrap(), snapshot);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_load_invalid() {
+        assert!(Snapshot::load(Path::new("/nonexistent/snapshot.json")).is_err());
+
+        let path = temp_path("snapshot-invalid");
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Snapshot::load(&path).is_err());
+
+        // Unknown versions are rejected
+        let mut snapshot = Snapshot::new(json!({}), json!({}));
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        snapshot.save(&path).unwrap();
+        assert!(Snapshot::load(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_load_malformed_data() {
+        let path = temp_path("snapshot-malformed");
+
+        std::fs::write(
+            &path,
+            r#"{"version":1,"created_at":0,"advisor_data":{},"price_data":{}}"#,
+        )
+        .unwrap();
+        let error = Snapshot::load(&path).unwrap_err().to_string();
+        assert!(error.contains("spot_advisor"));
+        assert!(error.contains(&path.display().to_string()));
+
+        let snapshot = Snapshot::new(json!({"spot_advisor": {}}), json!({"config": {}}));
+        snapshot.save(&path).unwrap();
+        let error = Snapshot::load(&path).unwrap_err().to_string();
+        assert!(error.contains("config.regions"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
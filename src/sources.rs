@@ -0,0 +1,431 @@
+use crate::aws::{self, DataSource};
+use crate::cli::{AuditTarget, Cli, Command, ExportTarget, Provider, PublishTarget};
+use crate::history;
+use crate::provider::{azure, gcp};
+use crate::region;
+
+/// Auth of the AWS SDK calls, signed with the credentials of --aws-profile or --role-arn
+const AWS_CREDENTIALS: &str = "AWS credentials (SigV4)";
+
+const ON_DEMAND_PRICE_DATA: DataSource = DataSource {
+    name: "On-Demand Price Data (--spot-price, estimate, simulate)",
+    url: aws::ON_DEMAND_PRICE_DATA_URL,
+    auth: "None (public)",
+    cache_file: None,
+};
+
+const INSTANCE_METADATA: DataSource = DataSource {
+    name: "EC2 Instance Metadata (region detection without --region)",
+    url: region::IMDS_REGION_URL,
+    auth: "IMDSv2 session token",
+    cache_file: None,
+};
+
+const STS_ASSUME_ROLE: DataSource = DataSource {
+    name: "STS AssumeRole (--role-arn)",
+    url: "https://sts.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const EC2_SPOT_PRICE_HISTORY: DataSource = DataSource {
+    name: "EC2 DescribeSpotPriceHistory (--live, audit, when)",
+    url: "https://ec2.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const EC2_INSTANCE_TYPES: DataSource = DataSource {
+    name: "EC2 DescribeInstanceTypes (--specs, gpu, export skypilot)",
+    url: "https://ec2.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const EC2_PLACEMENT_SCORES: DataSource = DataSource {
+    name: "EC2 GetSpotPlacementScores (score)",
+    url: "https://ec2.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const EC2_INSTANCES: DataSource = DataSource {
+    name: "EC2 DescribeInstances and DescribeLaunchTemplateVersions (audit)",
+    url: "https://ec2.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const AUTO_SCALING: DataSource = DataSource {
+    name: "Auto Scaling DescribeAutoScalingGroups (audit asg)",
+    url: "https://autoscaling.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const COST_EXPLORER: DataSource = DataSource {
+    name: "Cost Explorer GetCostAndUsage (spend)",
+    url: "https://ce.us-east-1.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const COMPUTE_OPTIMIZER: DataSource = DataSource {
+    name: "Compute Optimizer GetEC2InstanceRecommendations (recommend --from-compute-optimizer)",
+    url: "https://compute-optimizer.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const CLOUDWATCH: DataSource = DataSource {
+    name: "CloudWatch PutMetricData (publish cloudwatch)",
+    url: "https://monitoring.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const SNS: DataSource = DataSource {
+    name: "SNS Publish (alert --sns-topic)",
+    url: "https://sns.<region>.amazonaws.com",
+    auth: AWS_CREDENTIALS,
+    cache_file: None,
+};
+
+const SLACK_WEBHOOK: DataSource = DataSource {
+    name: "Slack incoming webhook (alert --slack-webhook)",
+    url: "<--slack-webhook>",
+    auth: "Secret webhook URL",
+    cache_file: None,
+};
+
+const WEBHOOK: DataSource = DataSource {
+    name: "Webhook (alert --webhook)",
+    url: "<--webhook>",
+    auth: "None",
+    cache_file: None,
+};
+
+const SMTP: DataSource = DataSource {
+    name: "SMTP server (alert --smtp-host)",
+    url: "smtp://<--smtp-host>:<--smtp-port>",
+    auth: "SMTP username and password (--smtp-username)",
+    cache_file: None,
+};
+
+const DATADOG_API: DataSource = DataSource {
+    name: "Datadog Metrics API (publish datadog --api-key)",
+    url: "https://api.<--site>/api/v2/series",
+    auth: "Datadog API key (--api-key)",
+    cache_file: None,
+};
+
+const DOGSTATSD: DataSource = DataSource {
+    name: "DogStatsD agent (publish datadog)",
+    url: "udp://<--statsd>",
+    auth: "None",
+    cache_file: None,
+};
+
+const KUBERNETES: DataSource = DataSource {
+    name: "Kubernetes API (k8s)",
+    url: "<server of the kubeconfig context>",
+    auth: "Kubeconfig credentials",
+    cache_file: None,
+};
+
+const POSTGRES: DataSource = DataSource {
+    name: "PostgreSQL history database (record, history, chart, when --db)",
+    url: "<--db>",
+    auth: "Credentials of the --db URL",
+    cache_file: None,
+};
+
+/// APIs of the commands and their flags, listed by `spotter sources` since it runs none of them
+const COMMAND_SOURCES: [DataSource; 17] = [
+    ON_DEMAND_PRICE_DATA,
+    EC2_SPOT_PRICE_HISTORY,
+    EC2_INSTANCE_TYPES,
+    EC2_PLACEMENT_SCORES,
+    EC2_INSTANCES,
+    AUTO_SCALING,
+    COST_EXPLORER,
+    COMPUTE_OPTIMIZER,
+    CLOUDWATCH,
+    SNS,
+    SLACK_WEBHOOK,
+    WEBHOOK,
+    SMTP,
+    DATADOG_API,
+    DOGSTATSD,
+    KUBERNETES,
+    POSTGRES,
+];
+
+/// Returns the feeds of the spot data of a cloud
+fn provider_sources(provider: Provider) -> Vec<DataSource> {
+    match provider {
+        Provider::Aws => aws::data_sources(),
+        Provider::Gcp => vec![gcp::DATA_SOURCE],
+        Provider::Azure => vec![azure::DATA_SOURCE],
+    }
+}
+
+/// Returns the data sources of a command beyond the spot data of --provider
+fn command_sources(command: &Command) -> Vec<DataSource> {
+    match command {
+        Command::Sources => COMMAND_SOURCES.to_vec(),
+        Command::Estimate { .. } | Command::Simulate { .. } => vec![ON_DEMAND_PRICE_DATA],
+        Command::Spend => vec![COST_EXPLORER],
+        Command::Score { .. } => vec![EC2_PLACEMENT_SCORES],
+        Command::Audit { target, .. } => match target {
+            Some(AuditTarget::Asg { .. }) => vec![AUTO_SCALING, EC2_INSTANCES],
+            None => vec![EC2_INSTANCES, EC2_SPOT_PRICE_HISTORY],
+        },
+        Command::Export {
+            target: ExportTarget::Skypilot {
+                gpus, gpu_filter, ..
+            },
+        } if gpus.is_some() || !gpu_filter.is_empty() => vec![EC2_INSTANCE_TYPES],
+        Command::K8s { .. } => vec![KUBERNETES],
+        Command::Record { db, .. }
+        | Command::History { db, .. }
+        | Command::Chart { db, .. }
+        | Command::When { db: Some(db), .. } => match history::Backend::parse(db) {
+            history::Backend::Postgres(_) => vec![POSTGRES],
+            _ => Vec::new(),
+        },
+        Command::When { db: None, .. } => vec![EC2_SPOT_PRICE_HISTORY],
+        Command::Gpu { .. } => vec![EC2_INSTANCE_TYPES],
+        // Every cloud is compared
+        Command::CompareClouds { .. } => [Provider::Aws, Provider::Gcp, Provider::Azure]
+            .into_iter()
+            .flat_map(provider_sources)
+            .collect(),
+        Command::Recommend {
+            from_compute_optimizer: true,
+            ..
+        } => vec![COMPUTE_OPTIMIZER],
+        Command::Alert {
+            rules_file,
+            slack_webhook,
+            webhook,
+            sns_topic,
+            smtp,
+            ..
+        } => {
+            // A rules file can configure any notifier
+            let any = rules_file.is_some();
+            [
+                (slack_webhook.is_some(), SLACK_WEBHOOK),
+                (webhook.is_some(), WEBHOOK),
+                (sns_topic.is_some(), SNS),
+                (smtp.smtp_host.is_some(), SMTP),
+            ]
+            .into_iter()
+            .filter(|(used, _)| any || *used)
+            .map(|(_, source)| source)
+            .collect()
+        }
+        Command::Publish { target } => match target {
+            PublishTarget::Cloudwatch { .. } => vec![CLOUDWATCH],
+            PublishTarget::Datadog {
+                api_key: Some(_), ..
+            } => vec![DATADOG_API],
+            PublishTarget::Datadog { api_key: None, .. } => vec![DOGSTATSD],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Returns every upstream data source the command line reaches out to: the spot data of
+/// --provider, unless --from-snapshot replaces it, and the APIs of its flags and command
+pub fn data_sources(cli: &Cli) -> Vec<DataSource> {
+    let mut sources = Vec::new();
+    if cli.from_snapshot.is_none() {
+        sources.extend(provider_sources(cli.provider));
+    }
+    if cli.provider == Provider::Aws {
+        if cli.region.is_none() {
+            sources.push(INSTANCE_METADATA);
+        }
+        if cli.spot_price {
+            sources.push(ON_DEMAND_PRICE_DATA);
+        }
+        if cli.live {
+            sources.push(EC2_SPOT_PRICE_HISTORY);
+        }
+        if cli.specs {
+            sources.push(EC2_INSTANCE_TYPES);
+        }
+    }
+    if let Some(command) = &cli.command {
+        for source in command_sources(command) {
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+    }
+    if cli.aws.role_arn.is_some() && sources.iter().any(|source| source.auth == AWS_CREDENTIALS) {
+        sources.push(STS_ASSUME_ROLE);
+    }
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn urls(args: &[&str]) -> Vec<&'static str> {
+        let cli = Cli::parse_from(args);
+        data_sources(&cli).iter().map(|source| source.url).collect()
+    }
+
+    fn names(args: &[&str]) -> Vec<&'static str> {
+        let cli = Cli::parse_from(args);
+        data_sources(&cli)
+            .iter()
+            .map(|source| source.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_data_sources_of_flags() {
+        assert_eq!(
+            urls(&["spotter"]),
+            vec![
+                aws::SPOT_ADVISOR_DATA_URL,
+                aws::SPOT_PRICE_DATA_URL,
+                region::IMDS_REGION_URL,
+            ]
+        );
+        assert_eq!(
+            urls(&["spotter", "--region", "us-east-1", "--spot-price"]),
+            vec![
+                aws::SPOT_ADVISOR_DATA_URL,
+                aws::SPOT_PRICE_DATA_URL,
+                aws::ON_DEMAND_PRICE_DATA_URL,
+            ]
+        );
+        assert_eq!(
+            urls(&["spotter", "--provider", "gcp", "--spot-price"]),
+            vec![gcp::SKUS_URL]
+        );
+        assert_eq!(
+            urls(&["spotter", "--from-snapshot", "snap.json", "-r", "us-east-1"]),
+            Vec::<&str>::new()
+        );
+
+        let cli = Cli::parse_from(["spotter", "-r", "us-east-1", "--live", "--specs"]);
+        let sources = data_sources(&cli);
+        assert_eq!(sources[2..], [EC2_SPOT_PRICE_HISTORY, EC2_INSTANCE_TYPES]);
+        assert!(
+            sources[2..]
+                .iter()
+                .all(|source| source.auth == AWS_CREDENTIALS)
+        );
+    }
+
+    #[test]
+    fn test_data_sources_of_commands() {
+        assert!(names(&["spotter", "-r", "us-east-1", "spend"]).contains(&COST_EXPLORER.name));
+        assert!(
+            names(&["spotter", "-r", "us-east-1", "audit", "asg", "web"])
+                .contains(&AUTO_SCALING.name)
+        );
+        assert!(
+            names(&["spotter", "-r", "us-east-1", "when", "-i", "p4d.24xlarge"])
+                .contains(&EC2_SPOT_PRICE_HISTORY.name)
+        );
+        assert!(
+            names(&[
+                "spotter",
+                "-r",
+                "us-east-1",
+                "estimate",
+                "--spec",
+                "2x m5.large"
+            ])
+            .contains(&ON_DEMAND_PRICE_DATA.name)
+        );
+        // The on-demand prices are listed once with --spot-price
+        let estimate = names(&[
+            "spotter",
+            "-r",
+            "us-east-1",
+            "--spot-price",
+            "estimate",
+            "--spec",
+            "2x m5.large",
+        ]);
+        assert_eq!(estimate.len(), 3);
+
+        assert!(
+            names(&[
+                "spotter",
+                "-r",
+                "us-east-1",
+                "history",
+                "-i",
+                "m5.xlarge",
+                "--db",
+                "postgres://localhost/spot"
+            ])
+            .contains(&POSTGRES.name)
+        );
+        assert!(
+            !names(&["spotter", "-r", "us-east-1", "history", "-i", "m5.xlarge"])
+                .contains(&POSTGRES.name)
+        );
+
+        assert!(
+            names(&["spotter", "-r", "us-east-1", "publish", "cloudwatch"])
+                .contains(&CLOUDWATCH.name)
+        );
+
+        assert!(
+            names(&[
+                "spotter",
+                "-r",
+                "us-east-1",
+                "recommend",
+                "--from-compute-optimizer"
+            ])
+            .contains(&COMPUTE_OPTIMIZER.name)
+        );
+
+        let alert = names(&[
+            "spotter",
+            "-r",
+            "us-east-1",
+            "alert",
+            "--rule",
+            "m5.xlarge@us-east-1 price>0.1",
+            "--sns-topic",
+            "arn:aws:sns:us-east-1:123456789012:alerts",
+        ]);
+        assert!(alert.contains(&SNS.name));
+        assert!(!alert.contains(&SLACK_WEBHOOK.name));
+        assert!(!alert.contains(&STS_ASSUME_ROLE.name));
+
+        let alert = names(&[
+            "spotter",
+            "-r",
+            "us-east-1",
+            "--role-arn",
+            "arn:aws:iam::123456789012:role/SpotterReadOnly",
+            "alert",
+            "--rules",
+            "alerts.yaml",
+        ]);
+        for source in [SLACK_WEBHOOK, WEBHOOK, SNS, SMTP, STS_ASSUME_ROLE] {
+            assert!(alert.contains(&source.name));
+        }
+
+        // The inventory lists the APIs of every command
+        let sources = names(&["spotter", "sources"]);
+        for source in COMMAND_SOURCES {
+            assert!(sources.contains(&source.name));
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use crate::cli::AwsArgs;
+use anyhow::{Context, Result};
+use aws_sdk_ec2::types::{InstanceType, InstanceTypeInfo};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Instance types per DescribeInstanceTypes request, the API limit
+const INSTANCE_TYPES_PER_REQUEST: usize = 100;
+
+/// Specs of an instance type missing from the public advisor data
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InstanceSpecs {
+    /// Supported architectures (e.g., "x86_64", "arm64")
+    pub architectures: Vec<String>,
+    /// Network performance (e.g., "Up to 10 Gigabit")
+    pub network_performance: Option<String>,
+    /// Baseline EBS bandwidth in Mbps
+    pub ebs_baseline_mbps: Option<i32>,
+    /// GPU devices, like 8 NVIDIA A100s
+    pub gpus: Vec<Gpu>,
+    /// Hypervisor, none on bare metal
+    pub hypervisor: Option<String>,
+}
+
+/// GPU devices of an instance type of the same model
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gpu {
+    pub manufacturer: Option<String>,
+    /// Model like "A100"
+    pub name: Option<String>,
+    pub count: u32,
+    /// Memory of each device in MiB
+    pub memory_mib: Option<u32>,
+}
+
+impl Gpu {
+    /// Returns whether the GPU is of a model like "a10g", ignoring case
+    pub fn is_model(&self, model: &str) -> bool {
+        self.name
+            .as_deref()
+            .is_some_and(|name| name.eq_ignore_ascii_case(model))
+    }
+}
+
+/// Formats GPU devices like "8x NVIDIA A100 (40 GiB)"
+impl fmt::Display for Gpu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x", self.count)?;
+        for part in [&self.manufacturer, &self.name].into_iter().flatten() {
+            write!(f, " {}", part)?;
+        }
+        if let Some(memory_mib) = self.memory_mib {
+            write!(f, " ({} GiB)", memory_mib / 1024)?;
+        }
+        Ok(())
+    }
+}
+
+impl InstanceSpecs {
+    /// Headers of the spec columns
+    pub const HEADERS: [&'static str; 5] = [
+        "Architecture",
+        "Network",
+        "EBS Bandwidth",
+        "GPUs",
+        "Hypervisor",
+    ];
+
+    /// Returns the cells of the spec columns
+    pub fn cells(&self) -> [String; 5] {
+        let or_dash = |value: String| {
+            if value.is_empty() {
+                "-".to_string()
+            } else {
+                value
+            }
+        };
+        [
+            or_dash(self.architectures.join(", ")),
+            or_dash(self.network_performance.clone().unwrap_or_default()),
+            self.ebs_baseline_mbps
+                .map_or_else(|| "-".to_string(), |mbps| format!("{} Mbps", mbps)),
+            or_dash(
+                self.gpus
+                    .iter()
+                    .map(Gpu::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            self.hypervisor
+                .clone()
+                .unwrap_or_else(|| "none (bare metal)".to_string()),
+        ]
+    }
+
+    /// Returns the number of GPUs of a model like "a10g", ignoring case
+    pub fn gpu_count(&self, model: &str) -> u32 {
+        self.gpus
+            .iter()
+            .filter(|gpu| gpu.is_model(model))
+            .map(|gpu| gpu.count)
+            .sum()
+    }
+}
+
+/// Returns the specs of an instance type from DescribeInstanceTypes
+fn instance_specs(info: &InstanceTypeInfo) -> InstanceSpecs {
+    InstanceSpecs {
+        architectures: info
+            .processor_info()
+            .map(|processor| {
+                processor
+                    .supported_architectures()
+                    .iter()
+                    .map(|arch| arch.as_str().to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        network_performance: info
+            .network_info()
+            .and_then(|network| network.network_performance())
+            .map(str::to_string),
+        ebs_baseline_mbps: info
+            .ebs_info()
+            .and_then(|ebs| ebs.ebs_optimized_info())
+            .and_then(|ebs| ebs.baseline_bandwidth_in_mbps()),
+        gpus: info
+            .gpu_info()
+            .map(|gpu_info| {
+                gpu_info
+                    .gpus()
+                    .iter()
+                    .map(|gpu| Gpu {
+                        manufacturer: gpu.manufacturer().map(str::to_string),
+                        name: gpu.name().map(str::to_string),
+                        count: gpu
+                            .count()
+                            .and_then(|count| u32::try_from(count).ok())
+                            .unwrap_or(1),
+                        memory_mib: gpu
+                            .memory_info()
+                            .and_then(|memory| memory.size_in_mib())
+                            .and_then(|memory_mib| u32::try_from(memory_mib).ok()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        hypervisor: info
+            .hypervisor()
+            .map(|hypervisor| hypervisor.as_str().to_string()),
+    }
+}
+
+/// Fetches the specs of the instance types (every instance type when empty) with
+/// DescribeInstanceTypes, using the credentials of the AWS environment
+pub async fn fetch_specs(
+    aws: &AwsArgs,
+    region: &str,
+    instance_types: &[String],
+) -> Result<HashMap<String, InstanceSpecs>> {
+    let config = aws.sdk_config(region).await;
+    let client = aws_sdk_ec2::Client::new(&config);
+
+    let batches: Vec<&[String]> = if instance_types.is_empty() {
+        vec![&[]]
+    } else {
+        instance_types.chunks(INSTANCE_TYPES_PER_REQUEST).collect()
+    };
+    let mut specs = HashMap::new();
+    for batch in batches {
+        let infos = client
+            .describe_instance_types()
+            .set_instance_types((!batch.is_empty()).then(|| {
+                batch
+                    .iter()
+                    .map(|t| InstanceType::from(t.as_str()))
+                    .collect()
+            }))
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to describe the instance types in {} (are AWS credentials configured?)",
+                    region
+                )
+            })?;
+        for info in infos {
+            if let Some(instance_type) = info.instance_type() {
+                specs.insert(instance_type.as_str().to_string(), instance_specs(&info));
+            }
+        }
+    }
+    Ok(specs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_ec2::types::{
+        ArchitectureType, GpuDeviceInfo, GpuDeviceMemoryInfo, GpuInfo, InstanceTypeHypervisor,
+        NetworkInfo, ProcessorInfo,
+    };
+
+    #[test]
+    fn test_format_gpu() {
+        let gpu = Gpu {
+            manufacturer: Some("NVIDIA".to_string()),
+            name: Some("A100".to_string()),
+            count: 8,
+            memory_mib: Some(40960),
+        };
+        assert_eq!(gpu.to_string(), "8x NVIDIA A100 (40 GiB)");
+        let gpu = Gpu {
+            name: Some("T4".to_string()),
+            count: 1,
+            ..Default::default()
+        };
+        assert_eq!(gpu.to_string(), "1x T4");
+    }
+
+    #[test]
+    fn test_gpu_count() {
+        let specs = InstanceSpecs {
+            gpus: vec![Gpu {
+                name: Some("A10G".to_string()),
+                count: 4,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(specs.gpu_count("a10g"), 4);
+        assert_eq!(specs.gpu_count("t4"), 0);
+    }
+
+    #[test]
+    fn test_instance_specs() {
+        let info = InstanceTypeInfo::builder()
+            .instance_type(InstanceType::from("g4dn.xlarge"))
+            .processor_info(
+                ProcessorInfo::builder()
+                    .supported_architectures(ArchitectureType::X8664)
+                    .build(),
+            )
+            .network_info(
+                NetworkInfo::builder()
+                    .network_performance("Up to 25 Gigabit")
+                    .build(),
+            )
+            .gpu_info(
+                GpuInfo::builder()
+                    .gpus(
+                        GpuDeviceInfo::builder()
+                            .count(1)
+                            .manufacturer("NVIDIA")
+                            .name("T4")
+                            .memory_info(GpuDeviceMemoryInfo::builder().size_in_mib(16384).build())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .hypervisor(InstanceTypeHypervisor::Nitro)
+            .build();
+
+        let specs = instance_specs(&info);
+        assert_eq!(
+            specs.cells(),
+            [
+                "x86_64".to_string(),
+                "Up to 25 Gigabit".to_string(),
+                "-".to_string(),
+                "1x NVIDIA T4 (16 GiB)".to_string(),
+                "nitro".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cells_of_bare_metal() {
+        let cells = InstanceSpecs::default().cells();
+        assert_eq!(cells[0], "-");
+        assert_eq!(cells[4], "none (bare metal)");
+    }
+}
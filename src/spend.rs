@@ -0,0 +1,238 @@
+use crate::cli::{AwsArgs, Os};
+use crate::display::{self, InstanceData};
+use anyhow::{Context, Result};
+use aws_sdk_costexplorer::types::{
+    DateInterval, Dimension, DimensionValues, Expression, Granularity, Group, GroupDefinition,
+    GroupDefinitionType,
+};
+
+/// Region of the Cost Explorer API endpoint
+const COST_EXPLORER_REGION: &str = "us-east-1";
+
+/// Service name of EC2 instances in Cost Explorer
+const EC2_SERVICE: &str = "Amazon Elastic Compute Cloud - Compute";
+
+/// Purchase type of on-demand instances in Cost Explorer, the spend that could move to spot
+const ON_DEMAND_PURCHASE_TYPE: &str = "On Demand Instances";
+
+/// On-demand EC2 spend of an instance type in a region
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spend {
+    pub instance_type: String,
+    pub region: String,
+    /// Unblended cost in USD
+    pub amount: f64,
+}
+
+/// Spend of an instance type with its potential savings on spot from the advisor data
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpendOverlay {
+    pub spend: Spend,
+    pub interruption_rate: String,
+    /// Savings over on-demand (%)
+    pub savings: Option<f64>,
+    /// Savings in USD had the spend run on spot
+    pub potential_savings: Option<f64>,
+}
+
+/// Returns the first days of last month and this month ("YYYY-MM-DD") of a Unix time (seconds),
+/// the period of last month in Cost Explorer with an exclusive end
+fn last_month(now: i64) -> (String, String) {
+    let (year, month, ..) = display::civil_datetime(now);
+    let (last_year, last_month) = if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    };
+    (
+        format!("{:04}-{:02}-01", last_year, last_month),
+        format!("{:04}-{:02}-01", year, month),
+    )
+}
+
+/// Returns the spend of a Cost Explorer group keyed by instance type and region
+fn group_spend(group: &Group) -> Option<Spend> {
+    let [instance_type, region] = group.keys() else {
+        return None;
+    };
+    let amount: f64 = group
+        .metrics()?
+        .get("UnblendedCost")?
+        .amount()?
+        .parse()
+        .ok()?;
+    // Costs without an instance type, like data transfer, can't move to spot
+    if instance_type == "NoInstanceType" || amount < 0.01 {
+        return None;
+    }
+    Some(Spend {
+        instance_type: instance_type.clone(),
+        region: region.clone(),
+        amount,
+    })
+}
+
+fn dimension(key: Dimension, value: &str) -> Expression {
+    Expression::builder()
+        .dimensions(DimensionValues::builder().key(key).values(value).build())
+        .build()
+}
+
+/// Fetches last month's on-demand EC2 spend of the account per instance type and region from
+/// Cost Explorer
+pub async fn fetch_spend(aws: &AwsArgs, now: i64) -> Result<Vec<Spend>> {
+    let config = aws.sdk_config(COST_EXPLORER_REGION).await;
+    let client = aws_sdk_costexplorer::Client::new(&config);
+    let (start, end) = last_month(now);
+
+    let mut spends = Vec::new();
+    let mut next_page_token = None;
+    loop {
+        let output = client
+            .get_cost_and_usage()
+            .time_period(DateInterval::builder().start(&start).end(&end).build()?)
+            .granularity(Granularity::Monthly)
+            .metrics("UnblendedCost")
+            .filter(
+                Expression::builder()
+                    .and(dimension(Dimension::Service, EC2_SERVICE))
+                    .and(dimension(Dimension::PurchaseType, ON_DEMAND_PURCHASE_TYPE))
+                    .build(),
+            )
+            .group_by(
+                GroupDefinition::builder()
+                    .r#type(GroupDefinitionType::Dimension)
+                    .key("INSTANCE_TYPE")
+                    .build(),
+            )
+            .group_by(
+                GroupDefinition::builder()
+                    .r#type(GroupDefinitionType::Dimension)
+                    .key("REGION")
+                    .build(),
+            )
+            .set_next_page_token(next_page_token)
+            .send()
+            .await
+            .context("Failed to get the cost and usage from Cost Explorer (are AWS credentials configured?)")?;
+        spends.extend(
+            output
+                .results_by_time()
+                .iter()
+                .flat_map(|result| result.groups())
+                .filter_map(group_spend),
+        );
+        next_page_token = output.next_page_token().map(str::to_string);
+        if next_page_token.is_none() {
+            break;
+        }
+    }
+    Ok(spends)
+}
+
+/// Overlays the potential spot savings of each region's advisor data on the spend, the largest
+/// potential savings first
+pub fn overlay(
+    spends: Vec<Spend>,
+    instance_data_of: impl Fn(&str) -> InstanceData,
+    os: Os,
+) -> Vec<SpendOverlay> {
+    let os_name = os.advisor_keys()[0];
+    let mut overlays: Vec<SpendOverlay> = spends
+        .into_iter()
+        .map(|spend| {
+            let instance_data = instance_data_of(&spend.region);
+            let info = instance_data
+                .get(&(spend.instance_type.clone(), os_name.to_string()))
+                .and_then(|region_map| region_map.get(&spend.region));
+            let savings = info.and_then(|info| info.savings_value());
+            SpendOverlay {
+                interruption_rate: info
+                    .map_or_else(|| "N/A".to_string(), |info| info.interruption_rate.clone()),
+                potential_savings: savings.map(|savings| spend.amount * savings / 100.0),
+                savings,
+                spend,
+            }
+        })
+        .collect();
+
+    overlays.sort_by(|a, b| {
+        b.potential_savings
+            .unwrap_or(0.0)
+            .total_cmp(&a.potential_savings.unwrap_or(0.0))
+            .then_with(|| b.spend.amount.total_cmp(&a.spend.amount))
+    });
+    overlays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+    use aws_sdk_costexplorer::types::MetricValue;
+
+    fn spend(instance_type: &str, region: &str, amount: f64) -> Spend {
+        Spend {
+            instance_type: instance_type.to_string(),
+            region: region.to_string(),
+            amount,
+        }
+    }
+
+    fn region_data(region: &str) -> InstanceData {
+        instance_data([("m5.xlarge", "Linux", region, info("< 5%", "0.05"))])
+    }
+
+    #[test]
+    fn test_last_month() {
+        // 2025-03-15
+        assert_eq!(
+            last_month(1742040000),
+            ("2025-02-01".to_string(), "2025-03-01".to_string())
+        );
+        // 2025-01-10
+        assert_eq!(
+            last_month(1736500000),
+            ("2024-12-01".to_string(), "2025-01-01".to_string())
+        );
+    }
+
+    #[test]
+    fn test_group_spend() {
+        let group = |instance_type: &str, amount: &str| {
+            Group::builder()
+                .keys(instance_type)
+                .keys("us-east-1")
+                .metrics(
+                    "UnblendedCost",
+                    MetricValue::builder().amount(amount).unit("USD").build(),
+                )
+                .build()
+        };
+        assert_eq!(
+            group_spend(&group("m5.xlarge", "1234.5")),
+            Some(spend("m5.xlarge", "us-east-1", 1234.5))
+        );
+        assert_eq!(group_spend(&group("NoInstanceType", "10")), None);
+        assert_eq!(group_spend(&group("m5.xlarge", "0")), None);
+    }
+
+    #[test]
+    fn test_overlay() {
+        let overlays = overlay(
+            vec![
+                spend("c5.xlarge", "us-east-1", 5000.0),
+                spend("m5.xlarge", "us-east-1", 1000.0),
+            ],
+            region_data,
+            Os::Linux,
+        );
+        assert_eq!(overlays[0].spend.instance_type, "m5.xlarge");
+        assert_eq!(overlays[0].savings, Some(70.0));
+        assert!((overlays[0].potential_savings.unwrap() - 700.0).abs() < 1e-9);
+
+        // Instance types missing from the advisor data come last
+        assert_eq!(overlays[1].interruption_rate, "N/A");
+        assert_eq!(overlays[1].potential_savings, None);
+    }
+}
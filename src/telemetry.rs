@@ -0,0 +1,144 @@
+#[cfg(feature = "otel")]
+use anyhow::Result;
+#[cfg(feature = "otel")]
+use opentelemetry::trace::{Span, Tracer};
+#[cfg(feature = "otel")]
+use opentelemetry::{KeyValue, global};
+#[cfg(feature = "otel")]
+use opentelemetry_otlp::WithExportConfig;
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::{Resource, metrics::SdkMeterProvider, trace::SdkTracerProvider};
+use std::future::Future;
+use std::time::Instant;
+
+/// Name of the service, tracer and meter reported to the OTLP collector
+#[cfg(feature = "otel")]
+const INSTRUMENTATION_NAME: &str = "spotter";
+
+/// Runs a phase of the work (e.g., "parse" or "render"), logging its duration and, with the
+/// 'otel' feature, recording it as a span and a histogram
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    #[cfg(feature = "otel")]
+    let result = global::tracer(INSTRUMENTATION_NAME).in_span(name, |_| f());
+    #[cfg(not(feature = "otel"))]
+    let result = f();
+    finish(name, start);
+    result
+}
+
+/// Runs an asynchronous phase of the work (e.g., "fetch"), like `phase`
+pub async fn phase_async<T>(name: &'static str, f: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    #[cfg(feature = "otel")]
+    let mut span = global::tracer(INSTRUMENTATION_NAME).start(name);
+    let result = f.await;
+    #[cfg(feature = "otel")]
+    span.end();
+    finish(name, start);
+    result
+}
+
+fn finish(name: &'static str, start: Instant) {
+    let elapsed = start.elapsed().as_secs_f64();
+    log::debug!("Phase {} took {:.3}s", name, elapsed);
+
+    #[cfg(feature = "otel")]
+    global::meter(INSTRUMENTATION_NAME)
+        .f64_histogram("spotter.phase.duration")
+        .with_unit("s")
+        .with_description("Duration of the fetch, parse, render and store phases")
+        .build()
+        .record(elapsed, &[KeyValue::new("phase", name)]);
+}
+
+/// Export of the metrics and traces, flushed when dropped
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    providers: Option<(SdkTracerProvider, SdkMeterProvider)>,
+}
+
+/// Exports the metrics and traces to an OTLP/HTTP collector (e.g., "http://localhost:4318"),
+/// warning rather than failing when the export can't be set up
+#[cfg(feature = "otel")]
+pub fn init(endpoint: &str) -> Telemetry {
+    match providers(endpoint) {
+        Ok(providers) => {
+            log::info!("Exporting OTLP metrics and traces to {}", endpoint);
+            Telemetry {
+                providers: Some(providers),
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to set up the OTLP export: {}", e);
+            Telemetry { providers: None }
+        }
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+pub fn init(_endpoint: &str) -> Telemetry {
+    log::warn!(
+        "Exporting OTLP metrics and traces requires building spotter with the 'otel' feature"
+    );
+    Telemetry {}
+}
+
+#[cfg(feature = "otel")]
+fn providers(endpoint: &str) -> Result<(SdkTracerProvider, SdkMeterProvider)> {
+    let endpoint = endpoint.trim_end_matches('/');
+    let resource = Resource::builder()
+        .with_service_name(INSTRUMENTATION_NAME)
+        .build();
+
+    let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint))
+        .build()?;
+    let tracer_provider = SdkTracerProvider::builder()
+        .with_batch_exporter(span_exporter)
+        .with_resource(resource.clone())
+        .build();
+
+    let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/metrics", endpoint))
+        .build()?;
+    let meter_provider = SdkMeterProvider::builder()
+        .with_periodic_exporter(metric_exporter)
+        .with_resource(resource)
+        .build();
+
+    global::set_tracer_provider(tracer_provider.clone());
+    global::set_meter_provider(meter_provider.clone());
+    Ok((tracer_provider, meter_provider))
+}
+
+#[cfg(feature = "otel")]
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some((tracer_provider, meter_provider)) = self.providers.take() {
+            if let Err(e) = tracer_provider.shutdown() {
+                log::warn!("Failed to flush the traces: {}", e);
+            }
+            if let Err(e) = meter_provider.shutdown() {
+                log::warn!("Failed to flush the metrics: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase() {
+        assert_eq!(phase("test", || 42), 42);
+    }
+
+    #[tokio::test]
+    async fn test_phase_async() {
+        assert_eq!(phase_async("test", async { 42 }).await, 42);
+    }
+}
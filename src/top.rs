@@ -0,0 +1,162 @@
+use crate::cli::{Os, RankBy};
+use crate::display::{self, InstanceData, InstanceInfo};
+use std::cmp::Ordering;
+
+/// Instance type at a rank of the top view
+#[derive(Clone, Debug)]
+pub struct Ranked {
+    pub instance_type: String,
+    pub info: InstanceInfo,
+}
+
+/// Compares optional values, ordering missing values last
+fn cmp_present(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Returns the top instance types of the region matching the filter: the cheapest by spot price,
+/// the safest by interruption rate (cheapest first within a bucket), or the ones saving the most.
+/// Instance types missing the ranked value are left out.
+pub fn rank(
+    instance_data: &InstanceData,
+    region: &str,
+    os: Os,
+    filter: Option<&str>,
+    by: RankBy,
+    limit: usize,
+) -> Vec<Ranked> {
+    let os_name = os.advisor_keys()[0];
+    let price_column = os.price_column();
+
+    let mut ranked: Vec<Ranked> = instance_data
+        .iter()
+        .filter(|((name, instance_os), _)| {
+            instance_os == os_name
+                && filter.is_none_or(|filter| display::matches_instance_filter(name, filter))
+        })
+        .filter_map(|((name, _), region_map)| {
+            let info = region_map.get(region)?;
+            let ranked_value_present = match by {
+                RankBy::Price => info.spot_price_value(price_column).is_some(),
+                RankBy::Interruption => info.interruption_bucket().is_some(),
+                RankBy::Savings => info.savings_value().is_some(),
+            };
+            ranked_value_present.then(|| Ranked {
+                instance_type: name.clone(),
+                info: info.clone(),
+            })
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        let price = |r: &Ranked| r.info.spot_price_value(price_column);
+        let ordering = match by {
+            RankBy::Price => cmp_present(price(a), price(b)),
+            RankBy::Interruption => a
+                .info
+                .interruption_bucket()
+                .cmp(&b.info.interruption_bucket())
+                .then_with(|| cmp_present(price(a), price(b))),
+            // Descending savings
+            RankBy::Savings => cmp_present(b.info.savings_value(), a.info.savings_value())
+                .then_with(|| cmp_present(price(a), price(b))),
+        };
+        ordering.then_with(|| a.instance_type.cmp(&b.instance_type))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Returns how an instance type moved since the previous ranking: "▲2" when it rose two ranks,
+/// "▼1" when it fell one, "-" when it stayed, or "new" when it entered the ranking
+pub fn movement(previous: &[String], instance_type: &str, rank: usize) -> String {
+    let Some(previous_rank) = previous.iter().position(|name| name == instance_type) else {
+        return if previous.is_empty() {
+            "-".to_string()
+        } else {
+            "new".to_string()
+        };
+    };
+    match previous_rank.cmp(&rank) {
+        Ordering::Greater => format!("▲{}", previous_rank - rank),
+        Ordering::Less => format!("▼{}", rank - previous_rank),
+        Ordering::Equal => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tests::{info, instance_data};
+
+    fn sample_data() -> InstanceData {
+        instance_data([
+            ("m5.large", "Linux", "us-east-1", info("5-10%", "0.04")),
+            (
+                "c5.large",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.05").with_savings("60%"),
+            ),
+            (
+                "r5.large",
+                "Linux",
+                "us-east-1",
+                info("< 5%", "0.03").with_savings("80%"),
+            ),
+            (
+                "t3.large",
+                "Linux",
+                "us-east-1",
+                info("10-15%", "N/A").with_savings("N/A"),
+            ),
+        ])
+    }
+
+    fn names(ranked: &[Ranked]) -> Vec<&str> {
+        ranked.iter().map(|r| r.instance_type.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rank() {
+        let data = sample_data();
+        let by_price = rank(&data, "us-east-1", Os::Linux, None, RankBy::Price, 10);
+        assert_eq!(names(&by_price), vec!["r5.large", "m5.large", "c5.large"]);
+
+        let by_interruption = rank(
+            &data,
+            "us-east-1",
+            Os::Linux,
+            None,
+            RankBy::Interruption,
+            10,
+        );
+        assert_eq!(
+            names(&by_interruption),
+            vec!["r5.large", "c5.large", "m5.large", "t3.large"]
+        );
+
+        let by_savings = rank(&data, "us-east-1", Os::Linux, None, RankBy::Savings, 2);
+        assert_eq!(names(&by_savings), vec!["r5.large", "m5.large"]);
+
+        let filtered = rank(&data, "us-east-1", Os::Linux, Some("c5"), RankBy::Price, 10);
+        assert_eq!(names(&filtered), vec!["c5.large"]);
+    }
+
+    #[test]
+    fn test_movement() {
+        let previous = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert_eq!(movement(&previous, "c", 0), "▲2");
+        assert_eq!(movement(&previous, "a", 1), "▼1");
+        assert_eq!(movement(&previous, "b", 1), "-");
+        assert_eq!(movement(&previous, "d", 2), "new");
+
+        // Nothing moved on the first refresh
+        assert_eq!(movement(&[], "a", 0), "-");
+    }
+}
@@ -0,0 +1,65 @@
+use crate::aws::DataSource;
+use crate::cli::Cli;
+use crate::display;
+use crate::sources;
+use serde::Serialize;
+
+/// Build metadata printed by `spotter --version --json`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Abbreviated commit the binary was built from, missing outside a git checkout
+    pub git_sha: Option<&'static str>,
+    /// Build time in RFC 3339
+    pub build_date: String,
+    pub target: &'static str,
+    /// Upstream data sources of the command line
+    pub data_sources: Vec<DataSource>,
+}
+
+impl BuildInfo {
+    pub fn current(cli: &Cli) -> Self {
+        let git_sha = env!("SPOTTER_GIT_SHA");
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: (!git_sha.is_empty()).then_some(git_sha),
+            build_date: display::format_rfc3339(
+                env!("SPOTTER_BUILD_TIMESTAMP").parse().unwrap_or_default(),
+            ),
+            target: env!("SPOTTER_TARGET"),
+            data_sources: sources::data_sources(cli),
+        }
+    }
+
+    /// Returns the plain version line, like clap's "spotter 0.1.4"
+    pub fn text(&self) -> String {
+        format!("{} {}", env!("CARGO_PKG_NAME"), self.version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aws;
+    use clap::Parser;
+
+    #[test]
+    fn test_build_info() {
+        let cli = Cli::parse_from(["spotter", "--version", "--json"]);
+        let info = BuildInfo::current(&cli);
+        assert_eq!(
+            info.text(),
+            format!("spotter {}", env!("CARGO_PKG_VERSION"))
+        );
+        assert!(info.build_date.ends_with('Z'));
+        assert!(!info.target.is_empty());
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["data_sources"][0]["url"], aws::SPOT_ADVISOR_DATA_URL);
+        assert_eq!(
+            json["data_sources"].as_array().unwrap().len(),
+            sources::data_sources(&cli).len()
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use crate::display;
+use crate::history::HistoryRecord;
+use crate::live::AzPrice;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Names of the days of the week, from Monday
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Price (%) over the median price of the period above which a sample counts as a spike
+pub const SPIKE_THRESHOLD: f64 = 20.0;
+
+/// Number of hours of the week reported as the best
+pub const BEST_SLOTS: usize = 5;
+
+const HOUR: i64 = 3600;
+const DAY: i64 = 24 * HOUR;
+
+/// Spot price in effect at a Unix time (seconds)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceSample {
+    pub at: i64,
+    pub price: f64,
+}
+
+/// Prices of the samples falling in a day of the week, an hour of the day or an hour of the week
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotStats {
+    pub samples: usize,
+    pub avg_price: f64,
+    pub min_price: f64,
+    /// Samples priced above the spike threshold
+    pub spikes: usize,
+}
+
+impl SlotStats {
+    /// Returns the share (%) of the samples that were spikes
+    pub fn spike_rate(&self) -> f64 {
+        self.spikes as f64 / self.samples as f64 * 100.0
+    }
+}
+
+/// Spot prices of a pool grouped by day of the week and hour of the day (UTC)
+#[derive(Clone, Debug, PartialEq)]
+pub struct WhenReport {
+    pub samples: usize,
+    pub first_at: i64,
+    pub last_at: i64,
+    /// Median price of the period, which spikes are measured against
+    pub median_price: f64,
+    /// Stats of each day of the week from Monday, None for days without samples
+    pub weekdays: Vec<Option<SlotStats>>,
+    /// Stats of each hour of the day, None for hours without samples
+    pub hours: Vec<Option<SlotStats>>,
+    /// Hours of the week as (day, hour, stats), by lowest average price then fewest spikes
+    pub best_slots: Vec<(usize, usize, SlotStats)>,
+}
+
+impl WhenReport {
+    /// Returns the price above which a sample counts as a spike
+    pub fn spike_price(&self) -> f64 {
+        spike_price(self.median_price)
+    }
+
+    /// Returns the day of the week with the lowest average price
+    pub fn cheapest_weekday(&self) -> Option<usize> {
+        best(&self.weekdays, by_price)
+    }
+
+    /// Returns the day of the week with the fewest spikes, the cheaper one on ties
+    pub fn calmest_weekday(&self) -> Option<usize> {
+        best(&self.weekdays, by_spikes)
+    }
+
+    /// Returns the hour of the day with the lowest average price
+    pub fn cheapest_hour(&self) -> Option<usize> {
+        best(&self.hours, by_price)
+    }
+
+    /// Returns the hour of the day with the fewest spikes, the cheaper one on ties
+    pub fn calmest_hour(&self) -> Option<usize> {
+        best(&self.hours, by_spikes)
+    }
+}
+
+/// Returns the price above which a sample counts as a spike, given the median price
+fn spike_price(median_price: f64) -> f64 {
+    median_price * (1.0 + SPIKE_THRESHOLD / 100.0)
+}
+
+/// Orders stats by lowest average price, then fewest spikes
+fn by_price(a: &SlotStats, b: &SlotStats) -> Ordering {
+    a.avg_price
+        .total_cmp(&b.avg_price)
+        .then(a.spike_rate().total_cmp(&b.spike_rate()))
+}
+
+/// Orders stats by fewest spikes, then lowest average price
+fn by_spikes(a: &SlotStats, b: &SlotStats) -> Ordering {
+    a.spike_rate()
+        .total_cmp(&b.spike_rate())
+        .then(a.avg_price.total_cmp(&b.avg_price))
+}
+
+/// Returns the index of the best stats by an ordering, skipping slots without samples
+fn best(slots: &[Option<SlotStats>], cmp: fn(&SlotStats, &SlotStats) -> Ordering) -> Option<usize> {
+    slots
+        .iter()
+        .enumerate()
+        .filter_map(|(index, stats)| Some((index, stats.as_ref()?)))
+        .min_by(|(_, a), (_, b)| cmp(a, b))
+        .map(|(index, _)| index)
+}
+
+/// Returns the day of the week (0 for Monday) and the hour of the day of a Unix time (seconds),
+/// in UTC
+pub fn weekday_hour(secs: i64) -> (usize, usize) {
+    // 1970-01-01 was a Thursday
+    let weekday = (secs.div_euclid(DAY) + 3).rem_euclid(7);
+    let hour = secs.rem_euclid(DAY) / HOUR;
+    (weekday as usize, hour as usize)
+}
+
+/// Returns the samples of the recorded history, leaving out records without a spot price
+pub fn recorded_samples(records: &[HistoryRecord]) -> Vec<PriceSample> {
+    records
+        .iter()
+        .filter_map(|record| {
+            Some(PriceSample {
+                at: record.recorded_at,
+                price: record.spot_price?,
+            })
+        })
+        .collect()
+}
+
+/// Returns a sample per Availability Zone and hour between two Unix times (seconds) from the price
+/// changes of DescribeSpotPriceHistory, with the price in effect at the top of the hour, so that
+/// prices count for as long as they were in effect rather than once per change
+pub fn hourly_samples(changes: &[AzPrice], start: i64, end: i64) -> Vec<PriceSample> {
+    let mut zones: BTreeMap<&str, Vec<&AzPrice>> = BTreeMap::new();
+    for change in changes {
+        zones
+            .entry(&change.availability_zone)
+            .or_default()
+            .push(change);
+    }
+
+    let mut samples = Vec::new();
+    for mut zone in zones.into_values() {
+        zone.sort_by_key(|change| change.timestamp);
+        let mut next = 0;
+        let mut price = None;
+        let mut at = (start + HOUR - 1).div_euclid(HOUR) * HOUR;
+        while at < end {
+            while next < zone.len() && zone[next].timestamp <= at {
+                price = Some(zone[next].price);
+                next += 1;
+            }
+            if let Some(price) = price {
+                samples.push(PriceSample { at, price });
+            }
+            at += HOUR;
+        }
+    }
+    samples
+}
+
+/// Returns the stats of the prices of a slot, or None without prices
+fn slot_stats(prices: &[f64], spike_price: f64) -> Option<SlotStats> {
+    if prices.is_empty() {
+        return None;
+    }
+    Some(SlotStats {
+        samples: prices.len(),
+        avg_price: prices.iter().sum::<f64>() / prices.len() as f64,
+        min_price: prices.iter().copied().fold(f64::INFINITY, f64::min),
+        spikes: prices.iter().filter(|price| **price > spike_price).count(),
+    })
+}
+
+/// Groups the samples by day of the week and hour of the day, keeping the best hours of the week
+/// up to a limit, or returns None without samples
+pub fn analyze(samples: &[PriceSample], limit: usize) -> Option<WhenReport> {
+    let mut sorted: Vec<f64> = samples.iter().map(|sample| sample.price).collect();
+    sorted.sort_by(f64::total_cmp);
+    let median_price = display::median(&sorted)?;
+    let spike_price = spike_price(median_price);
+
+    let mut weekdays = vec![Vec::new(); WEEKDAYS.len()];
+    let mut hours = vec![Vec::new(); 24];
+    let mut slots: BTreeMap<(usize, usize), Vec<f64>> = BTreeMap::new();
+    for sample in samples {
+        let (weekday, hour) = weekday_hour(sample.at);
+        weekdays[weekday].push(sample.price);
+        hours[hour].push(sample.price);
+        slots.entry((weekday, hour)).or_default().push(sample.price);
+    }
+
+    let mut best_slots: Vec<(usize, usize, SlotStats)> = slots
+        .into_iter()
+        .filter_map(|((weekday, hour), prices)| {
+            Some((weekday, hour, slot_stats(&prices, spike_price)?))
+        })
+        .collect();
+    best_slots.sort_by(|(_, _, a), (_, _, b)| by_price(a, b));
+    best_slots.truncate(limit);
+
+    Some(WhenReport {
+        samples: samples.len(),
+        first_at: samples.iter().map(|sample| sample.at).min()?,
+        last_at: samples.iter().map(|sample| sample.at).max()?,
+        median_price,
+        weekdays: weekdays
+            .iter()
+            .map(|prices| slot_stats(prices, spike_price))
+            .collect(),
+        hours: hours
+            .iter()
+            .map(|prices| slot_stats(prices, spike_price))
+            .collect(),
+        best_slots,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Monday 2024-01-01 00:00 UTC
+    const MONDAY: i64 = 1_704_067_200;
+
+    fn sample(at: i64, price: f64) -> PriceSample {
+        PriceSample { at, price }
+    }
+
+    fn change(availability_zone: &str, timestamp: i64, price: f64) -> AzPrice {
+        AzPrice {
+            instance_type: "p4d.24xlarge".to_string(),
+            availability_zone: availability_zone.to_string(),
+            price,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_weekday_hour() {
+        assert_eq!(weekday_hour(0), (3, 0));
+        assert_eq!(weekday_hour(MONDAY), (0, 0));
+        assert_eq!(weekday_hour(MONDAY + 6 * DAY + 23 * HOUR + 59), (6, 23));
+        assert_eq!(weekday_hour(MONDAY - HOUR), (6, 23));
+    }
+
+    #[test]
+    fn test_recorded_samples() {
+        let record = |recorded_at: i64, spot_price: Option<f64>| HistoryRecord {
+            recorded_at,
+            region: "us-east-1".to_string(),
+            instance_type: "p4d.24xlarge".to_string(),
+            os: "Linux".to_string(),
+            interruption_rate: "5-10%".to_string(),
+            savings: Some(70.0),
+            spot_price,
+        };
+        assert_eq!(
+            recorded_samples(&[record(MONDAY, Some(10.0)), record(MONDAY + HOUR, None)]),
+            vec![sample(MONDAY, 10.0)]
+        );
+    }
+
+    #[test]
+    fn test_hourly_samples() {
+        let changes = [
+            change("us-east-1a", MONDAY + 2 * HOUR + 1800, 12.0),
+            change("us-east-1a", MONDAY - 600, 10.0),
+            change("us-east-1b", MONDAY + HOUR + 60, 9.0),
+        ];
+        assert_eq!(
+            hourly_samples(&changes, MONDAY - 60, MONDAY + 4 * HOUR),
+            vec![
+                sample(MONDAY, 10.0),
+                sample(MONDAY + HOUR, 10.0),
+                sample(MONDAY + 2 * HOUR, 10.0),
+                sample(MONDAY + 3 * HOUR, 12.0),
+                // No price of us-east-1b was in effect before 01:01
+                sample(MONDAY + 2 * HOUR, 9.0),
+                sample(MONDAY + 3 * HOUR, 9.0),
+            ]
+        );
+        assert!(hourly_samples(&[], MONDAY, MONDAY + DAY).is_empty());
+    }
+
+    #[test]
+    fn test_analyze() {
+        // Four weeks of hourly prices of 10.0, except for 8.0 on Sunday mornings and 15.0 spikes
+        // on weekday afternoons at 14:00
+        let samples: Vec<PriceSample> = (0..4 * 7 * 24)
+            .map(|i| {
+                let at = MONDAY + i * HOUR;
+                let price = match weekday_hour(at) {
+                    (6, hour) if hour < 6 => 8.0,
+                    (0..=4, 14) => 15.0,
+                    _ => 10.0,
+                };
+                sample(at, price)
+            })
+            .collect();
+
+        let report = analyze(&samples, 3).unwrap();
+        assert_eq!(report.samples, 672);
+        assert_eq!(report.first_at, MONDAY);
+        assert_eq!(report.last_at, MONDAY + 671 * HOUR);
+        assert_eq!(report.median_price, 10.0);
+        assert!((report.spike_price() - 12.0).abs() < 1e-9);
+
+        assert_eq!(report.cheapest_weekday(), Some(6));
+        assert_eq!(report.calmest_weekday(), Some(6));
+        let monday = report.weekdays[0].as_ref().unwrap();
+        assert_eq!(monday.samples, 96);
+        assert_eq!(monday.spikes, 4);
+        assert_eq!(monday.min_price, 10.0);
+
+        let afternoon = report.hours[14].as_ref().unwrap();
+        assert_eq!(afternoon.spikes, 20);
+        assert_eq!(report.cheapest_hour(), Some(0));
+        assert_eq!(report.calmest_hour(), Some(0));
+
+        assert_eq!(report.best_slots.len(), 3);
+        for (weekday, hour, stats) in &report.best_slots {
+            assert_eq!(*weekday, 6);
+            assert!(*hour < 6);
+            assert_eq!(stats.avg_price, 8.0);
+            assert_eq!(stats.spike_rate(), 0.0);
+        }
+
+        assert_eq!(analyze(&[], 3), None);
+    }
+}
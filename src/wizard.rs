@@ -0,0 +1,110 @@
+use crate::cli;
+use crate::recommend::Constraints;
+use anyhow::{Context, Result};
+use dialoguer::{Input, Select};
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Number of instance types recommended at the end of the wizard
+pub const RECOMMENDATION_LIMIT: usize = 10;
+
+/// Risk tolerances offered by the wizard, with their maximum interruption rate (%)
+const RISK_TOLERANCES: [(&str, Option<f64>); 4] = [
+    ("Low: interruption rate under 5%", Some(5.0)),
+    ("Medium: interruption rate up to 10%", Some(10.0)),
+    ("High: interruption rate up to 20%", Some(20.0)),
+    ("Any interruption rate", None),
+];
+
+/// Risk tolerance selected by default
+const DEFAULT_RISK_TOLERANCE: usize = 1;
+
+/// Returns true if spotter was launched without arguments on an interactive terminal, where
+/// the wizard is shown instead of the full table
+pub fn should_run() -> bool {
+    env::args_os().len() == 1
+        && io::stdin().is_terminal()
+        && io::stdout().is_terminal()
+        && io::stderr().is_terminal()
+}
+
+/// Returns the labels of the regions offered by the wizard, like "us-east-1 (US East (N. Virginia))"
+pub fn region_labels() -> Vec<String> {
+    cli::VALID_AWS_REGIONS
+        .iter()
+        .map(|(code, name)| format!("{} ({})", code, name))
+        .collect()
+}
+
+/// Returns the constraints of the answers, with the index of the risk tolerance
+fn constraints(vcpus: u32, memory_gb: f64, risk_tolerance: usize) -> Constraints {
+    Constraints {
+        min_vcpus: Some(f64::from(vcpus)),
+        min_memory_gb: Some(memory_gb),
+        max_interruption: RISK_TOLERANCES[risk_tolerance].1,
+    }
+}
+
+/// Asks for the region, the vCPU and memory needs and the risk tolerance step by step,
+/// returning the picked region and the constraints of the recommendations
+pub fn ask(default_region: &str) -> Result<(String, Constraints)> {
+    eprintln!("No options given, let's find spot instance types for your workload.");
+
+    let default_index = cli::VALID_AWS_REGIONS
+        .iter()
+        .position(|(code, _)| *code == default_region)
+        .unwrap_or(0);
+    let region = Select::new()
+        .with_prompt("Region")
+        .items(&region_labels())
+        .default(default_index)
+        .interact()
+        .context("Failed to ask for the region")?;
+
+    let vcpus: u32 = Input::new()
+        .with_prompt("Minimum vCPUs")
+        .default(2)
+        .interact_text()
+        .context("Failed to ask for the vCPUs")?;
+    let memory_gb: f64 = Input::new()
+        .with_prompt("Minimum memory (GiB)")
+        .default(4.0)
+        .interact_text()
+        .context("Failed to ask for the memory")?;
+
+    let risk_labels: Vec<&str> = RISK_TOLERANCES.iter().map(|(label, _)| *label).collect();
+    let risk_tolerance = Select::new()
+        .with_prompt("Risk tolerance")
+        .items(&risk_labels)
+        .default(DEFAULT_RISK_TOLERANCE)
+        .interact()
+        .context("Failed to ask for the risk tolerance")?;
+
+    Ok((
+        cli::VALID_AWS_REGIONS[region].0.to_string(),
+        constraints(vcpus, memory_gb, risk_tolerance),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_labels() {
+        let labels = region_labels();
+        assert_eq!(labels.len(), cli::VALID_AWS_REGIONS.len());
+        assert!(labels.contains(&"eu-west-1 (Europe (Ireland))".to_string()));
+    }
+
+    #[test]
+    fn test_constraints() {
+        let low = constraints(4, 16.0, 0);
+        assert_eq!(low.min_vcpus, Some(4.0));
+        assert_eq!(low.min_memory_gb, Some(16.0));
+        assert_eq!(low.max_interruption, Some(5.0));
+
+        let any = constraints(2, 4.0, RISK_TOLERANCES.len() - 1);
+        assert_eq!(any.max_interruption, None);
+    }
+}